@@ -3,6 +3,8 @@ extern crate bindgen;
 extern crate cc;
 
 use std::env;
+use std::fs;
+use std::path::PathBuf;
 
 fn main() {
     let mut build = cc::Build::new();
@@ -28,11 +30,82 @@ fn main() {
             .cpp(true);
     } else if target.contains("linux") {
         build.flag("-std=c++11").cpp_link_stdlib("stdc++").cpp(true);
+    } else if target == "wasm32-unknown-unknown" {
+        link_wasi_libc(&mut build);
+    }
+
+    generate_bindings("gen/bindings.rs");
+
+    let bindings = fs::read_to_string("gen/bindings.rs").expect("Unable to read bindings!");
+    for function in bindings.lines().filter_map(vendored_function) {
+        build.define(function, Some(&*format!("{}{}", VENDORED_PREFIX, function)));
     }
 
     build.compile("spirv_reflect_cpp");
 
-    generate_bindings("gen/bindings.rs");
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    fs::write(
+        out_dir.join("bindings.rs"),
+        link_vendored_functions(&bindings),
+    )
+    .expect("Unable to write bindings!");
+}
+
+// The C functions are compiled with this prefix, which leaves the
+// `spvReflect*` symbols free for the `capi` crate to export.
+const VENDORED_PREFIX: &str = "spirv_reflect_vendored_";
+
+fn vendored_function(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    if !line.starts_with("pub fn spvReflect") {
+        return None;
+    }
+    let name = &line["pub fn ".len()..];
+    name.find('(').map(|end| &name[..end])
+}
+
+// Points each function declaration of the bindings at its prefixed symbol.
+fn link_vendored_functions(bindings: &str) -> String {
+    let mut linked = String::with_capacity(bindings.len());
+    for line in bindings.lines() {
+        if let Some(function) = vendored_function(line) {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            linked.push_str(&format!(
+                "{}#[link_name = \"{}{}\"]\n",
+                indent, VENDORED_PREFIX, function
+            ));
+        }
+        linked.push_str(line);
+        linked.push('\n');
+    }
+    linked
+}
+
+// wasm32-unknown-unknown has no libc, so the C library is compiled against
+// the headers of a WASI sysroot and linked with its libc, which provides
+// the allocator and string functions without importing anything.
+fn link_wasi_libc(build: &mut cc::Build) {
+    println!("cargo:rerun-if-env-changed=WASI_SYSROOT");
+    let sysroot = match env::var("WASI_SYSROOT") {
+        Ok(sysroot) => PathBuf::from(sysroot),
+        Err(_) => panic!(
+            "Building for wasm32-unknown-unknown needs WASI_SYSROOT set to a WASI sysroot, \
+             such as share/wasi-sysroot of the WASI SDK"
+        ),
+    };
+    build.flag(format!("--sysroot={}", sysroot.display()));
+    // Newer sysroots keep headers and libraries in per-target directories.
+    for wasi_target in &["wasm32-wasi", "wasm32-wasip1"] {
+        let include = sysroot.join("include").join(wasi_target);
+        if include.is_dir() {
+            build.include(include);
+        }
+        let lib = sysroot.join("lib").join(wasi_target);
+        if lib.is_dir() {
+            println!("cargo:rustc-link-search=native={}", lib.display());
+        }
+    }
+    println!("cargo:rustc-link-lib=static=c");
 }
 
 #[cfg(feature = "generate_bindings")]
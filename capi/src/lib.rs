@@ -0,0 +1,1596 @@
+//! The SPIRV-Reflect C API over `spirv_reflect::ShaderModule`, so that C and
+//! C++ engines can switch to the sorted, annotated reflection of this crate
+//! by linking `spirv_reflect_rs` in place of `spirv_reflect.c`. Callers keep
+//! including upstream's `spirv_reflect.h`: the functions have the same names
+//! and signatures, and the structs are the ones in `spirv_reflect::ffi`.
+//!
+//! `spvReflectCreateShaderModule` converts the reflection up front into
+//! storage hung off the module's `_internal` pointer, which everything the
+//! module hands out points into until `spvReflectDestroyShaderModule`.
+
+#![allow(non_snake_case)]
+
+use spirv_reflect::ffi::{self, SpvReflectResult};
+use spirv_reflect::types::{
+    ReflectArrayTraits, ReflectBindingArrayTraits, ReflectBlockVariable, ReflectDecorationFlags,
+    ReflectDescriptorBinding, ReflectDescriptorSet, ReflectDescriptorType, ReflectDimension,
+    ReflectEntryPoint, ReflectFormat, ReflectImageTraits, ReflectInterfaceVariable,
+    ReflectNumericTraits, ReflectResourceType, ReflectStorageClass, ReflectTypeDescription,
+};
+use spirv_reflect::ShaderModule;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+
+const SUCCESS: SpvReflectResult = ffi::SpvReflectResult_SPV_REFLECT_RESULT_SUCCESS;
+const NULL_POINTER: SpvReflectResult = ffi::SpvReflectResult_SPV_REFLECT_RESULT_ERROR_NULL_POINTER;
+const NOT_FOUND: SpvReflectResult =
+    ffi::SpvReflectResult_SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND;
+const DONT_CHANGE: u32 = ffi::SPV_REFLECT_SET_NUMBER_DONT_CHANGE as u32;
+
+/// Everything a module points to. `internal` comes first, so the module's
+/// `_internal` pointer is also a pointer to the storage.
+#[repr(C)]
+struct Storage {
+    internal: ffi::SpvReflectShaderModule_Internal,
+    module: ShaderModule,
+    code: Vec<u32>,
+    strings: Vec<CString>,
+    /// Types of the reflected objects, one per id.
+    type_descriptions: Box<[ffi::SpvReflectTypeDescription]>,
+    /// Member lists, pointed to by `members` of the type descriptions,
+    /// blocks and interface variables.
+    type_members: Vec<Box<[ffi::SpvReflectTypeDescription]>>,
+    block_members: Vec<Box<[ffi::SpvReflectBlockVariable]>>,
+    variable_members: Vec<Box<[ffi::SpvReflectInterfaceVariable]>>,
+    descriptor_bindings: Box<[ffi::SpvReflectDescriptorBinding]>,
+    input_variables: Box<[ffi::SpvReflectInterfaceVariable]>,
+    output_variables: Box<[ffi::SpvReflectInterfaceVariable]>,
+    push_constant_blocks: Box<[ffi::SpvReflectBlockVariable]>,
+    entry_points: Box<[ffi::SpvReflectEntryPoint]>,
+    /// The input and output variables of each entry point.
+    entry_point_variables: Vec<Box<[ffi::SpvReflectInterfaceVariable]>>,
+    /// The `used_uniforms` and `used_push_constants` of each entry point.
+    entry_point_ids: Vec<Box<[u32]>>,
+    /// The descriptor sets of each entry point, rebuilt by `update`.
+    entry_point_sets: Vec<Box<[ffi::SpvReflectDescriptorSet]>>,
+    /// The binding pointers of every descriptor set, rebuilt by `update`.
+    set_bindings: Vec<Box<[*mut ffi::SpvReflectDescriptorBinding]>>,
+}
+
+impl Storage {
+    fn new(module: ShaderModule) -> Storage {
+        Storage {
+            internal: ffi::SpvReflectShaderModule_Internal {
+                spirv_size: 0,
+                spirv_code: ptr::null_mut(),
+                spirv_word_count: 0,
+                type_description_count: 0,
+                type_descriptions: ptr::null_mut(),
+            },
+            module,
+            code: Vec::new(),
+            strings: Vec::new(),
+            type_descriptions: Box::new([]),
+            type_members: Vec::new(),
+            block_members: Vec::new(),
+            variable_members: Vec::new(),
+            descriptor_bindings: Box::new([]),
+            input_variables: Box::new([]),
+            output_variables: Box::new([]),
+            push_constant_blocks: Box::new([]),
+            entry_points: Box::new([]),
+            entry_point_variables: Vec::new(),
+            entry_point_ids: Vec::new(),
+            entry_point_sets: Vec::new(),
+            set_bindings: Vec::new(),
+        }
+    }
+
+    /// Converts the reflection of `module` into the storage, except for the
+    /// `_internal` pointer. The storage must not move afterwards, as the
+    /// result points into it.
+    fn shader_module(&mut self) -> ffi::SpvReflectShaderModule {
+        let module = self.module.clone();
+        self.update_code();
+
+        let entry_points = module.entry_points();
+        let bindings = module
+            .descriptor_bindings()
+            .iter()
+            .flat_map(|binding| vec![&binding.type_description, &binding.block.type_description]);
+        let blocks = module
+            .push_constant_blocks()
+            .iter()
+            .map(|block| &block.type_description);
+        let variables = module
+            .input_variables()
+            .iter()
+            .chain(module.output_variables())
+            .chain(entry_points.iter().flat_map(|entry_point| {
+                entry_point
+                    .input_variables
+                    .iter()
+                    .chain(&entry_point.output_variables)
+            }))
+            .map(|variable| &variable.type_description);
+        let mut type_descriptions: Vec<&ReflectTypeDescription> = Vec::new();
+        for description in bindings.chain(blocks).chain(variables).flatten() {
+            if type_descriptions
+                .iter()
+                .all(|added| added.id != description.id)
+            {
+                type_descriptions.push(description);
+            }
+        }
+        self.type_descriptions = type_descriptions
+            .into_iter()
+            .map(|description| self.type_description(description))
+            .collect();
+        self.internal.type_description_count = self.type_descriptions.len();
+        self.internal.type_descriptions = array_pointer(&mut self.type_descriptions);
+
+        self.descriptor_bindings = module
+            .descriptor_bindings()
+            .iter()
+            .map(|binding| self.descriptor_binding(binding))
+            .collect();
+        for index in 0..self.descriptor_bindings.len() {
+            let counter_id = self.descriptor_bindings[index].uav_counter_id;
+            self.descriptor_bindings[index].uav_counter_binding = self.binding_pointer(counter_id);
+        }
+        self.input_variables = self.interface_variables(module.input_variables());
+        self.output_variables = self.interface_variables(module.output_variables());
+        self.push_constant_blocks = module
+            .push_constant_blocks()
+            .iter()
+            .map(|block| {
+                let type_description = self.type_pointer(&block.type_description);
+                self.block_variable(block, type_description)
+            })
+            .collect();
+        self.entry_points = entry_points
+            .iter()
+            .map(|entry_point| self.entry_point(entry_point))
+            .collect();
+
+        let entry_point_name = module.get_entry_point_name();
+        let entry_point_id = entry_points
+            .iter()
+            .find(|entry_point| entry_point.name == entry_point_name)
+            .map_or(0, |entry_point| entry_point.id);
+        let mut shader_module = ffi::SpvReflectShaderModule {
+            // The tool id of the generator word, as SPIRV-Reflect reports it.
+            generator: self.code.get(2).map_or(0, |word| (word >> 16) as i32),
+            entry_point_name: self.string(&entry_point_name),
+            entry_point_id,
+            entry_point_count: self.entry_points.len() as u32,
+            entry_points: array_pointer(&mut self.entry_points),
+            source_language: module.get_source_language() as i32,
+            source_language_version: module.get_source_language_version(),
+            source_file: self.string(&module.get_source_file()),
+            source_source: self.string(&module.get_source_text()),
+            spirv_execution_model: module.get_spirv_execution_model() as i32,
+            shader_stage: module.get_shader_stage().bits() as i32,
+            descriptor_binding_count: self.descriptor_bindings.len() as u32,
+            descriptor_bindings: array_pointer(&mut self.descriptor_bindings),
+            descriptor_set_count: 0,
+            descriptor_sets: [ffi::SpvReflectDescriptorSet {
+                set: 0,
+                binding_count: 0,
+                bindings: ptr::null_mut(),
+            }; ffi::SPV_REFLECT_MAX_DESCRIPTOR_SETS as usize],
+            input_variable_count: self.input_variables.len() as u32,
+            input_variables: array_pointer(&mut self.input_variables),
+            output_variable_count: self.output_variables.len() as u32,
+            output_variables: array_pointer(&mut self.output_variables),
+            push_constant_block_count: self.push_constant_blocks.len() as u32,
+            push_constant_blocks: array_pointer(&mut self.push_constant_blocks),
+            // Set by `spvReflectCreateShaderModule`, which owns the storage.
+            _internal: ptr::null_mut(),
+        };
+        self.update_descriptor_sets(&mut shader_module);
+        shader_module
+    }
+
+    /// Brings the converted reflection up to date after a change to the
+    /// numbers or locations of `module`. Pointers to bindings and variables
+    /// stay valid; the binding lists of the descriptor sets are rebuilt.
+    fn update(&mut self, shader_module: &mut ffi::SpvReflectShaderModule) {
+        self.update_code();
+        let module = self.module.clone();
+        for binding in self.descriptor_bindings.iter_mut() {
+            if let Some(reflected) = module.binding_for_id(binding.spirv_id) {
+                binding.binding = reflected.binding;
+                binding.set = reflected.set;
+            }
+        }
+        update_locations(&mut self.input_variables, module.input_variables());
+        update_locations(&mut self.output_variables, module.output_variables());
+        for (variables, entry_point) in self
+            .entry_point_variables
+            .chunks_mut(2)
+            .zip(module.entry_points())
+        {
+            update_locations(&mut variables[0], &entry_point.input_variables);
+            update_locations(&mut variables[1], &entry_point.output_variables);
+        }
+        self.update_descriptor_sets(shader_module);
+    }
+
+    fn update_code(&mut self) {
+        self.code = self.module.get_code();
+        self.internal.spirv_size = self.code.len() * 4;
+        self.internal.spirv_word_count = self.code.len() as u32;
+        self.internal.spirv_code = array_pointer(&mut self.code);
+    }
+
+    fn update_descriptor_sets(&mut self, shader_module: &mut ffi::SpvReflectShaderModule) {
+        self.set_bindings.clear();
+        self.entry_point_sets.clear();
+        let module = self.module.clone();
+        let sets = module.descriptor_sets();
+        let max_sets = shader_module.descriptor_sets.len();
+        for (converted, set) in shader_module.descriptor_sets.iter_mut().zip(sets) {
+            *converted = self.descriptor_set(set);
+        }
+        shader_module.descriptor_set_count = sets.len().min(max_sets) as u32;
+        for (index, entry_point) in module.entry_points().iter().enumerate() {
+            let mut sets: Box<[ffi::SpvReflectDescriptorSet]> = entry_point
+                .descriptor_sets
+                .iter()
+                .map(|set| self.descriptor_set(set))
+                .collect();
+            if let Some(converted) = self.entry_points.get_mut(index) {
+                converted.descriptor_set_count = sets.len() as u32;
+                converted.descriptor_sets = array_pointer(&mut sets);
+            }
+            self.entry_point_sets.push(sets);
+        }
+    }
+
+    /// A string that lives as long as the module, null for an empty one as
+    /// SPIRV-Reflect leaves missing names and sources null.
+    fn string(&mut self, value: &str) -> *const c_char {
+        if value.is_empty() {
+            return ptr::null();
+        }
+        // SPIR-V strings end at their first nul, so there is none inside.
+        let string = CString::new(value).unwrap_or_default();
+        let pointer = string.as_ptr();
+        self.strings.push(string);
+        pointer
+    }
+
+    fn type_pointer(
+        &mut self,
+        description: &Option<ReflectTypeDescription>,
+    ) -> *mut ffi::SpvReflectTypeDescription {
+        match description {
+            Some(description) => self
+                .type_descriptions
+                .iter_mut()
+                .find(|converted| converted.id == description.id)
+                .map_or(ptr::null_mut(), |converted| converted as *mut _),
+            None => ptr::null_mut(),
+        }
+    }
+
+    fn binding_pointer(&mut self, spirv_id: u32) -> *mut ffi::SpvReflectDescriptorBinding {
+        self.descriptor_bindings
+            .iter_mut()
+            .find(|binding| binding.spirv_id == spirv_id)
+            .map_or(ptr::null_mut(), |binding| binding as *mut _)
+    }
+
+    fn type_description(
+        &mut self,
+        description: &ReflectTypeDescription,
+    ) -> ffi::SpvReflectTypeDescription {
+        let mut members: Box<[ffi::SpvReflectTypeDescription]> = description
+            .members
+            .iter()
+            .map(|member| self.type_description(member))
+            .collect();
+        let converted = ffi::SpvReflectTypeDescription {
+            id: description.id,
+            op: description.op.as_raw() as i32,
+            type_name: self.string(&description.type_name),
+            struct_member_name: self.string(&description.struct_member_name),
+            storage_class: storage_class(description.storage_class),
+            type_flags: description.type_flags.bits(),
+            decoration_flags: description.decoration_flags.bits(),
+            traits: ffi::SpvReflectTypeDescription_Traits {
+                numeric: numeric_traits(&description.traits.numeric),
+                image: image_traits(&description.traits.image),
+                array: array_traits(&description.traits.array),
+            },
+            member_count: members.len() as u32,
+            members: array_pointer(&mut members),
+        };
+        self.type_members.push(members);
+        converted
+    }
+
+    /// `type_description` is the converted type of `block`, whose members
+    /// then point at its members like in SPIRV-Reflect.
+    fn block_variable(
+        &mut self,
+        block: &ReflectBlockVariable,
+        type_description: *mut ffi::SpvReflectTypeDescription,
+    ) -> ffi::SpvReflectBlockVariable {
+        let mut members: Box<[ffi::SpvReflectBlockVariable]> = block
+            .members
+            .iter()
+            .enumerate()
+            .map(|(index, member)| {
+                self.block_variable(member, member_type(type_description, index))
+            })
+            .collect();
+        let converted = ffi::SpvReflectBlockVariable {
+            spirv_id: block.spirv_id,
+            name: self.string(&block.name),
+            offset: block.offset,
+            absolute_offset: block.absolute_offset,
+            size: block.size,
+            padded_size: block.padded_size,
+            decoration_flags: block.decoration_flags.bits(),
+            numeric: numeric_traits(&block.numeric),
+            array: array_traits(&block.array),
+            member_count: members.len() as u32,
+            members: array_pointer(&mut members),
+            type_description,
+        };
+        self.block_members.push(members);
+        converted
+    }
+
+    fn descriptor_binding(
+        &mut self,
+        binding: &ReflectDescriptorBinding,
+    ) -> ffi::SpvReflectDescriptorBinding {
+        let block_type = self.type_pointer(&binding.block.type_description);
+        ffi::SpvReflectDescriptorBinding {
+            spirv_id: binding.spirv_id,
+            name: self.string(&binding.name),
+            binding: binding.binding,
+            input_attachment_index: binding.input_attachment_index,
+            set: binding.set,
+            descriptor_type: descriptor_type(binding.descriptor_type),
+            resource_type: resource_type(binding.resource_type),
+            image: image_traits(&binding.image),
+            block: self.block_variable(&binding.block, block_type),
+            array: binding_array_traits(&binding.array),
+            count: binding.count,
+            accessed: binding.accessed as u32,
+            uav_counter_id: binding.uav_counter_id,
+            // Set once all bindings are converted.
+            uav_counter_binding: ptr::null_mut(),
+            type_description: self.type_pointer(&binding.type_description),
+            word_offset: ffi::SpvReflectDescriptorBinding__bindgen_ty_1 {
+                binding: binding.word_offset.0,
+                set: binding.word_offset.1,
+            },
+        }
+    }
+
+    fn descriptor_set(&mut self, set: &ReflectDescriptorSet) -> ffi::SpvReflectDescriptorSet {
+        let mut bindings: Box<[*mut ffi::SpvReflectDescriptorBinding]> = set
+            .bindings
+            .iter()
+            .map(|binding| self.binding_pointer(binding.spirv_id))
+            .filter(|binding| !binding.is_null())
+            .collect();
+        let converted = ffi::SpvReflectDescriptorSet {
+            set: set.set,
+            binding_count: bindings.len() as u32,
+            bindings: array_pointer(&mut bindings),
+        };
+        self.set_bindings.push(bindings);
+        converted
+    }
+
+    fn interface_variables(
+        &mut self,
+        variables: &[ReflectInterfaceVariable],
+    ) -> Box<[ffi::SpvReflectInterfaceVariable]> {
+        variables
+            .iter()
+            .map(|variable| {
+                let type_description = self.type_pointer(&variable.type_description);
+                self.interface_variable(variable, type_description)
+            })
+            .collect()
+    }
+
+    fn interface_variable(
+        &mut self,
+        variable: &ReflectInterfaceVariable,
+        type_description: *mut ffi::SpvReflectTypeDescription,
+    ) -> ffi::SpvReflectInterfaceVariable {
+        let mut members: Box<[ffi::SpvReflectInterfaceVariable]> = variable
+            .members
+            .iter()
+            .enumerate()
+            .map(|(index, member)| {
+                self.interface_variable(member, member_type(type_description, index))
+            })
+            .collect();
+        let converted = ffi::SpvReflectInterfaceVariable {
+            spirv_id: variable.spirv_id,
+            name: self.string(&variable.name),
+            location: variable.location,
+            storage_class: storage_class(variable.storage_class),
+            semantic: self.string(&variable.semantic),
+            decoration_flags: variable.decoration_flags.bits(),
+            built_in: if variable
+                .decoration_flags
+                .contains(ReflectDecorationFlags::BUILT_IN)
+            {
+                variable.built_in.raw() as i32
+            } else {
+                -1
+            },
+            numeric: numeric_traits(&variable.numeric),
+            array: array_traits(&variable.array),
+            member_count: members.len() as u32,
+            members: array_pointer(&mut members),
+            format: format(variable.format),
+            type_description,
+            word_offset: ffi::SpvReflectInterfaceVariable__bindgen_ty_1 {
+                location: variable.word_offset,
+            },
+        };
+        self.variable_members.push(members);
+        converted
+    }
+
+    /// Converts `entry_point` except for its descriptor sets, which
+    /// `update_descriptor_sets` fills in.
+    fn entry_point(&mut self, entry_point: &ReflectEntryPoint) -> ffi::SpvReflectEntryPoint {
+        let mut input_variables = self.interface_variables(&entry_point.input_variables);
+        let mut output_variables = self.interface_variables(&entry_point.output_variables);
+        let mut used_uniforms: Box<[u32]> = entry_point.used_uniforms.clone().into();
+        let mut used_push_constants: Box<[u32]> = entry_point.used_push_constants.clone().into();
+        let converted = ffi::SpvReflectEntryPoint {
+            name: self.string(&entry_point.name),
+            id: entry_point.id,
+            spirv_execution_model: entry_point.spirv_execution_model as i32,
+            shader_stage: entry_point.shader_stage.bits() as i32,
+            input_variable_count: input_variables.len() as u32,
+            input_variables: array_pointer(&mut input_variables),
+            output_variable_count: output_variables.len() as u32,
+            output_variables: array_pointer(&mut output_variables),
+            descriptor_set_count: 0,
+            descriptor_sets: ptr::null_mut(),
+            used_uniform_count: used_uniforms.len() as u32,
+            used_uniforms: array_pointer(&mut used_uniforms),
+            used_push_constant_count: used_push_constants.len() as u32,
+            used_push_constants: array_pointer(&mut used_push_constants),
+        };
+        self.entry_point_variables.push(input_variables);
+        self.entry_point_variables.push(output_variables);
+        self.entry_point_ids.push(used_uniforms);
+        self.entry_point_ids.push(used_push_constants);
+        converted
+    }
+}
+
+fn update_locations(
+    variables: &mut [ffi::SpvReflectInterfaceVariable],
+    reflected: &[ReflectInterfaceVariable],
+) {
+    for variable in variables {
+        if let Some(reflected) = reflected
+            .iter()
+            .find(|reflected| reflected.spirv_id == variable.spirv_id)
+        {
+            variable.location = reflected.location;
+        }
+    }
+}
+
+/// The start of `items`, null if there are none as in SPIRV-Reflect.
+fn array_pointer<T>(items: &mut [T]) -> *mut T {
+    if items.is_empty() {
+        ptr::null_mut()
+    } else {
+        items.as_mut_ptr()
+    }
+}
+
+fn member_type(
+    type_description: *mut ffi::SpvReflectTypeDescription,
+    index: usize,
+) -> *mut ffi::SpvReflectTypeDescription {
+    match unsafe { type_description.as_ref() } {
+        Some(description) if index < description.member_count as usize => unsafe {
+            description.members.add(index)
+        },
+        _ => ptr::null_mut(),
+    }
+}
+
+fn numeric_traits(traits: &ReflectNumericTraits) -> ffi::SpvReflectNumericTraits {
+    ffi::SpvReflectNumericTraits {
+        scalar: ffi::SpvReflectNumericTraits_Scalar {
+            width: traits.scalar.width,
+            signedness: traits.scalar.signedness,
+        },
+        vector: ffi::SpvReflectNumericTraits_Vector {
+            component_count: traits.vector.component_count,
+        },
+        matrix: ffi::SpvReflectNumericTraits_Matrix {
+            column_count: traits.matrix.column_count,
+            row_count: traits.matrix.row_count,
+            stride: traits.matrix.stride,
+        },
+    }
+}
+
+fn image_traits(traits: &ReflectImageTraits) -> ffi::SpvReflectImageTraits {
+    ffi::SpvReflectImageTraits {
+        dim: dimension(traits.dim),
+        depth: traits.depth,
+        arrayed: traits.arrayed,
+        ms: traits.ms,
+        sampled: traits.sampled,
+        image_format: traits.image_format.as_raw() as i32,
+    }
+}
+
+/// Dimensions past `SPV_REFLECT_MAX_ARRAY_DIMS` are dropped.
+fn array_dims(dims: &[u32]) -> (u32, [u32; ffi::SPV_REFLECT_MAX_ARRAY_DIMS as usize]) {
+    let mut converted = [0; ffi::SPV_REFLECT_MAX_ARRAY_DIMS as usize];
+    for (converted, dim) in converted.iter_mut().zip(dims) {
+        *converted = *dim;
+    }
+    (dims.len().min(converted.len()) as u32, converted)
+}
+
+fn array_traits(traits: &ReflectArrayTraits) -> ffi::SpvReflectArrayTraits {
+    let (dims_count, dims) = array_dims(&traits.dims);
+    ffi::SpvReflectArrayTraits {
+        dims_count,
+        dims,
+        stride: traits.stride,
+    }
+}
+
+fn binding_array_traits(traits: &ReflectBindingArrayTraits) -> ffi::SpvReflectBindingArrayTraits {
+    let (dims_count, dims) = array_dims(&traits.dims);
+    ffi::SpvReflectBindingArrayTraits { dims_count, dims }
+}
+
+fn storage_class(storage_class: ReflectStorageClass) -> ffi::SpvStorageClass {
+    // SPIRV-Reflect marks an unknown storage class with all bits set.
+    storage_class.as_raw().unwrap_or(u32::MAX) as i32
+}
+
+fn dimension(dim: ReflectDimension) -> ffi::SpvDim {
+    // SPIRV-Reflect leaves the traits of types other than images zeroed.
+    dim.as_raw().unwrap_or(0) as i32
+}
+
+fn descriptor_type(descriptor_type: ReflectDescriptorType) -> ffi::SpvReflectDescriptorType {
+    match descriptor_type {
+        ReflectDescriptorType::Undefined => -1,
+        ReflectDescriptorType::Sampler => {
+            ffi::SpvReflectDescriptorType_SPV_REFLECT_DESCRIPTOR_TYPE_SAMPLER
+        }
+        ReflectDescriptorType::CombinedImageSampler => {
+            ffi::SpvReflectDescriptorType_SPV_REFLECT_DESCRIPTOR_TYPE_COMBINED_IMAGE_SAMPLER
+        }
+        ReflectDescriptorType::SampledImage => {
+            ffi::SpvReflectDescriptorType_SPV_REFLECT_DESCRIPTOR_TYPE_SAMPLED_IMAGE
+        }
+        ReflectDescriptorType::StorageImage => {
+            ffi::SpvReflectDescriptorType_SPV_REFLECT_DESCRIPTOR_TYPE_STORAGE_IMAGE
+        }
+        ReflectDescriptorType::UniformTexelBuffer => {
+            ffi::SpvReflectDescriptorType_SPV_REFLECT_DESCRIPTOR_TYPE_UNIFORM_TEXEL_BUFFER
+        }
+        ReflectDescriptorType::StorageTexelBuffer => {
+            ffi::SpvReflectDescriptorType_SPV_REFLECT_DESCRIPTOR_TYPE_STORAGE_TEXEL_BUFFER
+        }
+        ReflectDescriptorType::UniformBuffer => {
+            ffi::SpvReflectDescriptorType_SPV_REFLECT_DESCRIPTOR_TYPE_UNIFORM_BUFFER
+        }
+        ReflectDescriptorType::StorageBuffer => {
+            ffi::SpvReflectDescriptorType_SPV_REFLECT_DESCRIPTOR_TYPE_STORAGE_BUFFER
+        }
+        ReflectDescriptorType::UniformBufferDynamic => {
+            ffi::SpvReflectDescriptorType_SPV_REFLECT_DESCRIPTOR_TYPE_UNIFORM_BUFFER_DYNAMIC
+        }
+        ReflectDescriptorType::StorageBufferDynamic => {
+            ffi::SpvReflectDescriptorType_SPV_REFLECT_DESCRIPTOR_TYPE_STORAGE_BUFFER_DYNAMIC
+        }
+        ReflectDescriptorType::InputAttachment => {
+            ffi::SpvReflectDescriptorType_SPV_REFLECT_DESCRIPTOR_TYPE_INPUT_ATTACHMENT
+        }
+        ReflectDescriptorType::AccelerationStructureNV => {
+            ffi::SpvReflectDescriptorType_SPV_REFLECT_DESCRIPTOR_TYPE_ACCELERATION_STRUCTURE_NV
+        }
+    }
+}
+
+fn resource_type(resource_type: ReflectResourceType) -> ffi::SpvReflectResourceType {
+    match resource_type {
+        ReflectResourceType::Undefined => {
+            ffi::SpvReflectResourceType_SPV_REFLECT_RESOURCE_FLAG_UNDEFINED
+        }
+        ReflectResourceType::Sampler => {
+            ffi::SpvReflectResourceType_SPV_REFLECT_RESOURCE_FLAG_SAMPLER
+        }
+        ReflectResourceType::CombinedImageSampler => {
+            ffi::SpvReflectResourceType_SPV_REFLECT_RESOURCE_FLAG_SAMPLER
+                | ffi::SpvReflectResourceType_SPV_REFLECT_RESOURCE_FLAG_SRV
+        }
+        ReflectResourceType::ConstantBufferView => {
+            ffi::SpvReflectResourceType_SPV_REFLECT_RESOURCE_FLAG_CBV
+        }
+        ReflectResourceType::ShaderResourceView => {
+            ffi::SpvReflectResourceType_SPV_REFLECT_RESOURCE_FLAG_SRV
+        }
+        ReflectResourceType::UnorderedAccessView => {
+            ffi::SpvReflectResourceType_SPV_REFLECT_RESOURCE_FLAG_UAV
+        }
+    }
+}
+
+fn format(format: ReflectFormat) -> ffi::SpvReflectFormat {
+    match format {
+        ReflectFormat::Undefined => ffi::SpvReflectFormat_SPV_REFLECT_FORMAT_UNDEFINED,
+        ReflectFormat::R32_UINT => ffi::SpvReflectFormat_SPV_REFLECT_FORMAT_R32_UINT,
+        ReflectFormat::R32_SINT => ffi::SpvReflectFormat_SPV_REFLECT_FORMAT_R32_SINT,
+        ReflectFormat::R32_SFLOAT => ffi::SpvReflectFormat_SPV_REFLECT_FORMAT_R32_SFLOAT,
+        ReflectFormat::R32G32_UINT => ffi::SpvReflectFormat_SPV_REFLECT_FORMAT_R32G32_UINT,
+        ReflectFormat::R32G32_SINT => ffi::SpvReflectFormat_SPV_REFLECT_FORMAT_R32G32_SINT,
+        ReflectFormat::R32G32_SFLOAT => ffi::SpvReflectFormat_SPV_REFLECT_FORMAT_R32G32_SFLOAT,
+        ReflectFormat::R32G32B32_UINT => ffi::SpvReflectFormat_SPV_REFLECT_FORMAT_R32G32B32_UINT,
+        ReflectFormat::R32G32B32_SINT => ffi::SpvReflectFormat_SPV_REFLECT_FORMAT_R32G32B32_SINT,
+        ReflectFormat::R32G32B32_SFLOAT => {
+            ffi::SpvReflectFormat_SPV_REFLECT_FORMAT_R32G32B32_SFLOAT
+        }
+        ReflectFormat::R32G32B32A32_UINT => {
+            ffi::SpvReflectFormat_SPV_REFLECT_FORMAT_R32G32B32A32_UINT
+        }
+        ReflectFormat::R32G32B32A32_SINT => {
+            ffi::SpvReflectFormat_SPV_REFLECT_FORMAT_R32G32B32A32_SINT
+        }
+        ReflectFormat::R32G32B32A32_SFLOAT => {
+            ffi::SpvReflectFormat_SPV_REFLECT_FORMAT_R32G32B32A32_SFLOAT
+        }
+        // Newer than the bundled header; these are the values of the
+        // matching `VkFormat`, which newer SPIRV-Reflect versions use.
+        ReflectFormat::R64_UINT => 110,
+        ReflectFormat::R64_SINT => 111,
+        ReflectFormat::R64_SFLOAT => 112,
+        ReflectFormat::R64G64_UINT => 113,
+        ReflectFormat::R64G64_SINT => 114,
+        ReflectFormat::R64G64_SFLOAT => 115,
+        ReflectFormat::R64G64B64_UINT => 116,
+        ReflectFormat::R64G64B64_SINT => 117,
+        ReflectFormat::R64G64B64_SFLOAT => 118,
+        ReflectFormat::R64G64B64A64_UINT => 119,
+        ReflectFormat::R64G64B64A64_SINT => 120,
+        ReflectFormat::R64G64B64A64_SFLOAT => 121,
+    }
+}
+
+unsafe fn items<'a, T>(pointer: *const T, count: u32) -> &'a [T] {
+    if pointer.is_null() {
+        &[]
+    } else {
+        std::slice::from_raw_parts(pointer, count as usize)
+    }
+}
+
+unsafe fn storage<'a>(module: &ffi::SpvReflectShaderModule) -> Option<&'a mut Storage> {
+    (module._internal as *mut Storage).as_mut()
+}
+
+unsafe fn entry_point(
+    module: &ffi::SpvReflectShaderModule,
+    name: *const c_char,
+) -> Result<&ffi::SpvReflectEntryPoint, SpvReflectResult> {
+    if name.is_null() {
+        return Err(NOT_FOUND);
+    }
+    let name = CStr::from_ptr(name);
+    items(module.entry_points, module.entry_point_count)
+        .iter()
+        .find(|entry_point| !entry_point.name.is_null() && CStr::from_ptr(entry_point.name) == name)
+        .ok_or(NOT_FOUND)
+}
+
+unsafe fn module_push_constant_blocks(
+    module: &ffi::SpvReflectShaderModule,
+) -> &[ffi::SpvReflectBlockVariable] {
+    items(
+        module.push_constant_blocks,
+        module.push_constant_block_count,
+    )
+}
+
+unsafe fn entry_point_bindings<'a>(
+    module: &'a ffi::SpvReflectShaderModule,
+    entry_point: &ffi::SpvReflectEntryPoint,
+) -> Vec<&'a ffi::SpvReflectDescriptorBinding> {
+    let used_uniforms = items(entry_point.used_uniforms, entry_point.used_uniform_count);
+    items(module.descriptor_bindings, module.descriptor_binding_count)
+        .iter()
+        .filter(|binding| used_uniforms.contains(&binding.spirv_id))
+        .collect()
+}
+
+unsafe fn entry_point_push_constant_blocks<'a>(
+    module: &'a ffi::SpvReflectShaderModule,
+    entry_point: &ffi::SpvReflectEntryPoint,
+) -> Vec<&'a ffi::SpvReflectBlockVariable> {
+    let used_push_constants = items(
+        entry_point.used_push_constants,
+        entry_point.used_push_constant_count,
+    );
+    module_push_constant_blocks(module)
+        .iter()
+        .filter(|block| used_push_constants.contains(&block.spirv_id))
+        .collect()
+}
+
+fn variable_by_location(
+    variables: &[ffi::SpvReflectInterfaceVariable],
+    location: u32,
+) -> Result<&ffi::SpvReflectInterfaceVariable, SpvReflectResult> {
+    if location == u32::MAX {
+        return Err(NOT_FOUND);
+    }
+    variables
+        .iter()
+        .find(|variable| variable.location == location)
+        .ok_or(NOT_FOUND)
+}
+
+unsafe fn variable_by_semantic(
+    variables: &[ffi::SpvReflectInterfaceVariable],
+    semantic: *const c_char,
+) -> Result<&ffi::SpvReflectInterfaceVariable, SpvReflectResult> {
+    if semantic.is_null() {
+        return Err(NULL_POINTER);
+    }
+    let semantic = CStr::from_ptr(semantic);
+    if semantic.to_bytes().is_empty() {
+        return Err(NOT_FOUND);
+    }
+    variables
+        .iter()
+        .find(|variable| {
+            !variable.semantic.is_null() && CStr::from_ptr(variable.semantic) == semantic
+        })
+        .ok_or(NOT_FOUND)
+}
+
+/// Fills `*pp_items` with `items` the way the SPIRV-Reflect enumerations
+/// do: with `pp_items` null only the count is written, otherwise
+/// `*p_count` must already hold it.
+unsafe fn enumerate<T>(
+    p_module: *const ffi::SpvReflectShaderModule,
+    p_count: *mut u32,
+    pp_items: *mut *mut T,
+    items: impl FnOnce(&ffi::SpvReflectShaderModule) -> Result<Vec<&T>, SpvReflectResult>,
+) -> SpvReflectResult {
+    let module = match p_module.as_ref() {
+        Some(module) if !p_count.is_null() => module,
+        _ => return NULL_POINTER,
+    };
+    let items = match items(module) {
+        Ok(items) => items,
+        Err(result) => return result,
+    };
+    if pp_items.is_null() {
+        *p_count = items.len() as u32;
+        return SUCCESS;
+    }
+    if *p_count as usize != items.len() {
+        return ffi::SpvReflectResult_SPV_REFLECT_RESULT_ERROR_COUNT_MISMATCH;
+    }
+    for (index, item) in items.into_iter().enumerate() {
+        *pp_items.add(index) = item as *const T as *mut T;
+    }
+    SUCCESS
+}
+
+/// Finds an item the way the SPIRV-Reflect getters do, writing the result
+/// to `p_result` unless it is null.
+unsafe fn get<T>(
+    p_module: *const ffi::SpvReflectShaderModule,
+    p_result: *mut SpvReflectResult,
+    item: impl FnOnce(&ffi::SpvReflectShaderModule) -> Result<&T, SpvReflectResult>,
+) -> *const T {
+    let (item, result) = match p_module.as_ref().ok_or(NULL_POINTER).and_then(item) {
+        Ok(item) => (item as *const T, SUCCESS),
+        Err(result) => (ptr::null(), result),
+    };
+    if !p_result.is_null() {
+        *p_result = result;
+    }
+    item
+}
+
+/// Applies `change` to the module behind `p_module` and updates its
+/// converted reflection.
+unsafe fn change(
+    p_module: *mut ffi::SpvReflectShaderModule,
+    change: impl FnOnce(&mut ShaderModule) -> Result<(), SpvReflectResult>,
+) -> SpvReflectResult {
+    let shader_module = match p_module.as_mut() {
+        Some(shader_module) => shader_module,
+        None => return NULL_POINTER,
+    };
+    let storage = match storage(shader_module) {
+        Some(storage) => storage,
+        None => return NULL_POINTER,
+    };
+    match change(&mut storage.module) {
+        Ok(()) => {
+            storage.update(shader_module);
+            SUCCESS
+        }
+        Err(result) => result,
+    }
+}
+
+fn reflected_variable(
+    module: &ShaderModule,
+    input: bool,
+    spirv_id: u32,
+) -> Result<ReflectInterfaceVariable, SpvReflectResult> {
+    let entry_points = module.entry_points().iter();
+    let variable = if input {
+        module
+            .input_variables()
+            .iter()
+            .chain(entry_points.flat_map(|entry_point| &entry_point.input_variables))
+            .find(|variable| variable.spirv_id == spirv_id)
+    } else {
+        module
+            .output_variables()
+            .iter()
+            .chain(entry_points.flat_map(|entry_point| &entry_point.output_variables))
+            .find(|variable| variable.spirv_id == spirv_id)
+    };
+    variable.cloned().ok_or(NOT_FOUND)
+}
+
+fn change_result(result: Result<(), &'static str>) -> Result<(), SpvReflectResult> {
+    result.map_err(|_| ffi::SpvReflectResult_SPV_REFLECT_RESULT_ERROR_INTERNAL_ERROR)
+}
+
+/// # Safety
+///
+/// `p_code` must point to `size` readable bytes, and `p_module` to a
+/// writable module.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectCreateShaderModule(
+    size: usize,
+    p_code: *const c_void,
+    p_module: *mut ffi::SpvReflectShaderModule,
+) -> SpvReflectResult {
+    if p_code.is_null() || p_module.is_null() {
+        return NULL_POINTER;
+    }
+    let code = std::slice::from_raw_parts(p_code as *const u8, size);
+    match ShaderModule::load_u8_data(code) {
+        Ok(module) => {
+            let storage = Box::into_raw(Box::new(Storage::new(module)));
+            *p_module = (*storage).shader_module();
+            (*p_module)._internal = storage as *mut ffi::SpvReflectShaderModule_Internal;
+            SUCCESS
+        }
+        Err(_) => ffi::SpvReflectResult_SPV_REFLECT_RESULT_ERROR_PARSE_FAILED,
+    }
+}
+
+/// # Safety
+///
+/// See `spvReflectCreateShaderModule`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetShaderModule(
+    size: usize,
+    p_code: *const c_void,
+    p_module: *mut ffi::SpvReflectShaderModule,
+) -> SpvReflectResult {
+    spvReflectCreateShaderModule(size, p_code, p_module)
+}
+
+/// # Safety
+///
+/// `p_module` must be null, zeroed or created by
+/// `spvReflectCreateShaderModule`, and is zeroed afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectDestroyShaderModule(p_module: *mut ffi::SpvReflectShaderModule) {
+    if let Some(shader_module) = p_module.as_mut() {
+        if !shader_module._internal.is_null() {
+            drop(Box::from_raw(shader_module._internal as *mut Storage));
+            *shader_module = std::mem::zeroed();
+        }
+    }
+}
+
+/// # Safety
+///
+/// `p_module` must be null or a live module.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetCodeSize(
+    p_module: *const ffi::SpvReflectShaderModule,
+) -> u32 {
+    match p_module
+        .as_ref()
+        .and_then(|module| module._internal.as_ref())
+    {
+        Some(internal) => internal.spirv_size as u32,
+        None => 0,
+    }
+}
+
+/// # Safety
+///
+/// `p_module` must be null or a live module.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetCode(
+    p_module: *const ffi::SpvReflectShaderModule,
+) -> *const u32 {
+    match p_module
+        .as_ref()
+        .and_then(|module| module._internal.as_ref())
+    {
+        Some(internal) => internal.spirv_code,
+        None => ptr::null(),
+    }
+}
+
+/// # Safety
+///
+/// `p_module` must be null or a live module, and `entry_point` null or a
+/// nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetEntryPoint(
+    p_module: *const ffi::SpvReflectShaderModule,
+    entry_point: *const c_char,
+) -> *const ffi::SpvReflectEntryPoint {
+    get(p_module, ptr::null_mut(), |module| {
+        self::entry_point(module, entry_point)
+    })
+}
+
+/// # Safety
+///
+/// `p_module` must be a live module and `p_count` writable; a non-null
+/// `pp_bindings` must have room for `*p_count` pointers.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectEnumerateDescriptorBindings(
+    p_module: *const ffi::SpvReflectShaderModule,
+    p_count: *mut u32,
+    pp_bindings: *mut *mut ffi::SpvReflectDescriptorBinding,
+) -> SpvReflectResult {
+    enumerate(p_module, p_count, pp_bindings, |module| {
+        Ok(
+            items(module.descriptor_bindings, module.descriptor_binding_count)
+                .iter()
+                .collect(),
+        )
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectEnumerateDescriptorBindings`, with `entry_point` null or a
+/// nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectEnumerateEntryPointDescriptorBindings(
+    p_module: *const ffi::SpvReflectShaderModule,
+    entry_point: *const c_char,
+    p_count: *mut u32,
+    pp_bindings: *mut *mut ffi::SpvReflectDescriptorBinding,
+) -> SpvReflectResult {
+    enumerate(p_module, p_count, pp_bindings, |module| {
+        Ok(entry_point_bindings(
+            module,
+            self::entry_point(module, entry_point)?,
+        ))
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectEnumerateDescriptorBindings`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectEnumerateDescriptorSets(
+    p_module: *const ffi::SpvReflectShaderModule,
+    p_count: *mut u32,
+    pp_sets: *mut *mut ffi::SpvReflectDescriptorSet,
+) -> SpvReflectResult {
+    enumerate(p_module, p_count, pp_sets, |module| {
+        Ok(module
+            .descriptor_sets
+            .iter()
+            .take(module.descriptor_set_count as usize)
+            .collect())
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectEnumerateEntryPointDescriptorBindings`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectEnumerateEntryPointDescriptorSets(
+    p_module: *const ffi::SpvReflectShaderModule,
+    entry_point: *const c_char,
+    p_count: *mut u32,
+    pp_sets: *mut *mut ffi::SpvReflectDescriptorSet,
+) -> SpvReflectResult {
+    enumerate(p_module, p_count, pp_sets, |module| {
+        let entry_point = self::entry_point(module, entry_point)?;
+        Ok(items(
+            entry_point.descriptor_sets,
+            entry_point.descriptor_set_count,
+        )
+        .iter()
+        .collect())
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectEnumerateDescriptorBindings`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectEnumerateInputVariables(
+    p_module: *const ffi::SpvReflectShaderModule,
+    p_count: *mut u32,
+    pp_variables: *mut *mut ffi::SpvReflectInterfaceVariable,
+) -> SpvReflectResult {
+    enumerate(p_module, p_count, pp_variables, |module| {
+        Ok(items(module.input_variables, module.input_variable_count)
+            .iter()
+            .collect())
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectEnumerateEntryPointDescriptorBindings`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectEnumerateEntryPointInputVariables(
+    p_module: *const ffi::SpvReflectShaderModule,
+    entry_point: *const c_char,
+    p_count: *mut u32,
+    pp_variables: *mut *mut ffi::SpvReflectInterfaceVariable,
+) -> SpvReflectResult {
+    enumerate(p_module, p_count, pp_variables, |module| {
+        let entry_point = self::entry_point(module, entry_point)?;
+        Ok(items(
+            entry_point.input_variables,
+            entry_point.input_variable_count,
+        )
+        .iter()
+        .collect())
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectEnumerateDescriptorBindings`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectEnumerateOutputVariables(
+    p_module: *const ffi::SpvReflectShaderModule,
+    p_count: *mut u32,
+    pp_variables: *mut *mut ffi::SpvReflectInterfaceVariable,
+) -> SpvReflectResult {
+    enumerate(p_module, p_count, pp_variables, |module| {
+        Ok(items(module.output_variables, module.output_variable_count)
+            .iter()
+            .collect())
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectEnumerateEntryPointDescriptorBindings`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectEnumerateEntryPointOutputVariables(
+    p_module: *const ffi::SpvReflectShaderModule,
+    entry_point: *const c_char,
+    p_count: *mut u32,
+    pp_variables: *mut *mut ffi::SpvReflectInterfaceVariable,
+) -> SpvReflectResult {
+    enumerate(p_module, p_count, pp_variables, |module| {
+        let entry_point = self::entry_point(module, entry_point)?;
+        Ok(items(
+            entry_point.output_variables,
+            entry_point.output_variable_count,
+        )
+        .iter()
+        .collect())
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectEnumerateDescriptorBindings`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectEnumeratePushConstantBlocks(
+    p_module: *const ffi::SpvReflectShaderModule,
+    p_count: *mut u32,
+    pp_blocks: *mut *mut ffi::SpvReflectBlockVariable,
+) -> SpvReflectResult {
+    enumerate(p_module, p_count, pp_blocks, |module| {
+        Ok(module_push_constant_blocks(module).iter().collect())
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectEnumerateDescriptorBindings`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectEnumeratePushConstants(
+    p_module: *const ffi::SpvReflectShaderModule,
+    p_count: *mut u32,
+    pp_blocks: *mut *mut ffi::SpvReflectBlockVariable,
+) -> SpvReflectResult {
+    spvReflectEnumeratePushConstantBlocks(p_module, p_count, pp_blocks)
+}
+
+/// # Safety
+///
+/// As `spvReflectEnumerateEntryPointDescriptorBindings`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectEnumerateEntryPointPushConstantBlocks(
+    p_module: *const ffi::SpvReflectShaderModule,
+    entry_point: *const c_char,
+    p_count: *mut u32,
+    pp_blocks: *mut *mut ffi::SpvReflectBlockVariable,
+) -> SpvReflectResult {
+    enumerate(p_module, p_count, pp_blocks, |module| {
+        Ok(entry_point_push_constant_blocks(
+            module,
+            self::entry_point(module, entry_point)?,
+        ))
+    })
+}
+
+/// # Safety
+///
+/// `p_module` must be null or a live module, and `p_result` null or
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetDescriptorBinding(
+    p_module: *const ffi::SpvReflectShaderModule,
+    binding_number: u32,
+    set_number: u32,
+    p_result: *mut SpvReflectResult,
+) -> *const ffi::SpvReflectDescriptorBinding {
+    get(p_module, p_result, |module| {
+        items(module.descriptor_bindings, module.descriptor_binding_count)
+            .iter()
+            .find(|binding| binding.binding == binding_number && binding.set == set_number)
+            .ok_or(NOT_FOUND)
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectGetDescriptorBinding`, with `entry_point` null or a
+/// nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetEntryPointDescriptorBinding(
+    p_module: *const ffi::SpvReflectShaderModule,
+    entry_point: *const c_char,
+    binding_number: u32,
+    set_number: u32,
+    p_result: *mut SpvReflectResult,
+) -> *const ffi::SpvReflectDescriptorBinding {
+    get(p_module, p_result, |module| {
+        entry_point_bindings(module, self::entry_point(module, entry_point)?)
+            .into_iter()
+            .find(|binding| binding.binding == binding_number && binding.set == set_number)
+            .ok_or(NOT_FOUND)
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectGetDescriptorBinding`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetDescriptorSet(
+    p_module: *const ffi::SpvReflectShaderModule,
+    set_number: u32,
+    p_result: *mut SpvReflectResult,
+) -> *const ffi::SpvReflectDescriptorSet {
+    get(p_module, p_result, |module| {
+        module
+            .descriptor_sets
+            .iter()
+            .take(module.descriptor_set_count as usize)
+            .find(|set| set.set == set_number)
+            .ok_or(NOT_FOUND)
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectGetEntryPointDescriptorBinding`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetEntryPointDescriptorSet(
+    p_module: *const ffi::SpvReflectShaderModule,
+    entry_point: *const c_char,
+    set_number: u32,
+    p_result: *mut SpvReflectResult,
+) -> *const ffi::SpvReflectDescriptorSet {
+    get(p_module, p_result, |module| {
+        let entry_point = self::entry_point(module, entry_point)?;
+        items(
+            entry_point.descriptor_sets,
+            entry_point.descriptor_set_count,
+        )
+        .iter()
+        .find(|set| set.set == set_number)
+        .ok_or(NOT_FOUND)
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectGetDescriptorBinding`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetInputVariableByLocation(
+    p_module: *const ffi::SpvReflectShaderModule,
+    location: u32,
+    p_result: *mut SpvReflectResult,
+) -> *const ffi::SpvReflectInterfaceVariable {
+    get(p_module, p_result, |module| {
+        variable_by_location(
+            items(module.input_variables, module.input_variable_count),
+            location,
+        )
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectGetDescriptorBinding`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetInputVariable(
+    p_module: *const ffi::SpvReflectShaderModule,
+    location: u32,
+    p_result: *mut SpvReflectResult,
+) -> *const ffi::SpvReflectInterfaceVariable {
+    spvReflectGetInputVariableByLocation(p_module, location, p_result)
+}
+
+/// # Safety
+///
+/// As `spvReflectGetEntryPointDescriptorBinding`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetEntryPointInputVariableByLocation(
+    p_module: *const ffi::SpvReflectShaderModule,
+    entry_point: *const c_char,
+    location: u32,
+    p_result: *mut SpvReflectResult,
+) -> *const ffi::SpvReflectInterfaceVariable {
+    get(p_module, p_result, |module| {
+        let entry_point = self::entry_point(module, entry_point)?;
+        variable_by_location(
+            items(
+                entry_point.input_variables,
+                entry_point.input_variable_count,
+            ),
+            location,
+        )
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectGetDescriptorBinding`, with `semantic` null or a
+/// nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetInputVariableBySemantic(
+    p_module: *const ffi::SpvReflectShaderModule,
+    semantic: *const c_char,
+    p_result: *mut SpvReflectResult,
+) -> *const ffi::SpvReflectInterfaceVariable {
+    get(p_module, p_result, |module| {
+        variable_by_semantic(
+            items(module.input_variables, module.input_variable_count),
+            semantic,
+        )
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectGetEntryPointDescriptorBinding`, with `semantic` null or
+/// a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetEntryPointInputVariableBySemantic(
+    p_module: *const ffi::SpvReflectShaderModule,
+    entry_point: *const c_char,
+    semantic: *const c_char,
+    p_result: *mut SpvReflectResult,
+) -> *const ffi::SpvReflectInterfaceVariable {
+    get(p_module, p_result, |module| {
+        let entry_point = self::entry_point(module, entry_point)?;
+        variable_by_semantic(
+            items(
+                entry_point.input_variables,
+                entry_point.input_variable_count,
+            ),
+            semantic,
+        )
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectGetDescriptorBinding`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetOutputVariableByLocation(
+    p_module: *const ffi::SpvReflectShaderModule,
+    location: u32,
+    p_result: *mut SpvReflectResult,
+) -> *const ffi::SpvReflectInterfaceVariable {
+    get(p_module, p_result, |module| {
+        variable_by_location(
+            items(module.output_variables, module.output_variable_count),
+            location,
+        )
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectGetDescriptorBinding`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetOutputVariable(
+    p_module: *const ffi::SpvReflectShaderModule,
+    location: u32,
+    p_result: *mut SpvReflectResult,
+) -> *const ffi::SpvReflectInterfaceVariable {
+    spvReflectGetOutputVariableByLocation(p_module, location, p_result)
+}
+
+/// # Safety
+///
+/// As `spvReflectGetEntryPointDescriptorBinding`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetEntryPointOutputVariableByLocation(
+    p_module: *const ffi::SpvReflectShaderModule,
+    entry_point: *const c_char,
+    location: u32,
+    p_result: *mut SpvReflectResult,
+) -> *const ffi::SpvReflectInterfaceVariable {
+    get(p_module, p_result, |module| {
+        let entry_point = self::entry_point(module, entry_point)?;
+        variable_by_location(
+            items(
+                entry_point.output_variables,
+                entry_point.output_variable_count,
+            ),
+            location,
+        )
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectGetInputVariableBySemantic`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetOutputVariableBySemantic(
+    p_module: *const ffi::SpvReflectShaderModule,
+    semantic: *const c_char,
+    p_result: *mut SpvReflectResult,
+) -> *const ffi::SpvReflectInterfaceVariable {
+    get(p_module, p_result, |module| {
+        variable_by_semantic(
+            items(module.output_variables, module.output_variable_count),
+            semantic,
+        )
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectGetEntryPointInputVariableBySemantic`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetEntryPointOutputVariableBySemantic(
+    p_module: *const ffi::SpvReflectShaderModule,
+    entry_point: *const c_char,
+    semantic: *const c_char,
+    p_result: *mut SpvReflectResult,
+) -> *const ffi::SpvReflectInterfaceVariable {
+    get(p_module, p_result, |module| {
+        let entry_point = self::entry_point(module, entry_point)?;
+        variable_by_semantic(
+            items(
+                entry_point.output_variables,
+                entry_point.output_variable_count,
+            ),
+            semantic,
+        )
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectGetDescriptorBinding`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetPushConstantBlock(
+    p_module: *const ffi::SpvReflectShaderModule,
+    index: u32,
+    p_result: *mut SpvReflectResult,
+) -> *const ffi::SpvReflectBlockVariable {
+    get(p_module, p_result, |module| {
+        module_push_constant_blocks(module)
+            .get(index as usize)
+            .ok_or(NOT_FOUND)
+    })
+}
+
+/// # Safety
+///
+/// As `spvReflectGetDescriptorBinding`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetPushConstant(
+    p_module: *const ffi::SpvReflectShaderModule,
+    index: u32,
+    p_result: *mut SpvReflectResult,
+) -> *const ffi::SpvReflectBlockVariable {
+    spvReflectGetPushConstantBlock(p_module, index, p_result)
+}
+
+/// # Safety
+///
+/// As `spvReflectGetEntryPointDescriptorBinding`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectGetEntryPointPushConstantBlock(
+    p_module: *const ffi::SpvReflectShaderModule,
+    entry_point: *const c_char,
+    p_result: *mut SpvReflectResult,
+) -> *const ffi::SpvReflectBlockVariable {
+    get(p_module, p_result, |module| {
+        entry_point_push_constant_blocks(module, self::entry_point(module, entry_point)?)
+            .into_iter()
+            .next()
+            .ok_or(NOT_FOUND)
+    })
+}
+
+/// # Safety
+///
+/// `p_module` must be null or a live module, and `p_binding` null or one
+/// of its bindings.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectChangeDescriptorBindingNumbers(
+    p_module: *mut ffi::SpvReflectShaderModule,
+    p_binding: *const ffi::SpvReflectDescriptorBinding,
+    new_binding_number: u32,
+    new_set_number: u32,
+) -> SpvReflectResult {
+    let spirv_id = match p_binding.as_ref() {
+        Some(binding) => binding.spirv_id,
+        None => return NULL_POINTER,
+    };
+    change(p_module, |module| {
+        let binding = module.binding_for_id(spirv_id).cloned().ok_or(NOT_FOUND)?;
+        let new_binding = if new_binding_number == DONT_CHANGE {
+            binding.binding
+        } else {
+            new_binding_number
+        };
+        let new_set = if new_set_number == DONT_CHANGE {
+            None
+        } else {
+            Some(new_set_number)
+        };
+        change_result(module.change_descriptor_binding_numbers(&binding, new_binding, new_set))
+    })
+}
+
+/// # Safety
+///
+/// See `spvReflectChangeDescriptorBindingNumbers`.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectChangeDescriptorBindingNumber(
+    p_module: *mut ffi::SpvReflectShaderModule,
+    p_descriptor_binding: *const ffi::SpvReflectDescriptorBinding,
+    new_binding_number: u32,
+    optional_new_set_number: u32,
+) -> SpvReflectResult {
+    spvReflectChangeDescriptorBindingNumbers(
+        p_module,
+        p_descriptor_binding,
+        new_binding_number,
+        optional_new_set_number,
+    )
+}
+
+/// # Safety
+///
+/// `p_module` must be null or a live module, and `p_set` null or one of its
+/// descriptor sets.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectChangeDescriptorSetNumber(
+    p_module: *mut ffi::SpvReflectShaderModule,
+    p_set: *const ffi::SpvReflectDescriptorSet,
+    new_set_number: u32,
+) -> SpvReflectResult {
+    let set_number = match p_set.as_ref() {
+        Some(set) => set.set,
+        None => return NULL_POINTER,
+    };
+    if new_set_number == DONT_CHANGE {
+        return SUCCESS;
+    }
+    change(p_module, |module| {
+        let set = module
+            .descriptor_sets()
+            .iter()
+            .find(|set| set.set == set_number)
+            .cloned()
+            .ok_or(NOT_FOUND)?;
+        change_result(module.change_descriptor_set_number(&set, new_set_number))
+    })
+}
+
+/// # Safety
+///
+/// `p_module` must be null or a live module, and `p_input_variable` null
+/// or one of its input variables.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectChangeInputVariableLocation(
+    p_module: *mut ffi::SpvReflectShaderModule,
+    p_input_variable: *const ffi::SpvReflectInterfaceVariable,
+    new_location: u32,
+) -> SpvReflectResult {
+    let spirv_id = match p_input_variable.as_ref() {
+        Some(variable) => variable.spirv_id,
+        None => return NULL_POINTER,
+    };
+    change(p_module, |module| {
+        let variable = reflected_variable(module, true, spirv_id)?;
+        change_result(module.change_input_variable_location(&variable, new_location))
+    })
+}
+
+/// # Safety
+///
+/// `p_module` must be null or a live module, and `p_output_variable` null
+/// or one of its output variables.
+#[no_mangle]
+pub unsafe extern "C" fn spvReflectChangeOutputVariableLocation(
+    p_module: *mut ffi::SpvReflectShaderModule,
+    p_output_variable: *const ffi::SpvReflectInterfaceVariable,
+    new_location: u32,
+) -> SpvReflectResult {
+    let spirv_id = match p_output_variable.as_ref() {
+        Some(variable) => variable.spirv_id,
+        None => return NULL_POINTER,
+    };
+    change(p_module, |module| {
+        let variable = reflected_variable(module, false, spirv_id)?;
+        change_result(module.change_output_variable_location(&variable, new_location))
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn spvReflectSourceLanguage(source_lang: ffi::SpvSourceLanguage) -> *const c_char {
+    let name: &'static [u8] = match source_lang {
+        ffi::SpvSourceLanguage__SpvSourceLanguageESSL => b"ESSL\0",
+        ffi::SpvSourceLanguage__SpvSourceLanguageGLSL => b"GLSL\0",
+        ffi::SpvSourceLanguage__SpvSourceLanguageOpenCL_C => b"OpenCL_C\0",
+        ffi::SpvSourceLanguage__SpvSourceLanguageOpenCL_CPP => b"OpenCL_CPP\0",
+        ffi::SpvSourceLanguage__SpvSourceLanguageHLSL => b"HLSL\0",
+        _ => b"Unknown\0",
+    };
+    name.as_ptr() as *const c_char
+}
@@ -0,0 +1,429 @@
+/*
+ * Calls every function of the SPIRV-Reflect API exported by
+ * libspirv_reflect_rs on the module given on the command line, checking the
+ * getters against the enumerations and the changes against a reload of the
+ * changed code. Built and run by smoke.sh.
+ */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "spirv_reflect.h"
+
+#define CHECK(condition)                                                   \
+  do {                                                                     \
+    if (!(condition)) {                                                    \
+      fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__,    \
+              #condition);                                                 \
+      exit(1);                                                             \
+    }                                                                      \
+  } while (0)
+
+#define CHECK_RESULT(call, expected) CHECK((call) == (expected))
+#define CHECK_SUCCESS(call) CHECK_RESULT(call, SPV_REFLECT_RESULT_SUCCESS)
+
+/* Fills a new array the way callers of SPIRV-Reflect do, asking for the
+ * count first. */
+#define ENUMERATE(items, count, function, ...)                             \
+  do {                                                                     \
+    CHECK_SUCCESS(function(__VA_ARGS__, &(count), NULL));                  \
+    (items) = calloc((count) + 1, sizeof *(items));                        \
+    CHECK((items) != NULL);                                                \
+    CHECK_SUCCESS(function(__VA_ARGS__, &(count), (items)));               \
+  } while (0)
+
+static const char* const kMissing = "no such entry point";
+
+static void* read_file(const char* path, size_t* p_size) {
+  FILE* file = fopen(path, "rb");
+  CHECK(file != NULL);
+  CHECK(fseek(file, 0, SEEK_END) == 0);
+  long size = ftell(file);
+  CHECK(size > 0);
+  CHECK(fseek(file, 0, SEEK_SET) == 0);
+  void* data = malloc((size_t)size);
+  CHECK(data != NULL);
+  CHECK(fread(data, 1, (size_t)size, file) == (size_t)size);
+  fclose(file);
+  *p_size = (size_t)size;
+  return data;
+}
+
+static int has_location(const SpvReflectInterfaceVariable* p_variable) {
+  return (p_variable->decoration_flags & SPV_REFLECT_DECORATION_BUILT_IN) == 0 &&
+         p_variable->location != UINT32_MAX;
+}
+
+static void check_bindings(const SpvReflectShaderModule* p_module) {
+  SpvReflectResult result;
+  uint32_t count = 0;
+  SpvReflectDescriptorBinding** bindings;
+  ENUMERATE(bindings, count, spvReflectEnumerateDescriptorBindings, p_module);
+  CHECK(count == p_module->descriptor_binding_count);
+  for (uint32_t i = 0; i < count; ++i) {
+    const SpvReflectDescriptorBinding* p_binding = spvReflectGetDescriptorBinding(
+        p_module, bindings[i]->binding, bindings[i]->set, &result);
+    CHECK_SUCCESS(result);
+    CHECK(p_binding->binding == bindings[i]->binding);
+    CHECK(p_binding->set == bindings[i]->set);
+  }
+  spvReflectGetDescriptorBinding(p_module, UINT32_MAX - 1, 0, &result);
+  CHECK_RESULT(result, SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+  if (count > 0) {
+    uint32_t wrong_count = count + 1;
+    CHECK_RESULT(
+        spvReflectEnumerateDescriptorBindings(p_module, &wrong_count, bindings),
+        SPV_REFLECT_RESULT_ERROR_COUNT_MISMATCH);
+  }
+  free(bindings);
+
+  SpvReflectDescriptorSet** sets;
+  ENUMERATE(sets, count, spvReflectEnumerateDescriptorSets, p_module);
+  CHECK(count == p_module->descriptor_set_count);
+  for (uint32_t i = 0; i < count; ++i) {
+    CHECK(spvReflectGetDescriptorSet(p_module, sets[i]->set, &result) == sets[i]);
+    CHECK_SUCCESS(result);
+    for (uint32_t j = 0; j < sets[i]->binding_count; ++j) {
+      CHECK(sets[i]->bindings[j]->set == sets[i]->set);
+    }
+  }
+  spvReflectGetDescriptorSet(p_module, UINT32_MAX - 1, &result);
+  CHECK_RESULT(result, SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+  free(sets);
+}
+
+static void check_variables(const SpvReflectShaderModule* p_module, int input) {
+  SpvReflectResult result;
+  uint32_t count = 0;
+  SpvReflectInterfaceVariable** variables;
+  if (input) {
+    ENUMERATE(variables, count, spvReflectEnumerateInputVariables, p_module);
+    CHECK(count == p_module->input_variable_count);
+  } else {
+    ENUMERATE(variables, count, spvReflectEnumerateOutputVariables, p_module);
+    CHECK(count == p_module->output_variable_count);
+  }
+  for (uint32_t i = 0; i < count; ++i) {
+    const SpvReflectInterfaceVariable* p_variable = variables[i];
+    if (has_location(p_variable)) {
+      const SpvReflectInterfaceVariable* p_found =
+          input ? spvReflectGetInputVariableByLocation(
+                      p_module, p_variable->location, &result)
+                : spvReflectGetOutputVariableByLocation(
+                      p_module, p_variable->location, &result);
+      CHECK_SUCCESS(result);
+      CHECK(p_found->location == p_variable->location);
+      p_found = input ? spvReflectGetInputVariable(p_module, p_variable->location,
+                                                   &result)
+                      : spvReflectGetOutputVariable(
+                            p_module, p_variable->location, &result);
+      CHECK_SUCCESS(result);
+      CHECK(p_found->location == p_variable->location);
+    }
+    if (p_variable->semantic != NULL) {
+      const SpvReflectInterfaceVariable* p_found =
+          input ? spvReflectGetInputVariableBySemantic(
+                      p_module, p_variable->semantic, &result)
+                : spvReflectGetOutputVariableBySemantic(
+                      p_module, p_variable->semantic, &result);
+      CHECK_SUCCESS(result);
+      CHECK(strcmp(p_found->semantic, p_variable->semantic) == 0);
+    }
+  }
+  if (input) {
+    spvReflectGetInputVariableByLocation(p_module, UINT32_MAX, &result);
+    CHECK_RESULT(result, SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+    spvReflectGetInputVariableBySemantic(p_module, "", &result);
+    CHECK_RESULT(result, SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+    spvReflectGetInputVariableBySemantic(p_module, NULL, &result);
+    CHECK_RESULT(result, SPV_REFLECT_RESULT_ERROR_NULL_POINTER);
+  } else {
+    spvReflectGetOutputVariableByLocation(p_module, UINT32_MAX, &result);
+    CHECK_RESULT(result, SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+    spvReflectGetOutputVariableBySemantic(p_module, "", &result);
+    CHECK_RESULT(result, SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+    spvReflectGetOutputVariableBySemantic(p_module, NULL, &result);
+    CHECK_RESULT(result, SPV_REFLECT_RESULT_ERROR_NULL_POINTER);
+  }
+  free(variables);
+}
+
+static void check_push_constants(const SpvReflectShaderModule* p_module) {
+  SpvReflectResult result;
+  uint32_t count = 0;
+  SpvReflectBlockVariable** blocks;
+  ENUMERATE(blocks, count, spvReflectEnumeratePushConstantBlocks, p_module);
+  CHECK(count == p_module->push_constant_block_count);
+  for (uint32_t i = 0; i < count; ++i) {
+    CHECK(spvReflectGetPushConstantBlock(p_module, i, &result) == blocks[i]);
+    CHECK_SUCCESS(result);
+    CHECK(spvReflectGetPushConstant(p_module, i, &result) == blocks[i]);
+    CHECK_SUCCESS(result);
+    const SpvReflectTypeDescription* p_type = blocks[i]->type_description;
+    CHECK(p_type == NULL || p_type->member_count == blocks[i]->member_count);
+  }
+  spvReflectGetPushConstantBlock(p_module, count, &result);
+  CHECK_RESULT(result, SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+  free(blocks);
+
+  SpvReflectBlockVariable** constants;
+  uint32_t constant_count = 0;
+  ENUMERATE(constants, constant_count, spvReflectEnumeratePushConstants,
+            p_module);
+  CHECK(constant_count == count);
+  free(constants);
+}
+
+static void check_entry_point(const SpvReflectShaderModule* p_module,
+                              const SpvReflectEntryPoint* p_entry_point) {
+  const char* name = p_entry_point->name;
+  SpvReflectResult result;
+  uint32_t count = 0;
+  CHECK(spvReflectGetEntryPoint(p_module, name) == p_entry_point);
+
+  SpvReflectDescriptorBinding** bindings;
+  ENUMERATE(bindings, count, spvReflectEnumerateEntryPointDescriptorBindings,
+            p_module, name);
+  for (uint32_t i = 0; i < count; ++i) {
+    const SpvReflectDescriptorBinding* p_binding =
+        spvReflectGetEntryPointDescriptorBinding(
+            p_module, name, bindings[i]->binding, bindings[i]->set, &result);
+    CHECK_SUCCESS(result);
+    CHECK(p_binding->binding == bindings[i]->binding);
+  }
+  free(bindings);
+
+  SpvReflectDescriptorSet** sets;
+  ENUMERATE(sets, count, spvReflectEnumerateEntryPointDescriptorSets, p_module,
+            name);
+  CHECK(count == p_entry_point->descriptor_set_count);
+  for (uint32_t i = 0; i < count; ++i) {
+    CHECK(spvReflectGetEntryPointDescriptorSet(p_module, name, sets[i]->set,
+                                               &result) == sets[i]);
+    CHECK_SUCCESS(result);
+  }
+  free(sets);
+
+  SpvReflectInterfaceVariable** variables;
+  ENUMERATE(variables, count, spvReflectEnumerateEntryPointInputVariables,
+            p_module, name);
+  CHECK(count == p_entry_point->input_variable_count);
+  for (uint32_t i = 0; i < count; ++i) {
+    if (has_location(variables[i])) {
+      spvReflectGetEntryPointInputVariableByLocation(
+          p_module, name, variables[i]->location, &result);
+      CHECK_SUCCESS(result);
+    }
+    if (variables[i]->semantic != NULL) {
+      spvReflectGetEntryPointInputVariableBySemantic(
+          p_module, name, variables[i]->semantic, &result);
+      CHECK_SUCCESS(result);
+    }
+  }
+  free(variables);
+
+  ENUMERATE(variables, count, spvReflectEnumerateEntryPointOutputVariables,
+            p_module, name);
+  CHECK(count == p_entry_point->output_variable_count);
+  for (uint32_t i = 0; i < count; ++i) {
+    if (has_location(variables[i])) {
+      spvReflectGetEntryPointOutputVariableByLocation(
+          p_module, name, variables[i]->location, &result);
+      CHECK_SUCCESS(result);
+    }
+    if (variables[i]->semantic != NULL) {
+      spvReflectGetEntryPointOutputVariableBySemantic(
+          p_module, name, variables[i]->semantic, &result);
+      CHECK_SUCCESS(result);
+    }
+  }
+  free(variables);
+
+  SpvReflectBlockVariable** blocks;
+  ENUMERATE(blocks, count, spvReflectEnumerateEntryPointPushConstantBlocks,
+            p_module, name);
+  const SpvReflectBlockVariable* p_block =
+      spvReflectGetEntryPointPushConstantBlock(p_module, name, &result);
+  if (count > 0) {
+    CHECK_SUCCESS(result);
+    CHECK(p_block == blocks[0]);
+  } else {
+    CHECK_RESULT(result, SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+  }
+  free(blocks);
+}
+
+static void check_missing_entry_point(const SpvReflectShaderModule* p_module) {
+  SpvReflectResult result;
+  uint32_t count = 0;
+  CHECK(spvReflectGetEntryPoint(p_module, kMissing) == NULL);
+  CHECK_RESULT(spvReflectEnumerateEntryPointDescriptorBindings(
+                   p_module, kMissing, &count, NULL),
+               SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+  CHECK_RESULT(spvReflectEnumerateEntryPointDescriptorSets(p_module, kMissing,
+                                                           &count, NULL),
+               SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+  CHECK_RESULT(spvReflectEnumerateEntryPointInputVariables(p_module, kMissing,
+                                                           &count, NULL),
+               SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+  CHECK_RESULT(spvReflectEnumerateEntryPointOutputVariables(p_module, kMissing,
+                                                            &count, NULL),
+               SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+  CHECK_RESULT(spvReflectEnumerateEntryPointPushConstantBlocks(
+                   p_module, kMissing, &count, NULL),
+               SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+  spvReflectGetEntryPointDescriptorBinding(p_module, kMissing, 0, 0, &result);
+  CHECK_RESULT(result, SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+  spvReflectGetEntryPointDescriptorSet(p_module, kMissing, 0, &result);
+  CHECK_RESULT(result, SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+  spvReflectGetEntryPointInputVariableByLocation(p_module, kMissing, 0, &result);
+  CHECK_RESULT(result, SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+  spvReflectGetEntryPointInputVariableBySemantic(p_module, kMissing, "A",
+                                                 &result);
+  CHECK_RESULT(result, SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+  spvReflectGetEntryPointOutputVariableByLocation(p_module, kMissing, 0,
+                                                  &result);
+  CHECK_RESULT(result, SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+  spvReflectGetEntryPointOutputVariableBySemantic(p_module, kMissing, "A",
+                                                  &result);
+  CHECK_RESULT(result, SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+  spvReflectGetEntryPointPushConstantBlock(p_module, kMissing, &result);
+  CHECK_RESULT(result, SPV_REFLECT_RESULT_ERROR_ELEMENT_NOT_FOUND);
+}
+
+static const SpvReflectInterfaceVariable* first_located(
+    const SpvReflectInterfaceVariable* p_variables, uint32_t count) {
+  for (uint32_t i = 0; i < count; ++i) {
+    if (has_location(&p_variables[i])) {
+      return &p_variables[i];
+    }
+  }
+  return NULL;
+}
+
+/* Changes the numbers of the first binding and the location of the first
+ * input and output, checking them in a module reloaded from the changed
+ * code. */
+static void check_changes(SpvReflectShaderModule* p_module) {
+  SpvReflectResult result;
+  const SpvReflectDescriptorBinding* p_binding =
+      p_module->descriptor_binding_count > 0 ? &p_module->descriptor_bindings[0]
+                                             : NULL;
+  const SpvReflectInterfaceVariable* p_input =
+      first_located(p_module->input_variables, p_module->input_variable_count);
+  const SpvReflectInterfaceVariable* p_output = first_located(
+      p_module->output_variables, p_module->output_variable_count);
+
+  if (p_binding != NULL) {
+    uint32_t binding_number = p_binding->binding;
+    uint32_t set_number = p_binding->set;
+    CHECK_SUCCESS(spvReflectChangeDescriptorBindingNumbers(
+        p_module, p_binding, binding_number + 100,
+        SPV_REFLECT_SET_NUMBER_DONT_CHANGE));
+    CHECK(p_binding->binding == binding_number + 100);
+    CHECK(p_binding->set == set_number);
+    CHECK_SUCCESS(spvReflectChangeDescriptorBindingNumber(
+        p_module, p_binding, SPV_REFLECT_BINDING_NUMBER_DONT_CHANGE,
+        set_number + 1));
+    CHECK(p_binding->binding == binding_number + 100);
+    CHECK(p_binding->set == set_number + 1);
+    const SpvReflectDescriptorSet* p_set =
+        spvReflectGetDescriptorSet(p_module, set_number + 1, &result);
+    CHECK_SUCCESS(result);
+    CHECK_SUCCESS(
+        spvReflectChangeDescriptorSetNumber(p_module, p_set, set_number + 2));
+    CHECK(p_binding->set == set_number + 2);
+    spvReflectGetDescriptorBinding(p_module, binding_number + 100,
+                                   set_number + 2, &result);
+    CHECK_SUCCESS(result);
+  } else {
+    CHECK_RESULT(spvReflectChangeDescriptorBindingNumbers(p_module, NULL, 0, 0),
+                 SPV_REFLECT_RESULT_ERROR_NULL_POINTER);
+    CHECK_RESULT(spvReflectChangeDescriptorBindingNumber(p_module, NULL, 0, 0),
+                 SPV_REFLECT_RESULT_ERROR_NULL_POINTER);
+    CHECK_RESULT(spvReflectChangeDescriptorSetNumber(p_module, NULL, 0),
+                 SPV_REFLECT_RESULT_ERROR_NULL_POINTER);
+  }
+  if (p_input != NULL) {
+    uint32_t location = p_input->location + 8;
+    CHECK_SUCCESS(
+        spvReflectChangeInputVariableLocation(p_module, p_input, location));
+    CHECK(p_input->location == location);
+    spvReflectGetInputVariableByLocation(p_module, location, &result);
+    CHECK_SUCCESS(result);
+  } else {
+    CHECK_RESULT(spvReflectChangeInputVariableLocation(p_module, NULL, 0),
+                 SPV_REFLECT_RESULT_ERROR_NULL_POINTER);
+  }
+  if (p_output != NULL) {
+    uint32_t location = p_output->location + 8;
+    CHECK_SUCCESS(
+        spvReflectChangeOutputVariableLocation(p_module, p_output, location));
+    CHECK(p_output->location == location);
+    spvReflectGetOutputVariableByLocation(p_module, location, &result);
+    CHECK_SUCCESS(result);
+  } else {
+    CHECK_RESULT(spvReflectChangeOutputVariableLocation(p_module, NULL, 0),
+                 SPV_REFLECT_RESULT_ERROR_NULL_POINTER);
+  }
+
+  SpvReflectShaderModule reloaded;
+  CHECK_SUCCESS(spvReflectCreateShaderModule(spvReflectGetCodeSize(p_module),
+                                             spvReflectGetCode(p_module),
+                                             &reloaded));
+  if (p_binding != NULL) {
+    const SpvReflectDescriptorBinding* p_reloaded = spvReflectGetDescriptorBinding(
+        &reloaded, p_binding->binding, p_binding->set, &result);
+    CHECK_SUCCESS(result);
+    CHECK(p_reloaded->spirv_id == p_binding->spirv_id);
+  }
+  if (p_input != NULL) {
+    spvReflectGetInputVariableByLocation(&reloaded, p_input->location, &result);
+    CHECK_SUCCESS(result);
+  }
+  if (p_output != NULL) {
+    spvReflectGetOutputVariableByLocation(&reloaded, p_output->location,
+                                          &result);
+    CHECK_SUCCESS(result);
+  }
+  spvReflectDestroyShaderModule(&reloaded);
+}
+
+int main(int argc, char** argv) {
+  if (argc != 2) {
+    fprintf(stderr, "usage: %s module.spirv\n", argv[0]);
+    return 2;
+  }
+  size_t size = 0;
+  void* code = read_file(argv[1], &size);
+
+  SpvReflectShaderModule module;
+  CHECK_SUCCESS(spvReflectCreateShaderModule(size, code, &module));
+  CHECK(spvReflectGetCodeSize(&module) == size);
+  CHECK(memcmp(spvReflectGetCode(&module), code, size) == 0);
+  CHECK(spvReflectSourceLanguage(module.source_language) != NULL);
+  CHECK(strcmp(spvReflectSourceLanguage(SpvSourceLanguageGLSL), "GLSL") == 0);
+
+  check_bindings(&module);
+  check_variables(&module, 1);
+  check_variables(&module, 0);
+  check_push_constants(&module);
+  for (uint32_t i = 0; i < module.entry_point_count; ++i) {
+    check_entry_point(&module, &module.entry_points[i]);
+  }
+  check_missing_entry_point(&module);
+  check_changes(&module);
+
+  spvReflectDestroyShaderModule(&module);
+  CHECK(module._internal == NULL);
+
+  SpvReflectShaderModule deprecated;
+  CHECK_SUCCESS(spvReflectGetShaderModule(size, code, &deprecated));
+  spvReflectDestroyShaderModule(&deprecated);
+
+  CHECK_RESULT(spvReflectCreateShaderModule(size, NULL, &module),
+               SPV_REFLECT_RESULT_ERROR_NULL_POINTER);
+  free(code);
+  printf("%s: ok\n", argv[1]);
+  return 0;
+}
@@ -0,0 +1,13 @@
+#!/bin/sh
+# Builds the static library and runs smoke.c against it on each module given,
+# linking the way a C engine would: with upstream's spirv_reflect.h and no
+# spirv_reflect.c.
+set -e
+capi=$(cd "$(dirname "$0")/.." && pwd)
+cargo build --manifest-path "$capi/Cargo.toml"
+cc -std=c99 -Wall -Wextra -Werror -I "$capi/../vendor" "$capi/tests/smoke.c" \
+    "$capi/target/debug/libspirv_reflect_rs.a" -lstdc++ -lpthread -ldl -lm \
+    -o "$capi/target/debug/smoke"
+for module in "$@"; do
+    "$capi/target/debug/smoke" "$module"
+done
@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use spirv_reflect::ShaderModule;
+
+fuzz_target!(|data: &[u8]| {
+    // Whatever decodes encodes to bytes that read back to the same bytes.
+    if let Ok(cache) = ShaderModule::from_cache_bytes(data) {
+        let bytes = cache.to_cache_bytes();
+        let cache = ShaderModule::from_cache_bytes(&bytes).unwrap();
+        assert_eq!(cache.to_cache_bytes(), bytes);
+    }
+});
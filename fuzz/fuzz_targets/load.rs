@@ -0,0 +1,61 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use spirv_reflect::ShaderModule;
+
+fn reflect_entry_point(module: &ShaderModule, entry_point: Option<&str>) {
+    let _ = module.enumerate_descriptor_bindings(entry_point);
+    let _ = module.enumerate_descriptor_sets(entry_point);
+    let _ = module.enumerate_input_variables(entry_point);
+    let _ = module.enumerate_output_variables(entry_point);
+    let _ = module.enumerate_push_constant_blocks(entry_point);
+    let _ = module.enumerate_input_locations(entry_point);
+    let _ = module.enumerate_output_locations(entry_point);
+    let _ = module.enumerate_global_variables(entry_point);
+    let _ = module.enumerate_kernel_arguments(entry_point);
+    let _ = module.enumerate_execution_modes(entry_point);
+    let _ = module.enumerate_binding_access(entry_point);
+    let _ = module.enumerate_push_constant_taint(entry_point);
+    let _ = module.get_metrics(entry_point);
+    let _ = module.get_control_flow(entry_point);
+    let _ = module.get_workgroup_size(entry_point);
+    let _ = module.get_float_controls(entry_point);
+    let _ = module.get_mesh_traits(entry_point);
+    let _ = module.get_tessellation_traits(entry_point);
+    let _ = module.get_fragment_output_flags(entry_point);
+    let _ = module.get_discard_flags(entry_point);
+    let _ = module.get_helper_invocation_flags(entry_point);
+    let _ = module.get_input_attachment_edges(entry_point);
+    let _ = module.get_required_features(entry_point);
+    let _ = module.call_graph_dot(entry_point);
+}
+
+fuzz_target!(|data: &[u8]| {
+    let module = match ShaderModule::load_u8_data(data) {
+        Ok(module) => module,
+        Err(_) => return,
+    };
+    reflect_entry_point(&module, None);
+    for entry_point in module.enumerate_entry_points().unwrap_or_default() {
+        reflect_entry_point(&module, Some(&entry_point.name));
+        if let Ok(extracted) = module.extract_entry_point(&entry_point.name) {
+            reflect_entry_point(&extracted, None);
+        }
+    }
+    let _ = module.enumerate_strings();
+    let _ = module.enumerate_sources();
+    let _ = module.enumerate_functions();
+    let _ = module.enumerate_function_control_flow();
+    let _ = module.enumerate_linkage_symbols();
+    let _ = module.enumerate_image_usage();
+    let _ = module.unused_objects();
+    let _ = module.check_interface_completeness();
+    let _ = module.disassemble_annotated();
+    let _ = module.describe();
+
+    // The cache reads back to the same bytes.
+    if let Ok(bytes) = module.to_cache_bytes() {
+        let cache = ShaderModule::from_cache_bytes(&bytes).unwrap();
+        assert_eq!(cache.to_cache_bytes(), bytes);
+    }
+});
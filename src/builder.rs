@@ -0,0 +1,177 @@
+//! Options for loading a `ShaderModule`: how strict to be about suspicious
+//! modules, which optional analyses to run and which generator quirks to
+//! work around.
+
+use crate::types::ReflectGenerator;
+use crate::ShaderModule;
+
+/// How `ShaderModuleBuilder` treats a module that reflects but looks wrong.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Strictness {
+    /// Fail to load when `ShaderModule::warnings` finds anything.
+    Strict,
+    /// Load anyway and keep the findings in `ShaderModule::warnings`.
+    #[default]
+    Lenient,
+    /// Reflect descriptors only, skipping every optional analysis.
+    Minimal,
+}
+
+bitflags! {
+    /// Optional analyses run on top of the C reflection.
+    pub struct ReflectAnalysisFlags: u32 {
+        const NONE = 0;
+        /// `ShaderModule::warnings`.
+        const WARNINGS = 1;
+        /// `ReflectEntryPoint::ext_inst_usage`.
+        const EXT_INST_USAGE = 2;
+        /// `ReflectEntryPoint::barriers`.
+        const BARRIERS = 4;
+    }
+}
+
+impl Default for ReflectAnalysisFlags {
+    fn default() -> Self {
+        ReflectAnalysisFlags::all()
+    }
+}
+
+bitflags! {
+    /// Workarounds for what known generators emit, each applied only to
+    /// modules from the generators it names.
+    pub struct ReflectQuirkFlags: u32 {
+        const NONE = 0;
+        /// glslang and shaderc: a buffer block whose members are all
+        /// `NonWritable`, as glslang emits `readonly` blocks, is reflected
+        /// `NON_WRITABLE` itself.
+        const GLSLANG_READONLY_MEMBERS = 1;
+        /// glslang, shaderc, DXC and unknown generators: UAV counters are
+        /// also matched by name, see `ShaderModuleBuilder::counter_name_suffix`.
+        const LEGACY_COUNTER_NAMES = 2;
+        /// rspirv: the structs rust-gpu wraps buffers in have no `OpName`, so
+        /// `ReflectWarning::UnnamedBlock` is not reported.
+        const RSPIRV_UNNAMED_BLOCKS = 4;
+    }
+}
+
+impl Default for ReflectQuirkFlags {
+    fn default() -> Self {
+        ReflectQuirkFlags::all()
+    }
+}
+
+impl ReflectQuirkFlags {
+    /// The quirks that apply to modules from `generator`.
+    pub fn for_generator(generator: ReflectGenerator) -> Self {
+        match generator {
+            ReflectGenerator::KhronosGlslangReferenceFrontEnd
+            | ReflectGenerator::GoogleShadercOverGlslang => {
+                ReflectQuirkFlags::GLSLANG_READONLY_MEMBERS
+                    | ReflectQuirkFlags::LEGACY_COUNTER_NAMES
+            }
+            ReflectGenerator::GoogleSpiregg | ReflectGenerator::Unknown => {
+                ReflectQuirkFlags::LEGACY_COUNTER_NAMES
+            }
+            ReflectGenerator::GoogleRspirv => ReflectQuirkFlags::RSPIRV_UNNAMED_BLOCKS,
+            _ => ReflectQuirkFlags::NONE,
+        }
+    }
+}
+
+/// Loads shader modules with the given strictness and analyses; start from
+/// `ShaderModule::builder()`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShaderModuleBuilder {
+    strictness: Strictness,
+    analyses: ReflectAnalysisFlags,
+    quirks: ReflectQuirkFlags,
+    pub(crate) entry_point: Option<String>,
+    pub(crate) counter_name_suffix: Option<String>,
+}
+
+impl Default for ShaderModuleBuilder {
+    fn default() -> Self {
+        ShaderModuleBuilder {
+            strictness: Strictness::default(),
+            analyses: ReflectAnalysisFlags::default(),
+            quirks: ReflectQuirkFlags::default(),
+            entry_point: None,
+            counter_name_suffix: Some("@count".to_owned()),
+        }
+    }
+}
+
+impl ShaderModuleBuilder {
+    /// Lenient, with every analysis enabled, as `ShaderModule::load_u8_data`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn strictness(mut self, strictness: Strictness) -> Self {
+        self.strictness = strictness;
+        self
+    }
+
+    /// Which optional analyses to run. `Strict` always collects warnings and
+    /// `Minimal` runs none.
+    pub fn analyses(mut self, analyses: ReflectAnalysisFlags) -> Self {
+        self.analyses = analyses;
+        self
+    }
+
+    /// Which generator quirks to work around, all by default.
+    pub fn quirks(mut self, quirks: ReflectQuirkFlags) -> Self {
+        self.quirks = quirks;
+        self
+    }
+
+    /// Reflects only the entry point called `name`: `entry_points` lists just
+    /// it, and module-level accessors such as `get_shader_stage` and
+    /// `input_variables`, and methods passed `None` for the entry point, use
+    /// it instead of the first entry point. This only filters what is
+    /// reported: the whole module is still parsed, so loading is no faster.
+    pub fn for_entry_point(mut self, name: &str) -> Self {
+        self.entry_point = Some(name.to_owned());
+        self
+    }
+
+    /// Suffix that the name of a legacy UAV counter adds to the name of its
+    /// buffer, `@count` by default, for counters without a `CounterBuffer`
+    /// decoration. `None` associates counters through the decoration only,
+    /// as do generators without the `LEGACY_COUNTER_NAMES` quirk.
+    pub fn counter_name_suffix(mut self, suffix: Option<&str>) -> Self {
+        self.counter_name_suffix = suffix.map(str::to_owned);
+        self
+    }
+
+    /// The analyses the loaded module runs, after applying the strictness.
+    pub fn enabled_analyses(&self) -> ReflectAnalysisFlags {
+        match self.strictness {
+            Strictness::Strict => self.analyses | ReflectAnalysisFlags::WARNINGS,
+            Strictness::Lenient => self.analyses,
+            Strictness::Minimal => ReflectAnalysisFlags::NONE,
+        }
+    }
+
+    /// The quirks worked around for a module from `generator`.
+    pub fn enabled_quirks(&self, generator: ReflectGenerator) -> ReflectQuirkFlags {
+        self.quirks & ReflectQuirkFlags::for_generator(generator)
+    }
+
+    pub fn load_u8_data(&self, spv_data: &[u8]) -> Result<ShaderModule, &'static str> {
+        let module = crate::create_shader_module_with(spv_data, self.clone())?;
+        if let Some(ref entry_point) = self.entry_point {
+            if !module.has_entry_point(entry_point) {
+                return Err("Entry point not found");
+            }
+        }
+        if self.strictness == Strictness::Strict && !module.warnings().is_empty() {
+            return Err("Shader module has warnings");
+        }
+        Ok(module)
+    }
+
+    pub fn load_u32_data(&self, spv_data: &[u32]) -> Result<ShaderModule, &'static str> {
+        self.load_u8_data(crate::words_as_bytes(spv_data))
+    }
+}
@@ -0,0 +1,664 @@
+use crate::types::*;
+use num_traits::cast::FromPrimitive;
+
+const CACHE_MAGIC: &[u8; 4] = b"SPRC";
+const CACHE_VERSION: u32 = 1;
+/// Lists nested in lists, far beyond what reflection produces, so that
+/// decoding crafted data cannot overflow the stack.
+const CACHE_NESTING_LIMIT: u32 = 256;
+
+/// Reflection data restored from `ShaderModule::to_cache_bytes`.
+///
+/// The SPIR-V code itself is not part of the cache, so this holds plain data
+/// instead of a live `ShaderModule`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectModuleCache {
+    pub generator: ReflectGenerator,
+    pub shader_stage: ReflectShaderStageFlags,
+    pub source_language: u32,
+    pub source_language_version: u32,
+    pub source_file: String,
+    /// Every `OpSource`, the one of `source_file` first.
+    pub sources: Vec<ReflectSource>,
+    pub entry_point_name: String,
+    pub entry_points: Vec<ReflectEntryPoint>,
+    pub descriptor_sets: Vec<ReflectDescriptorSet>,
+    pub push_constant_blocks: Vec<ReflectBlockVariable>,
+    pub input_variables: Vec<ReflectInterfaceVariable>,
+    pub output_variables: Vec<ReflectInterfaceVariable>,
+}
+
+impl ReflectModuleCache {
+    pub fn get_source_language(&self) -> spirv_headers::SourceLanguage {
+        match spirv_headers::SourceLanguage::from_u32(self.source_language) {
+            Some(language) => language,
+            None => spirv_headers::SourceLanguage::Unknown,
+        }
+    }
+
+    pub fn to_cache_bytes(&self) -> Vec<u8> {
+        let mut writer = CacheWriter { data: Vec::new() };
+        writer.data.extend_from_slice(CACHE_MAGIC);
+        CACHE_VERSION.encode(&mut writer);
+        self.generator.encode(&mut writer);
+        self.shader_stage.encode(&mut writer);
+        self.source_language.encode(&mut writer);
+        self.source_language_version.encode(&mut writer);
+        self.source_file.encode(&mut writer);
+        self.sources.encode(&mut writer);
+        self.entry_point_name.encode(&mut writer);
+        self.entry_points.encode(&mut writer);
+        self.descriptor_sets.encode(&mut writer);
+        self.push_constant_blocks.encode(&mut writer);
+        self.input_variables.encode(&mut writer);
+        self.output_variables.encode(&mut writer);
+        writer.data
+    }
+
+    pub fn from_cache_bytes(data: &[u8]) -> Result<ReflectModuleCache, &'static str> {
+        if data.len() < CACHE_MAGIC.len() || &data[..CACHE_MAGIC.len()] != CACHE_MAGIC {
+            return Err("Invalid cache magic");
+        }
+        let mut reader = CacheReader {
+            data: &data[CACHE_MAGIC.len()..],
+            depth: 0,
+        };
+        if u32::decode(&mut reader)? != CACHE_VERSION {
+            return Err("Unsupported cache version");
+        }
+        let cache = ReflectModuleCache {
+            generator: Decode::decode(&mut reader)?,
+            shader_stage: Decode::decode(&mut reader)?,
+            source_language: Decode::decode(&mut reader)?,
+            source_language_version: Decode::decode(&mut reader)?,
+            source_file: Decode::decode(&mut reader)?,
+            sources: Decode::decode(&mut reader)?,
+            entry_point_name: Decode::decode(&mut reader)?,
+            entry_points: Decode::decode(&mut reader)?,
+            descriptor_sets: Decode::decode(&mut reader)?,
+            push_constant_blocks: Decode::decode(&mut reader)?,
+            input_variables: Decode::decode(&mut reader)?,
+            output_variables: Decode::decode(&mut reader)?,
+        };
+        if !reader.data.is_empty() {
+            return Err("Trailing cache data");
+        }
+        Ok(cache)
+    }
+}
+
+struct CacheWriter {
+    data: Vec<u8>,
+}
+
+struct CacheReader<'a> {
+    data: &'a [u8],
+    depth: u32,
+}
+
+impl<'a> CacheReader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], &'static str> {
+        if self.data.len() < count {
+            return Err("Truncated cache data");
+        }
+        let (head, tail) = self.data.split_at(count);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn length(&mut self) -> Result<usize, &'static str> {
+        let length = u32::decode(self)? as usize;
+        // Every encoded element takes at least one byte.
+        if length > self.data.len() {
+            return Err("Truncated cache data");
+        }
+        Ok(length)
+    }
+}
+
+trait Encode {
+    fn encode(&self, writer: &mut CacheWriter);
+}
+
+trait Decode: Sized {
+    fn decode(reader: &mut CacheReader) -> Result<Self, &'static str>;
+}
+
+impl Encode for u32 {
+    fn encode(&self, writer: &mut CacheWriter) {
+        writer.data.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl Decode for u32 {
+    fn decode(reader: &mut CacheReader) -> Result<Self, &'static str> {
+        let bytes = reader.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+impl Encode for bool {
+    fn encode(&self, writer: &mut CacheWriter) {
+        writer.data.push(*self as u8);
+    }
+}
+
+impl Decode for bool {
+    fn decode(reader: &mut CacheReader) -> Result<Self, &'static str> {
+        match reader.take(1)?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err("Invalid cache data"),
+        }
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, writer: &mut CacheWriter) {
+        (self.len() as u32).encode(writer);
+        writer.data.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl Decode for String {
+    fn decode(reader: &mut CacheReader) -> Result<Self, &'static str> {
+        let length = reader.length()?;
+        let bytes = reader.take(length)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| "Invalid cache string")
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode(&self, writer: &mut CacheWriter) {
+        (self.len() as u32).encode(writer);
+        for item in self {
+            item.encode(writer);
+        }
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode(reader: &mut CacheReader) -> Result<Self, &'static str> {
+        let length = reader.length()?;
+        if reader.depth == CACHE_NESTING_LIMIT {
+            return Err("Cache data nested too deeply");
+        }
+        reader.depth += 1;
+        let mut items = Vec::with_capacity(length);
+        for _ in 0..length {
+            items.push(T::decode(reader)?);
+        }
+        reader.depth -= 1;
+        Ok(items)
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode(&self, writer: &mut CacheWriter) {
+        self.is_some().encode(writer);
+        if let Some(value) = self {
+            value.encode(writer);
+        }
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    fn decode(reader: &mut CacheReader) -> Result<Self, &'static str> {
+        if bool::decode(reader)? {
+            Ok(Some(T::decode(reader)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl Encode for (u32, u32) {
+    fn encode(&self, writer: &mut CacheWriter) {
+        self.0.encode(writer);
+        self.1.encode(writer);
+    }
+}
+
+impl Decode for (u32, u32) {
+    fn decode(reader: &mut CacheReader) -> Result<Self, &'static str> {
+        Ok((u32::decode(reader)?, u32::decode(reader)?))
+    }
+}
+
+impl<T: Encode> Encode for Box<T> {
+    fn encode(&self, writer: &mut CacheWriter) {
+        (**self).encode(writer);
+    }
+}
+
+impl<T: Decode> Decode for Box<T> {
+    fn decode(reader: &mut CacheReader) -> Result<Self, &'static str> {
+        Ok(Box::new(T::decode(reader)?))
+    }
+}
+
+macro_rules! cache_enum {
+    ($name:ident { $($variant:ident),* $(,)* }) => {
+        impl Encode for $name {
+            fn encode(&self, writer: &mut CacheWriter) {
+                (*self as u32).encode(writer);
+            }
+        }
+
+        impl Decode for $name {
+            fn decode(reader: &mut CacheReader) -> Result<Self, &'static str> {
+                let value = u32::decode(reader)?;
+                $(if value == $name::$variant as u32 {
+                    return Ok($name::$variant);
+                })*
+                Err("Invalid cache data")
+            }
+        }
+    };
+}
+
+cache_enum!(ReflectGenerator {
+    Unknown,
+    KhronosLlvmSpirvTranslator,
+    KhronosSpirvToolsAssembler,
+    KhronosGlslangReferenceFrontEnd,
+    GoogleShadercOverGlslang,
+    GoogleSpiregg,
+    GoogleRspirv,
+    XLegendMesaMesairSpirvTranslator,
+    KhronosSpirvToolsLinker,
+    WineVkd3dShaderCompiler,
+    ClayClayShaderCompiler,
+});
+
+cache_enum!(ReflectDescriptorType {
+    Undefined,
+    Sampler,
+    CombinedImageSampler,
+    SampledImage,
+    StorageImage,
+    UniformTexelBuffer,
+    StorageTexelBuffer,
+    UniformBuffer,
+    StorageBuffer,
+    UniformBufferDynamic,
+    StorageBufferDynamic,
+    InputAttachment,
+    AccelerationStructureNV,
+});
+
+cache_enum!(ReflectResourceType {
+    Undefined,
+    Sampler,
+    CombinedImageSampler,
+    ConstantBufferView,
+    ShaderResourceView,
+    UnorderedAccessView,
+});
+
+cache_enum!(ReflectCounterAssociation {
+    Decoration,
+    NameSuffix,
+});
+
+cache_enum!(ReflectDimension {
+    Undefined,
+    Type1d,
+    Type2d,
+    Type3d,
+    Cube,
+    Rect,
+    Buffer,
+    SubPassData,
+});
+
+cache_enum!(ReflectStorageClass {
+    Undefined,
+    UniformConstant,
+    Input,
+    Uniform,
+    Output,
+    WorkGroup,
+    CrossWorkGroup,
+    Private,
+    Function,
+    Generic,
+    PushConstant,
+    AtomicCounter,
+    Image,
+    StorageBuffer,
+    PhysicalStorageBuffer,
+});
+
+cache_enum!(ReflectFormat {
+    Undefined,
+    R32_UINT,
+    R32_SINT,
+    R32_SFLOAT,
+    R32G32_UINT,
+    R32G32_SINT,
+    R32G32_SFLOAT,
+    R32G32B32_UINT,
+    R32G32B32_SINT,
+    R32G32B32_SFLOAT,
+    R32G32B32A32_UINT,
+    R32G32B32A32_SINT,
+    R32G32B32A32_SFLOAT,
+    R64_UINT,
+    R64_SINT,
+    R64_SFLOAT,
+    R64G64_UINT,
+    R64G64_SINT,
+    R64G64_SFLOAT,
+    R64G64B64_UINT,
+    R64G64B64_SINT,
+    R64G64B64_SFLOAT,
+    R64G64B64A64_UINT,
+    R64G64B64A64_SINT,
+    R64G64B64A64_SFLOAT,
+});
+
+impl Encode for ReflectImageFormat {
+    fn encode(&self, writer: &mut CacheWriter) {
+        self.as_raw().encode(writer);
+    }
+}
+
+impl Decode for ReflectImageFormat {
+    fn decode(reader: &mut CacheReader) -> Result<Self, &'static str> {
+        match spirv_headers::ImageFormat::from_u32(u32::decode(reader)?) {
+            Some(format) => Ok(ReflectImageFormat::from(format)),
+            None => Err("Invalid cache data"),
+        }
+    }
+}
+
+impl Encode for ReflectOp {
+    fn encode(&self, writer: &mut CacheWriter) {
+        self.as_raw().encode(writer);
+    }
+}
+
+impl Decode for ReflectOp {
+    fn decode(reader: &mut CacheReader) -> Result<Self, &'static str> {
+        match spirv_headers::Op::from_u32(u32::decode(reader)?) {
+            Some(op) => Ok(ReflectOp::from(op)),
+            None => Err("Invalid cache data"),
+        }
+    }
+}
+
+impl Encode for ReflectBuiltIn {
+    fn encode(&self, writer: &mut CacheWriter) {
+        self.as_raw().encode(writer);
+    }
+}
+
+impl Decode for ReflectBuiltIn {
+    fn decode(reader: &mut CacheReader) -> Result<Self, &'static str> {
+        Ok(ReflectBuiltIn::from_raw(u32::decode(reader)?))
+    }
+}
+
+impl Encode for spirv_headers::ExecutionModel {
+    fn encode(&self, writer: &mut CacheWriter) {
+        (*self as u32).encode(writer);
+    }
+}
+
+impl Decode for spirv_headers::ExecutionModel {
+    fn decode(reader: &mut CacheReader) -> Result<Self, &'static str> {
+        spirv_headers::ExecutionModel::from_u32(u32::decode(reader)?).ok_or("Invalid cache data")
+    }
+}
+
+impl Encode for ReflectSizeExpr {
+    fn encode(&self, writer: &mut CacheWriter) {
+        match self {
+            ReflectSizeExpr::Constant(value) => {
+                0u32.encode(writer);
+                value.encode(writer);
+            }
+            ReflectSizeExpr::SpecConstant { spec_id, default } => {
+                1u32.encode(writer);
+                spec_id.encode(writer);
+                default.encode(writer);
+            }
+            ReflectSizeExpr::Op { opcode, operands } => {
+                2u32.encode(writer);
+                opcode.encode(writer);
+                operands.encode(writer);
+            }
+            ReflectSizeExpr::Unknown => 3u32.encode(writer),
+        }
+    }
+}
+
+impl Decode for ReflectSizeExpr {
+    fn decode(reader: &mut CacheReader) -> Result<Self, &'static str> {
+        match u32::decode(reader)? {
+            0 => Ok(ReflectSizeExpr::Constant(u32::decode(reader)?)),
+            1 => Ok(ReflectSizeExpr::SpecConstant {
+                spec_id: u32::decode(reader)?,
+                default: u32::decode(reader)?,
+            }),
+            2 => Ok(ReflectSizeExpr::Op {
+                opcode: u32::decode(reader)?,
+                operands: Decode::decode(reader)?,
+            }),
+            3 => Ok(ReflectSizeExpr::Unknown),
+            _ => Err("Invalid cache data"),
+        }
+    }
+}
+
+macro_rules! cache_flags {
+    ($($name:ident),* $(,)*) => {
+        $(
+            impl Encode for $name {
+                fn encode(&self, writer: &mut CacheWriter) {
+                    self.bits().encode(writer);
+                }
+            }
+
+            impl Decode for $name {
+                fn decode(reader: &mut CacheReader) -> Result<Self, &'static str> {
+                    $name::from_bits(u32::decode(reader)?).ok_or("Invalid cache data")
+                }
+            }
+        )*
+    };
+}
+
+cache_flags!(
+    ReflectDecorationFlags,
+    ReflectTypeFlags,
+    ReflectShaderStageFlags,
+);
+
+macro_rules! cache_struct {
+    ($name:ident { $($field:ident),* $(,)* } $($extra:tt)*) => {
+        impl Encode for $name {
+            fn encode(&self, writer: &mut CacheWriter) {
+                $(self.$field.encode(writer);)*
+            }
+        }
+
+        impl Decode for $name {
+            fn decode(reader: &mut CacheReader) -> Result<Self, &'static str> {
+                Ok($name {
+                    $($field: Decode::decode(reader)?,)*
+                    $($extra)*
+                })
+            }
+        }
+    };
+}
+
+cache_struct!(ReflectSource {
+    language,
+    language_version,
+    file,
+    text,
+});
+cache_struct!(ReflectBindingArrayTraits { dims });
+cache_struct!(ReflectNumericTraitsScalar { width, signedness });
+cache_struct!(ReflectNumericTraitsVector { component_count });
+cache_struct!(ReflectNumericTraitsMatrix {
+    column_count,
+    row_count,
+    stride,
+});
+cache_struct!(ReflectNumericTraits {
+    scalar,
+    vector,
+    matrix,
+});
+cache_struct!(ReflectArrayTraits {
+    dims,
+    stride,
+    strides,
+    runtime,
+    dim_exprs,
+});
+cache_struct!(ReflectTypeDescriptionTraits {
+    numeric,
+    image,
+    array,
+    pointer,
+});
+cache_struct!(ReflectPointerTraits {
+    storage_class,
+    pointee_type_id,
+});
+cache_struct!(ReflectImageTraits {
+    dim,
+    depth,
+    arrayed,
+    ms,
+    sampled,
+    image_format,
+});
+cache_struct!(ReflectTypeDescription {
+    id,
+    op,
+    type_name,
+    struct_member_name,
+    storage_class,
+    type_flags,
+    decoration_flags,
+    offset,
+    traits,
+    members,
+});
+cache_struct!(ReflectBlockVariable {
+    spirv_id,
+    name,
+    offset,
+    absolute_offset,
+    size,
+    padded_size,
+    decoration_flags,
+    numeric,
+    array,
+    members,
+    type_description,
+    name_synthesized,
+});
+cache_struct!(ReflectInterfaceVariable {
+    spirv_id,
+    name,
+    location,
+    component,
+    index,
+    stream,
+    storage_class,
+    semantic,
+    decoration_flags,
+    built_in,
+    numeric,
+    array,
+    members,
+    format,
+    type_description,
+    word_offset,
+    name_synthesized,
+});
+cache_struct!(ReflectDescriptorBinding {
+    spirv_id,
+    name,
+    binding,
+    input_attachment_index,
+    set,
+    descriptor_type,
+    resource_type,
+    image,
+    block,
+    array,
+    count,
+    accessed,
+    uav_counter_id,
+    uav_counter_binding,
+    uav_counter_association,
+    type_description,
+    word_offset,
+    name_synthesized,
+    stage_flags,
+} internal_data: std::ptr::null());
+cache_struct!(ReflectDescriptorSet { set, bindings } internal_data: std::ptr::null());
+cache_struct!(ReflectExtInstUsage {
+    set,
+    instruction,
+    name,
+    count,
+});
+cache_struct!(ReflectBarrier {
+    control,
+    execution_scope,
+    memory_scope,
+    semantics,
+    count,
+});
+
+impl Encode for ReflectEntryPoint {
+    fn encode(&self, writer: &mut CacheWriter) {
+        self.name.encode(writer);
+        self.id.encode(writer);
+        self.spirv_execution_model.encode(writer);
+        self.shader_stage.encode(writer);
+        self.input_variables.encode(writer);
+        self.output_variables.encode(writer);
+        self.descriptor_sets.encode(writer);
+        self.used_uniforms.encode(writer);
+        self.used_push_constants.encode(writer);
+        self.ext_inst_usage.encode(writer);
+        self.barriers.encode(writer);
+    }
+}
+
+impl Decode for ReflectEntryPoint {
+    fn decode(reader: &mut CacheReader) -> Result<Self, &'static str> {
+        let name = String::decode(reader)?;
+        let id = u32::decode(reader)?;
+        let spirv_execution_model = Decode::decode(reader)?;
+        let shader_stage = Decode::decode(reader)?;
+        let input_variables: Vec<ReflectInterfaceVariable> = Decode::decode(reader)?;
+        let output_variables: Vec<ReflectInterfaceVariable> = Decode::decode(reader)?;
+        Ok(ReflectEntryPoint {
+            name,
+            id,
+            spirv_execution_model,
+            shader_stage,
+            input_locations: build_location_map(&input_variables),
+            output_locations: build_location_map(&output_variables),
+            output_built_ins: build_built_in_map(&output_variables),
+            input_variables,
+            output_variables,
+            descriptor_sets: Decode::decode(reader)?,
+            used_uniforms: Decode::decode(reader)?,
+            used_push_constants: Decode::decode(reader)?,
+            ext_inst_usage: Decode::decode(reader)?,
+            barriers: Decode::decode(reader)?,
+        })
+    }
+}
@@ -4,6 +4,36 @@ use num_traits::cast::FromPrimitive;
 use spirv_headers;
 
 pub(crate) fn ffi_to_entry_point(ffi_type: &ffi::SpvReflectEntryPoint) -> ReflectEntryPoint {
+    let mut input_variables: Vec<ReflectInterfaceVariable> = unsafe {
+        std::slice::from_raw_parts(
+            ffi_type.input_variables,
+            ffi_type.input_variable_count as usize,
+        )
+    }
+    .iter()
+    .map(|var| ffi_to_interface_variable(var))
+    .collect();
+    let mut output_variables: Vec<ReflectInterfaceVariable> = unsafe {
+        std::slice::from_raw_parts(
+            ffi_type.output_variables,
+            ffi_type.output_variable_count as usize,
+        )
+    }
+    .iter()
+    .map(|var| ffi_to_interface_variable(var))
+    .collect();
+    input_variables.sort_by_key(|var| var.location);
+    output_variables.sort_by_key(|var| var.location);
+    let mut descriptor_sets: Vec<ReflectDescriptorSet> = unsafe {
+        std::slice::from_raw_parts(
+            ffi_type.descriptor_sets,
+            ffi_type.descriptor_set_count as usize,
+        )
+    }
+    .iter()
+    .map(|set| ffi_to_descriptor_set(set))
+    .collect();
+    descriptor_sets.sort_by_key(|set| set.set);
     ReflectEntryPoint {
         name: super::ffi_to_string(ffi_type.name),
         id: ffi_type.id,
@@ -14,33 +44,12 @@ pub(crate) fn ffi_to_entry_point(ffi_type: &ffi::SpvReflectEntryPoint) -> Reflec
             None => spirv_headers::ExecutionModel::Vertex,
         },
         shader_stage: ffi_to_shader_stage_flags(ffi_type.shader_stage),
-        input_variables: unsafe {
-            std::slice::from_raw_parts(
-                ffi_type.input_variables,
-                ffi_type.input_variable_count as usize,
-            )
-        }
-        .iter()
-        .map(|var| ffi_to_interface_variable(var))
-        .collect(),
-        output_variables: unsafe {
-            std::slice::from_raw_parts(
-                ffi_type.output_variables,
-                ffi_type.output_variable_count as usize,
-            )
-        }
-        .iter()
-        .map(|var| ffi_to_interface_variable(var))
-        .collect(),
-        descriptor_sets: unsafe {
-            std::slice::from_raw_parts(
-                ffi_type.descriptor_sets,
-                ffi_type.descriptor_set_count as usize,
-            )
-        }
-        .iter()
-        .map(|set| ffi_to_descriptor_set(set))
-        .collect(),
+        input_locations: build_location_map(&input_variables),
+        output_locations: build_location_map(&output_variables),
+        output_built_ins: build_built_in_map(&output_variables),
+        input_variables,
+        output_variables,
+        descriptor_sets,
         used_uniforms: unsafe {
             std::slice::from_raw_parts(ffi_type.used_uniforms, ffi_type.used_uniform_count as usize)
         }
@@ -52,41 +61,65 @@ pub(crate) fn ffi_to_entry_point(ffi_type: &ffi::SpvReflectEntryPoint) -> Reflec
             )
         }
         .to_vec(),
+        ext_inst_usage: Vec::new(),
+        barriers: Vec::new(),
     }
 }
 
 pub(crate) fn ffi_to_interface_variable(
     ffi_type_ptr: *const ffi::SpvReflectInterfaceVariable,
+) -> ReflectInterfaceVariable {
+    let mut variable = ffi_to_interface_variable_member(ffi_type_ptr);
+    variable.synthesize_name();
+    variable
+}
+
+fn ffi_to_interface_variable_member(
+    ffi_type_ptr: *const ffi::SpvReflectInterfaceVariable,
 ) -> ReflectInterfaceVariable {
     let ffi_type = unsafe { &*ffi_type_ptr };
     let ffi_members =
         unsafe { std::slice::from_raw_parts(ffi_type.members, ffi_type.member_count as usize) };
     let members: Vec<ReflectInterfaceVariable> = ffi_members
         .iter()
-        .map(|member| ffi_to_interface_variable(member))
+        .map(|member| ffi_to_interface_variable_member(member))
         .collect();
-    ReflectInterfaceVariable {
+    let type_description = if ffi_type.type_description.is_null() {
+        None
+    } else {
+        Some(ffi_to_type_description(unsafe {
+            &*ffi_type.type_description
+        }))
+    };
+    let numeric = ffi_to_numeric_traits(ffi_type.numeric);
+    let format = match ffi_to_format(ffi_type.format) {
+        ReflectFormat::Undefined => match type_description {
+            Some(ref type_description) => format_from_type(&numeric, type_description.type_flags),
+            None => ReflectFormat::Undefined,
+        },
+        format => format,
+    };
+    let mut variable = ReflectInterfaceVariable {
         spirv_id: ffi_type.spirv_id,
         name: super::ffi_to_string(ffi_type.name),
         location: ffi_type.location,
+        component: 0,
+        index: 0,
+        stream: 0,
         storage_class: ffi_to_storage_class(ffi_type.storage_class),
         semantic: super::ffi_to_string(ffi_type.semantic),
         decoration_flags: ffi_to_decoration_flags(ffi_type.decoration_flags),
         built_in: ReflectBuiltIn::from(ffi_type.built_in),
-        numeric: ffi_to_numeric_traits(ffi_type.numeric),
+        numeric,
         array: ffi_to_array_traits(ffi_type.array),
         members,
-        format: ffi_to_format(ffi_type.format),
-        type_description: if ffi_type.type_description.is_null() {
-            None
-        } else {
-            Some(ffi_to_type_description(unsafe {
-                &*ffi_type.type_description
-            }))
-        },
+        format,
+        type_description,
         word_offset: ffi_type.word_offset.location,
-        internal_data: ffi_type_ptr,
-    }
+        name_synthesized: false,
+    };
+    variable.synthesize_member_names();
+    variable
 }
 
 pub(crate) fn ffi_to_type_description(
@@ -106,6 +139,7 @@ pub(crate) fn ffi_to_type_description(
         storage_class: ffi_to_storage_class(ffi_type.storage_class),
         type_flags: ffi_to_type_flags(ffi_type.type_flags as i32),
         decoration_flags: ffi_to_decoration_flags(ffi_type.decoration_flags),
+        offset: None,
         traits: ffi_to_type_description_traits(ffi_type.traits),
         members,
     }
@@ -122,6 +156,7 @@ pub(crate) fn ffi_to_descriptor_set(
     for ffi_binding in ffi_bindings {
         bindings.push(ffi_to_descriptor_binding(*ffi_binding));
     }
+    bindings.sort_by_key(|binding| binding.binding);
     descriptor::ReflectDescriptorSet {
         set: ffi_type.set,
         bindings,
@@ -133,7 +168,7 @@ pub(crate) fn ffi_to_descriptor_binding(
     ffi_type_ptr: *const ffi::SpvReflectDescriptorBinding,
 ) -> ReflectDescriptorBinding {
     let ffi_type = unsafe { &*ffi_type_ptr };
-    ReflectDescriptorBinding {
+    let mut binding = ReflectDescriptorBinding {
         spirv_id: ffi_type.spirv_id,
         name: super::ffi_to_string(ffi_type.name),
         binding: ffi_type.binding,
@@ -145,6 +180,7 @@ pub(crate) fn ffi_to_descriptor_binding(
         block: ffi_to_block_variable(&ffi_type.block),
         array: ffi_to_binding_array_traits(ffi_type.array),
         count: ffi_type.count,
+        accessed: ffi_type.accessed != 0,
         uav_counter_id: ffi_type.uav_counter_id,
         uav_counter_binding: if ffi_type.uav_counter_binding.is_null() {
             None
@@ -153,6 +189,7 @@ pub(crate) fn ffi_to_descriptor_binding(
                 ffi_type.uav_counter_binding,
             )))
         },
+        uav_counter_association: None,
         type_description: if ffi_type.type_description.is_null() {
             None
         } else {
@@ -161,44 +198,12 @@ pub(crate) fn ffi_to_descriptor_binding(
             }))
         },
         word_offset: (ffi_type.word_offset.binding, ffi_type.word_offset.set),
+        name_synthesized: false,
+        stage_flags: ReflectShaderStageFlags::UNDEFINED,
         internal_data: ffi_type_ptr,
-    }
-}
-
-pub(crate) fn ffi_to_generator(ffi_type: ffi::SpvReflectGenerator) -> ReflectGenerator {
-    match ffi_type {
-        ffi::SpvReflectGenerator_SPV_REFLECT_GENERATOR_KHRONOS_LLVM_SPIRV_TRANSLATOR => {
-            ReflectGenerator::KhronosLlvmSpirvTranslator
-        }
-        ffi::SpvReflectGenerator_SPV_REFLECT_GENERATOR_KHRONOS_SPIRV_TOOLS_ASSEMBLER => {
-            ReflectGenerator::KhronosSpirvToolsAssembler
-        }
-        ffi::SpvReflectGenerator_SPV_REFLECT_GENERATOR_KHRONOS_GLSLANG_REFERENCE_FRONT_END => {
-            ReflectGenerator::KhronosGlslangReferenceFrontEnd
-        }
-        ffi::SpvReflectGenerator_SPV_REFLECT_GENERATOR_GOOGLE_SHADERC_OVER_GLSLANG => {
-            ReflectGenerator::GoogleShadercOverGlslang
-        }
-        ffi::SpvReflectGenerator_SPV_REFLECT_GENERATOR_GOOGLE_SPIREGG => {
-            ReflectGenerator::GoogleSpiregg
-        }
-        ffi::SpvReflectGenerator_SPV_REFLECT_GENERATOR_GOOGLE_RSPIRV => {
-            ReflectGenerator::GoogleRspirv
-        }
-        ffi::SpvReflectGenerator_SPV_REFLECT_GENERATOR_X_LEGEND_MESA_MESAIR_SPIRV_TRANSLATOR => {
-            ReflectGenerator::XLegendMesaMesairSpirvTranslator
-        }
-        ffi::SpvReflectGenerator_SPV_REFLECT_GENERATOR_KHRONOS_SPIRV_TOOLS_LINKER => {
-            ReflectGenerator::KhronosSpirvToolsLinker
-        }
-        ffi::SpvReflectGenerator_SPV_REFLECT_GENERATOR_WINE_VKD3D_SHADER_COMPILER => {
-            ReflectGenerator::WineVkd3dShaderCompiler
-        }
-        ffi::SpvReflectGenerator_SPV_REFLECT_GENERATOR_CLAY_CLAY_SHADER_COMPILER => {
-            ReflectGenerator::ClayClayShaderCompiler
-        }
-        _ => ReflectGenerator::Unknown,
-    }
+    };
+    binding.synthesize_names();
+    binding
 }
 
 pub(crate) fn ffi_to_descriptor_type(
@@ -241,7 +246,7 @@ pub(crate) fn ffi_to_descriptor_type(
         ffi::SpvReflectDescriptorType_SPV_REFLECT_DESCRIPTOR_TYPE_ACCELERATION_STRUCTURE_NV => {
             ReflectDescriptorType::AccelerationStructureNV
         }
-        _ => unimplemented!(),
+        _ => ReflectDescriptorType::Undefined,
     }
 }
 
@@ -267,7 +272,7 @@ pub(crate) fn ffi_to_resource_type(ffi_type: ffi::SpvReflectResourceType) -> Ref
         ffi::SpvReflectResourceType_SPV_REFLECT_RESOURCE_FLAG_UAV => {
             ReflectResourceType::UnorderedAccessView
         }
-        _ => unimplemented!(),
+        _ => ReflectResourceType::Undefined,
     }
 }
 
@@ -280,7 +285,7 @@ pub(crate) fn ffi_to_dimension(ffi_type: ffi::SpvDim) -> ReflectDimension {
         ffi::SpvDim__SpvDimRect => ReflectDimension::Rect,
         ffi::SpvDim__SpvDimBuffer => ReflectDimension::Buffer,
         ffi::SpvDim__SpvDimSubpassData => ReflectDimension::SubPassData,
-        _ => unimplemented!(),
+        _ => ReflectDimension::Undefined,
     }
 }
 
@@ -291,6 +296,7 @@ pub(crate) fn ffi_to_type_description_traits(
         numeric: ffi_to_numeric_traits(ffi_type.numeric),
         image: ffi_to_image_traits(ffi_type.image),
         array: ffi_to_array_traits(ffi_type.array),
+        pointer: None,
     }
 }
 
@@ -347,7 +353,7 @@ pub(crate) fn ffi_to_image_format(ffi_type: ffi::SpvImageFormat) -> ReflectImage
         ffi::SpvImageFormat__SpvImageFormatRg8ui => ReflectImageFormat::RG8_UINT,
         ffi::SpvImageFormat__SpvImageFormatR16ui => ReflectImageFormat::R16_UINT,
         ffi::SpvImageFormat__SpvImageFormatR8ui => ReflectImageFormat::R8_UINT,
-        _ => unimplemented!(),
+        _ => ReflectImageFormat::Undefined,
     }
 }
 
@@ -374,7 +380,63 @@ pub(crate) fn ffi_to_format(ffi_type: ffi::SpvReflectFormat) -> ReflectFormat {
         ffi::SpvReflectFormat_SPV_REFLECT_FORMAT_R32G32B32A32_SFLOAT => {
             ReflectFormat::R32G32B32A32_SFLOAT
         }
-        _ => unimplemented!(),
+        // 64-bit formats aren't emitted by the bundled SPIRV-Reflect, but
+        // newer versions use the matching VkFormat values.
+        110 => ReflectFormat::R64_UINT,
+        111 => ReflectFormat::R64_SINT,
+        112 => ReflectFormat::R64_SFLOAT,
+        113 => ReflectFormat::R64G64_UINT,
+        114 => ReflectFormat::R64G64_SINT,
+        115 => ReflectFormat::R64G64_SFLOAT,
+        116 => ReflectFormat::R64G64B64_UINT,
+        117 => ReflectFormat::R64G64B64_SINT,
+        118 => ReflectFormat::R64G64B64_SFLOAT,
+        119 => ReflectFormat::R64G64B64A64_UINT,
+        120 => ReflectFormat::R64G64B64A64_SINT,
+        121 => ReflectFormat::R64G64B64A64_SFLOAT,
+        _ => ReflectFormat::Undefined,
+    }
+}
+
+pub(crate) fn format_from_type(
+    numeric: &ReflectNumericTraits,
+    type_flags: ReflectTypeFlags,
+) -> ReflectFormat {
+    if numeric.scalar.width != 64 || type_flags.contains(ReflectTypeFlags::MATRIX) {
+        return ReflectFormat::Undefined;
+    }
+
+    let float = type_flags.contains(ReflectTypeFlags::FLOAT);
+    let signed = numeric.scalar.signedness != 0;
+    let formats = if float {
+        [
+            ReflectFormat::R64_SFLOAT,
+            ReflectFormat::R64G64_SFLOAT,
+            ReflectFormat::R64G64B64_SFLOAT,
+            ReflectFormat::R64G64B64A64_SFLOAT,
+        ]
+    } else if type_flags.contains(ReflectTypeFlags::INT) && signed {
+        [
+            ReflectFormat::R64_SINT,
+            ReflectFormat::R64G64_SINT,
+            ReflectFormat::R64G64B64_SINT,
+            ReflectFormat::R64G64B64A64_SINT,
+        ]
+    } else if type_flags.contains(ReflectTypeFlags::INT) {
+        [
+            ReflectFormat::R64_UINT,
+            ReflectFormat::R64G64_UINT,
+            ReflectFormat::R64G64B64_UINT,
+            ReflectFormat::R64G64B64A64_UINT,
+        ]
+    } else {
+        return ReflectFormat::Undefined;
+    };
+
+    match numeric.vector.component_count {
+        0 | 1 => formats[0],
+        count @ 2..=4 => formats[count as usize - 1],
+        _ => ReflectFormat::Undefined,
     }
 }
 
@@ -399,28 +461,26 @@ pub(crate) fn ffi_to_storage_class(ffi_type: ffi::SpvStorageClass) -> ReflectSto
         ffi::SpvStorageClass__SpvStorageClassAtomicCounter => ReflectStorageClass::AtomicCounter,
         ffi::SpvStorageClass__SpvStorageClassImage => ReflectStorageClass::Image,
         ffi::SpvStorageClass__SpvStorageClassStorageBuffer => ReflectStorageClass::StorageBuffer,
-        ffi::SpvStorageClass__SpvStorageClassMax => ReflectStorageClass::Undefined,
-        _ => {
-            println!("value is {}", ffi_type);
-            unimplemented!()
-        }
+        // Newer than the C headers.
+        5349 => ReflectStorageClass::PhysicalStorageBuffer,
+        _ => ReflectStorageClass::Undefined,
     }
 }
 
 pub(crate) fn ffi_to_shader_stage_flags(
     ffi_type: ffi::SpvReflectShaderStageFlagBits,
 ) -> ReflectShaderStageFlags {
-    ReflectShaderStageFlags::from_bits(ffi_type as u32).unwrap()
+    ReflectShaderStageFlags::from_bits_truncate(ffi_type as u32)
 }
 
 pub(crate) fn ffi_to_type_flags(ffi_type: ffi::SpvReflectTypeFlagBits) -> ReflectTypeFlags {
-    ReflectTypeFlags::from_bits(ffi_type as u32).unwrap()
+    ReflectTypeFlags::from_bits_truncate(ffi_type as u32)
 }
 
 pub(crate) fn ffi_to_decoration_flags(
     ffi_type: ffi::SpvReflectDecorationFlags,
 ) -> ReflectDecorationFlags {
-    ReflectDecorationFlags::from_bits(ffi_type).unwrap()
+    ReflectDecorationFlags::from_bits_truncate(ffi_type)
 }
 
 pub(crate) fn ffi_to_numeric_traits(
@@ -446,8 +506,14 @@ pub(crate) fn ffi_to_array_traits(ffi_type: ffi::SpvReflectArrayTraits) -> Refle
     let mut dims = ffi_type.dims.to_vec();
     dims.truncate(ffi_type.dims_count as usize);
     ReflectArrayTraits {
+        dim_exprs: dims
+            .iter()
+            .map(|dim| ReflectSizeExpr::Constant(*dim))
+            .collect(),
         dims,
         stride: ffi_type.stride,
+        strides: Vec::new(),
+        runtime: false,
     }
 }
 
@@ -468,7 +534,7 @@ pub(crate) fn ffi_to_block_variable(
         .iter()
         .map(|member| ffi_to_block_variable(member))
         .collect();
-    ReflectBlockVariable {
+    let mut block = ReflectBlockVariable {
         spirv_id: ffi_type.spirv_id,
         name: super::ffi_to_string(ffi_type.name),
         offset: ffi_type.offset,
@@ -486,7 +552,14 @@ pub(crate) fn ffi_to_block_variable(
                 &*ffi_type.type_description
             }))
         },
-    }
+        name_synthesized: false,
+    };
+    let type_id = block
+        .type_description
+        .as_ref()
+        .map_or(0, |type_description| type_description.id);
+    block.synthesize_member_names(type_id);
+    block
 }
 
 pub fn result_to_string(result: ffi::SpvReflectResult) -> &'static str {
@@ -517,6 +590,6 @@ pub fn result_to_string(result: ffi::SpvReflectResult) -> &'static str {
             "Invalid Storage Class"
         }
         ffi::SpvReflectResult_SPV_REFLECT_RESULT_ERROR_SPIRV_RECURSION => "Spirv Recursion",
-        _ => unimplemented!(),
+        _ => "Unknown Result",
     }
 }
@@ -0,0 +1,249 @@
+//! Suggested D3D12 root signatures for Vulkan-style pipeline layouts, for
+//! porting analysis and translation layers. Registers follow the
+//! DXC and SPIRV-Cross convention: binding `n` of set `s` is register `n` in
+//! space `s`.
+
+use crate::pipeline::ReflectPushConstantRange;
+use crate::types::{
+    ReflectDescriptorBinding, ReflectDescriptorSet, ReflectDescriptorType, ReflectResourceType,
+    ReflectShaderStageFlags,
+};
+
+/// `D3D12_DESCRIPTOR_RANGE_TYPE`, also the kind of a root descriptor.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ReflectD3d12RangeType {
+    Srv,
+    Uav,
+    Cbv,
+    Sampler,
+}
+
+/// `D3D12_SHADER_VISIBILITY`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ReflectD3d12Visibility {
+    All,
+    Vertex,
+    Hull,
+    Domain,
+    Geometry,
+    Pixel,
+}
+
+impl ReflectD3d12Visibility {
+    /// The one stage in `stage_flags`, `All` for several or none.
+    fn of(stage_flags: ReflectShaderStageFlags) -> Self {
+        match stage_flags {
+            ReflectShaderStageFlags::VERTEX => ReflectD3d12Visibility::Vertex,
+            ReflectShaderStageFlags::TESSELLATION_CONTROL => ReflectD3d12Visibility::Hull,
+            ReflectShaderStageFlags::TESSELLATION_EVALUATION => ReflectD3d12Visibility::Domain,
+            ReflectShaderStageFlags::GEOMETRY => ReflectD3d12Visibility::Geometry,
+            ReflectShaderStageFlags::FRAGMENT => ReflectD3d12Visibility::Pixel,
+            _ => ReflectD3d12Visibility::All,
+        }
+    }
+}
+
+/// `D3D12_DESCRIPTOR_RANGE`, with the binding it was made from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectD3d12DescriptorRange {
+    pub range_type: ReflectD3d12RangeType,
+    /// `u32::MAX` for an unbounded array.
+    pub num_descriptors: u32,
+    pub base_shader_register: u32,
+    pub register_space: u32,
+    pub offset_in_descriptors_from_table_start: u32,
+    pub name: String,
+}
+
+/// `D3D12_ROOT_PARAMETER`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ReflectD3d12RootParameter {
+    /// Root constants holding the push constants.
+    Constants {
+        num_32bit_values: u32,
+        shader_register: u32,
+        register_space: u32,
+        visibility: ReflectD3d12Visibility,
+    },
+    /// A root CBV, SRV or UAV for a buffer binding.
+    Descriptor {
+        range_type: ReflectD3d12RangeType,
+        shader_register: u32,
+        register_space: u32,
+        visibility: ReflectD3d12Visibility,
+    },
+    /// The CBV, SRV and UAV bindings of a set, or its samplers, which D3D12
+    /// keeps in tables of their own.
+    DescriptorTable {
+        set: u32,
+        ranges: Vec<ReflectD3d12DescriptorRange>,
+        visibility: ReflectD3d12Visibility,
+    },
+}
+
+impl ReflectD3d12RootParameter {
+    /// Size in the root signature, out of 64 DWORDs.
+    pub fn cost(&self) -> u32 {
+        match self {
+            ReflectD3d12RootParameter::Constants {
+                num_32bit_values, ..
+            } => *num_32bit_values,
+            ReflectD3d12RootParameter::Descriptor { .. } => 2,
+            ReflectD3d12RootParameter::DescriptorTable { .. } => 1,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectD3d12Options {
+    /// `(set, binding)` of buffers to bind as root descriptors instead of
+    /// through a table, such as the ones updated most often. Only single
+    /// uniform and storage buffers can be; others stay in their tables.
+    pub root_descriptors: Vec<(u32, u32)>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectD3d12RootSignature {
+    /// Root constants first, then root descriptors, then a table of CBVs,
+    /// SRVs and UAVs and a table of samplers per set.
+    pub parameters: Vec<ReflectD3d12RootParameter>,
+}
+
+impl ReflectD3d12RootSignature {
+    /// Total size in DWORDs; D3D12 allows 64.
+    pub fn cost(&self) -> u32 {
+        self.parameters
+            .iter()
+            .map(ReflectD3d12RootParameter::cost)
+            .sum()
+    }
+}
+
+/// The ranges a binding takes: a combined image sampler is an SRV and a
+/// sampler.
+fn range_types(
+    binding: &ReflectDescriptorBinding,
+) -> Result<Vec<ReflectD3d12RangeType>, &'static str> {
+    match binding.resource_type {
+        ReflectResourceType::ConstantBufferView => Ok(vec![ReflectD3d12RangeType::Cbv]),
+        ReflectResourceType::ShaderResourceView => Ok(vec![ReflectD3d12RangeType::Srv]),
+        ReflectResourceType::UnorderedAccessView => Ok(vec![ReflectD3d12RangeType::Uav]),
+        ReflectResourceType::Sampler => Ok(vec![ReflectD3d12RangeType::Sampler]),
+        ReflectResourceType::CombinedImageSampler => Ok(vec![
+            ReflectD3d12RangeType::Srv,
+            ReflectD3d12RangeType::Sampler,
+        ]),
+        ReflectResourceType::Undefined => Err("Descriptor binding has no D3D12 resource type"),
+    }
+}
+
+fn push_table(
+    parameters: &mut Vec<ReflectD3d12RootParameter>,
+    set: u32,
+    ranges: Vec<ReflectD3d12DescriptorRange>,
+    stage_flags: ReflectShaderStageFlags,
+) {
+    if !ranges.is_empty() {
+        parameters.push(ReflectD3d12RootParameter::DescriptorTable {
+            set,
+            ranges,
+            visibility: ReflectD3d12Visibility::of(stage_flags),
+        });
+    }
+}
+
+/// Suggests a root signature for a pipeline with the descriptor sets and
+/// push constant ranges of `PipelineReflection`. The push constants become
+/// root constants at register 0 of the space past the last set.
+pub fn root_signature(
+    sets: &[ReflectDescriptorSet],
+    push_constant_ranges: &[ReflectPushConstantRange],
+    options: &ReflectD3d12Options,
+) -> Result<ReflectD3d12RootSignature, &'static str> {
+    let mut sets: Vec<&ReflectDescriptorSet> = sets.iter().collect();
+    sets.sort_by_key(|set| set.set);
+
+    let mut constants = Vec::new();
+    if let Some(end) = push_constant_ranges
+        .iter()
+        .map(|range| range.offset + range.size)
+        .max()
+    {
+        let stage_flags = push_constant_ranges
+            .iter()
+            .fold(ReflectShaderStageFlags::UNDEFINED, |flags, range| {
+                flags | range.stage_flags
+            });
+        constants.push(ReflectD3d12RootParameter::Constants {
+            num_32bit_values: end.div_ceil(4),
+            shader_register: 0,
+            register_space: sets.last().map_or(0, |set| set.set + 1),
+            visibility: ReflectD3d12Visibility::of(stage_flags),
+        });
+    }
+
+    let mut descriptors = Vec::new();
+    let mut tables = Vec::new();
+    for set in sets {
+        let mut bindings: Vec<&ReflectDescriptorBinding> = set.bindings.iter().collect();
+        bindings.sort_by_key(|binding| binding.binding);
+        let mut views = Vec::new();
+        let mut samplers = Vec::new();
+        let mut view_stages = ReflectShaderStageFlags::UNDEFINED;
+        let mut sampler_stages = ReflectShaderStageFlags::UNDEFINED;
+        for binding in bindings {
+            let range_types = range_types(binding)?;
+            let is_buffer = match binding.descriptor_type {
+                ReflectDescriptorType::UniformBuffer
+                | ReflectDescriptorType::UniformBufferDynamic
+                | ReflectDescriptorType::StorageBuffer
+                | ReflectDescriptorType::StorageBufferDynamic => binding.count == 1,
+                _ => false,
+            };
+            if is_buffer
+                && options
+                    .root_descriptors
+                    .contains(&(binding.set, binding.binding))
+            {
+                descriptors.push(ReflectD3d12RootParameter::Descriptor {
+                    range_type: range_types[0],
+                    shader_register: binding.binding,
+                    register_space: binding.set,
+                    visibility: ReflectD3d12Visibility::of(binding.stage_flags),
+                });
+                continue;
+            }
+            for range_type in range_types {
+                let (ranges, stages) = if range_type == ReflectD3d12RangeType::Sampler {
+                    (&mut samplers, &mut sampler_stages)
+                } else {
+                    (&mut views, &mut view_stages)
+                };
+                let offset = ranges
+                    .last()
+                    .map_or(0, |range: &ReflectD3d12DescriptorRange| {
+                        range.offset_in_descriptors_from_table_start + range.num_descriptors
+                    });
+                ranges.push(ReflectD3d12DescriptorRange {
+                    range_type,
+                    num_descriptors: match binding.count {
+                        0 => u32::MAX,
+                        count => count,
+                    },
+                    base_shader_register: binding.binding,
+                    register_space: binding.set,
+                    offset_in_descriptors_from_table_start: offset,
+                    name: binding.name.clone(),
+                });
+                *stages |= binding.stage_flags;
+            }
+        }
+        push_table(&mut tables, set.set, views, view_stages);
+        push_table(&mut tables, set.set, samplers, sampler_stages);
+    }
+
+    let mut parameters = constants;
+    parameters.extend(descriptors);
+    parameters.extend(tables);
+    Ok(ReflectD3d12RootSignature { parameters })
+}
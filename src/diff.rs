@@ -0,0 +1,154 @@
+//! Differences between the reflection data of two versions of a module.
+
+use crate::cache::ReflectModuleCache;
+use crate::types::{
+    ReflectBlockVariable, ReflectDecorationFlags, ReflectDescriptorBinding,
+    ReflectInterfaceVariable,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectChange<T> {
+    pub before: T,
+    pub after: T,
+}
+
+/// What changed between two modules. Bindings are matched by set, binding
+/// number and descriptor type, interface variables by location and index
+/// (built-ins by built-in), and only layout-relevant differences count as
+/// changes, not renames.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectionDiff {
+    pub added_bindings: Vec<ReflectDescriptorBinding>,
+    pub removed_bindings: Vec<ReflectDescriptorBinding>,
+    pub changed_bindings: Vec<ReflectChange<ReflectDescriptorBinding>>,
+    /// Set when the push constant blocks differ in number or layout.
+    pub push_constants: Option<ReflectChange<Vec<ReflectBlockVariable>>>,
+    pub added_inputs: Vec<ReflectInterfaceVariable>,
+    pub removed_inputs: Vec<ReflectInterfaceVariable>,
+    pub changed_inputs: Vec<ReflectChange<ReflectInterfaceVariable>>,
+    pub added_outputs: Vec<ReflectInterfaceVariable>,
+    pub removed_outputs: Vec<ReflectInterfaceVariable>,
+    pub changed_outputs: Vec<ReflectChange<ReflectInterfaceVariable>>,
+}
+
+impl ReflectionDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == ReflectionDiff::default()
+    }
+}
+
+type Changes<T> = (Vec<T>, Vec<T>, Vec<ReflectChange<T>>);
+
+/// Pairs each item with its key and the number of earlier items sharing that
+/// key, so that items aliasing one key are matched up in order.
+fn keyed<T, K: PartialEq>(items: &[T], key: impl Fn(&T) -> K) -> Vec<((K, usize), &T)> {
+    let mut keyed: Vec<((K, usize), &T)> = Vec::with_capacity(items.len());
+    for item in items {
+        let item_key = key(item);
+        let nth = keyed
+            .iter()
+            .filter(|((other, _), _)| *other == item_key)
+            .count();
+        keyed.push(((item_key, nth), item));
+    }
+    keyed
+}
+
+/// Splits `before` and `after` into added, removed and changed items.
+fn diff_by_key<T: Clone, K: PartialEq>(
+    before: &[T],
+    after: &[T],
+    key: impl Fn(&T) -> K,
+    same: impl Fn(&T, &T) -> bool,
+) -> Changes<T> {
+    let before = keyed(before, &key);
+    let after = keyed(after, &key);
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+    for (new_key, new) in &after {
+        match before.iter().find(|(old_key, _)| old_key == new_key) {
+            Some((_, old)) if !same(old, new) => changed.push(ReflectChange {
+                before: (*old).clone(),
+                after: (*new).clone(),
+            }),
+            Some(_) => {}
+            None => added.push((*new).clone()),
+        }
+    }
+    let removed = before
+        .iter()
+        .filter(|(old_key, _)| !after.iter().any(|(new_key, _)| old_key == new_key))
+        .map(|(_, old)| (*old).clone())
+        .collect();
+    (added, removed, changed)
+}
+
+fn interface_key(variable: &ReflectInterfaceVariable) -> (bool, u32, u32) {
+    if variable
+        .decoration_flags
+        .contains(ReflectDecorationFlags::BUILT_IN)
+    {
+        (true, variable.built_in.raw(), 0)
+    } else {
+        (false, variable.location, variable.index)
+    }
+}
+
+fn interface_same(a: &ReflectInterfaceVariable, b: &ReflectInterfaceVariable) -> bool {
+    a.format == b.format && a.numeric == b.numeric && a.array == b.array
+}
+
+fn bindings(module: &ReflectModuleCache) -> Vec<ReflectDescriptorBinding> {
+    module
+        .descriptor_sets
+        .iter()
+        .flat_map(|set| set.bindings.iter().cloned())
+        .collect()
+}
+
+/// Compares two reflections, e.g. before and after a hot reload. Use
+/// `ShaderModule::to_cache` to compare live modules.
+pub fn reflect_diff(a: &ReflectModuleCache, b: &ReflectModuleCache) -> ReflectionDiff {
+    let (added_bindings, removed_bindings, changed_bindings) = diff_by_key(
+        &bindings(a),
+        &bindings(b),
+        |binding| (binding.set, binding.binding, binding.descriptor_type),
+        |old, new| old.layout_compatible(new),
+    );
+    let push_constants_same = a.push_constant_blocks.len() == b.push_constant_blocks.len()
+        && a.push_constant_blocks
+            .iter()
+            .zip(&b.push_constant_blocks)
+            .all(|(old, new)| old.layout_compatible(new));
+    let (added_inputs, removed_inputs, changed_inputs) = diff_by_key(
+        &a.input_variables,
+        &b.input_variables,
+        interface_key,
+        interface_same,
+    );
+    let (added_outputs, removed_outputs, changed_outputs) = diff_by_key(
+        &a.output_variables,
+        &b.output_variables,
+        interface_key,
+        interface_same,
+    );
+    ReflectionDiff {
+        added_bindings,
+        removed_bindings,
+        changed_bindings,
+        push_constants: if push_constants_same {
+            None
+        } else {
+            Some(ReflectChange {
+                before: a.push_constant_blocks.clone(),
+                after: b.push_constant_blocks.clone(),
+            })
+        },
+        added_inputs,
+        removed_inputs,
+        changed_inputs,
+        added_outputs,
+        removed_outputs,
+        changed_outputs,
+    }
+}
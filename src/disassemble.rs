@@ -0,0 +1,817 @@
+use crate::parser;
+use num_traits::cast::FromPrimitive;
+use spirv_headers::Op;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// How the words following the result id of an instruction are printed.
+#[derive(Copy, Clone)]
+enum Operand {
+    Id,
+    Literal,
+    LiteralString,
+    /// Value of `OpConstant` and `OpSpecConstant`, typed by the result type.
+    TypedLiteral,
+    Capability,
+    ExecutionModel,
+    AddressingModel,
+    MemoryModel,
+    ExecutionMode,
+    StorageClass,
+    Dim,
+    ImageFormat,
+    AccessQualifier,
+    SourceLanguage,
+    SamplerAddressingMode,
+    SamplerFilterMode,
+    GroupOperation,
+    /// A decoration followed by its operands.
+    Decoration,
+    FunctionControl,
+    SelectionControl,
+    /// Loop control mask followed by its literal parameters.
+    LoopControl,
+    /// Memory access mask followed by its parameters.
+    MemoryAccess,
+    /// Image operands mask followed by its ids.
+    ImageOperands,
+    /// Instruction number within the extended instruction set.
+    ExtInstruction,
+    /// Opcode of `OpSpecConstantOp`.
+    Opcode,
+    /// All remaining words are ids.
+    Ids,
+    /// All remaining words are literals.
+    Literals,
+    /// All remaining words are literal, id pairs (`OpSwitch` targets).
+    LiteralIdPairs,
+}
+
+use self::Operand::*;
+
+struct Signature {
+    result_type: bool,
+    result: bool,
+    operands: &'static [Operand],
+}
+
+const fn sig(result_type: bool, result: bool, operands: &'static [Operand]) -> Signature {
+    Signature {
+        result_type,
+        result,
+        operands,
+    }
+}
+
+/// Operand layout of `op`. Anything not listed produces a typed value from
+/// ids, which covers the arithmetic, conversion, relational, atomic and
+/// group instructions.
+fn signature(op: Op) -> Signature {
+    match op {
+        Op::Nop | Op::NoLine | Op::FunctionEnd | Op::Kill | Op::Return | Op::Unreachable => {
+            sig(false, false, &[])
+        }
+        Op::SourceContinued | Op::SourceExtension | Op::Extension | Op::ModuleProcessed => {
+            sig(false, false, &[LiteralString])
+        }
+        Op::Source => sig(false, false, &[SourceLanguage, Literal, Id, LiteralString]),
+        Op::Name => sig(false, false, &[Id, LiteralString]),
+        Op::MemberName => sig(false, false, &[Id, Literal, LiteralString]),
+        Op::String | Op::ExtInstImport => sig(false, true, &[LiteralString]),
+        Op::Line => sig(false, false, &[Id, Literal, Literal]),
+        Op::MemoryModel => sig(false, false, &[AddressingModel, MemoryModel]),
+        Op::EntryPoint => sig(false, false, &[ExecutionModel, Id, LiteralString, Ids]),
+        Op::ExecutionMode => sig(false, false, &[Id, ExecutionMode, Literals]),
+        Op::ExecutionModeId => sig(false, false, &[Id, ExecutionMode, Ids]),
+        Op::Capability => sig(false, false, &[Capability]),
+        Op::Decorate => sig(false, false, &[Id, Decoration]),
+        Op::MemberDecorate => sig(false, false, &[Id, Literal, Decoration]),
+        Op::DecorateString => sig(false, false, &[Id, Decoration]),
+        Op::MemberDecorateString => sig(false, false, &[Id, Literal, Decoration]),
+        Op::DecorateId => sig(false, false, &[Id, Decoration]),
+        Op::DecorationGroup => sig(false, true, &[]),
+        Op::GroupDecorate => sig(false, false, &[Ids]),
+        Op::GroupMemberDecorate => sig(false, false, &[Id, Ids]),
+        Op::ExtInst => sig(true, true, &[Id, ExtInstruction, Ids]),
+
+        Op::TypeVoid
+        | Op::TypeBool
+        | Op::TypeSampler
+        | Op::TypeEvent
+        | Op::TypeDeviceEvent
+        | Op::TypeReserveId
+        | Op::TypeQueue
+        | Op::TypePipeStorage
+        | Op::TypeNamedBarrier
+        | Op::TypeAccelerationStructureNV
+        | Op::TypeRayQueryProvisionalKHR => sig(false, true, &[]),
+        Op::TypeInt => sig(false, true, &[Literal, Literal]),
+        Op::TypeFloat => sig(false, true, &[Literal]),
+        Op::TypeVector | Op::TypeMatrix => sig(false, true, &[Id, Literal]),
+        Op::TypeImage => sig(
+            false,
+            true,
+            &[
+                Id,
+                Dim,
+                Literal,
+                Literal,
+                Literal,
+                Literal,
+                ImageFormat,
+                AccessQualifier,
+            ],
+        ),
+        Op::TypeSampledImage | Op::TypeRuntimeArray => sig(false, true, &[Id]),
+        Op::TypeArray | Op::TypeStruct | Op::TypeFunction | Op::TypeCooperativeMatrixNV => {
+            sig(false, true, &[Ids])
+        }
+        Op::TypeOpaque => sig(false, true, &[LiteralString]),
+        Op::TypePointer => sig(false, true, &[StorageClass, Id]),
+        Op::TypePipe => sig(false, true, &[AccessQualifier]),
+        Op::TypeForwardPointer => sig(false, false, &[Id, StorageClass]),
+
+        Op::ConstantTrue
+        | Op::ConstantFalse
+        | Op::ConstantNull
+        | Op::SpecConstantTrue
+        | Op::SpecConstantFalse
+        | Op::Undef
+        | Op::FunctionParameter => sig(true, true, &[]),
+        Op::Constant | Op::SpecConstant => sig(true, true, &[TypedLiteral]),
+        Op::ConstantSampler => sig(
+            true,
+            true,
+            &[SamplerAddressingMode, Literal, SamplerFilterMode],
+        ),
+        Op::SpecConstantOp => sig(true, true, &[Opcode, Ids]),
+
+        Op::Function => sig(true, true, &[FunctionControl, Id]),
+        Op::Variable => sig(true, true, &[StorageClass, Id]),
+        Op::Load => sig(true, true, &[Id, MemoryAccess]),
+        Op::Store => sig(false, false, &[Id, Id, MemoryAccess]),
+        Op::CopyMemory => sig(false, false, &[Id, Id, MemoryAccess, MemoryAccess]),
+        Op::CopyMemorySized => sig(false, false, &[Id, Id, Id, MemoryAccess, MemoryAccess]),
+        Op::ArrayLength => sig(true, true, &[Id, Literal]),
+
+        Op::VectorShuffle => sig(true, true, &[Id, Id, Literals]),
+        Op::CompositeExtract => sig(true, true, &[Id, Literals]),
+        Op::CompositeInsert => sig(true, true, &[Id, Id, Literals]),
+
+        Op::ImageSampleImplicitLod
+        | Op::ImageSampleExplicitLod
+        | Op::ImageSampleProjImplicitLod
+        | Op::ImageSampleProjExplicitLod
+        | Op::ImageFetch
+        | Op::ImageRead
+        | Op::ImageSparseSampleImplicitLod
+        | Op::ImageSparseSampleExplicitLod
+        | Op::ImageSparseSampleProjImplicitLod
+        | Op::ImageSparseSampleProjExplicitLod
+        | Op::ImageSparseFetch
+        | Op::ImageSparseRead => sig(true, true, &[Id, Id, ImageOperands]),
+        Op::ImageSampleDrefImplicitLod
+        | Op::ImageSampleDrefExplicitLod
+        | Op::ImageSampleProjDrefImplicitLod
+        | Op::ImageSampleProjDrefExplicitLod
+        | Op::ImageGather
+        | Op::ImageDrefGather
+        | Op::ImageSparseSampleDrefImplicitLod
+        | Op::ImageSparseSampleDrefExplicitLod
+        | Op::ImageSparseSampleProjDrefImplicitLod
+        | Op::ImageSparseSampleProjDrefExplicitLod
+        | Op::ImageSparseGather
+        | Op::ImageSparseDrefGather => sig(true, true, &[Id, Id, Id, ImageOperands]),
+        Op::ImageWrite => sig(false, false, &[Id, Id, Id, ImageOperands]),
+
+        Op::GroupIAdd
+        | Op::GroupFAdd
+        | Op::GroupFMin
+        | Op::GroupUMin
+        | Op::GroupSMin
+        | Op::GroupFMax
+        | Op::GroupUMax
+        | Op::GroupSMax
+        | Op::GroupNonUniformIAdd
+        | Op::GroupNonUniformFAdd
+        | Op::GroupNonUniformIMul
+        | Op::GroupNonUniformFMul
+        | Op::GroupNonUniformSMin
+        | Op::GroupNonUniformUMin
+        | Op::GroupNonUniformFMin
+        | Op::GroupNonUniformSMax
+        | Op::GroupNonUniformUMax
+        | Op::GroupNonUniformFMax
+        | Op::GroupNonUniformBitwiseAnd
+        | Op::GroupNonUniformBitwiseOr
+        | Op::GroupNonUniformBitwiseXor
+        | Op::GroupNonUniformLogicalAnd
+        | Op::GroupNonUniformLogicalOr
+        | Op::GroupNonUniformLogicalXor
+        | Op::GroupNonUniformBallotBitCount => sig(true, true, &[Id, GroupOperation, Ids]),
+
+        Op::EmitVertex
+        | Op::EndPrimitive
+        | Op::BeginInvocationInterlockEXT
+        | Op::EndInvocationInterlockEXT
+        | Op::DemoteToHelperInvocationEXT
+        | Op::IgnoreIntersectionNV
+        | Op::TerminateRayNV => sig(false, false, &[]),
+        Op::EmitStreamVertex
+        | Op::EndStreamPrimitive
+        | Op::ControlBarrier
+        | Op::MemoryBarrier
+        | Op::MemoryNamedBarrier
+        | Op::AtomicStore
+        | Op::AtomicFlagClear
+        | Op::ReturnValue
+        | Op::Branch
+        | Op::LifetimeStart
+        | Op::LifetimeStop
+        | Op::GroupWaitEvents
+        | Op::CommitReadPipe
+        | Op::CommitWritePipe
+        | Op::GroupCommitReadPipe
+        | Op::GroupCommitWritePipe
+        | Op::RetainEvent
+        | Op::ReleaseEvent
+        | Op::SetUserEventStatus
+        | Op::CaptureEventProfilingInfo
+        | Op::TraceNV
+        | Op::ExecuteCallableNV
+        | Op::WritePackedPrimitiveIndices4x8NV
+        | Op::CooperativeMatrixStoreNV
+        | Op::SubgroupBlockWriteINTEL
+        | Op::SubgroupImageBlockWriteINTEL
+        | Op::SubgroupImageMediaBlockWriteINTEL
+        | Op::RayQueryInitializeKHR
+        | Op::RayQueryTerminateKHR
+        | Op::RayQueryGenerateIntersectionKHR
+        | Op::RayQueryConfirmIntersectionKHR => sig(false, false, &[Ids]),
+        Op::Label => sig(false, true, &[]),
+        Op::LoopMerge => sig(false, false, &[Id, Id, LoopControl]),
+        Op::SelectionMerge => sig(false, false, &[Id, SelectionControl]),
+        Op::BranchConditional => sig(false, false, &[Id, Id, Id, Literals]),
+        Op::Switch => sig(false, false, &[Id, Id, LiteralIdPairs]),
+
+        _ => sig(true, true, &[Ids]),
+    }
+}
+
+/// Names of the `GLSL.std.450` extended instructions, indexed by number.
+pub(crate) const GLSL_STD_450: &[&str] = &[
+    "",
+    "Round",
+    "RoundEven",
+    "Trunc",
+    "FAbs",
+    "SAbs",
+    "FSign",
+    "SSign",
+    "Floor",
+    "Ceil",
+    "Fract",
+    "Radians",
+    "Degrees",
+    "Sin",
+    "Cos",
+    "Tan",
+    "Asin",
+    "Acos",
+    "Atan",
+    "Sinh",
+    "Cosh",
+    "Tanh",
+    "Asinh",
+    "Acosh",
+    "Atanh",
+    "Atan2",
+    "Pow",
+    "Exp",
+    "Log",
+    "Exp2",
+    "Log2",
+    "Sqrt",
+    "InverseSqrt",
+    "Determinant",
+    "MatrixInverse",
+    "Modf",
+    "ModfStruct",
+    "FMin",
+    "UMin",
+    "SMin",
+    "FMax",
+    "UMax",
+    "SMax",
+    "FClamp",
+    "UClamp",
+    "SClamp",
+    "FMix",
+    "IMix",
+    "Step",
+    "SmoothStep",
+    "Fma",
+    "Frexp",
+    "FrexpStruct",
+    "Ldexp",
+    "PackSnorm4x8",
+    "PackUnorm4x8",
+    "PackSnorm2x16",
+    "PackUnorm2x16",
+    "PackHalf2x16",
+    "PackDouble2x32",
+    "UnpackSnorm2x16",
+    "UnpackUnorm2x16",
+    "UnpackHalf2x16",
+    "UnpackSnorm4x8",
+    "UnpackUnorm4x8",
+    "UnpackDouble2x32",
+    "Length",
+    "Distance",
+    "Cross",
+    "Normalize",
+    "FaceForward",
+    "Reflect",
+    "Refract",
+    "FindILsb",
+    "FindSMsb",
+    "FindUMsb",
+    "InterpolateAtCentroid",
+    "InterpolateAtSample",
+    "InterpolateAtOffset",
+    "NMin",
+    "NMax",
+    "NClamp",
+];
+
+const FUNCTION_CONTROL: &[(u32, &str)] =
+    &[(1, "Inline"), (2, "DontInline"), (4, "Pure"), (8, "Const")];
+const SELECTION_CONTROL: &[(u32, &str)] = &[(1, "Flatten"), (2, "DontFlatten")];
+const LOOP_CONTROL: &[(u32, &str)] = &[
+    (0x1, "Unroll"),
+    (0x2, "DontUnroll"),
+    (0x4, "DependencyInfinite"),
+    (0x8, "DependencyLength"),
+    (0x10, "MinIterations"),
+    (0x20, "MaxIterations"),
+    (0x40, "IterationMultiple"),
+    (0x80, "PeelCount"),
+    (0x100, "PartialCount"),
+];
+const MEMORY_ACCESS: &[(u32, &str)] = &[
+    (0x1, "Volatile"),
+    (0x2, "Aligned"),
+    (0x4, "Nontemporal"),
+    (0x8, "MakePointerAvailable"),
+    (0x10, "MakePointerVisible"),
+    (0x20, "NonPrivatePointer"),
+];
+const IMAGE_OPERANDS: &[(u32, &str)] = &[
+    (0x1, "Bias"),
+    (0x2, "Lod"),
+    (0x4, "Grad"),
+    (0x8, "ConstOffset"),
+    (0x10, "Offset"),
+    (0x20, "ConstOffsets"),
+    (0x40, "Sample"),
+    (0x80, "MinLod"),
+    (0x100, "MakeTexelAvailable"),
+    (0x200, "MakeTexelVisible"),
+    (0x400, "NonPrivateTexel"),
+    (0x800, "VolatileTexel"),
+    (0x1000, "SignExtend"),
+    (0x2000, "ZeroExtend"),
+];
+
+fn mask_name(mask: u32, names: &[(u32, &str)]) -> String {
+    if mask == 0 {
+        return "None".to_owned();
+    }
+    let mut parts = Vec::new();
+    let mut rest = mask;
+    for &(bit, name) in names {
+        if mask & bit != 0 {
+            parts.push(name.to_owned());
+            rest &= !bit;
+        }
+    }
+    if rest != 0 {
+        parts.push(format!("0x{:x}", rest));
+    }
+    parts.join("|")
+}
+
+fn enum_name<T: FromPrimitive + std::fmt::Debug>(value: u32) -> String {
+    match T::from_u32(value) {
+        Some(value) => format!("{:?}", value),
+        None => value.to_string(),
+    }
+}
+
+fn dim_name(value: u32) -> String {
+    // `Dim1D` and friends are spelled `1D` in assembly.
+    let name = enum_name::<spirv_headers::Dim>(value);
+    match name.strip_prefix("Dim") {
+        Some(stripped) if stripped.starts_with(char::is_numeric) => stripped.to_owned(),
+        _ => name,
+    }
+}
+
+fn f16_to_f32(bits: u32) -> f32 {
+    let sign = (bits >> 15) & 1;
+    let exponent = (bits >> 10) & 0x1f;
+    let mantissa = bits & 0x3ff;
+    let magnitude = match exponent {
+        0 => mantissa as f32 * (2.0f32).powi(-24),
+        0x1f if mantissa == 0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1.0 + mantissa as f32 / 1024.0) * (2.0f32).powi(exponent as i32 - 15),
+    };
+    if sign == 1 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Scalar {
+    Int { width: u32, signed: bool },
+    Float { width: u32 },
+}
+
+struct Disassembler<'a> {
+    out: String,
+    words: &'a [u32],
+    cursor: usize,
+    scalars: &'a HashMap<u32, Scalar>,
+    glsl_std_450: &'a [u32],
+    /// Ids read so far, in operand order.
+    ids: Vec<u32>,
+}
+
+impl<'a> Disassembler<'a> {
+    fn next(&mut self) -> Option<u32> {
+        let word = self.words.get(self.cursor).cloned();
+        self.cursor += 1;
+        word
+    }
+
+    fn id(&mut self) {
+        if let Some(id) = self.next() {
+            write!(self.out, " %{}", id).unwrap();
+            self.ids.push(id);
+        }
+    }
+
+    fn ids(&mut self) {
+        while self.cursor < self.words.len() {
+            self.id();
+        }
+    }
+
+    fn literal(&mut self) {
+        if let Some(word) = self.next() {
+            write!(self.out, " {}", word).unwrap();
+        }
+    }
+
+    fn literals(&mut self) {
+        while self.cursor < self.words.len() {
+            self.literal();
+        }
+    }
+
+    fn name(&mut self, name: String) {
+        write!(self.out, " {}", name).unwrap();
+    }
+
+    fn named<T: FromPrimitive + std::fmt::Debug>(&mut self) {
+        if let Some(value) = self.next() {
+            self.name(enum_name::<T>(value));
+        }
+    }
+
+    fn string(&mut self) {
+        let rest = &self.words[self.cursor.min(self.words.len())..];
+        let (string, used) = parser::parse_string(rest).unwrap_or_default();
+        self.cursor += used.max(1);
+        self.out.push_str(" \"");
+        for c in string.chars() {
+            if c == '"' || c == '\\' {
+                self.out.push('\\');
+            }
+            self.out.push(c);
+        }
+        self.out.push('"');
+    }
+
+    fn typed_literal(&mut self, type_id: u32) {
+        let rest = &self.words[self.cursor.min(self.words.len())..];
+        let low = rest.first().cloned().unwrap_or(0);
+        let wide = || (u64::from(rest.get(1).cloned().unwrap_or(0)) << 32) | u64::from(low);
+        let text = match self.scalars.get(&type_id) {
+            Some(Scalar::Float { width: 16 }) => format!("{:?}", f16_to_f32(low)),
+            Some(Scalar::Float { width: 64 }) => format!("{:?}", f64::from_bits(wide())),
+            Some(Scalar::Float { .. }) => format!("{:?}", f32::from_bits(low)),
+            Some(Scalar::Int {
+                width: 64,
+                signed: true,
+            }) => format!("{}", wide() as i64),
+            Some(Scalar::Int { width: 64, .. }) => format!("{}", wide()),
+            Some(Scalar::Int { signed: true, .. }) => format!("{}", low as i32),
+            _ => {
+                self.literals();
+                return;
+            }
+        };
+        self.cursor = self.words.len();
+        write!(self.out, " {}", text.trim_end_matches(".0")).unwrap();
+    }
+
+    fn decoration(&mut self) {
+        let decoration = match self.next() {
+            Some(decoration) => decoration,
+            None => return,
+        };
+        self.name(enum_name::<spirv_headers::Decoration>(decoration));
+        match spirv_headers::Decoration::from_u32(decoration) {
+            Some(spirv_headers::Decoration::BuiltIn) => {
+                if let Some(built_in) = self.next() {
+                    self.name(enum_name::<spirv_headers::BuiltIn>(built_in));
+                }
+            }
+            Some(spirv_headers::Decoration::FuncParamAttr) => {
+                if let Some(attribute) = self.next() {
+                    self.name(enum_name::<spirv_headers::FunctionParameterAttribute>(
+                        attribute,
+                    ));
+                }
+            }
+            Some(spirv_headers::Decoration::FPRoundingMode) => {
+                if let Some(mode) = self.next() {
+                    self.name(enum_name::<spirv_headers::FPRoundingMode>(mode));
+                }
+            }
+            Some(spirv_headers::Decoration::LinkageAttributes) => {
+                self.string();
+                if let Some(linkage) = self.next() {
+                    self.name(enum_name::<spirv_headers::LinkageType>(linkage));
+                }
+            }
+            Some(spirv_headers::Decoration::UserSemantic)
+            | Some(spirv_headers::Decoration::UserTypeGOOGLE) => self.string(),
+            Some(spirv_headers::Decoration::CounterBuffer)
+            | Some(spirv_headers::Decoration::UniformId)
+            | Some(spirv_headers::Decoration::AlignmentId)
+            | Some(spirv_headers::Decoration::MaxByteOffsetId) => self.ids(),
+            _ => self.literals(),
+        }
+    }
+
+    fn masked(&mut self, names: &[(u32, &str)]) -> Option<u32> {
+        let mask = self.next()?;
+        self.name(mask_name(mask, names));
+        Some(mask)
+    }
+
+    fn memory_access(&mut self) {
+        if let Some(mask) = self.masked(MEMORY_ACCESS) {
+            if mask & 0x2 != 0 {
+                self.literal();
+            }
+            if mask & 0x8 != 0 {
+                self.id();
+            }
+            if mask & 0x10 != 0 {
+                self.id();
+            }
+        }
+    }
+
+    fn operand(&mut self, operand: Operand, result_type: u32, ext_set: Option<u32>) {
+        if self.cursor >= self.words.len() {
+            return;
+        }
+        match operand {
+            Id => self.id(),
+            Literal => self.literal(),
+            LiteralString => self.string(),
+            TypedLiteral => self.typed_literal(result_type),
+            Capability => self.named::<spirv_headers::Capability>(),
+            ExecutionModel => self.named::<spirv_headers::ExecutionModel>(),
+            AddressingModel => self.named::<spirv_headers::AddressingModel>(),
+            MemoryModel => self.named::<spirv_headers::MemoryModel>(),
+            ExecutionMode => self.named::<spirv_headers::ExecutionMode>(),
+            StorageClass => self.named::<spirv_headers::StorageClass>(),
+            Dim => {
+                let value = self.next().unwrap();
+                self.name(dim_name(value));
+            }
+            ImageFormat => self.named::<spirv_headers::ImageFormat>(),
+            AccessQualifier => self.named::<spirv_headers::AccessQualifier>(),
+            SourceLanguage => self.named::<spirv_headers::SourceLanguage>(),
+            SamplerAddressingMode => self.named::<spirv_headers::SamplerAddressingMode>(),
+            SamplerFilterMode => self.named::<spirv_headers::SamplerFilterMode>(),
+            GroupOperation => self.named::<spirv_headers::GroupOperation>(),
+            Decoration => self.decoration(),
+            FunctionControl => {
+                self.masked(FUNCTION_CONTROL);
+            }
+            SelectionControl => {
+                self.masked(SELECTION_CONTROL);
+            }
+            LoopControl => {
+                self.masked(LOOP_CONTROL);
+                self.literals();
+            }
+            MemoryAccess => self.memory_access(),
+            ImageOperands => {
+                self.masked(IMAGE_OPERANDS);
+                self.ids();
+            }
+            ExtInstruction => {
+                let number = self.next().unwrap();
+                let is_glsl = ext_set.is_some_and(|set| self.glsl_std_450.contains(&set));
+                match GLSL_STD_450.get(number as usize) {
+                    Some(name) if is_glsl && !name.is_empty() => self.name((*name).to_owned()),
+                    _ => self.name(number.to_string()),
+                }
+            }
+            Opcode => {
+                let opcode = self.next().unwrap();
+                self.name(match Op::from_u32(opcode) {
+                    Some(op) => format!("{:?}", op),
+                    None => opcode.to_string(),
+                })
+            }
+            Ids => self.ids(),
+            Literals => self.literals(),
+            LiteralIdPairs => {
+                while self.cursor < self.words.len() {
+                    self.literal();
+                    self.id();
+                }
+            }
+        }
+    }
+}
+
+/// Splits the result type and result id, when the signature has them, off
+/// the front of `operands`.
+fn split_operands<'a>(
+    signature: &Signature,
+    mut operands: &'a [u32],
+) -> (u32, Option<u32>, &'a [u32]) {
+    let mut result_type = 0;
+    if signature.result_type {
+        result_type = operands.first().cloned().unwrap_or(0);
+        operands = operands.get(1..).unwrap_or(&[]);
+    }
+    let mut result = None;
+    if signature.result {
+        result = operands.first().cloned();
+        operands = operands.get(1..).unwrap_or(&[]);
+    }
+    (result_type, result, operands)
+}
+
+/// Result type and result id of `instruction`, when its opcode has them.
+pub(crate) fn result_ids(instruction: &parser::Instruction) -> (Option<u32>, Option<u32>) {
+    let op = match Op::from_u32(instruction.opcode) {
+        Some(op) => op,
+        None => return (None, None),
+    };
+    let signature = signature(op);
+    let (result_type, result, _) = split_operands(&signature, instruction.operands);
+    (Some(result_type).filter(|_| signature.result_type), result)
+}
+
+/// Ids `instruction` reads, not counting its result type and result id.
+pub(crate) fn operand_ids(instruction: &parser::Instruction) -> Vec<u32> {
+    let op = match Op::from_u32(instruction.opcode) {
+        Some(op) => op,
+        None => return Vec::new(),
+    };
+    let signature = signature(op);
+    let (result_type, _, operands) = split_operands(&signature, instruction.operands);
+    let scalars = HashMap::new();
+    let mut reader = Disassembler {
+        out: String::new(),
+        words: operands,
+        cursor: 0,
+        scalars: &scalars,
+        glsl_std_450: &[],
+        ids: Vec::new(),
+    };
+    for operand in signature.operands {
+        reader.operand(*operand, result_type, None);
+    }
+    reader.ids
+}
+
+/// Renders `code` as SPIR-V assembly with raw `%<id>` names. `comments` holds
+/// text appended as a `;` comment to the instruction producing each id.
+pub(crate) fn disassemble(
+    code: &[u32],
+    comments: &HashMap<u32, String>,
+) -> Result<String, &'static str> {
+    let instructions = parser::parse_instructions(code)?;
+
+    let mut scalars = HashMap::new();
+    let mut glsl_std_450 = Vec::new();
+    for instruction in &instructions {
+        match (Op::from_u32(instruction.opcode), instruction.operands) {
+            (Some(Op::TypeInt), [id, width, signed, ..]) => {
+                scalars.insert(
+                    *id,
+                    Scalar::Int {
+                        width: *width,
+                        signed: *signed != 0,
+                    },
+                );
+            }
+            (Some(Op::TypeFloat), [id, width, ..]) => {
+                scalars.insert(*id, Scalar::Float { width: *width });
+            }
+            (Some(Op::ExtInstImport), [id, name @ ..])
+                if parser::parse_string(name).map(|(name, _)| name)
+                    == Ok("GLSL.std.450".to_owned()) =>
+            {
+                glsl_std_450.push(*id);
+            }
+            _ => {}
+        }
+    }
+
+    let bound = code[3];
+    let id_width = bound.to_string().len() + 1;
+    let mut out = String::new();
+    writeln!(out, "; SPIR-V").unwrap();
+    writeln!(
+        out,
+        "; Version: {}.{}",
+        (code[1] >> 16) & 0xff,
+        (code[1] >> 8) & 0xff
+    )
+    .unwrap();
+    writeln!(out, "; Generator: 0x{:08x}", code[2]).unwrap();
+    writeln!(out, "; Bound: {}", bound).unwrap();
+    writeln!(out, "; Schema: {}", code[4]).unwrap();
+
+    for instruction in &instructions {
+        let op = match Op::from_u32(instruction.opcode) {
+            Some(op) => op,
+            None => {
+                writeln!(
+                    out,
+                    "{:width$}; unknown opcode {} with operands {:?}",
+                    "",
+                    instruction.opcode,
+                    instruction.operands,
+                    width = id_width + 3
+                )
+                .unwrap();
+                continue;
+            }
+        };
+        let signature = signature(op);
+        let (result_type, result, operands) = split_operands(&signature, instruction.operands);
+
+        let mut line = Disassembler {
+            out: String::new(),
+            words: operands,
+            cursor: 0,
+            scalars: &scalars,
+            glsl_std_450: &glsl_std_450,
+            ids: Vec::new(),
+        };
+        match result {
+            Some(result) => write!(
+                line.out,
+                "{:>width$} = ",
+                format!("%{}", result),
+                width = id_width
+            )
+            .unwrap(),
+            None => write!(line.out, "{:width$}", "", width = id_width + 3).unwrap(),
+        }
+        write!(line.out, "Op{:?}", op).unwrap();
+        if signature.result_type {
+            write!(line.out, " %{}", result_type).unwrap();
+        }
+        let ext_set = operands.first().cloned();
+        for operand in signature.operands {
+            line.operand(*operand, result_type, ext_set);
+        }
+        // Words the signature did not account for are still shown.
+        line.literals();
+
+        out.push_str(&line.out);
+        if let Some(comment) = result.and_then(|result| comments.get(&result)) {
+            write!(out, " ; {}", comment).unwrap();
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
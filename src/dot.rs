@@ -0,0 +1,148 @@
+use crate::disassemble;
+use crate::parser;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+use std::ops::Range;
+
+/// A function body: the functions it calls and every id it reads.
+pub(crate) struct Function {
+    pub(crate) id: u32,
+    pub(crate) calls: Vec<u32>,
+    pub(crate) references: BTreeSet<u32>,
+    /// Indices of its instructions, from `OpFunction` to `OpFunctionEnd`.
+    pub(crate) instructions: Range<usize>,
+}
+
+pub(crate) fn parse_functions(code: &[u32]) -> Result<Vec<Function>, &'static str> {
+    Ok(functions_of(&parser::parse_instructions(code)?))
+}
+
+pub(crate) fn functions_of(instructions: &[parser::Instruction]) -> Vec<Function> {
+    let mut functions = Vec::new();
+    let mut current: Option<Function> = None;
+    for (index, instruction) in instructions.iter().enumerate() {
+        if instruction.is(spirv_headers::Op::Function) {
+            current = Some(Function {
+                id: instruction.operands.get(1).cloned().unwrap_or(0),
+                calls: Vec::new(),
+                references: BTreeSet::new(),
+                instructions: index..index + 1,
+            });
+        } else if instruction.is(spirv_headers::Op::FunctionEnd) {
+            functions.extend(current.take().map(|mut function| {
+                function.instructions.end = index + 1;
+                function
+            }));
+        } else if let Some(function) = current.as_mut() {
+            if instruction.is(spirv_headers::Op::FunctionCall) {
+                if let Some(&callee) = instruction.operands.get(2) {
+                    if !function.calls.contains(&callee) {
+                        function.calls.push(callee);
+                    }
+                }
+            }
+            function
+                .references
+                .extend(disassemble::operand_ids(instruction));
+        }
+    }
+    functions
+}
+
+/// The functions reachable from `roots` through calls, roots first.
+pub(crate) fn reachable<'a>(roots: &[u32], functions: &'a [Function]) -> Vec<&'a Function> {
+    let functions: HashMap<u32, &Function> = functions
+        .iter()
+        .map(|function| (function.id, function))
+        .collect();
+    let mut reachable = Vec::new();
+    let mut seen = HashSet::new();
+    let mut queue: VecDeque<u32> = roots.iter().cloned().collect();
+    while let Some(id) = queue.pop_front() {
+        if !seen.insert(id) {
+            continue;
+        }
+        if let Some(function) = functions.get(&id) {
+            reachable.push(*function);
+            queue.extend(function.calls.iter().cloned());
+        }
+    }
+    reachable
+}
+
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Renders the functions reachable from `roots` as a DOT digraph: solid edges
+/// for calls, dashed edges to the `resources` (variable id and label) each
+/// function references directly.
+pub(crate) fn render(
+    graph_name: &str,
+    roots: &[u32],
+    functions: &[Function],
+    function_names: &HashMap<u32, String>,
+    resources: &[(u32, String)],
+) -> String {
+    let reachable = reachable(roots, functions);
+    let functions: HashSet<u32> = functions.iter().map(|function| function.id).collect();
+
+    let function_label = |id: u32| match function_names.get(&id) {
+        Some(name) if !name.is_empty() => name.clone(),
+        _ => format!("%{}", id),
+    };
+
+    let mut out = String::new();
+    writeln!(out, "digraph {} {{", quote(graph_name)).unwrap();
+    writeln!(out, "    node [shape=box];").unwrap();
+    for function in &reachable {
+        let style = if roots.contains(&function.id) {
+            ", style=bold"
+        } else {
+            ""
+        };
+        writeln!(
+            out,
+            "    f{} [label={}{}];",
+            function.id,
+            quote(&function_label(function.id)),
+            style
+        )
+        .unwrap();
+    }
+    for (id, label) in resources {
+        if reachable
+            .iter()
+            .any(|function| function.references.contains(id))
+        {
+            writeln!(out, "    r{} [shape=ellipse, label={}];", id, quote(label)).unwrap();
+        }
+    }
+    for function in &reachable {
+        for callee in &function.calls {
+            if functions.contains(callee) {
+                writeln!(out, "    f{} -> f{};", function.id, callee).unwrap();
+            }
+        }
+        for (id, _) in resources {
+            if function.references.contains(id) {
+                writeln!(out, "    f{} -> r{} [style=dashed];", function.id, id).unwrap();
+            }
+        }
+    }
+    out.push_str("}\n");
+    out
+}
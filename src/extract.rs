@@ -0,0 +1,168 @@
+//! Copies of a module trimmed down to one entry point.
+
+use crate::disassemble;
+use crate::dot;
+use crate::parser::{self, Instruction};
+use num_traits::cast::FromPrimitive;
+use spirv_headers::Op;
+use std::collections::{HashMap, HashSet};
+
+/// Every id `instruction` mentions, including its result type.
+fn referenced_ids(instruction: &Instruction) -> Vec<u32> {
+    let mut ids = disassemble::operand_ids(instruction);
+    ids.extend(disassemble::result_ids(instruction).0);
+    ids
+}
+
+fn push_instruction(words: &mut Vec<u32>, opcode: u32, operands: &[u32]) {
+    words.push(((operands.len() as u32 + 1) << 16) | opcode);
+    words.extend_from_slice(operands);
+}
+
+/// Index of the first `OpFunction`, where the module-level instructions end.
+fn first_function(instructions: &[Instruction], functions: &[dot::Function]) -> usize {
+    functions
+        .first()
+        .map_or(instructions.len(), |function| function.instructions.start)
+}
+
+/// Every id reachable from `roots`: the functions they call and the globals,
+/// types and constants those use, following `OpDecorateId` and
+/// `OpExecutionModeId` operands of the ids reached.
+pub(crate) fn live_ids(
+    instructions: &[Instruction],
+    functions: &[dot::Function],
+    mut roots: Vec<u32>,
+) -> HashSet<u32> {
+    let function_ids: HashMap<u32, &dot::Function> = functions
+        .iter()
+        .map(|function| (function.id, function))
+        .collect();
+
+    // Global definitions, and the ids the decorations and execution modes of
+    // each target refer to, such as the counter of a buffer.
+    let mut definitions = HashMap::new();
+    let mut target_ids: HashMap<u32, Vec<u32>> = HashMap::new();
+    for instruction in &instructions[..first_function(instructions, functions)] {
+        let op = Op::from_u32(instruction.opcode);
+        if let Some(id) = disassemble::result_ids(instruction).1 {
+            definitions.insert(id, instruction);
+        }
+        match (op, instruction.operands) {
+            (Some(Op::ExecutionModeId), [target, _, ids @ ..])
+            | (Some(Op::DecorateId), [target, _, ids @ ..]) => {
+                target_ids.entry(*target).or_default().extend(ids)
+            }
+            // The `WorkgroupSize` built-in overrides the execution modes
+            // without being referenced.
+            (Some(Op::Decorate), [target, decoration, built_in, ..])
+                if *decoration == spirv_headers::Decoration::BuiltIn as u32
+                    && *built_in == spirv_headers::BuiltIn::WorkgroupSize as u32 =>
+            {
+                roots.push(*target)
+            }
+            (Some(Op::DecorationGroup), [group]) => roots.push(*group),
+            _ => {}
+        }
+    }
+
+    let mut live = HashSet::new();
+    while let Some(id) = roots.pop() {
+        if !live.insert(id) {
+            continue;
+        }
+        if let Some(function) = function_ids.get(&id) {
+            for instruction in &instructions[function.instructions.clone()] {
+                roots.extend(referenced_ids(instruction));
+            }
+        } else if let Some(instruction) = definitions.get(&id) {
+            roots.extend(referenced_ids(instruction));
+        }
+        roots.extend(target_ids.get(&id).into_iter().flatten());
+    }
+    live
+}
+
+/// `code` with only the entry point called `name`, the functions it calls
+/// and the globals, types, constants, names and decorations they use. Ids are
+/// not renumbered, so the bound stays the same.
+pub(crate) fn extract_entry_point(code: &[u32], name: &str) -> Result<Vec<u32>, &'static str> {
+    let instructions = parser::parse_instructions(code)?;
+    let entry_points = parser::parse_entry_points(&instructions)?;
+    let entry_point = parser::find_entry_point(&entry_points, Some(name))?;
+    let functions = dot::functions_of(&instructions);
+    let first_function = first_function(&instructions, &functions);
+    let mut roots = vec![entry_point.id];
+    roots.extend(&entry_point.interface);
+    let live = live_ids(&instructions, &functions, roots);
+
+    let mut words = code[..parser::HEADER_WORD_COUNT].to_vec();
+    for instruction in &instructions[..first_function] {
+        let operands = instruction.operands;
+        let keep = match Op::from_u32(instruction.opcode) {
+            Some(Op::EntryPoint) => operands.get(1) == Some(&entry_point.id),
+            Some(Op::ExecutionMode) | Some(Op::ExecutionModeId) => {
+                operands.first() == Some(&entry_point.id)
+            }
+            Some(Op::Name)
+            | Some(Op::MemberName)
+            | Some(Op::Decorate)
+            | Some(Op::MemberDecorate)
+            | Some(Op::DecorateId)
+            | Some(Op::DecorateString)
+            | Some(Op::MemberDecorateString)
+            | Some(Op::TypeForwardPointer) => {
+                operands.first().is_some_and(|target| live.contains(target))
+            }
+            Some(Op::GroupDecorate) => {
+                let targets: Vec<u32> = operands
+                    .iter()
+                    .skip(1)
+                    .filter(|target| live.contains(target))
+                    .cloned()
+                    .collect();
+                if !targets.is_empty() {
+                    let mut kept = vec![operands[0]];
+                    kept.extend(targets);
+                    push_instruction(&mut words, instruction.opcode, &kept);
+                }
+                continue;
+            }
+            Some(Op::GroupMemberDecorate) => {
+                let targets: Vec<u32> = operands
+                    .get(1..)
+                    .unwrap_or(&[])
+                    .chunks(2)
+                    .filter(|pair| live.contains(&pair[0]))
+                    .flatten()
+                    .cloned()
+                    .collect();
+                if !targets.is_empty() {
+                    let mut kept = vec![operands[0]];
+                    kept.extend(targets);
+                    push_instruction(&mut words, instruction.opcode, &kept);
+                }
+                continue;
+            }
+            // Capabilities, extensions, sources and strings stay, as do the
+            // extended instruction sets.
+            _ => match disassemble::result_ids(instruction).1 {
+                Some(id) if !instruction.is(Op::ExtInstImport) && !instruction.is(Op::String) => {
+                    live.contains(&id)
+                }
+                _ => true,
+            },
+        };
+        if keep {
+            push_instruction(&mut words, instruction.opcode, operands);
+        }
+    }
+    for function in &functions {
+        if live.contains(&function.id) {
+            for instruction in &instructions[function.instructions.clone()] {
+                push_instruction(&mut words, instruction.opcode, instruction.operands);
+            }
+        }
+    }
+    Ok(words)
+}
@@ -3,4 +3,5 @@
 #![allow(non_snake_case)]
 #![cfg_attr(feature = "cargo-clippy", allow(clippy::unreadable_literal))]
 
-include!("../gen/bindings.rs");
+// `gen/bindings.rs` with the prefixed names of the C functions, see build.rs.
+include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
@@ -0,0 +1,106 @@
+//! Flat GL binding points for Vulkan-style descriptor sets, for running
+//! SPIR-V shaders through GL or GLES after cross-compiling them, as done with
+//! SPIRV-Cross.
+
+use crate::types::{ReflectDescriptorSet, ReflectDescriptorType};
+
+/// The GL namespace a descriptor takes its binding point from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ReflectGlBindingKind {
+    /// `GL_UNIFORM_BUFFER` binding points.
+    UniformBuffer,
+    /// `GL_SHADER_STORAGE_BUFFER` binding points.
+    ShaderStorageBuffer,
+    /// Texture units: sampled images, uniform texel buffers and input
+    /// attachments.
+    Texture,
+    /// Image units: storage images and storage texel buffers.
+    Image,
+}
+
+impl ReflectGlBindingKind {
+    /// `None` for separate samplers, which GLSL for GL does not have; the
+    /// cross-compiler combines them with the images they sample.
+    fn of(descriptor_type: ReflectDescriptorType) -> Result<Option<Self>, &'static str> {
+        match descriptor_type {
+            ReflectDescriptorType::UniformBuffer | ReflectDescriptorType::UniformBufferDynamic => {
+                Ok(Some(ReflectGlBindingKind::UniformBuffer))
+            }
+            ReflectDescriptorType::StorageBuffer | ReflectDescriptorType::StorageBufferDynamic => {
+                Ok(Some(ReflectGlBindingKind::ShaderStorageBuffer))
+            }
+            ReflectDescriptorType::CombinedImageSampler
+            | ReflectDescriptorType::SampledImage
+            | ReflectDescriptorType::UniformTexelBuffer
+            | ReflectDescriptorType::InputAttachment => Ok(Some(ReflectGlBindingKind::Texture)),
+            ReflectDescriptorType::StorageImage | ReflectDescriptorType::StorageTexelBuffer => {
+                Ok(Some(ReflectGlBindingKind::Image))
+            }
+            ReflectDescriptorType::Sampler => Ok(None),
+            ReflectDescriptorType::AccelerationStructureNV | ReflectDescriptorType::Undefined => {
+                Err("Descriptor type not supported by GL")
+            }
+        }
+    }
+}
+
+/// First binding point handed out in each GL namespace, e.g. to keep the
+/// low texture units for an engine's own use.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectGlBindingBases {
+    pub uniform_buffer: u32,
+    pub shader_storage_buffer: u32,
+    pub texture: u32,
+    pub image: u32,
+}
+
+/// Where one descriptor binding lands in GL.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectGlBinding {
+    pub set: u32,
+    pub binding: u32,
+    pub name: String,
+    pub kind: ReflectGlBindingKind,
+    /// First binding point; an array takes `count` consecutive ones.
+    pub gl_binding: u32,
+    pub count: u32,
+}
+
+/// Assigns GL binding points to the bindings of `sets`, numbering each
+/// namespace in set and binding order from its base in `bases`. Pass the
+/// sets of every stage of a program, such as from
+/// `PipelineReflection::descriptor_sets`, as GL binding points are shared by
+/// the whole program. Separate samplers are left out.
+pub fn gl_bindings(
+    sets: &[ReflectDescriptorSet],
+    bases: &ReflectGlBindingBases,
+) -> Result<Vec<ReflectGlBinding>, &'static str> {
+    let mut bindings: Vec<_> = sets.iter().flat_map(|set| &set.bindings).collect();
+    bindings.sort_by_key(|binding| (binding.set, binding.binding));
+
+    let mut next = *bases;
+    let mut gl_bindings = Vec::new();
+    for binding in bindings {
+        let kind = match ReflectGlBindingKind::of(binding.descriptor_type)? {
+            Some(kind) => kind,
+            None => continue,
+        };
+        let next_binding = match kind {
+            ReflectGlBindingKind::UniformBuffer => &mut next.uniform_buffer,
+            ReflectGlBindingKind::ShaderStorageBuffer => &mut next.shader_storage_buffer,
+            ReflectGlBindingKind::Texture => &mut next.texture,
+            ReflectGlBindingKind::Image => &mut next.image,
+        };
+        let count = binding.count.max(1);
+        gl_bindings.push(ReflectGlBinding {
+            set: binding.set,
+            binding: binding.binding,
+            name: binding.name.clone(),
+            kind,
+            gl_binding: *next_binding,
+            count,
+        });
+        *next_binding += count;
+    }
+    Ok(gl_bindings)
+}
@@ -0,0 +1,394 @@
+//! Traces sampling, storage and atomic instructions back to the image and
+//! sampler variables they use.
+
+use crate::parser::{self, Instruction, ParsedModule};
+use crate::types::{
+    ReflectConstant, ReflectConstantValue, ReflectImageUsage, ReflectImageUsageFlags,
+};
+use num_traits::cast::FromPrimitive;
+use spirv_headers::Op;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+const IMAGE_OPERANDS_BIAS: u32 = 0x1;
+const IMAGE_OPERANDS_GRAD: u32 = 0x4;
+const IMAGE_OPERANDS_CONST_OFFSET: u32 = 0x8;
+const IMAGE_OPERANDS_OFFSET: u32 = 0x10;
+const IMAGE_OPERANDS_CONST_OFFSETS: u32 = 0x20;
+const IMAGE_OPERANDS_MIN_LOD: u32 = 0x80;
+const IMAGE_OPERANDS_OFFSETS: u32 = 0x10000;
+
+/// Maps every id derived from a variable of one of `storage_classes`,
+/// through loads, access chains, `OpSampledImage`, `OpImage` and function
+/// parameters, to the variables it comes from.
+pub(crate) fn trace_variables(
+    instructions: &[Instruction],
+    module: &ParsedModule,
+    storage_classes: &[u32],
+) -> HashMap<u32, Vec<u32>> {
+    let mut sources: HashMap<u32, Vec<u32>> = module
+        .variables
+        .iter()
+        .filter(|variable| storage_classes.contains(&variable.storage_class))
+        .map(|variable| (variable.id, vec![variable.id]))
+        .collect();
+    // Callees can be defined before their callers, so repeat until function
+    // parameters stop picking up new variables.
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for instruction in instructions {
+            let operands = instruction.operands;
+            let derived: Vec<(u32, &[u32])> = match Op::from_u32(instruction.opcode) {
+                Some(Op::Load)
+                | Some(Op::AccessChain)
+                | Some(Op::InBoundsAccessChain)
+                | Some(Op::PtrAccessChain)
+                | Some(Op::InBoundsPtrAccessChain)
+                | Some(Op::CopyObject)
+                | Some(Op::Image)
+                | Some(Op::ImageTexelPointer)
+                    if operands.len() >= 3 =>
+                {
+                    vec![(operands[1], &operands[2..3])]
+                }
+                Some(Op::SampledImage) if operands.len() >= 4 => {
+                    vec![(operands[1], &operands[2..4])]
+                }
+                Some(Op::FunctionCall) if operands.len() >= 3 => {
+                    match module.function_parameters.get(&operands[2]) {
+                        Some(parameters) => parameters
+                            .iter()
+                            .zip(operands[3..].chunks(1))
+                            .map(|(parameter, argument)| (parameter.id, argument))
+                            .collect(),
+                        None => Vec::new(),
+                    }
+                }
+                _ => continue,
+            };
+            for (result, from) in derived {
+                let found: Vec<u32> = from
+                    .iter()
+                    .filter_map(|id| sources.get(id))
+                    .flatten()
+                    .cloned()
+                    .collect();
+                if found.is_empty() {
+                    continue;
+                }
+                let entry = sources.entry(result).or_default();
+                for variable in found {
+                    if !entry.contains(&variable) {
+                        entry.push(variable);
+                        changed = true;
+                    }
+                }
+            }
+        }
+    }
+    sources
+}
+
+/// Descriptor variables that an instruction in a function body reads,
+/// writes or derives an id from, through any number of sampled images,
+/// texel pointers and function calls in between.
+pub(crate) fn accessed_variables(
+    instructions: &[Instruction],
+    module: &ParsedModule,
+) -> HashSet<u32> {
+    let sources = trace_variables(
+        instructions,
+        module,
+        &[
+            spirv_headers::StorageClass::UniformConstant as u32,
+            spirv_headers::StorageClass::Uniform as u32,
+            spirv_headers::StorageClass::StorageBuffer as u32,
+        ],
+    );
+    let mut accessed = HashSet::new();
+    let mut in_function = false;
+    for instruction in instructions {
+        match Op::from_u32(instruction.opcode) {
+            Some(Op::Function) => in_function = true,
+            Some(Op::FunctionEnd) => in_function = false,
+            // Arguments are accessed where the callee uses its parameters.
+            Some(Op::FunctionParameter) | Some(Op::FunctionCall) => {}
+            _ if in_function => {
+                for id in instruction.operands {
+                    if let Some(variables) = sources.get(id) {
+                        accessed.extend(variables.iter().cloned());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    accessed
+}
+
+/// The ids following an image operands mask that belong to `bit`.
+pub(crate) fn image_operand(mask: u32, operands: &[u32], bit: u32) -> Option<&[u32]> {
+    let mut cursor = 0;
+    for index in 0..32 {
+        let flag = 1 << index;
+        if mask & flag == 0 {
+            continue;
+        }
+        let count = match flag {
+            IMAGE_OPERANDS_GRAD => 2,
+            // NonPrivateTexel, VolatileTexel, SignExtend, ZeroExtend, Nontemporal.
+            0x400 | 0x800 | 0x1000 | 0x2000 | 0x4000 => 0,
+            _ => 1,
+        };
+        if flag == bit {
+            return operands.get(cursor..cursor + count);
+        }
+        cursor += count;
+    }
+    None
+}
+
+/// What a sampling opcode implies, and the index of its image operands mask.
+pub(crate) fn sampling_op(op: Op) -> Option<(ReflectImageUsageFlags, usize)> {
+    use ReflectImageUsageFlags as Flags;
+    Some(match op {
+        Op::ImageSampleImplicitLod | Op::ImageSparseSampleImplicitLod => (Flags::IMPLICIT_LOD, 4),
+        Op::ImageSampleExplicitLod | Op::ImageSparseSampleExplicitLod => (Flags::EXPLICIT_LOD, 4),
+        Op::ImageSampleProjImplicitLod | Op::ImageSparseSampleProjImplicitLod => {
+            (Flags::IMPLICIT_LOD | Flags::PROJECTION, 4)
+        }
+        Op::ImageSampleProjExplicitLod | Op::ImageSparseSampleProjExplicitLod => {
+            (Flags::EXPLICIT_LOD | Flags::PROJECTION, 4)
+        }
+        Op::ImageSampleDrefImplicitLod | Op::ImageSparseSampleDrefImplicitLod => {
+            (Flags::IMPLICIT_LOD | Flags::DEPTH_COMPARE, 5)
+        }
+        Op::ImageSampleDrefExplicitLod | Op::ImageSparseSampleDrefExplicitLod => {
+            (Flags::EXPLICIT_LOD | Flags::DEPTH_COMPARE, 5)
+        }
+        Op::ImageSampleProjDrefImplicitLod | Op::ImageSparseSampleProjDrefImplicitLod => (
+            Flags::IMPLICIT_LOD | Flags::PROJECTION | Flags::DEPTH_COMPARE,
+            5,
+        ),
+        Op::ImageSampleProjDrefExplicitLod | Op::ImageSparseSampleProjDrefExplicitLod => (
+            Flags::EXPLICIT_LOD | Flags::PROJECTION | Flags::DEPTH_COMPARE,
+            5,
+        ),
+        Op::ImageFetch | Op::ImageSparseFetch => (Flags::FETCH, 4),
+        Op::ImageGather | Op::ImageSparseGather => (Flags::GATHER, 5),
+        Op::ImageDrefGather | Op::ImageSparseDrefGather => {
+            (Flags::GATHER | Flags::DEPTH_COMPARE, 5)
+        }
+        _ => return None,
+    })
+}
+
+/// What a storage image read or write implies, and the index of its image.
+pub(crate) fn storage_op(op: Op) -> Option<(ReflectImageUsageFlags, usize)> {
+    match op {
+        Op::ImageRead | Op::ImageSparseRead => Some((ReflectImageUsageFlags::READ, 2)),
+        Op::ImageWrite => Some((ReflectImageUsageFlags::WRITE, 0)),
+        _ => None,
+    }
+}
+
+/// Operand index of the pointer of an atomic instruction, which is an
+/// `OpImageTexelPointer` result for image atomics.
+pub(crate) fn atomic_pointer_index(opcode: u32) -> Option<usize> {
+    // OpAtomicFMinEXT, OpAtomicFMaxEXT and OpAtomicFAddEXT.
+    if opcode == 5614 || opcode == 5615 || opcode == 6035 {
+        return Some(2);
+    }
+    match Op::from_u32(opcode)? {
+        Op::AtomicStore | Op::AtomicFlagClear => Some(0),
+        Op::AtomicLoad
+        | Op::AtomicExchange
+        | Op::AtomicCompareExchange
+        | Op::AtomicCompareExchangeWeak
+        | Op::AtomicIIncrement
+        | Op::AtomicIDecrement
+        | Op::AtomicIAdd
+        | Op::AtomicISub
+        | Op::AtomicSMin
+        | Op::AtomicUMin
+        | Op::AtomicSMax
+        | Op::AtomicUMax
+        | Op::AtomicAnd
+        | Op::AtomicOr
+        | Op::AtomicXor
+        | Op::AtomicFlagTestAndSet => Some(2),
+        _ => None,
+    }
+}
+
+/// Operands of the `OpTypeImage` held by `variable`, from the sampled type
+/// on.
+fn image_type_operands(module: &ParsedModule, variable: u32) -> Option<&[u32]> {
+    let variable = module.variables.iter().find(|other| other.id == variable)?;
+    let type_id = module.array_element_type_id(module.pointee_type_id(variable.type_id));
+    match module.types.get(&type_id) {
+        Some(ty) if ty.is(Op::TypeImage) => Some(&ty.operands),
+        _ => None,
+    }
+}
+
+/// Whether the image held by `variable` has a 64-bit integer sampled type.
+fn has_64_bit_texels(module: &ParsedModule, variable: u32) -> bool {
+    let sampled_type_id = match image_type_operands(module, variable) {
+        Some([sampled_type_id, ..]) => *sampled_type_id,
+        _ => return false,
+    };
+    match module.types.get(&sampled_type_id) {
+        Some(ty) => ty.is(Op::TypeInt) && ty.operands.first() == Some(&64),
+        None => false,
+    }
+}
+
+/// Whether `variable` is a storage image declared with the `Unknown` format.
+fn is_storage_image_without_format(module: &ParsedModule, variable: u32) -> bool {
+    match image_type_operands(module, variable) {
+        Some([_, dim, _, _, _, sampled, format, ..]) => {
+            *dim != spirv_headers::Dim::DimSubpassData as u32
+                && *sampled == 2
+                && *format == spirv_headers::ImageFormat::Unknown as u32
+        }
+        _ => false,
+    }
+}
+
+fn constant_ints(constant: &ReflectConstant, ints: &mut Vec<i32>) {
+    match &constant.value {
+        ReflectConstantValue::Scalar(words) => ints.extend(words.first().map(|&word| word as i32)),
+        ReflectConstantValue::Composite(constituents) => {
+            for constituent in constituents {
+                constant_ints(constituent, ints);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn widen(range: &mut Option<(i32, i32)>, values: &[i32]) {
+    for &value in values {
+        *range = Some(match *range {
+            Some((min, max)) => (min.min(value), max.max(value)),
+            None => (value, value),
+        });
+    }
+}
+
+/// Usage of every image and sampler variable reached by a sampling, storage
+/// or atomic instruction, sorted by set and binding.
+pub(crate) fn image_usage(
+    code: &[u32],
+    module: &ParsedModule,
+) -> Result<Vec<ReflectImageUsage>, &'static str> {
+    let instructions = parser::parse_instructions(code)?;
+    let sources = trace_variables(
+        &instructions,
+        module,
+        &[spirv_headers::StorageClass::UniformConstant as u32],
+    );
+    let mut usages: BTreeMap<u32, ReflectImageUsage> = BTreeMap::new();
+    for instruction in &instructions {
+        let operands = instruction.operands;
+        let (mut flags, image_index, mask_index) = match Op::from_u32(instruction.opcode)
+            .and_then(sampling_op)
+        {
+            Some((flags, mask_index)) => (flags, 2, Some(mask_index)),
+            None => match Op::from_u32(instruction.opcode).and_then(storage_op) {
+                Some((flags, image_index)) => (flags, image_index, None),
+                None => match atomic_pointer_index(instruction.opcode) {
+                    Some(pointer_index) => (ReflectImageUsageFlags::ATOMIC, pointer_index, None),
+                    None => continue,
+                },
+            },
+        };
+        let variables = match operands.get(image_index).and_then(|id| sources.get(id)) {
+            Some(variables) => variables,
+            None => continue,
+        };
+        let mask = mask_index
+            .and_then(|mask_index| operands.get(mask_index))
+            .cloned()
+            .unwrap_or(0);
+        let mask_operands = mask_index
+            .and_then(|mask_index| operands.get(mask_index + 1..))
+            .unwrap_or(&[]);
+        if mask & IMAGE_OPERANDS_BIAS != 0 {
+            flags |= ReflectImageUsageFlags::BIAS;
+        }
+        if mask & IMAGE_OPERANDS_GRAD != 0 {
+            flags |= ReflectImageUsageFlags::GRADIENT;
+        }
+        if mask & IMAGE_OPERANDS_MIN_LOD != 0 {
+            flags |= ReflectImageUsageFlags::MIN_LOD;
+        }
+        let offset_bits = IMAGE_OPERANDS_CONST_OFFSET
+            | IMAGE_OPERANDS_OFFSET
+            | IMAGE_OPERANDS_CONST_OFFSETS
+            | IMAGE_OPERANDS_OFFSETS;
+        if mask & offset_bits != 0 {
+            flags |= ReflectImageUsageFlags::OFFSET;
+        }
+        let constant_operand = |bit| match image_operand(mask, mask_operands, bit) {
+            Some(&[id]) if module.constants.contains_key(&id) => Some(module.constant(id)),
+            _ => None,
+        };
+        let bias = constant_operand(IMAGE_OPERANDS_BIAS);
+        let min_lod = constant_operand(IMAGE_OPERANDS_MIN_LOD);
+        let mut offsets = Vec::new();
+        for &bit in &[IMAGE_OPERANDS_CONST_OFFSET, IMAGE_OPERANDS_CONST_OFFSETS] {
+            if let Some(&[id]) = image_operand(mask, mask_operands, bit) {
+                constant_ints(&module.constant(id), &mut offsets);
+            }
+        }
+
+        for &variable in variables {
+            let usage = usages.entry(variable).or_insert_with(|| ReflectImageUsage {
+                spirv_id: variable,
+                name: module.name(variable),
+                set: module
+                    .decoration(variable, spirv_headers::Decoration::DescriptorSet)
+                    .and_then(|operands| operands.first().cloned())
+                    .unwrap_or(0),
+                binding: module
+                    .decoration(variable, spirv_headers::Decoration::Binding)
+                    .and_then(|operands| operands.first().cloned())
+                    .unwrap_or(0),
+                ..Default::default()
+            });
+            usage.flags |= flags;
+            if flags.contains(ReflectImageUsageFlags::ATOMIC) && has_64_bit_texels(module, variable)
+            {
+                usage.flags |= ReflectImageUsageFlags::ATOMIC_64;
+            }
+            if is_storage_image_without_format(module, variable) {
+                if flags.contains(ReflectImageUsageFlags::READ) {
+                    usage.flags |= ReflectImageUsageFlags::READ_WITHOUT_FORMAT;
+                }
+                if flags.contains(ReflectImageUsageFlags::WRITE) {
+                    usage.flags |= ReflectImageUsageFlags::WRITE_WITHOUT_FORMAT;
+                }
+            }
+            for (constant, constants) in [
+                (&bias, &mut usage.lod_biases),
+                (&min_lod, &mut usage.min_lods),
+            ] {
+                if let Some(constant) = constant {
+                    if !constants.contains(constant) {
+                        constants.push(constant.clone());
+                    }
+                }
+            }
+            if flags.contains(ReflectImageUsageFlags::GATHER) {
+                widen(&mut usage.gather_offset_range, &offsets);
+            } else {
+                widen(&mut usage.texel_offset_range, &offsets);
+            }
+        }
+    }
+    let mut usages: Vec<ReflectImageUsage> = usages.into_values().collect();
+    usages.sort_by_key(|usage| (usage.set, usage.binding));
+    Ok(usages)
+}
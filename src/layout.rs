@@ -0,0 +1,102 @@
+//! Checks of CPU-side struct layouts against reflected blocks, so that a
+//! unit test catches a struct that drifted from its shader.
+
+use crate::types::ReflectBlockVariable;
+
+/// One field of a CPU-side struct. `offset` and `size` are typically taken
+/// with `memoffset::offset_of!` and `std::mem::size_of`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CpuField<'a> {
+    /// Member path in the block, names separated by dots as in
+    /// `light.position`. Arrays along the path are entered at their first
+    /// element.
+    pub name: &'a str,
+    /// Byte offset from the start of the struct.
+    pub offset: usize,
+    pub size: usize,
+}
+
+impl<'a> CpuField<'a> {
+    pub fn new(name: &'a str, offset: usize, size: usize) -> Self {
+        CpuField { name, offset, size }
+    }
+}
+
+/// A difference between a CPU-side struct and the block it mirrors.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ReflectLayoutMismatch {
+    /// The block has no member at the field's path.
+    UnknownField { name: String },
+    /// No field names the direct member of the block.
+    MissingField { name: String },
+    Offset {
+        name: String,
+        cpu: usize,
+        reflected: u32,
+    },
+    Size {
+        name: String,
+        cpu: usize,
+        reflected: u32,
+    },
+}
+
+/// The member at `path` and its offset from the start of `block`.
+fn find_member<'a>(
+    block: &'a ReflectBlockVariable,
+    path: &str,
+) -> Option<(u32, &'a ReflectBlockVariable)> {
+    let mut offset = 0;
+    let mut member = block;
+    for name in path.split('.') {
+        member = member.members.iter().find(|member| member.name == name)?;
+        offset += member.offset;
+    }
+    Some((offset, member))
+}
+
+/// Compares `fields` with the members of `block`, in the order of `fields`
+/// followed by the direct members no field covers. The size of a trailing
+/// runtime array is not compared, and it may be left out of `fields`.
+pub fn check_cpu_layout(
+    block: &ReflectBlockVariable,
+    fields: &[CpuField],
+) -> Vec<ReflectLayoutMismatch> {
+    let mut mismatches = Vec::new();
+    for field in fields {
+        let name = field.name.to_owned();
+        let (offset, member) = match find_member(block, field.name) {
+            Some(found) => found,
+            None => {
+                mismatches.push(ReflectLayoutMismatch::UnknownField { name });
+                continue;
+            }
+        };
+        if field.offset != offset as usize {
+            mismatches.push(ReflectLayoutMismatch::Offset {
+                name: name.clone(),
+                cpu: field.offset,
+                reflected: offset,
+            });
+        }
+        if !member.array.runtime && field.size != member.size as usize {
+            mismatches.push(ReflectLayoutMismatch::Size {
+                name,
+                cpu: field.size,
+                reflected: member.size,
+            });
+        }
+    }
+
+    for member in &block.members {
+        let covered = fields
+            .iter()
+            .any(|field| field.name.split('.').next() == Some(member.name.as_str()));
+        if !covered && !member.array.runtime {
+            mismatches.push(ReflectLayoutMismatch::MissingField {
+                name: member.name.clone(),
+            });
+        }
+    }
+    mismatches
+}
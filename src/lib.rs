@@ -6,10 +6,37 @@ extern crate spirv_headers;
 extern crate serde_derive;
 
 use num_traits::cast::FromPrimitive;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
 
+pub mod builder;
+pub mod cache;
 pub mod convert;
+#[cfg(feature = "d3d12")]
+pub mod d3d12;
+pub mod diff;
+mod disassemble;
+mod dot;
+mod extract;
 pub mod ffi;
+pub mod gl;
+mod image_usage;
+pub mod layout;
+mod metrics;
+#[cfg(feature = "msl")]
+pub mod msl;
+#[cfg(feature = "naga-interface")]
+pub mod naga_interface;
+mod parser;
+pub mod pipeline;
+pub mod synthetic;
+mod taint;
+mod trace;
 pub mod types;
+pub mod update_template;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 pub(crate) fn ffi_to_string(ffi: *const ::std::os::raw::c_char) -> String {
     if ffi.is_null() {
@@ -19,6 +46,10 @@ pub(crate) fn ffi_to_string(ffi: *const ::std::os::raw::c_char) -> String {
     }
 }
 
+fn entry_point_to_cstring(entry_point: &str) -> Result<std::ffi::CString, &'static str> {
+    std::ffi::CString::new(entry_point).map_err(|_| "Entry point name contains a nul byte")
+}
+
 impl Default for ffi::SpvReflectShaderModule {
     fn default() -> Self {
         unsafe { std::mem::zeroed() }
@@ -31,9 +62,169 @@ impl Default for ffi::SpvReflectDescriptorSet {
     }
 }
 
+/// Reflection of a SPIR-V module. Clones share the underlying data, so a
+/// module can be cloned (or put in an `Arc`) and read from several threads;
+/// the `change_*` methods give the modified clone its own copy first.
 #[derive(Default, Clone)]
 pub struct ShaderModule {
-    module: Option<ffi::SpvReflectShaderModule>,
+    handle: Option<Arc<ModuleHandle>>,
+}
+
+/// The C module, plus the conversions cached for the borrowing accessors.
+struct ModuleHandle {
+    module: ffi::SpvReflectShaderModule,
+    options: builder::ShaderModuleBuilder,
+    generator: types::ReflectGenerator,
+    reflection: OnceLock<ModuleReflection>,
+    parsed: OnceLock<Result<ParsedCode, &'static str>>,
+}
+
+// The C module is only written through `ShaderModule::module_mut`, which
+// requires unique access to the handle, and reads don't touch any shared
+// state in the C library.
+unsafe impl Send for ModuleHandle {}
+unsafe impl Sync for ModuleHandle {}
+
+impl Drop for ModuleHandle {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::spvReflectDestroyShaderModule(&mut self.module);
+        }
+    }
+}
+
+/// What the Rust side parses out of the module's code, done once and shared
+/// by everything that reads more than the C library reflects.
+struct ParsedCode {
+    module: parser::ParsedModule,
+    sources: Result<Vec<types::ReflectSource>, &'static str>,
+    /// Descriptor variables used by a function body, for `accessed`.
+    accessed_variables: HashSet<u32>,
+}
+
+/// Module-wide enumerations, converted once and borrowed by the slice
+/// accessors of `ShaderModule`.
+#[derive(Default, Clone)]
+struct ModuleReflection {
+    descriptor_bindings: Vec<types::ReflectDescriptorBinding>,
+    /// Index into `descriptor_bindings` by variable id.
+    binding_indices: HashMap<u32, usize>,
+    descriptor_sets: Vec<types::ReflectDescriptorSet>,
+    input_variables: Vec<types::ReflectInterfaceVariable>,
+    output_variables: Vec<types::ReflectInterfaceVariable>,
+    push_constant_blocks: Vec<types::ReflectBlockVariable>,
+    entry_points: Vec<types::ReflectEntryPoint>,
+    warnings: Vec<types::ReflectWarning>,
+}
+
+impl ModuleReflection {
+    /// Applies `change` to every copy of the descriptor bindings, counters
+    /// included, then orders and groups them the way a fresh reflection does.
+    fn update_bindings(&mut self, change: impl Fn(&mut types::ReflectDescriptorBinding)) {
+        let change = |binding: &mut types::ReflectDescriptorBinding| {
+            change(binding);
+            if let Some(ref mut counter) = binding.uav_counter_binding {
+                change(counter);
+            }
+        };
+        self.descriptor_bindings.iter_mut().for_each(change);
+        self.descriptor_bindings
+            .sort_by_key(|binding| (binding.set, binding.binding));
+        self.binding_indices = self
+            .descriptor_bindings
+            .iter()
+            .enumerate()
+            .map(|(index, binding)| (binding.spirv_id, index))
+            .collect();
+        regroup_descriptor_sets(&mut self.descriptor_sets, change);
+        for entry_point in &mut self.entry_points {
+            regroup_descriptor_sets(&mut entry_point.descriptor_sets, change);
+        }
+        for warning in &mut self.warnings {
+            if let types::ReflectWarning::UnusedBinding {
+                spirv_id,
+                set,
+                binding,
+                ..
+            } = warning
+            {
+                if let Some(&index) = self.binding_indices.get(spirv_id) {
+                    *set = self.descriptor_bindings[index].set;
+                    *binding = self.descriptor_bindings[index].binding;
+                }
+            }
+        }
+    }
+
+    /// Moves the interface variable `spirv_id` to `location`, in the module
+    /// and in every entry point.
+    fn update_location(&mut self, input: bool, spirv_id: u32, location: u32) {
+        let relocate = |variables: &mut Vec<types::ReflectInterfaceVariable>| {
+            for variable in variables.iter_mut() {
+                if variable.spirv_id == spirv_id {
+                    variable.location = location;
+                }
+            }
+            variables.sort_by_key(|variable| variable.location);
+        };
+        if input {
+            relocate(&mut self.input_variables);
+        } else {
+            relocate(&mut self.output_variables);
+        }
+        for entry_point in &mut self.entry_points {
+            if input {
+                relocate(&mut entry_point.input_variables);
+                entry_point.input_locations =
+                    types::variable::build_location_map(&entry_point.input_variables);
+            } else {
+                relocate(&mut entry_point.output_variables);
+                entry_point.output_locations =
+                    types::variable::build_location_map(&entry_point.output_variables);
+                entry_point.output_built_ins =
+                    types::variable::build_built_in_map(&entry_point.output_variables);
+            }
+        }
+    }
+
+    /// Points the sets at the C sets of the same number, which the C library
+    /// rebuilds after every change.
+    fn refresh_set_handles(&mut self, module: &ffi::SpvReflectShaderModule) {
+        let entry_point_sets = self
+            .entry_points
+            .iter_mut()
+            .flat_map(|entry_point| entry_point.descriptor_sets.iter_mut());
+        for set in self.descriptor_sets.iter_mut().chain(entry_point_sets) {
+            set.internal_data = find_descriptor_set(module, set.set).unwrap_or(std::ptr::null());
+        }
+    }
+}
+
+/// Applies `change` to the bindings of `sets` and regroups them by set, with
+/// the sets and the bindings of each ordered by number.
+fn regroup_descriptor_sets(
+    sets: &mut Vec<types::ReflectDescriptorSet>,
+    change: impl Fn(&mut types::ReflectDescriptorBinding),
+) {
+    let mut grouped: BTreeMap<u32, types::ReflectDescriptorSet> = BTreeMap::new();
+    for set in sets.drain(..) {
+        for mut binding in set.bindings {
+            change(&mut binding);
+            grouped
+                .entry(binding.set)
+                .or_insert_with(|| types::ReflectDescriptorSet {
+                    set: binding.set,
+                    bindings: Vec::new(),
+                    internal_data: std::ptr::null(),
+                })
+                .bindings
+                .push(binding);
+        }
+    }
+    for mut set in grouped.into_values() {
+        set.bindings.sort_by_key(|binding| binding.binding);
+        sets.push(set);
+    }
 }
 
 impl ShaderModule {
@@ -42,45 +233,50 @@ impl ShaderModule {
     }
 
     pub fn load_u32_data(spv_data: &[u32]) -> Result<ShaderModule, &'static str> {
-        let u8_data: &[u8] = unsafe {
-            std::slice::from_raw_parts(
-                spv_data.as_ptr() as *const u8,
-                spv_data.len() * std::mem::size_of::<u32>(),
-            )
-        };
-        Ok(create_shader_module(u8_data)?)
+        Ok(create_shader_module(words_as_bytes(spv_data))?)
+    }
+
+    /// Options for loading a module other than the defaults of `load_u8_data`.
+    pub fn builder() -> builder::ShaderModuleBuilder {
+        builder::ShaderModuleBuilder::new()
     }
 
     pub fn get_code(&self) -> Vec<u32> {
-        match self.module {
-            Some(ref module) => {
-                let code_size = unsafe { ffi::spvReflectGetCodeSize(module) as usize };
-                let code_slice = unsafe {
-                    std::slice::from_raw_parts(ffi::spvReflectGetCode(module), code_size / 4)
-                };
-                code_slice.to_owned()
-            }
+        match self.module() {
+            Some(module) => module_code(module),
             None => Vec::new(),
         }
     }
 
     pub fn get_generator(&self) -> types::ReflectGenerator {
-        match self.module {
-            Some(ref module) => convert::ffi_to_generator(module.generator),
+        match self.handle {
+            Some(ref handle) => handle.generator,
             None => types::ReflectGenerator::Unknown,
         }
     }
 
+    /// The generator quirks worked around for this module: the ones enabled
+    /// on the builder that apply to its generator.
+    pub fn get_quirks(&self) -> builder::ReflectQuirkFlags {
+        match self.handle {
+            Some(ref handle) => handle.options.enabled_quirks(handle.generator),
+            None => builder::ReflectQuirkFlags::NONE,
+        }
+    }
+
     pub fn get_shader_stage(&self) -> types::ReflectShaderStageFlags {
-        match self.module {
-            Some(ref module) => convert::ffi_to_shader_stage_flags(module.shader_stage),
+        if let Some(entry_point) = self.selected() {
+            return entry_point.shader_stage;
+        }
+        match self.module() {
+            Some(module) => convert::ffi_to_shader_stage_flags(module.shader_stage),
             None => types::ReflectShaderStageFlags::UNDEFINED,
         }
     }
 
     pub fn get_source_language(&self) -> spirv_headers::SourceLanguage {
-        match self.module {
-            Some(ref module) => {
+        match self.module() {
+            Some(module) => {
                 match spirv_headers::SourceLanguage::from_u32(module.source_language as u32) {
                     Some(language) => language,
                     None => spirv_headers::SourceLanguage::Unknown,
@@ -91,29 +287,164 @@ impl ShaderModule {
     }
 
     pub fn get_source_language_version(&self) -> u32 {
-        match self.module {
-            Some(ref module) => module.source_language_version,
+        match self.module() {
+            Some(module) => module.source_language_version,
             None => 0,
         }
     }
 
+    /// File of the first `OpSource`, the primary one of `enumerate_sources`.
     pub fn get_source_file(&self) -> String {
-        match self.module {
-            Some(ref module) => ffi_to_string(module.source_file),
-            None => String::new(),
+        match self.primary_source() {
+            Some(source) => source.file,
+            None => match self.module() {
+                Some(module) => ffi_to_string(module.source_file),
+                None => String::new(),
+            },
         }
     }
 
+    /// Text of the first `OpSource`, the primary one of `enumerate_sources`.
     pub fn get_source_text(&self) -> String {
-        match self.module {
-            Some(ref module) => ffi_to_string(module.source_source),
-            None => String::new(),
+        match self.primary_source() {
+            Some(source) => source.text,
+            None => match self.module() {
+                Some(module) => ffi_to_string(module.source_source),
+                None => String::new(),
+            },
+        }
+    }
+
+    /// Every `OpSource` in the module in declaration order, the primary file
+    /// first.
+    pub fn enumerate_sources(&self) -> Result<Vec<types::ReflectSource>, &'static str> {
+        match self.parsed_code()? {
+            Some(parsed) => parsed.sources.clone(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn primary_source(&self) -> Option<types::ReflectSource> {
+        self.enumerate_sources()
+            .ok()
+            .and_then(|sources| sources.into_iter().next())
+    }
+
+    /// `OpName` of an id.
+    pub fn name_of(&self, id: u32) -> Result<Option<String>, &'static str> {
+        Ok(self
+            .parsed_module()?
+            .and_then(|parsed| parsed.names.get(&id).cloned()))
+    }
+
+    /// `OpMemberName` of a struct member as a `Struct.member` path, or just
+    /// the member name if the struct is unnamed.
+    pub fn member_name_of(&self, id: u32, member: u32) -> Result<Option<String>, &'static str> {
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(None),
+        };
+        Ok(parsed
+            .member_names
+            .get(&(id, member))
+            .map(|member_name| match parsed.names.get(&id) {
+                Some(name) if !name.is_empty() => format!("{}.{}", name, member_name),
+                _ => member_name.clone(),
+            }))
+    }
+
+    /// Ids with the given `OpName`, in ascending order.
+    pub fn ids_named(&self, name: &str) -> Result<Vec<u32>, &'static str> {
+        let mut ids: Vec<u32> = match self.parsed_module()? {
+            Some(parsed) => parsed
+                .names
+                .iter()
+                .filter(|(_, id_name)| *id_name == name)
+                .map(|(id, _)| *id)
+                .collect(),
+            None => Vec::new(),
+        };
+        ids.sort_unstable();
+        Ok(ids)
+    }
+
+    /// `(struct id, member index)` of the members matching a `Struct.member`
+    /// path or a bare member name, in ascending order.
+    pub fn members_named(&self, name: &str) -> Result<Vec<(u32, u32)>, &'static str> {
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(Vec::new()),
+        };
+        let (struct_name, member_name) = match name.rfind('.') {
+            Some(dot) => (Some(&name[..dot]), &name[dot + 1..]),
+            None => (None, name),
+        };
+        let mut members: Vec<(u32, u32)> = parsed
+            .member_names
+            .iter()
+            .filter(|(&(id, _), name)| {
+                *name == member_name
+                    && match struct_name {
+                        Some(struct_name) => {
+                            parsed.names.get(&id).map(String::as_str) == Some(struct_name)
+                        }
+                        None => true,
+                    }
+            })
+            .map(|(member, _)| *member)
+            .collect();
+        members.sort_unstable();
+        Ok(members)
+    }
+
+    /// Every `OpString` in the module, in declaration order.
+    pub fn enumerate_strings(&self) -> Result<Vec<types::ReflectString>, &'static str> {
+        match self.module() {
+            Some(_) => parser::parse_strings(&parser::parse_instructions(&self.get_code())?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Shader model, profile and entry point DXC compiled an HLSL module
+    /// with, or `None` for other source languages.
+    pub fn get_hlsl_source_info(
+        &self,
+    ) -> Result<Option<types::ReflectHlslSourceInfo>, &'static str> {
+        match self.module() {
+            Some(_) => {
+                let code = self.get_code();
+                parser::parse_hlsl_source_info(&parser::parse_instructions(&code)?)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Functions and global variables the module imports or exports, for
+    /// checking a set of modules before handing them to `spirv-link`.
+    pub fn enumerate_linkage_symbols(
+        &self,
+    ) -> Result<Vec<types::ReflectLinkageSymbol>, &'static str> {
+        match self.parsed_module()? {
+            Some(parsed) => parsed.linkage_symbols(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Names and signatures of every function in the module, e.g. to match
+    /// the exports of a library against the imports of its users.
+    pub fn enumerate_functions(&self) -> Result<Vec<types::ReflectFunction>, &'static str> {
+        match self.parsed_module()? {
+            Some(parsed) => parsed.function_signatures(),
+            None => Ok(Vec::new()),
         }
     }
 
     pub fn get_spirv_execution_model(&self) -> spirv_headers::ExecutionModel {
-        match self.module {
-            Some(ref module) => {
+        if let Some(entry_point) = self.selected() {
+            return entry_point.spirv_execution_model;
+        }
+        match self.module() {
+            Some(module) => {
                 match spirv_headers::ExecutionModel::from_u32(module.spirv_execution_model as u32) {
                     Some(model) => model,
                     None => spirv_headers::ExecutionModel::Vertex,
@@ -123,16 +454,18 @@ impl ShaderModule {
         }
     }
 
+    /// Sorted by location, with built-ins last.
     pub fn enumerate_input_variables(
         &self,
         entry_point: Option<&str>,
     ) -> Result<Vec<types::ReflectInterfaceVariable>, &'static str> {
-        if let Some(ref module) = self.module {
+        let entry_point = entry_point.or_else(|| self.selected_entry_point());
+        if let Some(module) = self.module() {
             let mut count: u32 = 0;
             let result = unsafe {
                 match entry_point {
                     Some(entry_point) => {
-                        let entry_point_cstr = std::ffi::CString::new(entry_point).unwrap();
+                        let entry_point_cstr = entry_point_to_cstring(entry_point)?;
                         ffi::spvReflectEnumerateEntryPointInputVariables(
                             module,
                             entry_point_cstr.as_ptr(),
@@ -154,7 +487,7 @@ impl ShaderModule {
                     let mut out_count: u32 = count;
                     match entry_point {
                         Some(entry_point) => {
-                            let entry_point_cstr = std::ffi::CString::new(entry_point).unwrap();
+                            let entry_point_cstr = entry_point_to_cstring(entry_point)?;
                             ffi::spvReflectEnumerateEntryPointInputVariables(
                                 module,
                                 entry_point_cstr.as_ptr(),
@@ -171,10 +504,12 @@ impl ShaderModule {
                 };
                 match result {
                     ffi::SpvReflectResult_SPV_REFLECT_RESULT_SUCCESS => {
-                        let vars: Vec<types::ReflectInterfaceVariable> = ffi_vars
+                        let mut vars: Vec<types::ReflectInterfaceVariable> = ffi_vars
                             .iter()
                             .map(|&var| convert::ffi_to_interface_variable(var))
                             .collect();
+                        vars.sort_by_key(|var| var.location);
+                        self.annotate_interface_variables(vars.iter_mut())?;
                         Ok(vars)
                     }
                     _ => Err(convert::result_to_string(result)),
@@ -187,16 +522,18 @@ impl ShaderModule {
         }
     }
 
+    /// Sorted by location, with built-ins last.
     pub fn enumerate_output_variables(
         &self,
         entry_point: Option<&str>,
     ) -> Result<Vec<types::ReflectInterfaceVariable>, &'static str> {
-        if let Some(ref module) = self.module {
+        let entry_point = entry_point.or_else(|| self.selected_entry_point());
+        if let Some(module) = self.module() {
             let mut count: u32 = 0;
             let result = unsafe {
                 match entry_point {
                     Some(entry_point) => {
-                        let entry_point_cstr = std::ffi::CString::new(entry_point).unwrap();
+                        let entry_point_cstr = entry_point_to_cstring(entry_point)?;
                         ffi::spvReflectEnumerateEntryPointOutputVariables(
                             module,
                             entry_point_cstr.as_ptr(),
@@ -218,7 +555,7 @@ impl ShaderModule {
                     let mut out_count: u32 = count;
                     match entry_point {
                         Some(entry_point) => {
-                            let entry_point_cstr = std::ffi::CString::new(entry_point).unwrap();
+                            let entry_point_cstr = entry_point_to_cstring(entry_point)?;
                             ffi::spvReflectEnumerateEntryPointOutputVariables(
                                 module,
                                 entry_point_cstr.as_ptr(),
@@ -235,10 +572,12 @@ impl ShaderModule {
                 };
                 match result {
                     ffi::SpvReflectResult_SPV_REFLECT_RESULT_SUCCESS => {
-                        let vars: Vec<types::ReflectInterfaceVariable> = ffi_vars
+                        let mut vars: Vec<types::ReflectInterfaceVariable> = ffi_vars
                             .iter()
                             .map(|&var| convert::ffi_to_interface_variable(var))
                             .collect();
+                        vars.sort_by_key(|var| var.location);
+                        self.annotate_interface_variables(vars.iter_mut())?;
                         Ok(vars)
                     }
                     _ => Err(convert::result_to_string(result)),
@@ -251,16 +590,18 @@ impl ShaderModule {
         }
     }
 
+    /// Sorted by set, then by binding number.
     pub fn enumerate_descriptor_bindings(
         &self,
         entry_point: Option<&str>,
     ) -> Result<Vec<types::ReflectDescriptorBinding>, &'static str> {
-        if let Some(ref module) = self.module {
+        let entry_point = entry_point.or_else(|| self.selected_entry_point());
+        if let Some(module) = self.module() {
             let mut count: u32 = 0;
             let result = unsafe {
                 match entry_point {
                     Some(entry_point) => {
-                        let entry_point_cstr = std::ffi::CString::new(entry_point).unwrap();
+                        let entry_point_cstr = entry_point_to_cstring(entry_point)?;
                         ffi::spvReflectEnumerateEntryPointDescriptorBindings(
                             module,
                             entry_point_cstr.as_ptr(),
@@ -282,7 +623,7 @@ impl ShaderModule {
                     let mut out_count: u32 = count;
                     match entry_point {
                         Some(entry_point) => {
-                            let entry_point_cstr = std::ffi::CString::new(entry_point).unwrap();
+                            let entry_point_cstr = entry_point_to_cstring(entry_point)?;
                             ffi::spvReflectEnumerateEntryPointDescriptorBindings(
                                 module,
                                 entry_point_cstr.as_ptr(),
@@ -299,10 +640,15 @@ impl ShaderModule {
                 };
                 match result {
                     ffi::SpvReflectResult_SPV_REFLECT_RESULT_SUCCESS => {
-                        let bindings: Vec<types::ReflectDescriptorBinding> = ffi_bindings
+                        let mut bindings: Vec<types::ReflectDescriptorBinding> = ffi_bindings
                             .iter()
                             .map(|&binding| convert::ffi_to_descriptor_binding(binding))
                             .collect();
+                        bindings.sort_by_key(|binding| (binding.set, binding.binding));
+                        self.assign_binding_stages(entry_point, bindings.iter_mut());
+                        self.annotate_binding_layouts(bindings.iter_mut())?;
+                        self.mark_accessed_bindings(bindings.iter_mut())?;
+                        self.link_counter_bindings(bindings.iter_mut())?;
                         Ok(bindings)
                     }
                     _ => Err(convert::result_to_string(result)),
@@ -315,16 +661,19 @@ impl ShaderModule {
         }
     }
 
+    /// Sorted by set number, with the bindings of each set sorted by binding
+    /// number.
     pub fn enumerate_descriptor_sets(
         &self,
         entry_point: Option<&str>,
     ) -> Result<Vec<types::ReflectDescriptorSet>, &'static str> {
-        if let Some(ref module) = self.module {
+        let entry_point = entry_point.or_else(|| self.selected_entry_point());
+        if let Some(module) = self.module() {
             let mut count: u32 = 0;
             let result = unsafe {
                 match entry_point {
                     Some(entry_point) => {
-                        let entry_point_cstr = std::ffi::CString::new(entry_point).unwrap();
+                        let entry_point_cstr = entry_point_to_cstring(entry_point)?;
                         ffi::spvReflectEnumerateEntryPointDescriptorSets(
                             module,
                             entry_point_cstr.as_ptr(),
@@ -346,7 +695,7 @@ impl ShaderModule {
                     let mut out_count: u32 = count;
                     match entry_point {
                         Some(entry_point) => {
-                            let entry_point_cstr = std::ffi::CString::new(entry_point).unwrap();
+                            let entry_point_cstr = entry_point_to_cstring(entry_point)?;
                             ffi::spvReflectEnumerateEntryPointDescriptorSets(
                                 module,
                                 entry_point_cstr.as_ptr(),
@@ -362,10 +711,27 @@ impl ShaderModule {
                     }
                 };
                 match result {
-                    ffi::SpvReflectResult_SPV_REFLECT_RESULT_SUCCESS => Ok(ffi_sets
-                        .iter()
-                        .map(|&set| convert::ffi_to_descriptor_set(set))
-                        .collect()),
+                    ffi::SpvReflectResult_SPV_REFLECT_RESULT_SUCCESS => {
+                        let mut sets: Vec<types::ReflectDescriptorSet> = ffi_sets
+                            .iter()
+                            .map(|&set| convert::ffi_to_descriptor_set(set))
+                            .collect();
+                        sets.sort_by_key(|set| set.set);
+                        self.assign_binding_stages(
+                            entry_point,
+                            sets.iter_mut().flat_map(|set| set.bindings.iter_mut()),
+                        );
+                        self.annotate_binding_layouts(
+                            sets.iter_mut().flat_map(|set| set.bindings.iter_mut()),
+                        )?;
+                        self.mark_accessed_bindings(
+                            sets.iter_mut().flat_map(|set| set.bindings.iter_mut()),
+                        )?;
+                        self.link_counter_bindings(
+                            sets.iter_mut().flat_map(|set| set.bindings.iter_mut()),
+                        )?;
+                        Ok(sets)
+                    }
                     _ => Err(convert::result_to_string(result)),
                 }
             } else {
@@ -376,16 +742,87 @@ impl ShaderModule {
         }
     }
 
+    /// Like `enumerate_descriptor_bindings`, but skips bindings that are
+    /// never statically accessed by the shader code.
+    pub fn enumerate_used_descriptor_bindings(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Vec<types::ReflectDescriptorBinding>, &'static str> {
+        let mut bindings = self.enumerate_descriptor_bindings(entry_point)?;
+        bindings.retain(|binding| binding.accessed);
+        Ok(bindings)
+    }
+
+    /// Like `enumerate_descriptor_sets`, but only keeps statically accessed
+    /// bindings. Sets left without any bindings are dropped.
+    pub fn enumerate_used_descriptor_sets(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Vec<types::ReflectDescriptorSet>, &'static str> {
+        let mut sets = self.enumerate_descriptor_sets(entry_point)?;
+        for set in &mut sets {
+            set.bindings.retain(|binding| binding.accessed);
+        }
+        sets.retain(|set| !set.bindings.is_empty());
+        Ok(sets)
+    }
+
+    /// Like `enumerate_descriptor_sets`, but with an empty set inserted for every
+    /// unused set number below the highest one, matching what
+    /// `VkPipelineLayoutCreateInfo::pSetLayouts` expects.
+    pub fn enumerate_contiguous_descriptor_sets(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Vec<types::ReflectDescriptorSet>, &'static str> {
+        let sets = self.enumerate_descriptor_sets(entry_point)?;
+        let mut contiguous = Vec::with_capacity(sets.len());
+        for set in sets {
+            while (contiguous.len() as u32) < set.set {
+                contiguous.push(types::ReflectDescriptorSet {
+                    set: contiguous.len() as u32,
+                    bindings: Vec::new(),
+                    internal_data: std::ptr::null(),
+                });
+            }
+            contiguous.push(set);
+        }
+        Ok(contiguous)
+    }
+
+    /// Highest descriptor set number in use, or `None` without descriptor sets.
+    pub fn max_set_index(&self, entry_point: Option<&str>) -> Result<Option<u32>, &'static str> {
+        Ok(self
+            .enumerate_descriptor_sets(entry_point)?
+            .iter()
+            .map(|set| set.set)
+            .max())
+    }
+
+    /// Highest binding number in descriptor set `set`, or `None` if the set is unused.
+    pub fn max_binding_index(
+        &self,
+        set: u32,
+        entry_point: Option<&str>,
+    ) -> Result<Option<u32>, &'static str> {
+        Ok(self
+            .enumerate_descriptor_sets(entry_point)?
+            .iter()
+            .filter(|descriptor_set| descriptor_set.set == set)
+            .filter_map(|descriptor_set| descriptor_set.max_binding_index())
+            .max())
+    }
+
     pub fn enumerate_push_constant_blocks(
         &self,
         entry_point: Option<&str>,
     ) -> Result<Vec<types::ReflectBlockVariable>, &'static str> {
-        if let Some(ref module) = self.module {
+        let entry_point = entry_point.or_else(|| self.selected_entry_point());
+        if let Some(module) = self.module() {
             let mut count: u32 = 0;
             let result = unsafe {
                 match entry_point {
                     Some(entry_point) => {
-                        let entry_point_cstr = std::ffi::CString::new(entry_point).unwrap();
+                        let entry_point_cstr = entry_point_to_cstring(entry_point)?;
                         ffi::spvReflectEnumerateEntryPointPushConstantBlocks(
                             module,
                             entry_point_cstr.as_ptr(),
@@ -407,7 +844,7 @@ impl ShaderModule {
                     let mut out_count: u32 = count;
                     match entry_point {
                         Some(entry_point) => {
-                            let entry_point_cstr = std::ffi::CString::new(entry_point).unwrap();
+                            let entry_point_cstr = entry_point_to_cstring(entry_point)?;
                             ffi::spvReflectEnumerateEntryPointPushConstantBlocks(
                                 module,
                                 entry_point_cstr.as_ptr(),
@@ -424,10 +861,23 @@ impl ShaderModule {
                 };
                 match result {
                     ffi::SpvReflectResult_SPV_REFLECT_RESULT_SUCCESS => {
-                        let blocks: Vec<types::ReflectBlockVariable> = ffi_blocks
+                        let mut blocks: Vec<types::ReflectBlockVariable> = ffi_blocks
                             .iter()
-                            .map(|&block| convert::ffi_to_block_variable(unsafe { &*block }))
+                            .map(|&block| {
+                                let mut block = convert::ffi_to_block_variable(unsafe { &*block });
+                                let type_id = block
+                                    .type_description
+                                    .as_ref()
+                                    .map_or(0, |type_description| type_description.id);
+                                block.synthesize_name(type_id);
+                                block
+                            })
                             .collect();
+                        if let Some(parsed) = self.parsed_module()? {
+                            for block in &mut blocks {
+                                parsed.annotate_block_layout(block);
+                            }
+                        }
                         Ok(blocks)
                     }
                     _ => Err(convert::result_to_string(result)),
@@ -440,149 +890,2188 @@ impl ShaderModule {
         }
     }
 
+    /// Like `enumerate_push_constant_blocks`, with the names of each block's
+    /// variable and type and the stages of the entry points that use it.
+    pub fn enumerate_push_constants(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Vec<types::ReflectPushConstantBlock>, &'static str> {
+        let blocks = self.enumerate_push_constant_blocks(entry_point)?;
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(Vec::new()),
+        };
+        let entry_point = entry_point.or_else(|| self.selected_entry_point());
+        Ok(blocks
+            .into_iter()
+            .map(|block| {
+                let type_description = block.type_description.as_ref();
+                let type_id = type_description.map_or(0, |type_description| type_description.id);
+                let variable = parsed.variables.iter().find(|variable| {
+                    variable.storage_class == spirv_headers::StorageClass::PushConstant as u32
+                        && (variable.id == block.spirv_id
+                            || parsed.pointee_type_id(variable.type_id) == type_id)
+                });
+                let variable_id = variable.map_or(block.spirv_id, |variable| variable.id);
+                let type_name = match variable {
+                    Some(variable) => parsed.name(parsed.pointee_type_id(variable.type_id)),
+                    None => type_description
+                        .map(|type_description| type_description.type_name.clone())
+                        .unwrap_or_default(),
+                };
+                types::ReflectPushConstantBlock {
+                    name: parsed.name(variable_id),
+                    type_name,
+                    stage_flags: self.stages_using(entry_point, |ffi_entry_point| {
+                        unsafe {
+                            std::slice::from_raw_parts(
+                                ffi_entry_point.used_push_constants,
+                                ffi_entry_point.used_push_constant_count as usize,
+                            )
+                        }
+                        .contains(&variable_id)
+                    }),
+                    block,
+                }
+            })
+            .collect())
+    }
+
+    /// Like `enumerate_input_variables`, without built-ins and blocks holding
+    /// only built-ins.
+    pub fn enumerate_user_defined_inputs(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Vec<types::ReflectInterfaceVariable>, &'static str> {
+        let mut variables = self.enumerate_input_variables(entry_point)?;
+        variables.retain(|variable| !variable.is_built_in());
+        Ok(variables)
+    }
+
+    /// Like `enumerate_output_variables`, without built-ins and blocks holding
+    /// only built-ins such as `gl_PerVertex`.
+    pub fn enumerate_user_defined_outputs(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Vec<types::ReflectInterfaceVariable>, &'static str> {
+        let mut variables = self.enumerate_output_variables(entry_point)?;
+        variables.retain(|variable| !variable.is_built_in());
+        Ok(variables)
+    }
+
+    /// Inputs flattened to one entry per location: members of interface
+    /// blocks, array elements and matrix columns are listed separately. The
+    /// outer per-vertex array of geometry and tessellation inputs is dropped.
+    pub fn enumerate_input_locations(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Vec<types::ReflectInterfaceLocation>, &'static str> {
+        self.interface_locations(entry_point, spirv_headers::StorageClass::Input)
+    }
+
+    /// Like `enumerate_input_locations`, for outputs.
+    pub fn enumerate_output_locations(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Vec<types::ReflectInterfaceLocation>, &'static str> {
+        self.interface_locations(entry_point, spirv_headers::StorageClass::Output)
+    }
+
+    fn interface_locations(
+        &self,
+        entry_point: Option<&str>,
+        storage_class: spirv_headers::StorageClass,
+    ) -> Result<Vec<types::ReflectInterfaceLocation>, &'static str> {
+        match self.parsed_module()? {
+            Some(parsed) => {
+                let entry_point = parsed.find_entry_point(entry_point)?;
+                Ok(parsed.interface_locations(entry_point, storage_class as u32))
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// In declaration order. The interface variables and descriptor sets of
+    /// each entry point are sorted like the module-wide enumerations.
     pub fn enumerate_entry_points(&self) -> Result<Vec<types::ReflectEntryPoint>, &'static str> {
-        if let Some(ref module) = self.module {
+        if let Some(module) = self.module() {
             let ffi_entry_points = unsafe {
                 std::slice::from_raw_parts(module.entry_points, module.entry_point_count as usize)
             };
-            let entry_points: Vec<types::ReflectEntryPoint> = ffi_entry_points
+            let mut entry_points: Vec<types::ReflectEntryPoint> = ffi_entry_points
                 .iter()
                 .map(|entry_point| convert::ffi_to_entry_point(entry_point))
                 .collect();
+            if let Some(selected) = self.selected_entry_point() {
+                entry_points.retain(|entry_point| entry_point.name == selected);
+            }
+            // The sets of an entry point are the module's sets narrowed down
+            // to the bindings it accesses.
+            let sets = self.enumerate_descriptor_sets(None)?;
+            self.annotate_interface_variables(entry_points.iter_mut().flat_map(|entry_point| {
+                entry_point
+                    .input_variables
+                    .iter_mut()
+                    .chain(entry_point.output_variables.iter_mut())
+            }))?;
+            let analyses = self.analyses();
+            let code = self.get_code();
+            let instructions = parser::parse_instructions(&code)?;
+            let functions = dot::functions_of(&instructions);
+            for entry_point in &mut entry_points {
+                let shader_stage = entry_point.shader_stage;
+                if analyses.contains(builder::ReflectAnalysisFlags::EXT_INST_USAGE) {
+                    entry_point.ext_inst_usage =
+                        metrics::ext_inst_usage(&instructions, &functions, entry_point.id)?;
+                }
+                if analyses.contains(builder::ReflectAnalysisFlags::BARRIERS) {
+                    entry_point.barriers =
+                        metrics::barriers(&instructions, &functions, entry_point.id);
+                }
+                entry_point.descriptor_sets = sets
+                    .iter()
+                    .map(|set| types::ReflectDescriptorSet {
+                        bindings: set
+                            .bindings
+                            .iter()
+                            .filter(|binding| entry_point.used_uniforms.contains(&binding.spirv_id))
+                            .map(|binding| types::ReflectDescriptorBinding {
+                                stage_flags: shader_stage,
+                                ..binding.clone()
+                            })
+                            .collect(),
+                        ..set.clone()
+                    })
+                    .filter(|set| !set.bindings.is_empty())
+                    .collect();
+            }
             Ok(entry_points)
         } else {
             Ok(Vec::new())
         }
     }
 
-    pub fn get_entry_point_name(&self) -> String {
-        match self.module {
-            Some(ref module) => ffi_to_string(module.entry_point_name),
-            None => String::new(),
+    /// Functions, global variables and descriptor bindings that no entry
+    /// point reaches, e.g. to clean up interfaces or flag wasted descriptor
+    /// slots.
+    pub fn unused_objects(&self) -> Result<types::ReflectUnusedObjects, &'static str> {
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(Default::default()),
+        };
+        let code = self.get_code();
+        let instructions = parser::parse_instructions(&code)?;
+        let functions = dot::functions_of(&instructions);
+        let mut roots: Vec<u32> = parsed
+            .linkage_symbols()?
+            .iter()
+            .filter(|symbol| symbol.linkage_type == types::ReflectLinkageType::Export)
+            .map(|symbol| symbol.spirv_id)
+            .collect();
+        for entry_point in &parsed.entry_points {
+            roots.push(entry_point.id);
+            roots.extend(&entry_point.interface);
         }
-    }
+        let live = extract::live_ids(&instructions, &functions, roots);
 
-    pub fn change_descriptor_binding_numbers(
-        &mut self,
-        binding: &types::descriptor::ReflectDescriptorBinding,
-        new_binding: u32,
-        new_set: Option<u32>,
-    ) -> Result<(), &'static str> {
-        match self.module {
-            Some(ref mut module) => {
-                let new_set = new_set.unwrap_or(ffi::SPV_REFLECT_SET_NUMBER_DONT_CHANGE as u32);
-                let result = unsafe {
-                    ffi::spvReflectChangeDescriptorBindingNumbers(
-                        module as *mut ffi::SpvReflectShaderModule,
-                        binding.internal_data,
-                        new_binding,
-                        new_set,
-                    )
-                };
-                match result {
-                    ffi::SpvReflectResult_SPV_REFLECT_RESULT_SUCCESS => Ok(()),
-                    _ => Err(convert::result_to_string(result)),
+        let descriptor_bindings: Vec<types::ReflectDescriptorBinding> = self
+            .enumerate_descriptor_bindings(None)?
+            .into_iter()
+            .filter(|binding| !live.contains(&binding.spirv_id))
+            .collect();
+        let global_variables = parsed
+            .variables
+            .iter()
+            .filter(|variable| {
+                !live.contains(&variable.id)
+                    && parsed
+                        .decoration(variable.id, spirv_headers::Decoration::Binding)
+                        .is_none()
+            })
+            .map(|variable| {
+                let type_description = parsed.type_description(variable.type_id, None);
+                types::ReflectGlobalVariable {
+                    spirv_id: variable.id,
+                    name: parsed.name(variable.id),
+                    storage_class: type_description.storage_class,
+                    type_description,
+                    initializer: variable.initializer.map(|id| parsed.constant(id)),
                 }
-            }
-            None => Ok(()),
-        }
+            })
+            .collect();
+        let functions = parsed
+            .function_signatures()?
+            .into_iter()
+            .filter(|function| function.has_body && !live.contains(&function.spirv_id))
+            .collect();
+        Ok(types::ReflectUnusedObjects {
+            functions,
+            global_variables,
+            descriptor_bindings,
+        })
     }
 
-    pub fn change_descriptor_set_number(
-        &mut self,
-        set: &types::descriptor::ReflectDescriptorSet,
-        new_set: u32,
-    ) -> Result<(), &'static str> {
-        match self.module {
-            Some(ref mut module) => {
-                let result = unsafe {
-                    ffi::spvReflectChangeDescriptorSetNumber(
-                        module as *mut ffi::SpvReflectShaderModule,
-                        set.internal_data,
-                        new_set,
-                    )
-                };
-                match result {
-                    ffi::SpvReflectResult_SPV_REFLECT_RESULT_SUCCESS => Ok(()),
-                    _ => Err(convert::result_to_string(result)),
-                }
-            }
-            None => Ok(()),
-        }
+    /// A copy of the module with only the entry point called `name` and the
+    /// functions, variables, types and decorations it uses, e.g. to ship one
+    /// permutation of a library of entry points.
+    pub fn extract_entry_point(&self, name: &str) -> Result<ShaderModule, &'static str> {
+        let code = extract::extract_entry_point(&self.get_code(), name)?;
+        let mut options = match self.handle {
+            Some(ref handle) => handle.options.clone(),
+            None => builder::ShaderModuleBuilder::new(),
+        };
+        options.entry_point = None;
+        create_shader_module_with(words_as_bytes(&code), options)
     }
 
-    pub fn change_input_variable_location(
-        &mut self,
-        variable: &types::variable::ReflectInterfaceVariable,
-        new_location: u32,
-    ) -> Result<(), &'static str> {
-        match self.module {
-            Some(ref mut module) => {
-                let result = unsafe {
-                    ffi::spvReflectChangeInputVariableLocation(
-                        module as *mut ffi::SpvReflectShaderModule,
-                        variable.internal_data,
-                        new_location,
-                    )
-                };
-                match result {
-                    ffi::SpvReflectResult_SPV_REFLECT_RESULT_SUCCESS => Ok(()),
-                    _ => Err(convert::result_to_string(result)),
-                }
-            }
-            None => Ok(()),
-        }
+    /// Instruction, block, loop, sampling and buffer access counts over the
+    /// code the entry point can reach.
+    pub fn get_metrics(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<types::ReflectShaderMetrics, &'static str> {
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(Default::default()),
+        };
+        let entry_point = parsed.find_entry_point(entry_point)?;
+        let code = self.get_code();
+        let instructions = parser::parse_instructions(&code)?;
+        let functions = dot::functions_of(&instructions);
+        Ok(metrics::shader_metrics(
+            &instructions,
+            &functions,
+            parsed,
+            entry_point.id,
+        ))
     }
 
-    pub fn change_output_variable_location(
-        &mut self,
-        variable: &types::variable::ReflectInterfaceVariable,
-        new_location: u32,
-    ) -> Result<(), &'static str> {
-        match self.module {
-            Some(ref mut module) => {
-                let result = unsafe {
-                    ffi::spvReflectChangeOutputVariableLocation(
-                        module as *mut ffi::SpvReflectShaderModule,
-                        variable.internal_data,
-                        new_location,
-                    )
-                };
-                match result {
-                    ffi::SpvReflectResult_SPV_REFLECT_RESULT_SUCCESS => Ok(()),
-                    _ => Err(convert::result_to_string(result)),
-                }
-            }
-            None => Ok(()),
-        }
+    /// Loops, their nesting and the branches of every function with a body,
+    /// in module order. Calls are not followed; see `get_control_flow`.
+    pub fn enumerate_function_control_flow(
+        &self,
+    ) -> Result<Vec<types::ReflectControlFlow>, &'static str> {
+        let code = self.get_code();
+        let instructions = parser::parse_instructions(&code)?;
+        Ok(dot::functions_of(&instructions)
+            .iter()
+            .map(|function| metrics::function_control_flow(&instructions, function).summary)
+            .collect())
     }
-}
 
-impl Drop for ShaderModule {
-    fn drop(&mut self) {
-        if let Some(ref mut module) = self.module {
-            unsafe {
-                ffi::spvReflectDestroyShaderModule(module);
-            }
-        }
+    /// Loops, their nesting and the branches of the code the entry point can
+    /// reach. Loops in called functions nest inside the loops around the call.
+    pub fn get_control_flow(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<types::ReflectControlFlow, &'static str> {
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(Default::default()),
+        };
+        let entry_point = parsed.find_entry_point(entry_point)?;
+        let code = self.get_code();
+        let instructions = parser::parse_instructions(&code)?;
+        let functions = dot::functions_of(&instructions);
+        Ok(metrics::entry_point_control_flow(
+            &instructions,
+            &functions,
+            entry_point.id,
+        ))
     }
-}
 
-/*
-impl From<&[u8]> for ShaderModule {
-    fn from(spv_data: &[u8]) -> Result<ShaderModule, &str> {
-        create_shader_module(spv_data)?
+    /// Static load, store, sample and atomic sites of each descriptor binding
+    /// in the code the entry point can reach, and how many sit inside loops,
+    /// as hints for which bindings are worth push descriptors or root
+    /// descriptors.
+    pub fn enumerate_binding_access(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Vec<types::ReflectBindingAccess>, &'static str> {
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(Vec::new()),
+        };
+        let entry_point = parsed.find_entry_point(entry_point)?;
+        let code = self.get_code();
+        let instructions = parser::parse_instructions(&code)?;
+        let functions = dot::functions_of(&instructions);
+        Ok(metrics::binding_access(
+            &instructions,
+            &functions,
+            parsed,
+            entry_point.id,
+        ))
     }
-}
-*/
 
-/*impl<'a, T: AsRef<[u8]>> From<T> for ShaderModule {
-    fn from(v: T) -> Result<ShaderModule, &'static str> {
-        Ok(create_shader_module(v.as_ref())?)
-    }
-}*/
+    /// The outputs and descriptor bindings each member of the entry point's
+    /// push constant block can influence, through values, memory, calls and
+    /// the branches it decides. Coarse: a member reaching any part of a
+    /// value reaches all of it. Members with no sinks can be pruned.
+    pub fn enumerate_push_constant_taint(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Vec<types::ReflectPushConstantTaint>, &'static str> {
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(Vec::new()),
+        };
+        let entry_point = parsed.find_entry_point(entry_point)?;
+        let code = self.get_code();
+        let instructions = parser::parse_instructions(&code)?;
+        let functions = dot::functions_of(&instructions);
+        Ok(taint::push_constant_taint(
+            &instructions,
+            &functions,
+            parsed,
+            entry_point,
+        ))
+    }
+
+    /// How each image and sampler binding is sampled: implicit or explicit
+    /// LOD, bias, gradients, offsets, gathers and so on, for picking sampler
+    /// modes and checking offsets against device limits.
+    pub fn enumerate_image_usage(&self) -> Result<Vec<types::ReflectImageUsage>, &'static str> {
+        match self.parsed_module()? {
+            Some(parsed) => image_usage::image_usage(&self.get_code(), parsed),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Every decoration on `id`, in declaration order and including
+    /// repeats and those applied through decoration groups.
+    pub fn get_decorations(&self, id: u32) -> Result<Vec<types::ReflectDecoration>, &'static str> {
+        Ok(match self.parsed_module()? {
+            Some(parsed) => parsed.decorations.get(&id).cloned().unwrap_or_default(),
+            None => Vec::new(),
+        })
+    }
+
+    /// The constants behind the operands of an `OpDecorateId` decoration,
+    /// such as the `AlignmentId` of a kernel argument. Empty for decorations
+    /// with literal operands.
+    pub fn resolve_decoration_operands(
+        &self,
+        decoration: &types::ReflectDecoration,
+    ) -> Result<Vec<types::ReflectConstant>, &'static str> {
+        Ok(match self.parsed_module()? {
+            Some(parsed) if decoration.operands_are_ids => decoration
+                .operands
+                .iter()
+                .map(|operand| parsed.constant(*operand))
+                .collect(),
+            _ => Vec::new(),
+        })
+    }
+
+    /// Every decoration on member `member` of the struct `id`, like
+    /// `get_decorations`.
+    pub fn get_member_decorations(
+        &self,
+        id: u32,
+        member: u32,
+    ) -> Result<Vec<types::ReflectDecoration>, &'static str> {
+        Ok(match self.parsed_module()? {
+            Some(parsed) => parsed
+                .member_decorations
+                .get(&(id, member))
+                .cloned()
+                .unwrap_or_default(),
+            None => Vec::new(),
+        })
+    }
+
+    /// All `OpExecutionMode` and `OpExecutionModeId` instructions targeting
+    /// the entry point, in declaration order.
+    pub fn enumerate_execution_modes(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Vec<types::ReflectExecutionMode>, &'static str> {
+        match self.parsed_module()? {
+            Some(parsed) => {
+                let entry_point = parsed.find_entry_point(entry_point)?;
+                Ok(parsed.entry_point_execution_modes(entry_point.id))
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn get_float_controls(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<types::ReflectFloatControls, &'static str> {
+        let execution_modes = self.enumerate_execution_modes(entry_point)?;
+        Ok(types::ReflectFloatControls::from_execution_modes(
+            &execution_modes,
+        ))
+    }
+
+    /// Workgroup size of a compute-like entry point, keeping components that
+    /// depend on specialization constants symbolic. `None` if the entry point
+    /// declares none.
+    pub fn get_workgroup_size(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Option<[types::ReflectSizeExpr; 3]>, &'static str> {
+        match self.parsed_module()? {
+            Some(parsed) => {
+                let entry_point = parsed.find_entry_point(entry_point)?;
+                Ok(parsed.workgroup_size(entry_point))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Type description of the type with SPIR-V id `type_id`, such as the
+    /// pointee of a physical storage buffer pointer, or `None` if the id is
+    /// not a type.
+    pub fn get_type_description(
+        &self,
+        type_id: u32,
+    ) -> Result<Option<types::ReflectTypeDescription>, &'static str> {
+        match self.parsed_module()? {
+            Some(parsed) if parsed.types.contains_key(&type_id) => {
+                Ok(Some(parsed.type_description(type_id, None)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Array dimensions of a type, outermost first, as symbolic sizes. The
+    /// numeric `array.dims` of the same type hold the defaults.
+    pub fn get_array_size_exprs(
+        &self,
+        type_description: &types::ReflectTypeDescription,
+    ) -> Result<Vec<types::ReflectSizeExpr>, &'static str> {
+        match self.parsed_module()? {
+            Some(parsed) => Ok(parsed.array_size_exprs(type_description.id)),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Layout of the `ShaderRecordBufferKHR` block visible to a ray tracing
+    /// entry point, if it declares one.
+    pub fn get_shader_record_block(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Option<types::ReflectBlockVariable>, &'static str> {
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(None),
+        };
+        let entry_point = parsed.find_entry_point(entry_point)?;
+        Ok(parsed
+            .entry_point_variables(
+                entry_point,
+                spirv_headers::StorageClass::ShaderRecordBufferNV as u32,
+            )
+            .first()
+            .map(|variable| {
+                parsed.block_variable(variable.id, parsed.name(variable.id), variable.type_id)
+            }))
+    }
+
+    /// Output limits, topology and per-vertex/per-primitive outputs of a mesh
+    /// shader entry point, or `None` for other stages.
+    pub fn get_mesh_traits(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Option<types::ReflectMeshTraits>, &'static str> {
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(None),
+        };
+        let entry_point = parsed.find_entry_point(entry_point)?;
+        if entry_point.execution_model != spirv_headers::ExecutionModel::MeshNV as u32
+            && entry_point.execution_model != types::EXECUTION_MODEL_MESH_EXT
+        {
+            return Ok(None);
+        }
+
+        let mut traits = types::ReflectMeshTraits {
+            max_vertices: 0,
+            max_primitives: 0,
+            output_topology: types::ReflectMeshOutputTopology::Undefined,
+            per_vertex_outputs: Vec::new(),
+            per_primitive_outputs: Vec::new(),
+            primitive_indices: None,
+        };
+        for execution_mode in parsed.entry_point_execution_modes(entry_point.id) {
+            let operand = execution_mode.operands.first().cloned().unwrap_or(0);
+            match execution_mode.execution_mode() {
+                Some(spirv_headers::ExecutionMode::OutputVertices) => traits.max_vertices = operand,
+                Some(spirv_headers::ExecutionMode::OutputPrimitivesNV) => {
+                    traits.max_primitives = operand
+                }
+                Some(spirv_headers::ExecutionMode::OutputPoints) => {
+                    traits.output_topology = types::ReflectMeshOutputTopology::Points
+                }
+                Some(spirv_headers::ExecutionMode::OutputLinesNV) => {
+                    traits.output_topology = types::ReflectMeshOutputTopology::Lines
+                }
+                Some(spirv_headers::ExecutionMode::OutputTrianglesNV) => {
+                    traits.output_topology = types::ReflectMeshOutputTopology::Triangles
+                }
+                _ => {}
+            }
+        }
+
+        // PrimitiveIndicesNV, PrimitivePointIndicesEXT, PrimitiveLineIndicesEXT
+        // and PrimitiveTriangleIndicesEXT.
+        let index_built_ins = [5276, 5294, 5295, 5296];
+        for output in self.enumerate_output_variables(Some(&entry_point.name))? {
+            let built_in = parsed.built_in(output.spirv_id);
+            if matches!(built_in, Some(built_in) if index_built_ins.contains(&built_in)) {
+                traits.primitive_indices = Some(output);
+            } else if parsed
+                .variable_has_decoration(output.spirv_id, spirv_headers::Decoration::PerPrimitiveNV)
+            {
+                traits.per_primitive_outputs.push(output);
+            } else {
+                traits.per_vertex_outputs.push(output);
+            }
+        }
+        Ok(Some(traits))
+    }
+
+    /// Input primitive, output topology and vertex streams of a geometry
+    /// entry point, or `None` for other stages.
+    pub fn get_geometry_traits(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Option<types::ReflectGeometryTraits>, &'static str> {
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(None),
+        };
+        let entry_point = parsed.find_entry_point(entry_point)?;
+        if entry_point.execution_model != spirv_headers::ExecutionModel::Geometry as u32 {
+            return Ok(None);
+        }
+
+        let mut traits = types::ReflectGeometryTraits {
+            input_primitive: types::ReflectGeometryInputPrimitive::Undefined,
+            output_topology: types::ReflectGeometryOutputTopology::Undefined,
+            output_vertices: 0,
+            invocations: 1,
+            streams: Vec::new(),
+        };
+        for execution_mode in parsed.entry_point_execution_modes(entry_point.id) {
+            use spirv_headers::ExecutionMode;
+            use types::{ReflectGeometryInputPrimitive, ReflectGeometryOutputTopology};
+            let operand = execution_mode.operands.first().cloned().unwrap_or(0);
+            match execution_mode.execution_mode() {
+                Some(ExecutionMode::InputPoints) => {
+                    traits.input_primitive = ReflectGeometryInputPrimitive::Points
+                }
+                Some(ExecutionMode::InputLines) => {
+                    traits.input_primitive = ReflectGeometryInputPrimitive::Lines
+                }
+                Some(ExecutionMode::InputLinesAdjacency) => {
+                    traits.input_primitive = ReflectGeometryInputPrimitive::LinesAdjacency
+                }
+                Some(ExecutionMode::Triangles) => {
+                    traits.input_primitive = ReflectGeometryInputPrimitive::Triangles
+                }
+                Some(ExecutionMode::InputTrianglesAdjacency) => {
+                    traits.input_primitive = ReflectGeometryInputPrimitive::TrianglesAdjacency
+                }
+                Some(ExecutionMode::OutputPoints) => {
+                    traits.output_topology = ReflectGeometryOutputTopology::Points
+                }
+                Some(ExecutionMode::OutputLineStrip) => {
+                    traits.output_topology = ReflectGeometryOutputTopology::LineStrip
+                }
+                Some(ExecutionMode::OutputTriangleStrip) => {
+                    traits.output_topology = ReflectGeometryOutputTopology::TriangleStrip
+                }
+                Some(ExecutionMode::OutputVertices) => traits.output_vertices = operand,
+                Some(ExecutionMode::Invocations) => traits.invocations = operand,
+                _ => {}
+            }
+        }
+
+        fn collect_streams(
+            variable: &types::ReflectInterfaceVariable,
+            streams: &mut std::collections::BTreeSet<u32>,
+        ) {
+            if variable.members.is_empty() {
+                streams.insert(variable.stream);
+            }
+            for member in &variable.members {
+                collect_streams(member, streams);
+            }
+        }
+        let mut streams = std::collections::BTreeSet::new();
+        for output in self.enumerate_output_variables(Some(&entry_point.name))? {
+            collect_streams(&output, &mut streams);
+        }
+        traits.streams = streams.into_iter().collect();
+        Ok(Some(traits))
+    }
+
+    /// Stencil, depth and sample mask outputs that a fragment entry point or
+    /// a function it calls stores to, and whether it declares a dual-source
+    /// output; `NONE` for other stages.
+    pub fn get_fragment_output_flags(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<types::ReflectFragmentOutputFlags, &'static str> {
+        let mut flags = types::ReflectFragmentOutputFlags::NONE;
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(flags),
+        };
+        let entry_point = parsed.find_entry_point(entry_point)?;
+        if entry_point.execution_model != spirv_headers::ExecutionModel::Fragment as u32 {
+            return Ok(flags);
+        }
+        let code = self.get_code();
+        let instructions = parser::parse_instructions(&code)?;
+        let functions = dot::functions_of(&instructions);
+        let stored = metrics::stored_variables(
+            &instructions,
+            &functions,
+            parsed,
+            spirv_headers::StorageClass::Output as u32,
+            entry_point.id,
+        );
+        for output in
+            parsed.entry_point_variables(entry_point, spirv_headers::StorageClass::Output as u32)
+        {
+            // Declared outputs that are never stored to leave the value alone.
+            let written_built_in = parsed
+                .built_in(output.id)
+                .filter(|_| stored.contains(&output.id));
+            match written_built_in {
+                Some(built_in) if built_in == spirv_headers::BuiltIn::FragStencilRefEXT as u32 => {
+                    flags |= types::ReflectFragmentOutputFlags::STENCIL_EXPORT
+                }
+                Some(built_in) if built_in == spirv_headers::BuiltIn::FragDepth as u32 => {
+                    flags |= types::ReflectFragmentOutputFlags::DEPTH_EXPORT
+                }
+                Some(built_in) if built_in == spirv_headers::BuiltIn::SampleMask as u32 => {
+                    flags |= types::ReflectFragmentOutputFlags::SAMPLE_MASK_EXPORT
+                }
+                _ => {}
+            }
+            if parsed
+                .decoration(output.id, spirv_headers::Decoration::Index)
+                .and_then(|operands| operands.first().cloned())
+                == Some(1)
+            {
+                flags |= types::ReflectFragmentOutputFlags::DUAL_SOURCE_BLEND;
+            }
+        }
+        Ok(flags)
+    }
+
+    /// How the fragment entry point and the functions it calls discard
+    /// fragments; `NONE` for other stages.
+    pub fn get_discard_flags(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<types::ReflectDiscardFlags, &'static str> {
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(types::ReflectDiscardFlags::NONE),
+        };
+        let entry_point = parsed.find_entry_point(entry_point)?;
+        if entry_point.execution_model != spirv_headers::ExecutionModel::Fragment as u32 {
+            return Ok(types::ReflectDiscardFlags::NONE);
+        }
+        let code = self.get_code();
+        let instructions = parser::parse_instructions(&code)?;
+        let functions = dot::functions_of(&instructions);
+        Ok(metrics::discard_flags(
+            &instructions,
+            &functions,
+            entry_point.id,
+        ))
+    }
+
+    /// Whether the fragment entry point and the functions it calls read the
+    /// `HelperInvocation` built-in, query it with `OpIsHelperInvocationEXT`
+    /// or demote; `NONE` for other stages.
+    pub fn get_helper_invocation_flags(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<types::ReflectHelperInvocationFlags, &'static str> {
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(types::ReflectHelperInvocationFlags::NONE),
+        };
+        let entry_point = parsed.find_entry_point(entry_point)?;
+        if entry_point.execution_model != spirv_headers::ExecutionModel::Fragment as u32 {
+            return Ok(types::ReflectHelperInvocationFlags::NONE);
+        }
+        let built_in_ids = parsed
+            .entry_point_variables(entry_point, spirv_headers::StorageClass::Input as u32)
+            .into_iter()
+            .filter(|input| {
+                parsed.built_in(input.id) == Some(spirv_headers::BuiltIn::HelperInvocation as u32)
+            })
+            .map(|input| input.id)
+            .collect();
+        let code = self.get_code();
+        let instructions = parser::parse_instructions(&code)?;
+        let functions = dot::functions_of(&instructions);
+        Ok(metrics::helper_invocation_flags(
+            &instructions,
+            &functions,
+            &built_in_ids,
+            entry_point.id,
+        ))
+    }
+
+    /// The input attachments a fragment entry point reads, sorted by
+    /// `InputAttachmentIndex`; empty for other stages. Arrays take one index
+    /// per element. Depth comes from the image type, which compilers only
+    /// mark for depth input attachments when the source says so.
+    pub fn get_input_attachment_edges(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Vec<types::ReflectInputAttachmentEdge>, &'static str> {
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(Vec::new()),
+        };
+        let entry_point = parsed.find_entry_point(entry_point)?;
+        if entry_point.execution_model != spirv_headers::ExecutionModel::Fragment as u32 {
+            return Ok(Vec::new());
+        }
+        let mut edges = Vec::new();
+        for binding in self.enumerate_descriptor_bindings(Some(&entry_point.name))? {
+            if binding.descriptor_type != types::ReflectDescriptorType::InputAttachment {
+                continue;
+            }
+            let is_depth = binding.image.depth == 1;
+            for element in 0..binding.count.max(1) {
+                edges.push((
+                    binding.input_attachment_index + element,
+                    binding.set,
+                    binding.binding,
+                    is_depth,
+                ));
+            }
+        }
+        edges.sort_unstable();
+        Ok(edges)
+    }
+
+    pub fn get_tessellation_traits(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Option<types::ReflectTessellationTraits>, &'static str> {
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(None),
+        };
+        let entry_point = parsed.find_entry_point(entry_point)?;
+        let (shader_stage, tess_level_storage_class) = match entry_point.execution_model {
+            model if model == spirv_headers::ExecutionModel::TessellationControl as u32 => (
+                types::ReflectShaderStageFlags::TESSELLATION_CONTROL,
+                spirv_headers::StorageClass::Output,
+            ),
+            model if model == spirv_headers::ExecutionModel::TessellationEvaluation as u32 => (
+                types::ReflectShaderStageFlags::TESSELLATION_EVALUATION,
+                spirv_headers::StorageClass::Input,
+            ),
+            _ => return Ok(None),
+        };
+
+        let mut traits = types::ReflectTessellationTraits {
+            shader_stage,
+            output_vertices: 0,
+            input_patch_vertices: 0,
+            patch_inputs: Vec::new(),
+            patch_outputs: Vec::new(),
+            uses_tess_level_outer: false,
+            uses_tess_level_inner: false,
+        };
+        for execution_mode in parsed.entry_point_execution_modes(entry_point.id) {
+            if execution_mode.is(spirv_headers::ExecutionMode::OutputVertices) {
+                traits.output_vertices = execution_mode.operands.first().cloned().unwrap_or(0);
+            }
+        }
+
+        let is_patch = |variable: &types::ReflectInterfaceVariable| {
+            parsed.variable_has_decoration(variable.spirv_id, spirv_headers::Decoration::Patch)
+        };
+        let is_built_in = |variable: &types::ReflectInterfaceVariable| {
+            variable
+                .decoration_flags
+                .contains(types::ReflectDecorationFlags::BUILT_IN)
+        };
+        for input in self.enumerate_input_variables(Some(&entry_point.name))? {
+            if is_patch(&input) {
+                if !is_built_in(&input) {
+                    traits.patch_inputs.push(input);
+                }
+            } else if shader_stage == types::ReflectShaderStageFlags::TESSELLATION_CONTROL {
+                if let Some(length) = input.array.dims.first() {
+                    traits.input_patch_vertices = traits.input_patch_vertices.max(*length);
+                }
+            }
+        }
+        for output in self.enumerate_output_variables(Some(&entry_point.name))? {
+            if is_patch(&output) && !is_built_in(&output) {
+                traits.patch_outputs.push(output);
+            }
+        }
+
+        for variable in parsed.entry_point_variables(entry_point, tess_level_storage_class as u32) {
+            match parsed.built_in(variable.id) {
+                Some(built_in) if built_in == spirv_headers::BuiltIn::TessLevelOuter as u32 => {
+                    traits.uses_tess_level_outer = true
+                }
+                Some(built_in) if built_in == spirv_headers::BuiltIn::TessLevelInner as u32 => {
+                    traits.uses_tess_level_inner = true
+                }
+                _ => {}
+            }
+        }
+        Ok(Some(traits))
+    }
+
+    /// The task payload written by a task shader or read by a mesh shader:
+    /// the `TaskPayloadWorkgroupEXT` variable, or the `PerTaskNV` block for
+    /// `SPV_NV_mesh_shader`. Offsets follow std430 rules when the payload has
+    /// no explicit layout.
+    pub fn get_task_payload(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Option<types::ReflectBlockVariable>, &'static str> {
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(None),
+        };
+        let entry_point = parsed.find_entry_point(entry_point)?;
+        let nv_storage_class = match entry_point.execution_model {
+            model if model == spirv_headers::ExecutionModel::TaskNV as u32 => {
+                Some(spirv_headers::StorageClass::Output)
+            }
+            model if model == spirv_headers::ExecutionModel::MeshNV as u32 => {
+                Some(spirv_headers::StorageClass::Input)
+            }
+            types::EXECUTION_MODEL_TASK_EXT | types::EXECUTION_MODEL_MESH_EXT => None,
+            _ => return Ok(None),
+        };
+        let mut candidates = parsed
+            .entry_point_variables(entry_point, types::STORAGE_CLASS_TASK_PAYLOAD_WORKGROUP_EXT);
+        if let Some(storage_class) = nv_storage_class {
+            candidates.extend(
+                parsed
+                    .entry_point_variables(entry_point, storage_class as u32)
+                    .into_iter()
+                    .filter(|variable| {
+                        parsed.variable_has_decoration(
+                            variable.id,
+                            spirv_headers::Decoration::PerTaskNV,
+                        )
+                    }),
+            );
+        }
+        Ok(candidates.first().map(|variable| {
+            let mut block =
+                parsed.block_variable(variable.id, parsed.name(variable.id), variable.type_id);
+            if !parsed.has_explicit_layout(variable.type_id) {
+                block.apply_layout(types::ReflectBlockLayout::Std430, 0);
+            }
+            block
+        }))
+    }
+
+    /// `Private` and `Workgroup` variables used by the entry point, with
+    /// their initializers.
+    pub fn enumerate_global_variables(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Vec<types::ReflectGlobalVariable>, &'static str> {
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(Vec::new()),
+        };
+        let entry_point = parsed.find_entry_point(entry_point)?;
+        let storage_classes = [
+            spirv_headers::StorageClass::Private,
+            spirv_headers::StorageClass::Workgroup,
+        ];
+        Ok(storage_classes
+            .iter()
+            .flat_map(|storage_class| {
+                parsed.entry_point_variables(entry_point, *storage_class as u32)
+            })
+            .map(|variable| {
+                let type_description = parsed.type_description(variable.type_id, None);
+                types::ReflectGlobalVariable {
+                    spirv_id: variable.id,
+                    name: parsed.name(variable.id),
+                    storage_class: type_description.storage_class,
+                    type_description,
+                    initializer: variable.initializer.map(|id| parsed.constant(id)),
+                }
+            })
+            .collect())
+    }
+
+    /// Initializer of any module-scope variable, including interface
+    /// variables and resources.
+    pub fn get_variable_initializer(
+        &self,
+        spirv_id: u32,
+    ) -> Result<Option<types::ReflectConstant>, &'static str> {
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(None),
+        };
+        Ok(parsed
+            .variables
+            .iter()
+            .find(|variable| variable.id == spirv_id)
+            .and_then(|variable| variable.initializer)
+            .map(|id| parsed.constant(id)))
+    }
+
+    /// Parameters of an `ExecutionModel::Kernel` entry point, in declaration
+    /// order. Empty for other execution models.
+    pub fn enumerate_kernel_arguments(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<Vec<types::ReflectKernelArgument>, &'static str> {
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(Vec::new()),
+        };
+        let entry_point = parsed.find_entry_point(entry_point)?;
+        if entry_point.execution_model != spirv_headers::ExecutionModel::Kernel as u32 {
+            return Ok(Vec::new());
+        }
+        Ok(parsed.kernel_arguments(entry_point))
+    }
+
+    /// Like `enumerate_input_variables`, sorted with `compare` instead.
+    pub fn enumerate_input_variables_sorted_by<F>(
+        &self,
+        entry_point: Option<&str>,
+        compare: F,
+    ) -> Result<Vec<types::ReflectInterfaceVariable>, &'static str>
+    where
+        F: FnMut(&types::ReflectInterfaceVariable, &types::ReflectInterfaceVariable) -> Ordering,
+    {
+        let mut vars = self.enumerate_input_variables(entry_point)?;
+        vars.sort_by(compare);
+        Ok(vars)
+    }
+
+    /// Like `enumerate_output_variables`, sorted with `compare` instead.
+    pub fn enumerate_output_variables_sorted_by<F>(
+        &self,
+        entry_point: Option<&str>,
+        compare: F,
+    ) -> Result<Vec<types::ReflectInterfaceVariable>, &'static str>
+    where
+        F: FnMut(&types::ReflectInterfaceVariable, &types::ReflectInterfaceVariable) -> Ordering,
+    {
+        let mut vars = self.enumerate_output_variables(entry_point)?;
+        vars.sort_by(compare);
+        Ok(vars)
+    }
+
+    /// Like `enumerate_descriptor_bindings`, sorted with `compare` instead.
+    pub fn enumerate_descriptor_bindings_sorted_by<F>(
+        &self,
+        entry_point: Option<&str>,
+        compare: F,
+    ) -> Result<Vec<types::ReflectDescriptorBinding>, &'static str>
+    where
+        F: FnMut(&types::ReflectDescriptorBinding, &types::ReflectDescriptorBinding) -> Ordering,
+    {
+        let mut bindings = self.enumerate_descriptor_bindings(entry_point)?;
+        bindings.sort_by(compare);
+        Ok(bindings)
+    }
+
+    /// Like `enumerate_descriptor_sets`, with the bindings of every set sorted
+    /// with `compare` instead.
+    pub fn enumerate_descriptor_sets_sorted_by<F>(
+        &self,
+        entry_point: Option<&str>,
+        mut compare: F,
+    ) -> Result<Vec<types::ReflectDescriptorSet>, &'static str>
+    where
+        F: FnMut(&types::ReflectDescriptorBinding, &types::ReflectDescriptorBinding) -> Ordering,
+    {
+        let mut sets = self.enumerate_descriptor_sets(entry_point)?;
+        for set in &mut sets {
+            set.bindings.sort_by(&mut compare);
+        }
+        Ok(sets)
+    }
+
+    /// Like `enumerate_entry_points`, sorted with `compare` instead.
+    pub fn enumerate_entry_points_sorted_by<F>(
+        &self,
+        compare: F,
+    ) -> Result<Vec<types::ReflectEntryPoint>, &'static str>
+    where
+        F: FnMut(&types::ReflectEntryPoint, &types::ReflectEntryPoint) -> Ordering,
+    {
+        let mut entry_points = self.enumerate_entry_points()?;
+        entry_points.sort_by(compare);
+        Ok(entry_points)
+    }
+
+    /// Module-wide descriptor bindings, ordered like
+    /// `enumerate_descriptor_bindings(None)`. The slice accessors convert
+    /// the reflection data once and borrow it afterwards; the `change_*`
+    /// methods update it in place rather than converting it again.
+    pub fn descriptor_bindings(&self) -> &[types::ReflectDescriptorBinding] {
+        &self.reflection().descriptor_bindings
+    }
+
+    /// The binding of the variable `spirv_id` among `descriptor_bindings`,
+    /// for matching ids from instruction analysis to the reflection.
+    pub fn binding_for_id(&self, spirv_id: u32) -> Option<&types::ReflectDescriptorBinding> {
+        let reflection = self.reflection();
+        reflection
+            .binding_indices
+            .get(&spirv_id)
+            .map(|&index| &reflection.descriptor_bindings[index])
+    }
+
+    pub fn descriptor_sets(&self) -> &[types::ReflectDescriptorSet] {
+        &self.reflection().descriptor_sets
+    }
+
+    pub fn input_variables(&self) -> &[types::ReflectInterfaceVariable] {
+        &self.reflection().input_variables
+    }
+
+    pub fn output_variables(&self) -> &[types::ReflectInterfaceVariable] {
+        &self.reflection().output_variables
+    }
+
+    pub fn push_constant_blocks(&self) -> &[types::ReflectBlockVariable] {
+        &self.reflection().push_constant_blocks
+    }
+
+    pub fn entry_points(&self) -> &[types::ReflectEntryPoint] {
+        &self.reflection().entry_points
+    }
+
+    /// SPIR-V assembly of the module, in the `%12 = OpTypeFloat 32` style of
+    /// `spirv-dis --raw-id`.
+    pub fn disassemble(&self) -> Result<String, &'static str> {
+        disassemble::disassemble(&self.get_code(), &HashMap::new())
+    }
+
+    /// Like `disassemble`, with the variables behind descriptor bindings, push
+    /// constant blocks and interface variables commented with their set and
+    /// binding, size or location.
+    pub fn disassemble_annotated(&self) -> Result<String, &'static str> {
+        let mut comments = HashMap::new();
+        for binding in self.enumerate_descriptor_bindings(None)? {
+            let mut comment = format!(
+                "set {}, binding {}, {:?}",
+                binding.set, binding.binding, binding.descriptor_type
+            );
+            if binding.count > 1 {
+                comment += &format!(" x{}", binding.count);
+            }
+            comments.insert(binding.spirv_id, comment);
+        }
+        for block in self.enumerate_push_constant_blocks(None)? {
+            comments.insert(
+                block.spirv_id,
+                format!("push constants, {} bytes", block.size),
+            );
+        }
+        let variables = self
+            .enumerate_input_variables(None)?
+            .into_iter()
+            .chain(self.enumerate_output_variables(None)?);
+        for variable in variables {
+            let comment = if variable
+                .decoration_flags
+                .contains(types::ReflectDecorationFlags::BUILT_IN)
+            {
+                variable.built_in.to_string()
+            } else {
+                format!("location {}", variable.location)
+            };
+            comments.insert(variable.spirv_id, comment);
+        }
+        disassemble::disassemble(&self.get_code(), &comments)
+    }
+
+    /// Graphviz DOT rendering of the call graph of `entry_point`, or of every
+    /// entry point, with dashed edges from each function to the descriptor
+    /// bindings and push constant blocks it references directly.
+    pub fn call_graph_dot(&self, entry_point: Option<&str>) -> Result<String, &'static str> {
+        let code = self.get_code();
+        let parsed = parser::ParsedModule::parse(&code)?;
+        let roots: Vec<&parser::EntryPoint> = match entry_point {
+            Some(name) => vec![parsed.find_entry_point(Some(name))?],
+            None => parsed.entry_points.iter().collect(),
+        };
+
+        let functions = dot::parse_functions(&code)?;
+        let mut function_names: HashMap<u32, String> = functions
+            .iter()
+            .map(|function| (function.id, parsed.name(function.id)))
+            .collect();
+        for root in &roots {
+            function_names.insert(root.id, root.name.clone());
+        }
+
+        let mut resources = Vec::new();
+        for binding in self.enumerate_descriptor_bindings(None)? {
+            resources.push((
+                binding.spirv_id,
+                format!(
+                    "{}\nset {}, binding {}",
+                    binding.name, binding.set, binding.binding
+                ),
+            ));
+        }
+        for block in self.enumerate_push_constant_blocks(None)? {
+            resources.push((block.spirv_id, format!("{}\npush constants", block.name)));
+        }
+
+        let root_ids: Vec<u32> = roots.iter().map(|root| root.id).collect();
+        Ok(dot::render(
+            entry_point.unwrap_or("module"),
+            &root_ids,
+            &functions,
+            &function_names,
+            &resources,
+        ))
+    }
+
+    /// Compact text report of each entry point's descriptor sets, push
+    /// constants and interface variables, for logs and snapshot tests. Also
+    /// what `Display` prints.
+    pub fn describe(&self) -> String {
+        use std::fmt::Write;
+
+        let mut out = String::new();
+        for entry_point in self.entry_points() {
+            writeln!(
+                out,
+                "entry point \"{}\" ({:?})",
+                entry_point.name, entry_point.shader_stage
+            )
+            .unwrap();
+            for set in &entry_point.descriptor_sets {
+                writeln!(out, "  set {}:", set.set).unwrap();
+                for binding in &set.bindings {
+                    write!(
+                        out,
+                        "    binding {}: \"{}\" {:?}",
+                        binding.binding, binding.name, binding.descriptor_type
+                    )
+                    .unwrap();
+                    if binding.count > 1 {
+                        write!(out, " x{}", binding.count).unwrap();
+                    }
+                    if !binding.block.members.is_empty() {
+                        write!(out, ", {} bytes", binding.block.size).unwrap();
+                    }
+                    out.push('\n');
+                }
+            }
+            for block in self
+                .enumerate_push_constant_blocks(Some(&entry_point.name))
+                .unwrap_or_default()
+            {
+                writeln!(
+                    out,
+                    "  push constants: \"{}\", {} bytes",
+                    block.name, block.size
+                )
+                .unwrap();
+            }
+            let variables = entry_point
+                .input_variables
+                .iter()
+                .map(|variable| ("input", variable))
+                .chain(
+                    entry_point
+                        .output_variables
+                        .iter()
+                        .map(|variable| ("output", variable)),
+                );
+            for (direction, variable) in variables {
+                if variable
+                    .decoration_flags
+                    .contains(types::ReflectDecorationFlags::BUILT_IN)
+                {
+                    writeln!(
+                        out,
+                        "  {} built-in {}: \"{}\"",
+                        direction, variable.built_in, variable.name
+                    )
+                    .unwrap();
+                } else {
+                    writeln!(
+                        out,
+                        "  {} location {}: \"{}\" {:?}",
+                        direction, variable.location, variable.name, variable.format
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        out
+    }
+
+    /// Runs `spirv-val` over the module's words, for gating reflection-based
+    /// code generation on the module being valid.
+    #[cfg(feature = "spirv-tools")]
+    pub fn validate(&self) -> Result<(), types::ReflectValidationError> {
+        use spirv_tools::val::Validator;
+
+        let validator = spirv_tools::val::create(None);
+        validator
+            .validate(self.get_code(), None)
+            .map_err(|error| match error.diagnostic {
+                Some(diagnostic) => types::ReflectValidationError {
+                    message: diagnostic.message,
+                    word_index: Some(diagnostic.index),
+                },
+                None => types::ReflectValidationError {
+                    message: error.to_string(),
+                    word_index: None,
+                },
+            })
+    }
+
+    /// Non-fatal findings about the module, such as resources without a
+    /// binding or bindings that are never accessed, for tooling to surface.
+    pub fn warnings(&self) -> &[types::ReflectWarning] {
+        &self.reflection().warnings
+    }
+
+    /// Every Location, DescriptorSet, Binding and Offset decoration the
+    /// module leaves out. The C library skips such variables without a word,
+    /// so check this before trusting the enumerations.
+    pub fn check_interface_completeness(&self) -> Result<Vec<types::ReflectWarning>, &'static str> {
+        Ok(match self.parsed_module()? {
+            Some(parsed) => parser::completeness_warnings(parsed),
+            None => Vec::new(),
+        })
+    }
+
+    /// The entry point called `name`, with its own interface variables and
+    /// descriptor sets.
+    pub fn entry_point(&self, name: &str) -> Option<&types::ReflectEntryPoint> {
+        self.entry_points()
+            .iter()
+            .find(|entry_point| entry_point.name == name)
+    }
+
+    pub fn descriptor_bindings_in_set(
+        &self,
+        set: u32,
+    ) -> impl Iterator<Item = &types::ReflectDescriptorBinding> {
+        self.descriptor_bindings()
+            .iter()
+            .filter(move |binding| binding.set == set)
+    }
+
+    pub fn descriptor_bindings_of_type(
+        &self,
+        descriptor_type: types::ReflectDescriptorType,
+    ) -> impl Iterator<Item = &types::ReflectDescriptorBinding> {
+        self.descriptor_bindings()
+            .iter()
+            .filter(move |binding| binding.descriptor_type == descriptor_type)
+    }
+
+    /// Entry points whose stage intersects `stages`.
+    pub fn entry_points_for_stages(
+        &self,
+        stages: types::ReflectShaderStageFlags,
+    ) -> impl Iterator<Item = &types::ReflectEntryPoint> {
+        self.entry_points()
+            .iter()
+            .filter(move |entry_point| stages.intersects(entry_point.shader_stage))
+    }
+
+    /// Device features required by the types used in descriptor bindings,
+    /// push constant blocks and interface variables, and by storage images
+    /// accessed without a declared format.
+    pub fn get_required_features(
+        &self,
+        entry_point: Option<&str>,
+    ) -> Result<types::ReflectFeatureFlags, &'static str> {
+        let mut features = types::ReflectFeatureFlags::NONE;
+        let bindings = self.enumerate_descriptor_bindings(entry_point)?;
+        for binding in &bindings {
+            features |= binding.required_features();
+        }
+        for usage in self.enumerate_image_usage()? {
+            if !bindings
+                .iter()
+                .any(|binding| binding.spirv_id == usage.spirv_id)
+            {
+                continue;
+            }
+            if usage
+                .flags
+                .contains(types::ReflectImageUsageFlags::READ_WITHOUT_FORMAT)
+            {
+                features |= types::ReflectFeatureFlags::SHADER_STORAGE_IMAGE_READ_WITHOUT_FORMAT;
+            }
+            if usage
+                .flags
+                .contains(types::ReflectImageUsageFlags::WRITE_WITHOUT_FORMAT)
+            {
+                features |= types::ReflectFeatureFlags::SHADER_STORAGE_IMAGE_WRITE_WITHOUT_FORMAT;
+            }
+        }
+        for block in self.enumerate_push_constant_blocks(entry_point)? {
+            features |= block.push_constant_required_features();
+        }
+        for variable in self.enumerate_input_variables(entry_point)? {
+            features |= variable.required_features();
+        }
+        for variable in self.enumerate_output_variables(entry_point)? {
+            features |= variable.required_features();
+        }
+        Ok(features)
+    }
+
+    /// Snapshot of the reflection data as plain data, the form
+    /// `from_cache_bytes` restores and `diff::reflect_diff` compares.
+    pub fn to_cache(&self) -> Result<cache::ReflectModuleCache, &'static str> {
+        Ok(cache::ReflectModuleCache {
+            generator: self.get_generator(),
+            shader_stage: self.get_shader_stage(),
+            source_language: self.get_source_language() as u32,
+            source_language_version: self.get_source_language_version(),
+            source_file: self.get_source_file(),
+            sources: self.enumerate_sources()?,
+            entry_point_name: self.get_entry_point_name(),
+            entry_points: self.enumerate_entry_points()?,
+            descriptor_sets: self.enumerate_descriptor_sets(None)?,
+            push_constant_blocks: self.enumerate_push_constant_blocks(None)?,
+            input_variables: self.enumerate_input_variables(None)?,
+            output_variables: self.enumerate_output_variables(None)?,
+        })
+    }
+
+    /// Encodes the reflection data in a compact versioned binary format that can be
+    /// loaded with `from_cache_bytes` without the original SPIR-V.
+    pub fn to_cache_bytes(&self) -> Result<Vec<u8>, &'static str> {
+        Ok(self.to_cache()?.to_cache_bytes())
+    }
+
+    pub fn from_cache_bytes(data: &[u8]) -> Result<cache::ReflectModuleCache, &'static str> {
+        cache::ReflectModuleCache::from_cache_bytes(data)
+    }
+
+    pub fn get_entry_point_name(&self) -> String {
+        if let Some(name) = self.selected_entry_point() {
+            return name.to_owned();
+        }
+        match self.module() {
+            Some(module) => ffi_to_string(module.entry_point_name),
+            None => String::new(),
+        }
+    }
+
+    pub fn change_descriptor_binding_numbers(
+        &mut self,
+        binding: &types::descriptor::ReflectDescriptorBinding,
+        new_binding: u32,
+        new_set: Option<u32>,
+    ) -> Result<(), &'static str> {
+        match self.module_mut()? {
+            Some((module, recreated)) => {
+                let internal_data = if recreated {
+                    find_descriptor_binding(module, binding.spirv_id)?
+                } else {
+                    binding.internal_data
+                };
+                let new_set = new_set.unwrap_or(ffi::SPV_REFLECT_SET_NUMBER_DONT_CHANGE as u32);
+                let result = unsafe {
+                    ffi::spvReflectChangeDescriptorBindingNumbers(
+                        module as *mut ffi::SpvReflectShaderModule,
+                        internal_data,
+                        new_binding,
+                        new_set,
+                    )
+                };
+                let spirv_id = binding.spirv_id;
+                self.update_reflection(result, |reflection| {
+                    reflection.update_bindings(|binding| {
+                        if binding.spirv_id == spirv_id {
+                            binding.binding = new_binding;
+                            if new_set != ffi::SPV_REFLECT_SET_NUMBER_DONT_CHANGE as u32 {
+                                binding.set = new_set;
+                            }
+                        }
+                    })
+                })
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub fn change_descriptor_set_number(
+        &mut self,
+        set: &types::descriptor::ReflectDescriptorSet,
+        new_set: u32,
+    ) -> Result<(), &'static str> {
+        match self.module_mut()? {
+            Some((module, recreated)) => {
+                let internal_data = if recreated {
+                    find_descriptor_set(module, set.set)?
+                } else {
+                    set.internal_data
+                };
+                let result = unsafe {
+                    ffi::spvReflectChangeDescriptorSetNumber(
+                        module as *mut ffi::SpvReflectShaderModule,
+                        internal_data,
+                        new_set,
+                    )
+                };
+                let old_set = set.set;
+                self.update_reflection(result, |reflection| {
+                    reflection.update_bindings(|binding| {
+                        if binding.set == old_set {
+                            binding.set = new_set;
+                        }
+                    })
+                })
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that the borrowed reflection agrees with itself and with the
+    /// `DescriptorSet`, `Binding` and `Location` decorations in the code,
+    /// e.g. after a series of `change_*` calls in a hot-reload loop.
+    pub fn revalidate(&self) -> Result<(), &'static str> {
+        if self.module().is_none() {
+            return Ok(());
+        }
+        let code = self.get_code();
+        let mut decorations: HashMap<(u32, u32), u32> = HashMap::new();
+        for instruction in parser::parse_instructions(&code)? {
+            if instruction.is(spirv_headers::Op::Decorate) && instruction.operands.len() >= 3 {
+                decorations.insert(
+                    (instruction.operands[0], instruction.operands[1]),
+                    instruction.operands[2],
+                );
+            }
+        }
+        let decoration = |id: u32, decoration: spirv_headers::Decoration| {
+            decorations.get(&(id, decoration as u32)).cloned()
+        };
+
+        let reflection = self.reflection();
+        let sets = reflection.descriptor_sets.iter().chain(
+            reflection
+                .entry_points
+                .iter()
+                .flat_map(|entry_point| entry_point.descriptor_sets.iter()),
+        );
+        for set in sets {
+            for binding in &set.bindings {
+                if binding.set != set.set {
+                    return Err("Descriptor binding is in the wrong set");
+                }
+                match reflection.binding_indices.get(&binding.spirv_id) {
+                    Some(&index)
+                        if reflection.descriptor_bindings[index].binding == binding.binding
+                            && reflection.descriptor_bindings[index].set == binding.set => {}
+                    _ => return Err("Descriptor sets do not match the descriptor bindings"),
+                }
+            }
+            if !set
+                .bindings
+                .windows(2)
+                .all(|pair| pair[0].binding <= pair[1].binding)
+            {
+                return Err("Descriptor set bindings are out of order");
+            }
+        }
+        let grouped: usize = reflection
+            .descriptor_sets
+            .iter()
+            .map(|set| set.bindings.len())
+            .sum();
+        if grouped != reflection.descriptor_bindings.len()
+            || !reflection
+                .descriptor_sets
+                .windows(2)
+                .all(|pair| pair[0].set < pair[1].set)
+        {
+            return Err("Descriptor sets do not match the descriptor bindings");
+        }
+        for (index, binding) in reflection.descriptor_bindings.iter().enumerate() {
+            if reflection.binding_indices.get(&binding.spirv_id) != Some(&index) {
+                return Err("Descriptor binding index is stale");
+            }
+            let set = decoration(binding.spirv_id, spirv_headers::Decoration::DescriptorSet);
+            let number = decoration(binding.spirv_id, spirv_headers::Decoration::Binding);
+            if set.is_some_and(|set| set != binding.set)
+                || number.is_some_and(|number| number != binding.binding)
+            {
+                return Err("Descriptor binding does not match its decorations");
+            }
+        }
+        let variables =
+            reflection
+                .input_variables
+                .iter()
+                .chain(reflection.output_variables.iter().chain(
+                    reflection.entry_points.iter().flat_map(|entry_point| {
+                        entry_point
+                            .input_variables
+                            .iter()
+                            .chain(entry_point.output_variables.iter())
+                    }),
+                ));
+        for variable in variables {
+            if let Some(location) =
+                decoration(variable.spirv_id, spirv_headers::Decoration::Location)
+            {
+                if location != variable.location {
+                    return Err("Interface variable does not match its Location decoration");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Renumbers the descriptor sets to `0..n` in their current order, e.g.
+    /// sets 0, 3 and 7 become 0, 1 and 2. Returns the new number of every
+    /// set, by old number.
+    pub fn compact_descriptor_sets(&mut self) -> Result<BTreeMap<u32, u32>, &'static str> {
+        let mut set_numbers: Vec<u32> = self
+            .enumerate_descriptor_sets(None)?
+            .iter()
+            .map(|set| set.set)
+            .collect();
+        set_numbers.sort_unstable();
+        let mut remap = BTreeMap::new();
+        for (new_set, old_set) in set_numbers.into_iter().enumerate() {
+            let new_set = new_set as u32;
+            remap.insert(old_set, new_set);
+            if new_set == old_set {
+                continue;
+            }
+            // Every change re-sorts the sets, so look the set up afresh. Lower
+            // numbers are only taken by sets that were already moved.
+            let set = self
+                .descriptor_sets()
+                .iter()
+                .find(|set| set.set == old_set)
+                .cloned()
+                .ok_or("Descriptor set not found")?;
+            self.change_descriptor_set_number(&set, new_set)?;
+        }
+        Ok(remap)
+    }
+
+    pub fn change_input_variable_location(
+        &mut self,
+        variable: &types::variable::ReflectInterfaceVariable,
+        new_location: u32,
+    ) -> Result<(), &'static str> {
+        match self.module_mut()? {
+            Some((module, _)) => {
+                let spirv_id = variable.spirv_id;
+                let result = change_variable_location(module, true, spirv_id, new_location)?;
+                self.update_reflection(result, |reflection| {
+                    reflection.update_location(true, spirv_id, new_location)
+                })
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub fn change_output_variable_location(
+        &mut self,
+        variable: &types::variable::ReflectInterfaceVariable,
+        new_location: u32,
+    ) -> Result<(), &'static str> {
+        match self.module_mut()? {
+            Some((module, _)) => {
+                let spirv_id = variable.spirv_id;
+                let result = change_variable_location(module, false, spirv_id, new_location)?;
+                self.update_reflection(result, |reflection| {
+                    reflection.update_location(false, spirv_id, new_location)
+                })
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// The entry point picked with `ShaderModuleBuilder::for_entry_point`.
+    fn selected_entry_point(&self) -> Option<&str> {
+        self.handle
+            .as_ref()
+            .and_then(|handle| handle.options.entry_point.as_deref())
+    }
+
+    fn selected(&self) -> Option<&types::ReflectEntryPoint> {
+        self.selected_entry_point()?;
+        self.entry_points().first()
+    }
+
+    /// Whether the C module has an entry point called `name`, checked without
+    /// converting the rest of the reflection.
+    pub(crate) fn has_entry_point(&self, name: &str) -> bool {
+        match self.module() {
+            Some(module) => unsafe {
+                std::slice::from_raw_parts(module.entry_points, module.entry_point_count as usize)
+            }
+            .iter()
+            .any(|entry_point| ffi_to_string(entry_point.name) == name),
+            None => false,
+        }
+    }
+
+    /// Stages of the entry points for which `uses` holds, only `entry_point`
+    /// if given. Reads the C module, so it works while the cached reflection
+    /// is being built.
+    fn stages_using(
+        &self,
+        entry_point: Option<&str>,
+        uses: impl Fn(&ffi::SpvReflectEntryPoint) -> bool,
+    ) -> types::ReflectShaderStageFlags {
+        let ffi_entry_points = match self.module() {
+            Some(module) => unsafe {
+                std::slice::from_raw_parts(module.entry_points, module.entry_point_count as usize)
+            },
+            None => return types::ReflectShaderStageFlags::UNDEFINED,
+        };
+        ffi_entry_points
+            .iter()
+            .filter(|ffi_entry_point| match entry_point {
+                Some(name) => ffi_to_string(ffi_entry_point.name) == name,
+                None => true,
+            })
+            .filter(|ffi_entry_point| uses(ffi_entry_point))
+            .fold(
+                types::ReflectShaderStageFlags::UNDEFINED,
+                |stages, ffi_entry_point| {
+                    stages | convert::ffi_to_shader_stage_flags(ffi_entry_point.shader_stage)
+                },
+            )
+    }
+
+    /// Sets the stages of the entry points that use each binding, or just the
+    /// stage of `entry_point` if given.
+    fn assign_binding_stages<'a>(
+        &self,
+        entry_point: Option<&str>,
+        bindings: impl Iterator<Item = &'a mut types::ReflectDescriptorBinding>,
+    ) {
+        let stages_of = |spirv_id: u32| {
+            self.stages_using(entry_point, |ffi_entry_point| {
+                unsafe {
+                    std::slice::from_raw_parts(
+                        ffi_entry_point.used_uniforms,
+                        ffi_entry_point.used_uniform_count as usize,
+                    )
+                }
+                .contains(&spirv_id)
+            })
+        };
+        for binding in bindings {
+            binding.stage_flags = stages_of(binding.spirv_id);
+            if let Some(ref mut counter) = binding.uav_counter_binding {
+                counter.stage_flags = stages_of(counter.spirv_id);
+            }
+        }
+    }
+
+    /// The analyses enabled by the builder the module was loaded with.
+    fn analyses(&self) -> builder::ReflectAnalysisFlags {
+        match self.handle {
+            Some(ref handle) => handle.options.enabled_analyses(),
+            None => builder::ReflectAnalysisFlags::NONE,
+        }
+    }
+
+    fn module(&self) -> Option<&ffi::SpvReflectShaderModule> {
+        self.handle.as_ref().map(|handle| &handle.module)
+    }
+
+    /// Unique access to the C module, for the `change_*` methods. A module
+    /// shared with clones is re-created from its current code first, which is
+    /// reported so that `internal_data` pointers can be looked up again.
+    fn module_mut(
+        &mut self,
+    ) -> Result<Option<(&mut ffi::SpvReflectShaderModule, bool)>, &'static str> {
+        let handle = match self.handle {
+            Some(ref mut handle) => handle,
+            None => return Ok(None),
+        };
+        let recreated = Arc::get_mut(handle).is_none();
+        if recreated {
+            let code = module_code(&handle.module);
+            match create_shader_module_with(words_as_bytes(&code), handle.options.clone())?.handle {
+                Some(unique) => *handle = unique,
+                None => return Ok(None),
+            }
+        }
+        match Arc::get_mut(handle) {
+            Some(handle) => {
+                handle.parsed = OnceLock::new();
+                Ok(Some((&mut handle.module, recreated)))
+            }
+            None => Err("Shader module is shared"),
+        }
+    }
+
+    /// Brings the borrowed reflection in line with a change the C library
+    /// just made in place, instead of dropping it and reflecting the whole
+    /// module again. A failed change drops it, as the C module may be half
+    /// updated.
+    fn update_reflection(
+        &mut self,
+        result: ffi::SpvReflectResult,
+        update: impl FnOnce(&mut ModuleReflection),
+    ) -> Result<(), &'static str> {
+        let handle = match self.handle.as_mut().and_then(Arc::get_mut) {
+            Some(handle) => handle,
+            None => return Ok(()),
+        };
+        handle.parsed = OnceLock::new();
+        if result != ffi::SpvReflectResult_SPV_REFLECT_RESULT_SUCCESS {
+            handle.reflection = OnceLock::new();
+            return Err(convert::result_to_string(result));
+        }
+        if let Some(reflection) = handle.reflection.get_mut() {
+            update(reflection);
+            reflection.refresh_set_handles(&handle.module);
+        }
+        Ok(())
+    }
+
+    fn reflection(&self) -> &ModuleReflection {
+        static EMPTY: OnceLock<ModuleReflection> = OnceLock::new();
+        let reflection = match self.handle {
+            Some(ref handle) => &handle.reflection,
+            None => &EMPTY,
+        };
+        reflection.get_or_init(|| {
+            let descriptor_bindings = self.enumerate_descriptor_bindings(None).unwrap_or_default();
+            ModuleReflection {
+                binding_indices: descriptor_bindings
+                    .iter()
+                    .enumerate()
+                    .map(|(index, binding)| (binding.spirv_id, index))
+                    .collect(),
+                descriptor_bindings,
+                descriptor_sets: self.enumerate_descriptor_sets(None).unwrap_or_default(),
+                input_variables: self.enumerate_input_variables(None).unwrap_or_default(),
+                output_variables: self.enumerate_output_variables(None).unwrap_or_default(),
+                push_constant_blocks: self
+                    .enumerate_push_constant_blocks(None)
+                    .unwrap_or_default(),
+                entry_points: self.enumerate_entry_points().unwrap_or_default(),
+                warnings: self.collect_warnings().unwrap_or_default(),
+            }
+        })
+    }
+
+    fn collect_warnings(&self) -> Result<Vec<types::ReflectWarning>, &'static str> {
+        if !self
+            .analyses()
+            .contains(builder::ReflectAnalysisFlags::WARNINGS)
+        {
+            return Ok(Vec::new());
+        }
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(Vec::new()),
+        };
+        let mut warnings = parser::collect_warnings(&self.get_code(), parsed)?;
+        if self
+            .get_quirks()
+            .contains(builder::ReflectQuirkFlags::RSPIRV_UNNAMED_BLOCKS)
+        {
+            warnings
+                .retain(|warning| !matches!(warning, types::ReflectWarning::UnnamedBlock { .. }));
+        }
+        for binding in self.enumerate_descriptor_bindings(None)? {
+            if !binding.accessed {
+                warnings.push(types::ReflectWarning::UnusedBinding {
+                    spirv_id: binding.spirv_id,
+                    name: binding.name,
+                    set: binding.set,
+                    binding: binding.binding,
+                });
+            }
+        }
+        Ok(warnings)
+    }
+
+    fn annotate_interface_variables<'a>(
+        &self,
+        variables: impl Iterator<Item = &'a mut types::ReflectInterfaceVariable>,
+    ) -> Result<(), &'static str> {
+        let mut variables = variables.peekable();
+        if variables.peek().is_none() {
+            return Ok(());
+        }
+        if let Some(parsed) = self.parsed_module()? {
+            for variable in variables {
+                parsed.annotate_interface_variable(variable);
+            }
+        }
+        Ok(())
+    }
+
+    /// Fills in the per-dimension array traits of the bindings' blocks and
+    /// type descriptions, which the C library does not report, and fixes up
+    /// the sizes of their members to match. The block of an array of blocks
+    /// describes one element, whether the array has several dimensions or is
+    /// a runtime array. Array lengths are resolved again too, as the C
+    /// library reads them without checking what kind of constant they are.
+    fn annotate_binding_layouts<'a>(
+        &self,
+        bindings: impl Iterator<Item = &'a mut types::ReflectDescriptorBinding>,
+    ) -> Result<(), &'static str> {
+        let mut bindings = bindings.peekable();
+        if bindings.peek().is_none() {
+            return Ok(());
+        }
+        let readonly_members = self
+            .get_quirks()
+            .contains(builder::ReflectQuirkFlags::GLSLANG_READONLY_MEMBERS);
+        if let Some(parsed) = self.parsed_module()? {
+            for binding in bindings {
+                parsed.annotate_block_layout(&mut binding.block);
+                let members = &binding.block.members;
+                if readonly_members
+                    && binding.descriptor_type == types::ReflectDescriptorType::StorageBuffer
+                    && !members.is_empty()
+                    && members.iter().all(|member| {
+                        member
+                            .decoration_flags
+                            .contains(types::ReflectDecorationFlags::NON_WRITABLE)
+                    })
+                {
+                    binding.block.decoration_flags |= types::ReflectDecorationFlags::NON_WRITABLE;
+                }
+                if let Some(ref mut type_description) = binding.type_description {
+                    parsed.annotate_type_layout(type_description);
+                    let array = &type_description.traits.array;
+                    if !array.runtime && !array.dims.is_empty() {
+                        binding.array.dims = array.dims.clone();
+                        binding.count = array
+                            .dims
+                            .iter()
+                            .try_fold(1u32, |count, dim| count.checked_mul(*dim))
+                            .unwrap_or(0);
+                    }
+                    let is_block_array = array.runtime || !array.dims.is_empty();
+                    if is_block_array && !binding.block.members.is_empty() {
+                        parsed.annotate_block_element(&mut binding.block, type_description.id);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets `accessed` from the instructions that use each binding, following
+    /// loads through `OpSampledImage`, `OpImage` and `OpImageTexelPointer`.
+    fn mark_accessed_bindings<'a>(
+        &self,
+        bindings: impl Iterator<Item = &'a mut types::ReflectDescriptorBinding>,
+    ) -> Result<(), &'static str> {
+        let parsed = match self.parsed_code()? {
+            Some(parsed) => parsed,
+            None => return Ok(()),
+        };
+        for binding in bindings {
+            binding.accessed = parsed.accessed_variables.contains(&binding.spirv_id);
+        }
+        Ok(())
+    }
+
+    /// Links storage buffers to their UAV counters: through the
+    /// `CounterBuffer` decoration, or for legacy HLSL through the counter's
+    /// name when the builder's suffix is set and names are not stripped.
+    fn link_counter_bindings<'a>(
+        &self,
+        bindings: impl Iterator<Item = &'a mut types::ReflectDescriptorBinding>,
+    ) -> Result<(), &'static str> {
+        let parsed = match self.parsed_module()? {
+            Some(parsed) => parsed,
+            None => return Ok(()),
+        };
+        let suffix = match self.handle {
+            Some(ref handle)
+                if self
+                    .get_quirks()
+                    .contains(builder::ReflectQuirkFlags::LEGACY_COUNTER_NAMES) =>
+            {
+                handle.options.counter_name_suffix.clone()
+            }
+            _ => None,
+        };
+        let mut bindings: Vec<&mut types::ReflectDescriptorBinding> = bindings.collect();
+        for binding in bindings.iter_mut() {
+            binding.uav_counter_id = u32::MAX;
+            binding.uav_counter_binding = None;
+            binding.uav_counter_association = None;
+        }
+        for index in 0..bindings.len() {
+            let decorated = parsed
+                .decoration(
+                    bindings[index].spirv_id,
+                    spirv_headers::Decoration::CounterBuffer,
+                )
+                .and_then(|operands| operands.first().cloned());
+            let counter = match (decorated, &suffix) {
+                (Some(counter_id), _) => bindings
+                    .iter()
+                    .position(|binding| binding.spirv_id == counter_id)
+                    .map(|counter| (counter, types::ReflectCounterAssociation::Decoration)),
+                (None, Some(suffix)) if !bindings[index].name.is_empty() => {
+                    let name = format!("{}{}", bindings[index].name, suffix);
+                    bindings
+                        .iter()
+                        .position(|binding| binding.name == name)
+                        .map(|counter| (counter, types::ReflectCounterAssociation::NameSuffix))
+                }
+                _ => None,
+            };
+            if let Some((counter, association)) = counter {
+                let counter = Box::new((*bindings[counter]).clone());
+                bindings[index].uav_counter_id = counter.spirv_id;
+                bindings[index].uav_counter_binding = Some(counter);
+                bindings[index].uav_counter_association = Some(association);
+            }
+        }
+        Ok(())
+    }
+
+    /// The parsed module, with the selected entry point moved first so that
+    /// it is the one picked without a name.
+    fn parsed_module(&self) -> Result<Option<&parser::ParsedModule>, &'static str> {
+        Ok(self.parsed_code()?.map(|parsed| &parsed.module))
+    }
+
+    /// The code parsed on first use. Patching the module through
+    /// `module_mut` drops it.
+    fn parsed_code(&self) -> Result<Option<&ParsedCode>, &'static str> {
+        let handle = match self.handle {
+            Some(ref handle) => handle,
+            None => return Ok(None),
+        };
+        let parsed = handle.parsed.get_or_init(|| {
+            let code = module_code(&handle.module);
+            let phase = trace::Phase::start("instructions");
+            let instructions = parser::parse_instructions(&code)?;
+            phase.finish(instructions.len());
+            let mut module = parser::ParsedModule::from_instructions(&code, &instructions)?;
+            if let Some(selected) = self.selected_entry_point() {
+                if let Some(index) = module
+                    .entry_points
+                    .iter()
+                    .position(|entry_point| entry_point.name == selected)
+                {
+                    let entry_point = module.entry_points.remove(index);
+                    module.entry_points.insert(0, entry_point);
+                }
+            }
+            Ok(ParsedCode {
+                accessed_variables: image_usage::accessed_variables(&instructions, &module),
+                sources: parser::parse_sources(&instructions),
+                module,
+            })
+        });
+        match parsed {
+            Ok(parsed) => Ok(Some(parsed)),
+            Err(error) => Err(*error),
+        }
+    }
+}
+
+impl std::fmt::Display for ShaderModule {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.describe())
+    }
+}
+
+fn module_code(module: &ffi::SpvReflectShaderModule) -> Vec<u32> {
+    let code_size = unsafe { ffi::spvReflectGetCodeSize(module) as usize };
+    let code_slice =
+        unsafe { std::slice::from_raw_parts(ffi::spvReflectGetCode(module), code_size / 4) };
+    code_slice.to_owned()
+}
+
+fn find_descriptor_binding(
+    module: &ffi::SpvReflectShaderModule,
+    spirv_id: u32,
+) -> Result<*const ffi::SpvReflectDescriptorBinding, &'static str> {
+    let bindings = unsafe {
+        std::slice::from_raw_parts(
+            module.descriptor_bindings,
+            module.descriptor_binding_count as usize,
+        )
+    };
+    bindings
+        .iter()
+        .find(|binding| binding.spirv_id == spirv_id)
+        .map(|binding| binding as *const _)
+        .ok_or("Descriptor binding not found")
+}
+
+fn find_descriptor_set(
+    module: &ffi::SpvReflectShaderModule,
+    set: u32,
+) -> Result<*const ffi::SpvReflectDescriptorSet, &'static str> {
+    module.descriptor_sets[..module.descriptor_set_count as usize]
+        .iter()
+        .find(|descriptor_set| descriptor_set.set == set)
+        .map(|descriptor_set| descriptor_set as *const _)
+        .ok_or("Descriptor set not found")
+}
+
+fn find_interface_variable(
+    variables: *const ffi::SpvReflectInterfaceVariable,
+    count: u32,
+    spirv_id: u32,
+) -> Result<*const ffi::SpvReflectInterfaceVariable, &'static str> {
+    let variables = unsafe { std::slice::from_raw_parts(variables, count as usize) };
+    variables
+        .iter()
+        .find(|variable| variable.spirv_id == spirv_id)
+        .map(|variable| variable as *const _)
+        .ok_or("Interface variable not found")
+}
+
+/// Changes the location of the input or output variable `spirv_id` in the
+/// code, and in every copy the C module keeps of the variable. The C library
+/// only looks for it among the module-level variables, which are those of the
+/// first entry point, so a variable of another entry point is found among the
+/// variables of that entry point, which stand in for the module-level ones
+/// during the call.
+fn change_variable_location(
+    module: &mut ffi::SpvReflectShaderModule,
+    input: bool,
+    spirv_id: u32,
+    new_location: u32,
+) -> Result<ffi::SpvReflectResult, &'static str> {
+    let module_variables = if input {
+        (module.input_variables, module.input_variable_count)
+    } else {
+        (module.output_variables, module.output_variable_count)
+    };
+    let entry_points = if module.entry_points.is_null() {
+        &[][..]
+    } else {
+        unsafe {
+            std::slice::from_raw_parts(module.entry_points, module.entry_point_count as usize)
+        }
+    };
+    let all_variables: Vec<_> = std::iter::once(module_variables)
+        .chain(entry_points.iter().map(|entry_point| {
+            if input {
+                (
+                    entry_point.input_variables,
+                    entry_point.input_variable_count,
+                )
+            } else {
+                (
+                    entry_point.output_variables,
+                    entry_point.output_variable_count,
+                )
+            }
+        }))
+        .filter(|(variables, _)| !variables.is_null())
+        .collect();
+    let (variables, variable) = all_variables
+        .iter()
+        .find_map(|&(variables, count)| {
+            find_interface_variable(variables, count, spirv_id)
+                .ok()
+                .map(|variable| ((variables, count), variable))
+        })
+        .ok_or("Interface variable not found")?;
+
+    let set_module_variables = |module: &mut ffi::SpvReflectShaderModule, (variables, count)| {
+        if input {
+            module.input_variables = variables;
+            module.input_variable_count = count;
+        } else {
+            module.output_variables = variables;
+            module.output_variable_count = count;
+        }
+    };
+    set_module_variables(module, variables);
+    let result = unsafe {
+        if input {
+            ffi::spvReflectChangeInputVariableLocation(module, variable, new_location)
+        } else {
+            ffi::spvReflectChangeOutputVariableLocation(module, variable, new_location)
+        }
+    };
+    set_module_variables(module, module_variables);
+
+    if result == ffi::SpvReflectResult_SPV_REFLECT_RESULT_SUCCESS {
+        for (variables, count) in all_variables {
+            let variables = unsafe { std::slice::from_raw_parts_mut(variables, count as usize) };
+            for variable in variables
+                .iter_mut()
+                .filter(|variable| variable.spirv_id == spirv_id)
+            {
+                variable.location = new_location;
+            }
+        }
+    }
+    Ok(result)
+}
+
+/*
+impl From<&[u8]> for ShaderModule {
+    fn from(spv_data: &[u8]) -> Result<ShaderModule, &str> {
+        create_shader_module(spv_data)?
+    }
+}
+*/
+
+/*impl<'a, T: AsRef<[u8]>> From<T> for ShaderModule {
+    fn from(v: T) -> Result<ShaderModule, &'static str> {
+        Ok(create_shader_module(v.as_ref())?)
+    }
+}*/
+
+fn words_as_bytes(words: &[u32]) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(words.as_ptr() as *const u8, std::mem::size_of_val(words)) }
+}
 
 pub fn create_shader_module(spv_data: &[u8]) -> Result<ShaderModule, &'static str> {
+    create_shader_module_with(spv_data, builder::ShaderModuleBuilder::new())
+}
+
+#[cfg_attr(
+    feature = "trace",
+    tracing::instrument(level = "debug", skip_all, fields(bytes = spv_data.len()))
+)]
+pub(crate) fn create_shader_module_with(
+    spv_data: &[u8],
+    options: builder::ShaderModuleBuilder,
+) -> Result<ShaderModule, &'static str> {
+    if let Err(error) = parser::validate_instructions(spv_data) {
+        trace::failed(error);
+        return Err(error);
+    }
+    let phase = trace::Phase::start("c_reflection");
     let mut module: ffi::SpvReflectShaderModule = unsafe { std::mem::zeroed() };
     let result: ffi::SpvReflectResult = unsafe {
         ffi::spvReflectCreateShaderModule(
@@ -592,9 +3081,23 @@ pub fn create_shader_module(spv_data: &[u8]) -> Result<ShaderModule, &'static st
         )
     };
     match result {
-        ffi::SpvReflectResult_SPV_REFLECT_RESULT_SUCCESS => Ok(ShaderModule {
-            module: Some(module),
-        }),
-        _ => Err(convert::result_to_string(result)),
+        ffi::SpvReflectResult_SPV_REFLECT_RESULT_SUCCESS => {
+            phase.finish(spv_data.len() / 4);
+            trace::module_created(&module);
+            Ok(ShaderModule {
+                handle: Some(Arc::new(ModuleHandle {
+                    module,
+                    options,
+                    generator: types::ReflectGenerator::from_magic(parser::code_word(spv_data, 2)),
+                    reflection: OnceLock::new(),
+                    parsed: OnceLock::new(),
+                })),
+            })
+        }
+        _ => {
+            let error = convert::result_to_string(result);
+            trace::failed(error);
+            Err(error)
+        }
     }
 }
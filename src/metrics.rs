@@ -0,0 +1,596 @@
+//! Static statistics over the code an entry point can reach.
+
+use crate::disassemble;
+use crate::dot;
+use crate::image_usage;
+use crate::parser::{self, Instruction, ParsedModule};
+use crate::types::{
+    ReflectBarrier, ReflectBindingAccess, ReflectControlFlow, ReflectDiscardFlags,
+    ReflectExtInstUsage, ReflectHelperInvocationFlags, ReflectShaderMetrics,
+};
+use num_traits::cast::FromPrimitive;
+use spirv_headers::Op;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// The instructions of every function reachable from `entry_point_id`.
+pub(crate) fn reachable_instructions<'a>(
+    instructions: &'a [Instruction<'a>],
+    functions: &[dot::Function],
+    entry_point_id: u32,
+) -> impl Iterator<Item = &'a Instruction<'a>> {
+    let ranges: Vec<_> = dot::reachable(&[entry_point_id], functions)
+        .into_iter()
+        .map(|function| function.instructions.clone())
+        .collect();
+    ranges
+        .into_iter()
+        .flat_map(move |range| instructions[range].iter())
+}
+
+/// How often the code reachable from `entry_point_id` uses each extended
+/// instruction, sorted by set and instruction number.
+pub(crate) fn ext_inst_usage(
+    instructions: &[Instruction],
+    functions: &[dot::Function],
+    entry_point_id: u32,
+) -> Result<Vec<ReflectExtInstUsage>, &'static str> {
+    let mut sets = HashMap::new();
+    for instruction in instructions {
+        if instruction.is(Op::ExtInstImport) {
+            if instruction.operands.is_empty() {
+                return Err("Invalid OpExtInstImport");
+            }
+            let (name, _) = parser::parse_string(&instruction.operands[1..])?;
+            sets.insert(instruction.operands[0], name);
+        }
+    }
+
+    let mut counts: BTreeMap<(String, u32), u32> = BTreeMap::new();
+    for instruction in reachable_instructions(instructions, functions, entry_point_id) {
+        if Op::from_u32(instruction.opcode) != Some(Op::ExtInst) {
+            continue;
+        }
+        if let [_, _, set, number, ..] = instruction.operands {
+            let set = sets.get(set).cloned().unwrap_or_default();
+            *counts.entry((set, *number)).or_default() += 1;
+        }
+    }
+    Ok(counts
+        .into_iter()
+        .map(|((set, instruction), count)| ReflectExtInstUsage {
+            name: match set.as_str() {
+                "GLSL.std.450" => disassemble::GLSL_STD_450
+                    .get(instruction as usize)
+                    .map(|name| (*name).to_owned())
+                    .unwrap_or_default(),
+                _ => String::new(),
+            },
+            set,
+            instruction,
+            count,
+        })
+        .collect())
+}
+
+/// The barriers in the code reachable from `entry_point_id`, merging the
+/// ones with equal scopes and semantics.
+pub(crate) fn barriers(
+    instructions: &[Instruction],
+    functions: &[dot::Function],
+    entry_point_id: u32,
+) -> Vec<ReflectBarrier> {
+    let mut constants = HashMap::new();
+    for instruction in instructions {
+        if let (true, [_, result, value]) = (instruction.is(Op::Constant), instruction.operands) {
+            constants.insert(*result, *value);
+        }
+    }
+    let constant = |id: &u32| constants.get(id).cloned();
+
+    let mut counts = BTreeMap::new();
+    for instruction in reachable_instructions(instructions, functions, entry_point_id) {
+        let key = match (Op::from_u32(instruction.opcode), instruction.operands) {
+            (Some(Op::ControlBarrier), [execution, memory, semantics, ..]) => (
+                true,
+                constant(execution),
+                constant(memory),
+                constant(semantics),
+            ),
+            (Some(Op::MemoryBarrier), [memory, semantics, ..]) => {
+                (false, None, constant(memory), constant(semantics))
+            }
+            _ => continue,
+        };
+        *counts.entry(key).or_insert(0) += 1;
+    }
+    counts
+        .into_iter()
+        .map(
+            |((control, execution_scope, memory_scope, semantics), count)| ReflectBarrier {
+                control,
+                execution_scope,
+                memory_scope,
+                semantics,
+                count,
+            },
+        )
+        .collect()
+}
+
+/// Raw opcode of `OpTerminateInvocation`, which is newer than `spirv_headers`.
+pub(crate) const OP_TERMINATE_INVOCATION: u32 = 4416;
+
+/// The discarding instructions in the code reachable from `entry_point_id`.
+pub(crate) fn discard_flags(
+    instructions: &[Instruction],
+    functions: &[dot::Function],
+    entry_point_id: u32,
+) -> ReflectDiscardFlags {
+    let mut flags = ReflectDiscardFlags::NONE;
+    for instruction in reachable_instructions(instructions, functions, entry_point_id) {
+        match Op::from_u32(instruction.opcode) {
+            Some(Op::Kill) => flags |= ReflectDiscardFlags::KILL,
+            Some(Op::DemoteToHelperInvocationEXT) => {
+                flags |= ReflectDiscardFlags::DEMOTE_TO_HELPER_INVOCATION
+            }
+            _ if instruction.opcode == OP_TERMINATE_INVOCATION => {
+                flags |= ReflectDiscardFlags::TERMINATE_INVOCATION
+            }
+            _ => {}
+        }
+    }
+    flags
+}
+
+/// The helper invocation queries and demotes in the code reachable from
+/// `entry_point_id`, where `built_in_ids` are the `HelperInvocation` variables.
+pub(crate) fn helper_invocation_flags(
+    instructions: &[Instruction],
+    functions: &[dot::Function],
+    built_in_ids: &HashSet<u32>,
+    entry_point_id: u32,
+) -> ReflectHelperInvocationFlags {
+    let mut flags = ReflectHelperInvocationFlags::NONE;
+    for instruction in reachable_instructions(instructions, functions, entry_point_id) {
+        match (Op::from_u32(instruction.opcode), instruction.operands) {
+            (Some(Op::Load), [_, _, pointer, ..]) if built_in_ids.contains(pointer) => {
+                flags |= ReflectHelperInvocationFlags::BUILT_IN
+            }
+            (Some(Op::IsHelperInvocationEXT), _) => {
+                flags |= ReflectHelperInvocationFlags::IS_HELPER_INVOCATION
+            }
+            (Some(Op::DemoteToHelperInvocationEXT), _) => {
+                flags |= ReflectHelperInvocationFlags::DEMOTE
+            }
+            _ => {}
+        }
+    }
+    flags
+}
+
+/// The variables of `storage_class` that the code reachable from
+/// `entry_point_id` stores to, directly or through access chains and
+/// function parameters.
+pub(crate) fn stored_variables(
+    instructions: &[Instruction],
+    functions: &[dot::Function],
+    module: &ParsedModule,
+    storage_class: u32,
+    entry_point_id: u32,
+) -> HashSet<u32> {
+    let sources = image_usage::trace_variables(instructions, module, &[storage_class]);
+    let mut stored = HashSet::new();
+    for instruction in reachable_instructions(instructions, functions, entry_point_id) {
+        match (Op::from_u32(instruction.opcode), instruction.operands) {
+            (Some(Op::Store), [pointer, ..])
+            | (Some(Op::CopyMemory), [pointer, ..])
+            | (Some(Op::CopyMemorySized), [pointer, ..]) => {
+                if let Some(variables) = sources.get(pointer) {
+                    stored.extend(variables.iter().cloned());
+                }
+            }
+            _ => {}
+        }
+    }
+    stored
+}
+
+/// Whether pointers of `storage_class` point into a buffer.
+fn is_buffer_storage_class(storage_class: u32) -> bool {
+    use spirv_headers::StorageClass;
+    storage_class == StorageClass::Uniform as u32
+        || storage_class == StorageClass::StorageBuffer as u32
+        || storage_class == StorageClass::PushConstant as u32
+        || storage_class == StorageClass::PhysicalStorageBuffer as u32
+        || storage_class == StorageClass::ShaderRecordBufferNV as u32
+}
+
+/// Rough static counts over the code reachable from `entry_point_id`. Loops
+/// and calls are not unrolled, so every instruction counts once.
+pub(crate) fn shader_metrics(
+    instructions: &[Instruction],
+    functions: &[dot::Function],
+    module: &ParsedModule,
+    entry_point_id: u32,
+) -> ReflectShaderMetrics {
+    let mut pointer_types = HashMap::new();
+    for instruction in instructions {
+        let defines_pointer = matches!(
+            Op::from_u32(instruction.opcode),
+            Some(Op::Variable)
+                | Some(Op::AccessChain)
+                | Some(Op::InBoundsAccessChain)
+                | Some(Op::PtrAccessChain)
+                | Some(Op::InBoundsPtrAccessChain)
+                | Some(Op::CopyObject)
+                | Some(Op::FunctionParameter)
+                | Some(Op::ConvertUToPtr)
+                | Some(Op::Bitcast)
+                | Some(Op::Phi)
+                | Some(Op::Select)
+        );
+        if let (true, [result_type, result, ..]) = (defines_pointer, instruction.operands) {
+            pointer_types.insert(*result, *result_type);
+        }
+    }
+    let is_buffer_pointer = |id: Option<&u32>| {
+        let type_id = match id.and_then(|id| pointer_types.get(id)) {
+            Some(type_id) => type_id,
+            None => return false,
+        };
+        match module.types.get(type_id) {
+            Some(ty) if ty.is(Op::TypePointer) && !ty.operands.is_empty() => {
+                is_buffer_storage_class(ty.operands[0])
+            }
+            _ => false,
+        }
+    };
+
+    let mut metrics = ReflectShaderMetrics {
+        function_count: dot::reachable(&[entry_point_id], functions).len() as u32,
+        ..Default::default()
+    };
+    for instruction in reachable_instructions(instructions, functions, entry_point_id) {
+        let op = match Op::from_u32(instruction.opcode) {
+            Some(op) => op,
+            None => {
+                metrics.instruction_count += 1;
+                continue;
+            }
+        };
+        match op {
+            Op::Function | Op::FunctionParameter | Op::FunctionEnd => continue,
+            Op::Label => metrics.block_count += 1,
+            Op::LoopMerge => metrics.loop_count += 1,
+            Op::Load if is_buffer_pointer(instruction.operands.get(2)) => {
+                metrics.buffer_load_count += 1
+            }
+            Op::Store if is_buffer_pointer(instruction.operands.first()) => {
+                metrics.buffer_store_count += 1
+            }
+            _ if image_usage::sampling_op(op).is_some() => metrics.image_sample_count += 1,
+            _ => {}
+        }
+        if op != Op::Label {
+            metrics.instruction_count += 1;
+        }
+    }
+    metrics
+}
+
+/// Control flow of a function body on its own, and the calls it makes with
+/// the loop depth of each call site.
+pub(crate) struct FunctionControlFlow {
+    pub(crate) summary: ReflectControlFlow,
+    calls: Vec<(u32, u32)>,
+    /// Index of the `OpLabel` of each block and the block's loop depth.
+    block_depths: Vec<(usize, u32)>,
+}
+
+/// A basic block: where it starts, where it can branch to, the merge block
+/// of the loop it heads, and the functions it calls.
+#[derive(Default)]
+struct Block {
+    start: usize,
+    successors: Vec<u32>,
+    loop_merge: Option<u32>,
+    calls: Vec<u32>,
+}
+
+/// Splits `function` into basic blocks and nests every block in the loops
+/// whose header reaches it without going through the loop's merge block.
+pub(crate) fn function_control_flow(
+    instructions: &[Instruction],
+    function: &dot::Function,
+) -> FunctionControlFlow {
+    let mut summary = ReflectControlFlow {
+        spirv_id: function.id,
+        ..Default::default()
+    };
+    let mut blocks: Vec<(u32, Block)> = Vec::new();
+    for index in function.instructions.clone() {
+        let instruction = &instructions[index];
+        let op = match Op::from_u32(instruction.opcode) {
+            Some(op) => op,
+            None => continue,
+        };
+        if op == Op::Label {
+            blocks.push((
+                instruction.operands.first().cloned().unwrap_or(0),
+                Block {
+                    start: index,
+                    ..Default::default()
+                },
+            ));
+            continue;
+        }
+        let block = match blocks.last_mut() {
+            Some((_, block)) => block,
+            None => continue,
+        };
+        match (op, instruction.operands) {
+            (Op::LoopMerge, [merge, ..]) => {
+                summary.loop_count += 1;
+                block.loop_merge = Some(*merge);
+            }
+            (Op::SelectionMerge, _) => summary.selection_count += 1,
+            (Op::Branch, [target, ..]) => block.successors.push(*target),
+            (Op::BranchConditional, [_, on_true, on_false, ..]) => {
+                summary.divergent_branch_count += 1;
+                block.successors.extend(&[*on_true, *on_false]);
+            }
+            (Op::Switch, [_, default, targets @ ..]) => {
+                summary.divergent_branch_count += 1;
+                block.successors.push(*default);
+                block
+                    .successors
+                    .extend(targets.chunks(2).filter_map(|pair| pair.get(1)));
+            }
+            (Op::FunctionCall, [_, _, callee, ..]) => block.calls.push(*callee),
+            _ => {}
+        }
+    }
+
+    let indices: HashMap<u32, usize> = blocks
+        .iter()
+        .enumerate()
+        .map(|(index, (label, _))| (*label, index))
+        .collect();
+    let mut depths = vec![0u32; blocks.len()];
+    for (header, (_, block)) in blocks.iter().enumerate() {
+        let merge = match block.loop_merge {
+            Some(merge) => merge,
+            None => continue,
+        };
+        let mut seen = HashSet::new();
+        let mut stack = vec![header];
+        while let Some(index) = stack.pop() {
+            if !seen.insert(index) {
+                continue;
+            }
+            depths[index] += 1;
+            stack.extend(
+                blocks[index]
+                    .1
+                    .successors
+                    .iter()
+                    .filter(|label| **label != merge)
+                    .filter_map(|label| indices.get(label)),
+            );
+        }
+    }
+
+    summary.max_loop_depth = depths.iter().cloned().max().unwrap_or(0);
+    let calls = blocks
+        .iter()
+        .zip(&depths)
+        .flat_map(|((_, block), depth)| block.calls.iter().map(move |callee| (*callee, *depth)))
+        .collect();
+    let block_depths = blocks
+        .iter()
+        .zip(&depths)
+        .map(|((_, block), depth)| (block.start, *depth))
+        .collect();
+    FunctionControlFlow {
+        summary,
+        calls,
+        block_depths,
+    }
+}
+
+/// Deepest loop nesting reachable from `function_id`, following calls.
+/// Recursive calls, which shaders cannot make, are not followed.
+fn nested_loop_depth(
+    function_id: u32,
+    functions: &HashMap<u32, FunctionControlFlow>,
+    visiting: &mut HashSet<u32>,
+) -> u32 {
+    let function = match functions.get(&function_id) {
+        Some(function) => function,
+        None => return 0,
+    };
+    if !visiting.insert(function_id) {
+        return 0;
+    }
+    let depth = function
+        .calls
+        .iter()
+        .map(|(callee, depth)| depth + nested_loop_depth(*callee, functions, visiting))
+        .fold(function.summary.max_loop_depth, u32::max);
+    visiting.remove(&function_id);
+    depth
+}
+
+/// Control flow of everything reachable from `entry_point_id`, with the
+/// loops of called functions nested inside the loops around each call.
+pub(crate) fn entry_point_control_flow(
+    instructions: &[Instruction],
+    functions: &[dot::Function],
+    entry_point_id: u32,
+) -> ReflectControlFlow {
+    let flows: HashMap<u32, FunctionControlFlow> = dot::reachable(&[entry_point_id], functions)
+        .into_iter()
+        .map(|function| (function.id, function_control_flow(instructions, function)))
+        .collect();
+    let mut summary = ReflectControlFlow {
+        spirv_id: entry_point_id,
+        max_loop_depth: nested_loop_depth(entry_point_id, &flows, &mut HashSet::new()),
+        ..Default::default()
+    };
+    for flow in flows.values() {
+        summary.loop_count += flow.summary.loop_count;
+        summary.selection_count += flow.summary.selection_count;
+        summary.divergent_branch_count += flow.summary.divergent_branch_count;
+    }
+    summary
+}
+
+/// Raises `depths` to the loop depth around each call of the functions
+/// reachable from `function_id`, itself entered at loop depth `depth`.
+fn call_loop_depths(
+    function_id: u32,
+    depth: u32,
+    functions: &HashMap<u32, FunctionControlFlow>,
+    visiting: &mut HashSet<u32>,
+    depths: &mut HashMap<u32, u32>,
+) {
+    let function = match functions.get(&function_id) {
+        Some(function) => function,
+        None => return,
+    };
+    if !visiting.insert(function_id) {
+        return;
+    }
+    let entered = depths.entry(function_id).or_insert(depth);
+    *entered = (*entered).max(depth);
+    for (callee, call_depth) in &function.calls {
+        call_loop_depths(*callee, depth + call_depth, functions, visiting, depths);
+    }
+    visiting.remove(&function_id);
+}
+
+/// The count of `ReflectBindingAccess` an access site adds to.
+type AccessCount = fn(&mut ReflectBindingAccess) -> &mut u32;
+
+/// Static access sites of every descriptor binding variable in the code
+/// reachable from `entry_point_id`, sorted by set and binding. Bindings the
+/// entry point does not touch are listed with zero counts.
+pub(crate) fn binding_access(
+    instructions: &[Instruction],
+    functions: &[dot::Function],
+    module: &ParsedModule,
+    entry_point_id: u32,
+) -> Vec<ReflectBindingAccess> {
+    use spirv_headers::{Decoration, StorageClass};
+    let storage_classes = [
+        StorageClass::UniformConstant as u32,
+        StorageClass::Uniform as u32,
+        StorageClass::StorageBuffer as u32,
+    ];
+    let decoration = |id: u32, decoration: Decoration| {
+        module
+            .decoration(id, decoration)
+            .and_then(|operands| operands.first().cloned())
+    };
+    let mut accesses: BTreeMap<u32, ReflectBindingAccess> = module
+        .variables
+        .iter()
+        .filter(|variable| storage_classes.contains(&variable.storage_class))
+        .filter_map(|variable| {
+            let set = decoration(variable.id, Decoration::DescriptorSet);
+            let binding = decoration(variable.id, Decoration::Binding);
+            if set.is_none() && binding.is_none() {
+                return None;
+            }
+            Some((
+                variable.id,
+                ReflectBindingAccess {
+                    spirv_id: variable.id,
+                    name: module.name(variable.id),
+                    set: set.unwrap_or(0),
+                    binding: binding.unwrap_or(0),
+                    ..Default::default()
+                },
+            ))
+        })
+        .collect();
+    let handles: HashSet<u32> = module
+        .variables
+        .iter()
+        .filter(|variable| variable.storage_class == StorageClass::UniformConstant as u32)
+        .map(|variable| variable.id)
+        .collect();
+    let sources = image_usage::trace_variables(instructions, module, &storage_classes);
+
+    let flows: HashMap<u32, FunctionControlFlow> = dot::reachable(&[entry_point_id], functions)
+        .into_iter()
+        .map(|function| (function.id, function_control_flow(instructions, function)))
+        .collect();
+    let mut entered_depths = HashMap::new();
+    call_loop_depths(
+        entry_point_id,
+        0,
+        &flows,
+        &mut HashSet::new(),
+        &mut entered_depths,
+    );
+
+    for function in dot::reachable(&[entry_point_id], functions) {
+        let flow = &flows[&function.id];
+        let entered_depth = entered_depths.get(&function.id).cloned().unwrap_or(0);
+        let mut block_depths = flow.block_depths.iter().peekable();
+        let mut block_depth = 0;
+        for index in function.instructions.clone() {
+            if let Some((_, depth)) = block_depths.next_if(|(start, _)| *start == index) {
+                block_depth = *depth;
+            }
+            let instruction = &instructions[index];
+            let operands = instruction.operands;
+            let op = Op::from_u32(instruction.opcode);
+            // (operand index, is a buffer pointer rather than an image, count).
+            let sites: Vec<(usize, bool, AccessCount)> = match op {
+                Some(Op::Load) => vec![(2, true, |access| &mut access.load_count)],
+                Some(Op::Store) => vec![(0, true, |access| &mut access.store_count)],
+                Some(Op::CopyMemory) => vec![
+                    (0, true, |access| &mut access.store_count),
+                    (1, true, |access| &mut access.load_count),
+                ],
+                Some(op) if image_usage::sampling_op(op).is_some() => {
+                    vec![(2, false, |access| &mut access.sample_count)]
+                }
+                Some(Op::ImageRead) | Some(Op::ImageSparseRead) => {
+                    vec![(2, false, |access| &mut access.load_count)]
+                }
+                Some(Op::ImageWrite) => vec![(0, false, |access| &mut access.store_count)],
+                _ => match image_usage::atomic_pointer_index(instruction.opcode) {
+                    Some(index) => vec![(index, false, |access| &mut access.atomic_count)],
+                    None => continue,
+                },
+            };
+            let depth = entered_depth + block_depth;
+            for (operand, buffer_only, count) in sites {
+                let variables = match operands.get(operand).and_then(|id| sources.get(id)) {
+                    Some(variables) => variables,
+                    None => continue,
+                };
+                for variable in variables {
+                    // Loads of image and sampler handles are not accesses;
+                    // the instructions using them are.
+                    if buffer_only && handles.contains(variable) {
+                        continue;
+                    }
+                    if let Some(access) = accesses.get_mut(variable) {
+                        *count(access) += 1;
+                        if depth > 0 {
+                            access.loop_access_count += 1;
+                        }
+                        access.max_loop_depth = access.max_loop_depth.max(depth);
+                    }
+                }
+            }
+        }
+    }
+    let mut accesses: Vec<ReflectBindingAccess> = accesses.into_values().collect();
+    accesses.sort_by_key(|access| (access.set, access.binding));
+    accesses
+}
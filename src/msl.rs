@@ -0,0 +1,159 @@
+//! Metal slot assignments for Vulkan-style descriptor sets, following the
+//! conventions SPIRV-Cross uses when it translates SPIR-V to MSL without
+//! explicit resource remapping.
+
+use crate::types::{ReflectDescriptorSet, ReflectDescriptorType};
+
+/// How descriptors are bound to a Metal function.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ReflectMslBindingModel {
+    /// Each resource in its own `[[buffer(n)]]`, `[[texture(n)]]` or
+    /// `[[sampler(n)]]` slot, numbered separately.
+    #[default]
+    Discrete,
+    /// Each descriptor set in an argument buffer at `[[buffer(set)]]`, its
+    /// resources at `[[id(n)]]` in one index space per set.
+    ArgumentBuffers,
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectMslOptions {
+    pub model: ReflectMslBindingModel,
+    /// First `[[buffer]]` index handed out, to discrete buffers or to the
+    /// argument buffer of set 0.
+    pub buffer_base: u32,
+    /// First discrete `[[texture]]` index.
+    pub texture_base: u32,
+    /// First discrete `[[sampler]]` index.
+    pub sampler_base: u32,
+}
+
+/// The slots of one binding: `[[buffer]]`, `[[texture]]` and `[[sampler]]`
+/// indices for `Discrete`, `[[id]]` indices within the set's argument
+/// buffer for `ArgumentBuffers`. A combined image sampler has a texture and
+/// a sampler; an array takes `count` consecutive indices of each.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectMslSlots {
+    pub buffer: Option<u32>,
+    pub texture: Option<u32>,
+    pub sampler: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectMslBinding {
+    pub set: u32,
+    pub binding: u32,
+    pub name: String,
+    pub slots: ReflectMslSlots,
+    pub count: u32,
+}
+
+/// The `[[buffer]]` index of the argument buffer holding a set.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectMslArgumentBuffer {
+    pub set: u32,
+    pub buffer: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectMslLayout {
+    pub bindings: Vec<ReflectMslBinding>,
+    /// Empty for `Discrete`.
+    pub argument_buffers: Vec<ReflectMslArgumentBuffer>,
+    /// The `[[buffer]]` index of the push constant block, past every other
+    /// buffer. `None` without push constants.
+    pub push_constant_buffer: Option<u32>,
+}
+
+/// Which of the buffer, texture and sampler slots a descriptor takes.
+fn slot_kinds(descriptor_type: ReflectDescriptorType) -> Result<(bool, bool, bool), &'static str> {
+    match descriptor_type {
+        ReflectDescriptorType::UniformBuffer
+        | ReflectDescriptorType::UniformBufferDynamic
+        | ReflectDescriptorType::StorageBuffer
+        | ReflectDescriptorType::StorageBufferDynamic
+        | ReflectDescriptorType::AccelerationStructureNV => Ok((true, false, false)),
+        ReflectDescriptorType::SampledImage
+        | ReflectDescriptorType::StorageImage
+        | ReflectDescriptorType::UniformTexelBuffer
+        | ReflectDescriptorType::StorageTexelBuffer
+        | ReflectDescriptorType::InputAttachment => Ok((false, true, false)),
+        ReflectDescriptorType::Sampler => Ok((false, false, true)),
+        ReflectDescriptorType::CombinedImageSampler => Ok((false, true, true)),
+        ReflectDescriptorType::Undefined => Err("Descriptor type not supported by Metal"),
+    }
+}
+
+fn take(next: &mut u32, count: u32, used: bool) -> Option<u32> {
+    if used {
+        let index = *next;
+        *next += count;
+        Some(index)
+    } else {
+        None
+    }
+}
+
+/// Assigns Metal slots to the bindings of `sets`, in set and binding order,
+/// for one function: pass the sets of a single entry point, and whether it
+/// uses push constants.
+pub fn msl_layout(
+    sets: &[ReflectDescriptorSet],
+    push_constants: bool,
+    options: &ReflectMslOptions,
+) -> Result<ReflectMslLayout, &'static str> {
+    let mut bindings: Vec<_> = sets.iter().flat_map(|set| &set.bindings).collect();
+    bindings.sort_by_key(|binding| (binding.set, binding.binding));
+
+    let mut layout = ReflectMslLayout::default();
+    let (mut next_buffer, mut next_texture, mut next_sampler) = (
+        options.buffer_base,
+        options.texture_base,
+        options.sampler_base,
+    );
+    let mut next_id = 0;
+    for binding in bindings {
+        let (buffer, texture, sampler) = slot_kinds(binding.descriptor_type)?;
+        let count = binding.count.max(1);
+        let slots = match options.model {
+            ReflectMslBindingModel::Discrete => ReflectMslSlots {
+                buffer: take(&mut next_buffer, count, buffer),
+                texture: take(&mut next_texture, count, texture),
+                sampler: take(&mut next_sampler, count, sampler),
+            },
+            ReflectMslBindingModel::ArgumentBuffers => {
+                let new_set = match layout.argument_buffers.last() {
+                    Some(argument_buffer) => argument_buffer.set != binding.set,
+                    None => true,
+                };
+                if new_set {
+                    layout.argument_buffers.push(ReflectMslArgumentBuffer {
+                        set: binding.set,
+                        buffer: options.buffer_base + binding.set,
+                    });
+                    next_id = 0;
+                }
+                ReflectMslSlots {
+                    buffer: take(&mut next_id, count, buffer),
+                    texture: take(&mut next_id, count, texture),
+                    sampler: take(&mut next_id, count, sampler),
+                }
+            }
+        };
+        layout.bindings.push(ReflectMslBinding {
+            set: binding.set,
+            binding: binding.binding,
+            name: binding.name.clone(),
+            slots,
+            count,
+        });
+    }
+
+    if push_constants {
+        layout.push_constant_buffer = Some(match layout.argument_buffers.last() {
+            Some(argument_buffer) => argument_buffer.buffer + 1,
+            None => next_buffer,
+        });
+    }
+    Ok(layout)
+}
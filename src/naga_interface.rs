@@ -0,0 +1,233 @@
+//! Reflection results in the shape of naga's entry point interface, for
+//! pipelines that mix shaders translated by naga with SPIR-V reflected by
+//! this crate. The types mirror their naga namesakes field for field without
+//! depending on naga, so they convert with a plain `match`.
+
+use crate::types::{
+    ReflectBuiltIn, ReflectDecorationFlags, ReflectDescriptorBinding, ReflectDescriptorType,
+    ReflectInterfaceVariable, ReflectShaderStageFlags,
+};
+use crate::ShaderModule;
+
+/// `naga::ResourceBinding`: a descriptor set is a bind group.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct NagaResourceBinding {
+    pub group: u32,
+    pub binding: u32,
+}
+
+/// `naga::AddressSpace` of a global variable.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum NagaAddressSpace {
+    Uniform,
+    /// `load` and `store` are the `naga::StorageAccess` flags.
+    Storage {
+        load: bool,
+        store: bool,
+    },
+    /// Images, samplers, texel buffers and acceleration structures.
+    Handle,
+    PushConstant,
+}
+
+/// `naga::ShaderStage`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum NagaShaderStage {
+    Vertex,
+    Fragment,
+    Compute,
+}
+
+/// `naga::Interpolation`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum NagaInterpolation {
+    Perspective,
+    Linear,
+    Flat,
+}
+
+/// `naga::Binding` of an entry point argument or result.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum NagaBinding {
+    BuiltIn(ReflectBuiltIn),
+    Location {
+        location: u32,
+        /// The `Index` 1 output of dual-source blending.
+        second_blend_source: bool,
+        /// `None` for the default, perspective-correct interpolation.
+        interpolation: Option<NagaInterpolation>,
+    },
+}
+
+/// One resource or push constant block the entry point uses, like a
+/// `naga::GlobalVariable` without its type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NagaGlobalVariable {
+    /// `None` when the module has no name for it.
+    pub name: Option<String>,
+    pub space: NagaAddressSpace,
+    /// `None` for push constants.
+    pub binding: Option<NagaResourceBinding>,
+}
+
+/// One input or output of an entry point. Interface blocks are flattened
+/// into their members, named `block.member`, as naga passes them as struct
+/// members with a binding each.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NagaVarying {
+    pub name: Option<String>,
+    pub binding: NagaBinding,
+}
+
+/// The interface of a `naga::EntryPoint`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NagaEntryPoint {
+    pub name: String,
+    pub stage: NagaShaderStage,
+    /// Specialization constants at their defaults; zeros outside compute.
+    pub workgroup_size: [u32; 3],
+    pub inputs: Vec<NagaVarying>,
+    pub outputs: Vec<NagaVarying>,
+    pub globals: Vec<NagaGlobalVariable>,
+}
+
+fn stage(shader_stage: ReflectShaderStageFlags) -> Result<NagaShaderStage, &'static str> {
+    if shader_stage == ReflectShaderStageFlags::VERTEX {
+        Ok(NagaShaderStage::Vertex)
+    } else if shader_stage == ReflectShaderStageFlags::FRAGMENT {
+        Ok(NagaShaderStage::Fragment)
+    } else if shader_stage == ReflectShaderStageFlags::COMPUTE {
+        Ok(NagaShaderStage::Compute)
+    } else {
+        Err("Shader stage not supported by naga")
+    }
+}
+
+fn space(binding: &ReflectDescriptorBinding) -> NagaAddressSpace {
+    match binding.descriptor_type {
+        ReflectDescriptorType::UniformBuffer | ReflectDescriptorType::UniformBufferDynamic => {
+            NagaAddressSpace::Uniform
+        }
+        ReflectDescriptorType::StorageBuffer | ReflectDescriptorType::StorageBufferDynamic => {
+            let non_writable = |flags: ReflectDecorationFlags| {
+                flags.contains(ReflectDecorationFlags::NON_WRITABLE)
+            };
+            let read_only = non_writable(binding.block.decoration_flags)
+                || (!binding.block.members.is_empty()
+                    && binding
+                        .block
+                        .members
+                        .iter()
+                        .all(|member| non_writable(member.decoration_flags)));
+            NagaAddressSpace::Storage {
+                load: true,
+                store: !read_only,
+            }
+        }
+        _ => NagaAddressSpace::Handle,
+    }
+}
+
+fn name_of(name: &str, synthesized: bool) -> Option<String> {
+    if synthesized || name.is_empty() {
+        None
+    } else {
+        Some(name.to_owned())
+    }
+}
+
+fn push_varyings(
+    variable: &ReflectInterfaceVariable,
+    prefix: Option<&str>,
+    varyings: &mut Vec<NagaVarying>,
+) {
+    let name = name_of(&variable.name, variable.name_synthesized).map(|name| match prefix {
+        Some(prefix) => format!("{}.{}", prefix, name),
+        None => name,
+    });
+    if !variable.members.is_empty() {
+        for member in &variable.members {
+            push_varyings(member, name.as_deref(), varyings);
+        }
+        return;
+    }
+    let flags = variable.decoration_flags;
+    let binding = if flags.contains(ReflectDecorationFlags::BUILT_IN) {
+        NagaBinding::BuiltIn(variable.built_in)
+    } else {
+        NagaBinding::Location {
+            location: variable.location,
+            second_blend_source: variable.index == 1,
+            interpolation: if flags.contains(ReflectDecorationFlags::FLAT) {
+                Some(NagaInterpolation::Flat)
+            } else if flags.contains(ReflectDecorationFlags::NO_PERSPECTIVE) {
+                Some(NagaInterpolation::Linear)
+            } else {
+                None
+            },
+        }
+    };
+    varyings.push(NagaVarying { name, binding });
+}
+
+/// The interfaces of the entry points of `module`, in the order of
+/// `enumerate_entry_points`. Fails if one of them is a stage naga does not
+/// have.
+pub fn entry_points(module: &ShaderModule) -> Result<Vec<NagaEntryPoint>, &'static str> {
+    module
+        .enumerate_entry_points()?
+        .iter()
+        .map(|entry_point| {
+            let stage = stage(entry_point.shader_stage)?;
+            let workgroup_size = match module.get_workgroup_size(Some(&entry_point.name))? {
+                Some(size) if stage == NagaShaderStage::Compute => [
+                    size[0].default_value().unwrap_or(1),
+                    size[1].default_value().unwrap_or(1),
+                    size[2].default_value().unwrap_or(1),
+                ],
+                _ => [0; 3],
+            };
+            let mut inputs = Vec::new();
+            for variable in &entry_point.input_variables {
+                push_varyings(variable, None, &mut inputs);
+            }
+            let mut outputs = Vec::new();
+            for variable in &entry_point.output_variables {
+                push_varyings(variable, None, &mut outputs);
+            }
+
+            let mut globals: Vec<NagaGlobalVariable> = entry_point
+                .descriptor_sets
+                .iter()
+                .flat_map(|set| &set.bindings)
+                .map(|binding| NagaGlobalVariable {
+                    name: name_of(&binding.name, binding.name_synthesized),
+                    space: space(binding),
+                    binding: Some(NagaResourceBinding {
+                        group: binding.set,
+                        binding: binding.binding,
+                    }),
+                })
+                .collect();
+            globals.extend(
+                module
+                    .enumerate_push_constants(Some(&entry_point.name))?
+                    .into_iter()
+                    .map(|push_constants| NagaGlobalVariable {
+                        name: name_of(&push_constants.name, false),
+                        space: NagaAddressSpace::PushConstant,
+                        binding: None,
+                    }),
+            );
+
+            Ok(NagaEntryPoint {
+                name: entry_point.name.clone(),
+                stage,
+                workgroup_size,
+                inputs,
+                outputs,
+                globals,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,2325 @@
+//! Minimal walker over raw SPIR-V words, for the bits of information the C
+//! library does not reflect.
+
+use crate::trace;
+use crate::types::*;
+use num_traits::cast::FromPrimitive;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+pub(crate) const HEADER_WORD_COUNT: usize = 5;
+const CONSTANT_NESTING_LIMIT: u32 = 64;
+/// Constants visited for one composite or size expression. Constants cannot
+/// refer to later ones in a valid module, but malformed ones can share or
+/// repeat constituents to blow up the work exponentially.
+const CONSTANT_VISIT_LIMIT: u32 = 1 << 12;
+/// Interface arrays are flattened up to this many elements, far beyond the
+/// location limits of any implementation.
+const INTERFACE_ARRAY_LIMIT: u32 = 4096;
+/// Types visited when flattening one interface variable, so that malformed
+/// modules with types nested in themselves stay cheap to reflect.
+const INTERFACE_TYPE_VISIT_LIMIT: u32 = 1 << 16;
+
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Instruction<'a> {
+    pub(crate) opcode: u32,
+    pub(crate) operands: &'a [u32],
+}
+
+impl<'a> Instruction<'a> {
+    pub(crate) fn is(&self, op: spirv_headers::Op) -> bool {
+        self.opcode == op as u32
+    }
+}
+
+/// Word count of the instruction at `word_offset` that starts with
+/// `first_word`. It must be non-zero and fit in the rest of `code`; a zero
+/// count would never advance and an oversized one would read past the end.
+fn instruction_word_count(
+    first_word: u32,
+    word_offset: usize,
+    code_words: usize,
+) -> Result<usize, &'static str> {
+    let word_count = (first_word >> 16) as usize;
+    if word_count == 0 {
+        return Err("Invalid SPIR-V instruction word count");
+    }
+    if word_count > code_words - word_offset {
+        return Err("Unexpected end of SPIR-V code");
+    }
+    Ok(word_count)
+}
+
+pub(crate) fn parse_instructions(code: &[u32]) -> Result<Vec<Instruction<'_>>, &'static str> {
+    if code.len() < HEADER_WORD_COUNT || code[0] != SPIRV_MAGIC {
+        return Err("Invalid SPIR-V header");
+    }
+    let mut instructions = Vec::new();
+    let mut word_offset = HEADER_WORD_COUNT;
+    while word_offset < code.len() {
+        let word_count = instruction_word_count(code[word_offset], word_offset, code.len())?;
+        instructions.push(Instruction {
+            opcode: code[word_offset] & 0xffff,
+            operands: &code[word_offset + 1..word_offset + word_count],
+        });
+        word_offset += word_count;
+    }
+    Ok(instructions)
+}
+
+/// Checks the header and the word count of every instruction of a module
+/// before it is handed to the C library, which does not guard against
+/// either.
+// `usize::is_multiple_of` needs Rust 1.87.
+#[allow(unknown_lints, clippy::manual_is_multiple_of)]
+pub(crate) fn validate_instructions(spv_data: &[u8]) -> Result<(), &'static str> {
+    if spv_data.len() % 4 != 0 || spv_data.len() < HEADER_WORD_COUNT * 4 {
+        return Err("Invalid Code Size");
+    }
+    let word = |index: usize| code_word(spv_data, index);
+    if word(0) != SPIRV_MAGIC {
+        return Err("Invalid Magic Number");
+    }
+    let code_words = spv_data.len() / 4;
+    let mut word_offset = HEADER_WORD_COUNT;
+    while word_offset < code_words {
+        word_offset += instruction_word_count(word(word_offset), word_offset, code_words)?;
+    }
+    Ok(())
+}
+
+/// Word `index` of a module given as bytes, in the host's byte order like
+/// the C library reads it.
+pub(crate) fn code_word(spv_data: &[u8], index: usize) -> u32 {
+    let bytes = &spv_data[index * 4..index * 4 + 4];
+    u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// Decodes a nul-terminated literal string, returning it together with the
+/// number of words it occupies. Invalid UTF-8 is replaced with U+FFFD, which
+/// `collect_warnings` reports.
+pub(crate) fn parse_string(words: &[u32]) -> Result<(String, usize), &'static str> {
+    let (bytes, word_count) = parse_string_bytes(words)?;
+    Ok((String::from_utf8_lossy(&bytes).into_owned(), word_count))
+}
+
+fn parse_string_bytes(words: &[u32]) -> Result<(Vec<u8>, usize), &'static str> {
+    let mut bytes = Vec::new();
+    for (index, word) in words.iter().enumerate() {
+        for byte in word.to_le_bytes().iter() {
+            if *byte == 0 {
+                return Ok((bytes, index + 1));
+            }
+            bytes.push(*byte);
+        }
+    }
+    Err("Unterminated SPIR-V string literal")
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct EntryPoint {
+    pub(crate) execution_model: u32,
+    pub(crate) id: u32,
+    pub(crate) name: String,
+    pub(crate) interface: Vec<u32>,
+}
+
+pub(crate) fn parse_entry_points(
+    instructions: &[Instruction],
+) -> Result<Vec<EntryPoint>, &'static str> {
+    let mut entry_points = Vec::new();
+    for instruction in instructions {
+        if !instruction.is(spirv_headers::Op::EntryPoint) {
+            continue;
+        }
+        if instruction.operands.len() < 3 {
+            return Err("Invalid OpEntryPoint");
+        }
+        let (name, name_word_count) = parse_string(&instruction.operands[2..])?;
+        entry_points.push(EntryPoint {
+            execution_model: instruction.operands[0],
+            id: instruction.operands[1],
+            name,
+            interface: instruction.operands[2 + name_word_count..].to_vec(),
+        });
+    }
+    Ok(entry_points)
+}
+
+pub(crate) fn parse_strings(
+    instructions: &[Instruction],
+) -> Result<Vec<ReflectString>, &'static str> {
+    let mut strings = Vec::new();
+    for instruction in instructions {
+        if instruction.is(spirv_headers::Op::String) {
+            if instruction.operands.is_empty() {
+                return Err("Invalid OpString");
+            }
+            let (value, _) = parse_string(&instruction.operands[1..])?;
+            strings.push(ReflectString {
+                spirv_id: instruction.operands[0],
+                value,
+            });
+        }
+    }
+    Ok(strings)
+}
+
+/// Every `OpSource` in declaration order, such as the main file and the
+/// headers DXC embeds with `-Zi`.
+pub(crate) fn parse_sources(
+    instructions: &[Instruction],
+) -> Result<Vec<ReflectSource>, &'static str> {
+    let strings: HashMap<u32, String> = parse_strings(instructions)?
+        .into_iter()
+        .map(|string| (string.spirv_id, string.value))
+        .collect();
+    let mut sources: Vec<ReflectSource> = Vec::new();
+    for instruction in instructions {
+        if instruction.is(spirv_headers::Op::Source) {
+            if instruction.operands.len() < 2 {
+                return Err("Invalid OpSource");
+            }
+            let text = match instruction.operands.get(3..) {
+                Some(words) if !words.is_empty() => parse_string(words)?.0,
+                _ => String::new(),
+            };
+            sources.push(ReflectSource {
+                language: instruction.operands[0],
+                language_version: instruction.operands[1],
+                file: instruction
+                    .operands
+                    .get(2)
+                    .and_then(|id| strings.get(id).cloned())
+                    .unwrap_or_default(),
+                text,
+            });
+        } else if instruction.is(spirv_headers::Op::SourceContinued) {
+            let (text, _) = parse_string(instruction.operands)?;
+            match sources.last_mut() {
+                Some(source) => source.text.push_str(&text),
+                None => return Err("OpSourceContinued without OpSource"),
+            }
+        }
+    }
+    Ok(sources)
+}
+
+/// HLSL metadata from `OpSource`, `OpString` and `OpModuleProcessed`; `None`
+/// if the source language is not HLSL.
+pub(crate) fn parse_hlsl_source_info(
+    instructions: &[Instruction],
+) -> Result<Option<ReflectHlslSourceInfo>, &'static str> {
+    let strings: HashMap<u32, String> = parse_strings(instructions)?
+        .into_iter()
+        .map(|string| (string.spirv_id, string.value))
+        .collect();
+    let mut source = None;
+    let mut options = Vec::new();
+    for instruction in instructions {
+        if instruction.is(spirv_headers::Op::Source) {
+            if instruction.operands.len() < 2 {
+                return Err("Invalid OpSource");
+            }
+            source.get_or_insert(instruction.operands);
+        } else if instruction.is(spirv_headers::Op::ModuleProcessed) {
+            let (process, _) = parse_string(instruction.operands)?;
+            if let Some(arguments) = process.strip_prefix("dxc-cl-option:") {
+                options.extend(arguments.split_whitespace().map(str::to_owned));
+            }
+        }
+    }
+    let source = match source {
+        Some(source) if source[0] == spirv_headers::SourceLanguage::HLSL as u32 => source,
+        _ => return Ok(None),
+    };
+
+    // `-T ps_6_0`, `-Tps_6_0` and `/T ps_6_0` are all accepted by DXC.
+    let option_value = |flag: char| {
+        options.iter().enumerate().find_map(|(index, option)| {
+            let mut chars = option.chars();
+            match (chars.next(), chars.next()) {
+                (Some('-'), Some(c)) | (Some('/'), Some(c)) if c == flag => {
+                    let rest = chars.as_str();
+                    if rest.is_empty() {
+                        options.get(index + 1).cloned()
+                    } else {
+                        Some(rest.to_owned())
+                    }
+                }
+                _ => None,
+            }
+        })
+    };
+    Ok(Some(ReflectHlslSourceInfo {
+        shader_model: (source[1] / 100, source[1] / 10 % 10),
+        profile: option_value('T'),
+        entry_point: option_value('E'),
+        file: source.get(2).and_then(|id| strings.get(id).cloned()),
+        options,
+    }))
+}
+
+pub(crate) fn parse_function_parameters(
+    instructions: &[Instruction],
+) -> Result<HashMap<u32, Vec<FunctionParameter>>, &'static str> {
+    let mut parameters: HashMap<u32, Vec<FunctionParameter>> = HashMap::new();
+    let mut function_id = None;
+    for instruction in instructions {
+        if instruction.is(spirv_headers::Op::Function) {
+            if instruction.operands.len() < 2 {
+                return Err("Invalid OpFunction");
+            }
+            function_id = Some(instruction.operands[1]);
+        } else if instruction.is(spirv_headers::Op::FunctionParameter) {
+            if instruction.operands.len() < 2 {
+                return Err("Invalid OpFunctionParameter");
+            }
+            let function_id = function_id.ok_or("OpFunctionParameter outside of a function")?;
+            parameters
+                .entry(function_id)
+                .or_default()
+                .push(FunctionParameter {
+                    id: instruction.operands[1],
+                    type_id: instruction.operands[0],
+                });
+        } else if instruction.is(spirv_headers::Op::FunctionEnd) {
+            function_id = None;
+        }
+    }
+    Ok(parameters)
+}
+
+pub(crate) fn parse_function_declarations(
+    instructions: &[Instruction],
+) -> Result<Vec<FunctionDeclaration>, &'static str> {
+    let mut declarations: Vec<FunctionDeclaration> = Vec::new();
+    let mut in_function = false;
+    for instruction in instructions {
+        if instruction.is(spirv_headers::Op::Function) {
+            if instruction.operands.len() < 4 {
+                return Err("Invalid OpFunction");
+            }
+            declarations.push(FunctionDeclaration {
+                id: instruction.operands[1],
+                result_type_id: instruction.operands[0],
+                control: instruction.operands[2],
+                function_type_id: instruction.operands[3],
+                has_body: false,
+            });
+            in_function = true;
+        } else if instruction.is(spirv_headers::Op::Label) && in_function {
+            if let Some(declaration) = declarations.last_mut() {
+                declaration.has_body = true;
+            }
+        } else if instruction.is(spirv_headers::Op::FunctionEnd) {
+            in_function = false;
+        }
+    }
+    Ok(declarations)
+}
+
+/// Decorations a Vulkan consumer needs that the module leaves out: resource
+/// variables without a set or binding, user-defined entry point inputs and
+/// outputs without a location, and block members without an offset. NV task
+/// payloads live in `Output`/`Input` but take no locations.
+pub(crate) fn completeness_warnings(module: &ParsedModule) -> Vec<ReflectWarning> {
+    let mut warnings = Vec::new();
+    for variable in &module.variables {
+        let is_resource = variable.storage_class
+            == spirv_headers::StorageClass::UniformConstant as u32
+            || variable.storage_class == spirv_headers::StorageClass::Uniform as u32
+            || variable.storage_class == spirv_headers::StorageClass::StorageBuffer as u32;
+        if !is_resource || module.built_in(variable.id).is_some() {
+            continue;
+        }
+        if module
+            .decoration(variable.id, spirv_headers::Decoration::DescriptorSet)
+            .is_none()
+        {
+            warnings.push(ReflectWarning::MissingDescriptorSet {
+                spirv_id: variable.id,
+                name: module.name(variable.id),
+            });
+        }
+        if module
+            .decoration(variable.id, spirv_headers::Decoration::Binding)
+            .is_none()
+        {
+            warnings.push(ReflectWarning::MissingBinding {
+                spirv_id: variable.id,
+                name: module.name(variable.id),
+            });
+        }
+    }
+
+    let mut interface_ids = Vec::new();
+    for entry_point in &module.entry_points {
+        for &id in &entry_point.interface {
+            if !interface_ids.contains(&id) {
+                interface_ids.push(id);
+            }
+        }
+    }
+    for variable in &module.variables {
+        let is_interface = variable.storage_class == spirv_headers::StorageClass::Input as u32
+            || variable.storage_class == spirv_headers::StorageClass::Output as u32;
+        if !is_interface
+            || !interface_ids.contains(&variable.id)
+            || module.built_in(variable.id).is_some()
+            || module
+                .decoration(variable.id, spirv_headers::Decoration::Location)
+                .is_some()
+            || module.variable_has_decoration(variable.id, spirv_headers::Decoration::PerTaskNV)
+        {
+            continue;
+        }
+        let type_id = module.array_element_type_id(module.pointee_type_id(variable.type_id));
+        match module.types.get(&type_id) {
+            Some(ty) if ty.is(spirv_headers::Op::TypeStruct) => {
+                for member in 0..ty.operands.len() as u32 {
+                    let decorated = |decoration| {
+                        module
+                            .member_decoration(type_id, member, decoration)
+                            .is_some()
+                    };
+                    if !decorated(spirv_headers::Decoration::BuiltIn)
+                        && !decorated(spirv_headers::Decoration::Location)
+                    {
+                        warnings.push(ReflectWarning::MissingLocation {
+                            spirv_id: variable.id,
+                            name: module.name(variable.id),
+                            member: Some(member),
+                        });
+                    }
+                }
+            }
+            _ => warnings.push(ReflectWarning::MissingLocation {
+                spirv_id: variable.id,
+                name: module.name(variable.id),
+                member: None,
+            }),
+        }
+    }
+
+    let mut pending: Vec<u32> = module
+        .variables
+        .iter()
+        .filter(|variable| {
+            variable.storage_class == spirv_headers::StorageClass::Uniform as u32
+                || variable.storage_class == spirv_headers::StorageClass::StorageBuffer as u32
+                || variable.storage_class == spirv_headers::StorageClass::PushConstant as u32
+                || variable.storage_class
+                    == spirv_headers::StorageClass::ShaderRecordBufferNV as u32
+        })
+        .map(|variable| module.pointee_type_id(variable.type_id))
+        .collect();
+    let mut visited = std::collections::HashSet::new();
+    let mut missing_offsets = Vec::new();
+    while let Some(type_id) = pending.pop() {
+        let type_id = module.array_element_type_id(type_id);
+        if !visited.insert(type_id) {
+            continue;
+        }
+        let ty = match module.types.get(&type_id) {
+            Some(ty) if ty.is(spirv_headers::Op::TypeStruct) => ty,
+            _ => continue,
+        };
+        for (member, &member_type_id) in ty.operands.iter().enumerate() {
+            let member = member as u32;
+            if module
+                .member_decoration(type_id, member, spirv_headers::Decoration::Offset)
+                .is_none()
+            {
+                missing_offsets.push((type_id, member));
+            }
+            pending.push(member_type_id);
+        }
+    }
+    missing_offsets.sort_unstable();
+    warnings.extend(
+        missing_offsets
+            .into_iter()
+            .map(|(type_id, member)| ReflectWarning::MissingOffset { type_id, member }),
+    );
+    warnings
+}
+
+/// Warnings that can be found from the SPIR-V alone: the findings of
+/// `completeness_warnings`, repeated decorations, unnamed blocks,
+/// decorations on unknown ids and strings that are not UTF-8.
+pub(crate) fn collect_warnings(
+    code: &[u32],
+    module: &ParsedModule,
+) -> Result<Vec<ReflectWarning>, &'static str> {
+    let instructions = parse_instructions(code)?;
+    let bound = code.get(3).cloned().unwrap_or(0);
+
+    let mut warnings = completeness_warnings(module);
+
+    let mut duplicates = Vec::new();
+    let targets = module
+        .decorations
+        .iter()
+        .map(|(id, decorations)| (*id, None, decorations))
+        .chain(
+            module
+                .member_decorations
+                .iter()
+                .map(|((id, member), decorations)| (*id, Some(*member), decorations)),
+        );
+    for (target_id, member, decorations) in targets {
+        for (i, entry) in decorations.iter().enumerate() {
+            let is_first = !decorations[..i]
+                .iter()
+                .any(|other| other.decoration == entry.decoration);
+            if is_first
+                && decorations[i + 1..]
+                    .iter()
+                    .any(|other| other.decoration == entry.decoration)
+            {
+                duplicates.push((target_id, member, entry.decoration));
+            }
+        }
+    }
+    duplicates.sort_unstable();
+    warnings.extend(
+        duplicates
+            .into_iter()
+            .map(
+                |(target_id, member, decoration)| ReflectWarning::DuplicateDecoration {
+                    target_id,
+                    member,
+                    decoration,
+                },
+            ),
+    );
+
+    let mut block_ids: Vec<u32> = module
+        .decorations
+        .iter()
+        .filter(|(id, decorations)| {
+            module.types.contains_key(id)
+                && decorations.iter().any(|decoration| {
+                    decoration.decoration == spirv_headers::Decoration::Block as u32
+                        || decoration.decoration == spirv_headers::Decoration::BufferBlock as u32
+                })
+        })
+        .map(|(id, _)| *id)
+        .collect();
+    block_ids.sort_unstable();
+    // A module without any names had its debug info stripped on purpose, so
+    // an unnamed block says nothing about that block in particular.
+    if module.names.is_empty() {
+        block_ids.clear();
+    }
+    for type_id in block_ids {
+        if module.name(type_id).is_empty() {
+            warnings.push(ReflectWarning::UnnamedBlock { type_id });
+        }
+    }
+
+    // Without a grammar there is no telling result ids from literals, so an
+    // id counts as known if any non-debug, non-annotation instruction
+    // mentions it.
+    let mut known_ids = std::collections::HashSet::new();
+    for instruction in &instructions {
+        let is_debug_or_annotation = matches!(
+            spirv_headers::Op::from_u32(instruction.opcode),
+            Some(spirv_headers::Op::Name)
+                | Some(spirv_headers::Op::MemberName)
+                | Some(spirv_headers::Op::Decorate)
+                | Some(spirv_headers::Op::MemberDecorate)
+        );
+        if !is_debug_or_annotation {
+            known_ids.extend(instruction.operands.iter().cloned());
+        }
+    }
+    for instruction in &instructions {
+        let (target_id, decoration) = if instruction.is(spirv_headers::Op::Decorate) {
+            match instruction.operands {
+                [target_id, decoration, ..] => (*target_id, *decoration),
+                _ => continue,
+            }
+        } else if instruction.is(spirv_headers::Op::MemberDecorate) {
+            match instruction.operands {
+                [target_id, _, decoration, ..] => (*target_id, *decoration),
+                _ => continue,
+            }
+        } else {
+            continue;
+        };
+        if target_id >= bound || !known_ids.contains(&target_id) {
+            warnings.push(ReflectWarning::DecorationOnUnknownId {
+                target_id,
+                decoration,
+            });
+        }
+    }
+
+    for instruction in &instructions {
+        let operands = instruction.operands;
+        // Where the named id, the member index and the literal are.
+        let (target, member, literal) = match spirv_headers::Op::from_u32(instruction.opcode) {
+            Some(spirv_headers::Op::Name) | Some(spirv_headers::Op::String) => (0, None, 1),
+            Some(spirv_headers::Op::MemberName) => (0, Some(1), 2),
+            Some(spirv_headers::Op::DecorateString) => (0, None, 2),
+            Some(spirv_headers::Op::MemberDecorateString) => (0, Some(1), 3),
+            Some(spirv_headers::Op::EntryPoint) => (1, None, 2),
+            _ => continue,
+        };
+        if operands.len() <= literal {
+            continue;
+        }
+        let target_id = operands[target];
+        let member = member.map(|member| operands[member]);
+        let literal = &operands[literal..];
+        if let Ok((bytes, _)) = parse_string_bytes(literal) {
+            if std::str::from_utf8(&bytes).is_err() {
+                warnings.push(ReflectWarning::InvalidUtf8 {
+                    target_id,
+                    member,
+                    opcode: instruction.opcode,
+                });
+            }
+        }
+    }
+
+    warnings.extend(
+        module
+            .unresolved_forward_pointers()
+            .into_iter()
+            .map(|pointer_id| ReflectWarning::UnresolvedForwardPointer { pointer_id }),
+    );
+    Ok(warnings)
+}
+
+/// Finds the entry point called `name`, or the first one when `name` is `None`.
+pub(crate) fn find_entry_point<'a>(
+    entry_points: &'a [EntryPoint],
+    name: Option<&str>,
+) -> Result<&'a EntryPoint, &'static str> {
+    match name {
+        Some(name) => entry_points
+            .iter()
+            .find(|entry_point| entry_point.name == name),
+        None => entry_points.first(),
+    }
+    .ok_or("Entry point not found")
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Type {
+    pub(crate) opcode: u32,
+    /// Operands following the result id.
+    pub(crate) operands: Vec<u32>,
+}
+
+impl Type {
+    pub(crate) fn is(&self, op: spirv_headers::Op) -> bool {
+        self.opcode == op as u32
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Constant {
+    pub(crate) opcode: u32,
+    pub(crate) result_type_id: u32,
+    /// Operands following the result id.
+    pub(crate) operands: Vec<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct FunctionParameter {
+    pub(crate) id: u32,
+    pub(crate) type_id: u32,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct FunctionDeclaration {
+    pub(crate) id: u32,
+    pub(crate) result_type_id: u32,
+    pub(crate) control: u32,
+    pub(crate) function_type_id: u32,
+    /// Unset for functions imported through `LinkageAttributes`.
+    pub(crate) has_body: bool,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Variable {
+    pub(crate) id: u32,
+    pub(crate) type_id: u32,
+    pub(crate) storage_class: u32,
+    pub(crate) initializer: Option<u32>,
+}
+
+/// Module-level ids and decorations, gathered from everything preceding the
+/// first function.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ParsedModule {
+    pub(crate) version: u32,
+    pub(crate) entry_points: Vec<EntryPoint>,
+    /// `(entry point id, mode)` for every `OpExecutionMode` and `OpExecutionModeId`.
+    pub(crate) execution_modes: Vec<(u32, ReflectExecutionMode)>,
+    pub(crate) names: HashMap<u32, String>,
+    pub(crate) member_names: HashMap<(u32, u32), String>,
+    pub(crate) decorations: HashMap<u32, Vec<ReflectDecoration>>,
+    pub(crate) member_decorations: HashMap<(u32, u32), Vec<ReflectDecoration>>,
+    pub(crate) types: HashMap<u32, Type>,
+    /// Storage class of every `OpTypeForwardPointer`, by pointer id.
+    pub(crate) forward_pointers: HashMap<u32, u32>,
+    pub(crate) constants: HashMap<u32, Constant>,
+    pub(crate) variables: Vec<Variable>,
+    /// Parameters of every function, by function id.
+    pub(crate) function_parameters: HashMap<u32, Vec<FunctionParameter>>,
+    pub(crate) functions: Vec<FunctionDeclaration>,
+}
+
+impl ParsedModule {
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(level = "debug", skip_all, fields(words = code.len()))
+    )]
+    pub(crate) fn parse(code: &[u32]) -> Result<ParsedModule, &'static str> {
+        let phase = trace::Phase::start("instructions");
+        let instructions = parse_instructions(code)?;
+        phase.finish(instructions.len());
+        ParsedModule::from_instructions(code, &instructions)
+    }
+
+    /// Like `parse`, for callers that need the instructions of `code` too.
+    pub(crate) fn from_instructions(
+        code: &[u32],
+        instructions: &[Instruction],
+    ) -> Result<ParsedModule, &'static str> {
+        let phase = trace::Phase::start("functions");
+        let mut module = ParsedModule {
+            version: code[1],
+            entry_points: parse_entry_points(instructions)?,
+            function_parameters: parse_function_parameters(instructions)?,
+            functions: parse_function_declarations(instructions)?,
+            ..Default::default()
+        };
+        phase.finish(module.function_parameters.len());
+
+        let phase = trace::Phase::start("declarations");
+        let mut unknown_opcodes = Vec::new();
+        for instruction in instructions {
+            let op = match spirv_headers::Op::from_u32(instruction.opcode) {
+                Some(op) => op,
+                None => {
+                    unknown_opcodes.push(instruction.opcode);
+                    continue;
+                }
+            };
+            let operands = instruction.operands;
+            match op {
+                spirv_headers::Op::Function => break,
+                spirv_headers::Op::Name => {
+                    if operands.len() < 2 {
+                        return Err("Invalid OpName");
+                    }
+                    let (name, _) = parse_string(&operands[1..])?;
+                    module.names.insert(operands[0], name);
+                }
+                spirv_headers::Op::MemberName => {
+                    if operands.len() < 3 {
+                        return Err("Invalid OpMemberName");
+                    }
+                    let (name, _) = parse_string(&operands[2..])?;
+                    module.member_names.insert((operands[0], operands[1]), name);
+                }
+                spirv_headers::Op::Decorate
+                | spirv_headers::Op::DecorateId
+                | spirv_headers::Op::DecorateString => {
+                    if operands.len() < 2 {
+                        return Err("Invalid OpDecorate");
+                    }
+                    module
+                        .decorations
+                        .entry(operands[0])
+                        .or_default()
+                        .push(ReflectDecoration {
+                            decoration: operands[1],
+                            operands: operands[2..].to_vec(),
+                            operands_are_ids: op == spirv_headers::Op::DecorateId,
+                        });
+                }
+                spirv_headers::Op::MemberDecorate | spirv_headers::Op::MemberDecorateString => {
+                    if operands.len() < 3 {
+                        return Err("Invalid OpMemberDecorate");
+                    }
+                    module
+                        .member_decorations
+                        .entry((operands[0], operands[1]))
+                        .or_default()
+                        .push(ReflectDecoration {
+                            decoration: operands[2],
+                            operands: operands[3..].to_vec(),
+                            operands_are_ids: false,
+                        });
+                }
+                // Decorations on a group always precede the group's uses.
+                spirv_headers::Op::GroupDecorate => {
+                    if operands.is_empty() {
+                        return Err("Invalid OpGroupDecorate");
+                    }
+                    let group = module
+                        .decorations
+                        .get(&operands[0])
+                        .cloned()
+                        .unwrap_or_default();
+                    for &target in &operands[1..] {
+                        module
+                            .decorations
+                            .entry(target)
+                            .or_default()
+                            .extend(group.iter().cloned());
+                    }
+                }
+                spirv_headers::Op::GroupMemberDecorate => {
+                    if operands.len() % 2 == 0 {
+                        return Err("Invalid OpGroupMemberDecorate");
+                    }
+                    let group = module
+                        .decorations
+                        .get(&operands[0])
+                        .cloned()
+                        .unwrap_or_default();
+                    for target in operands[1..].chunks(2) {
+                        module
+                            .member_decorations
+                            .entry((target[0], target[1]))
+                            .or_default()
+                            .extend(group.iter().cloned());
+                    }
+                }
+                spirv_headers::Op::ExecutionMode | spirv_headers::Op::ExecutionModeId => {
+                    if operands.len() < 2 {
+                        return Err("Invalid OpExecutionMode");
+                    }
+                    module.execution_modes.push((
+                        operands[0],
+                        ReflectExecutionMode {
+                            mode: operands[1],
+                            operands: operands[2..].to_vec(),
+                            operands_are_ids: op == spirv_headers::Op::ExecutionModeId,
+                        },
+                    ));
+                }
+                spirv_headers::Op::Variable => {
+                    if operands.len() < 3 {
+                        return Err("Invalid OpVariable");
+                    }
+                    module.variables.push(Variable {
+                        type_id: operands[0],
+                        id: operands[1],
+                        storage_class: operands[2],
+                        initializer: operands.get(3).cloned(),
+                    });
+                }
+                spirv_headers::Op::TypeForwardPointer => {
+                    if operands.len() < 2 {
+                        return Err("Invalid OpTypeForwardPointer");
+                    }
+                    module.forward_pointers.insert(operands[0], operands[1]);
+                }
+                _ if is_type_op(op) => {
+                    if operands.is_empty() {
+                        return Err("Invalid type instruction");
+                    }
+                    module.types.insert(
+                        operands[0],
+                        Type {
+                            opcode: instruction.opcode,
+                            operands: operands[1..].to_vec(),
+                        },
+                    );
+                }
+                _ if is_constant_op(op) => {
+                    if operands.len() < 2 {
+                        return Err("Invalid constant instruction");
+                    }
+                    module.constants.insert(
+                        operands[1],
+                        Constant {
+                            opcode: instruction.opcode,
+                            result_type_id: operands[0],
+                            operands: operands[2..].to_vec(),
+                        },
+                    );
+                }
+                _ => {}
+            }
+        }
+        module.resolve_forward_pointers();
+        trace::unknown_opcodes(&unknown_opcodes);
+        phase.finish(module.types.len() + module.constants.len() + module.variables.len());
+        Ok(module)
+    }
+
+    /// Reconciles forward-declared pointers with their `OpTypePointer`. One
+    /// that is never defined becomes a pointer to an unknown type, id 0, so
+    /// that the types referring to it still see a pointer of its storage
+    /// class.
+    fn resolve_forward_pointers(&mut self) {
+        for (id, storage_class) in &self.forward_pointers {
+            self.types.entry(*id).or_insert_with(|| Type {
+                opcode: spirv_headers::Op::TypePointer as u32,
+                operands: vec![*storage_class, 0],
+            });
+        }
+    }
+
+    /// Forward-declared pointers without an `OpTypePointer` of the storage
+    /// class they were declared with.
+    pub(crate) fn unresolved_forward_pointers(&self) -> Vec<u32> {
+        let mut pointer_ids: Vec<u32> = self
+            .forward_pointers
+            .iter()
+            .filter(|(id, storage_class)| match self.types.get(id) {
+                Some(ty) if ty.is(spirv_headers::Op::TypePointer) && ty.operands.len() >= 2 => {
+                    ty.operands[1] == 0 || ty.operands[0] != **storage_class
+                }
+                _ => true,
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        pointer_ids.sort_unstable();
+        pointer_ids
+    }
+
+    pub(crate) fn find_entry_point(&self, name: Option<&str>) -> Result<&EntryPoint, &'static str> {
+        find_entry_point(&self.entry_points, name)
+    }
+
+    pub(crate) fn entry_point_execution_modes(
+        &self,
+        entry_point_id: u32,
+    ) -> Vec<ReflectExecutionMode> {
+        self.execution_modes
+            .iter()
+            .filter(|(id, _)| *id == entry_point_id)
+            .map(|(_, execution_mode)| execution_mode.clone())
+            .collect()
+    }
+
+    pub(crate) fn name(&self, id: u32) -> String {
+        self.names.get(&id).cloned().unwrap_or_default()
+    }
+
+    pub(crate) fn member_name(&self, id: u32, member: u32) -> String {
+        self.member_names
+            .get(&(id, member))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Signatures of every function, in declaration order, with parameter
+    /// types from the function's `OpTypeFunction`.
+    pub(crate) fn function_signatures(&self) -> Result<Vec<ReflectFunction>, &'static str> {
+        let linkage = self.linkage_symbols()?;
+        let no_parameters = Vec::new();
+        let mut functions = Vec::with_capacity(self.functions.len());
+        for declaration in &self.functions {
+            let parameter_type_ids = match self.types.get(&declaration.function_type_id) {
+                Some(ty) if ty.is(spirv_headers::Op::TypeFunction) && !ty.operands.is_empty() => {
+                    &ty.operands[1..]
+                }
+                _ => return Err("OpFunction without a function type"),
+            };
+            let parameters = self
+                .function_parameters
+                .get(&declaration.id)
+                .unwrap_or(&no_parameters);
+            let is_void = match self.types.get(&declaration.result_type_id) {
+                Some(ty) => ty.is(spirv_headers::Op::TypeVoid),
+                None => false,
+            };
+            functions.push(ReflectFunction {
+                spirv_id: declaration.id,
+                name: self.name(declaration.id),
+                return_type: if is_void {
+                    None
+                } else {
+                    Some(self.type_description(declaration.result_type_id, None))
+                },
+                parameters: parameter_type_ids
+                    .iter()
+                    .enumerate()
+                    .map(|(index, &type_id)| {
+                        let spirv_id = parameters.get(index).map_or(0, |parameter| parameter.id);
+                        ReflectFunctionParameter {
+                            spirv_id,
+                            name: self.name(spirv_id),
+                            type_description: self.type_description(type_id, None),
+                        }
+                    })
+                    .collect(),
+                control: declaration.control,
+                linkage: linkage
+                    .iter()
+                    .find(|symbol| symbol.spirv_id == declaration.id)
+                    .cloned(),
+                has_body: declaration.has_body,
+            });
+        }
+        Ok(functions)
+    }
+
+    /// Ids with `LinkageAttributes`, sorted by id. Unknown linkage types are
+    /// left out.
+    pub(crate) fn linkage_symbols(&self) -> Result<Vec<ReflectLinkageSymbol>, &'static str> {
+        let mut symbols = Vec::new();
+        for &id in self.decorations.keys() {
+            let operands = match self.decoration(id, spirv_headers::Decoration::LinkageAttributes) {
+                Some(operands) => operands,
+                None => continue,
+            };
+            let (name, words) = parse_string(operands)?;
+            let linkage_type = match operands.get(words).cloned() {
+                Some(0) => ReflectLinkageType::Export,
+                Some(1) => ReflectLinkageType::Import,
+                Some(2) => ReflectLinkageType::LinkOnceOdr,
+                _ => continue,
+            };
+            symbols.push(ReflectLinkageSymbol {
+                spirv_id: id,
+                name,
+                linkage_type,
+                is_function: !self.variables.iter().any(|variable| variable.id == id),
+            });
+        }
+        symbols.sort_by_key(|symbol| symbol.spirv_id);
+        Ok(symbols)
+    }
+
+    pub(crate) fn decoration(
+        &self,
+        id: u32,
+        decoration: spirv_headers::Decoration,
+    ) -> Option<&[u32]> {
+        find_decoration(self.decorations.get(&id), decoration)
+    }
+
+    /// Value of a decoration that also has an `OpDecorateId` form, such as
+    /// `Alignment` and `AlignmentId`. The id operand resolves through the
+    /// constant table, with specialization constants at their defaults.
+    pub(crate) fn decoration_value(
+        &self,
+        id: u32,
+        decoration: spirv_headers::Decoration,
+        id_decoration: spirv_headers::Decoration,
+    ) -> Option<u32> {
+        if let Some(value) = self
+            .decoration(id, decoration)
+            .and_then(|operands| operands.first().cloned())
+        {
+            return Some(value);
+        }
+        let operand = *self.decoration(id, id_decoration)?.first()?;
+        self.size_expr(operand).default_value()
+    }
+
+    pub(crate) fn member_decoration(
+        &self,
+        id: u32,
+        member: u32,
+        decoration: spirv_headers::Decoration,
+    ) -> Option<&[u32]> {
+        find_decoration(self.member_decorations.get(&(id, member)), decoration)
+    }
+
+    pub(crate) fn member_offset(&self, id: u32, member: u32) -> Option<u32> {
+        self.member_decoration(id, member, spirv_headers::Decoration::Offset)
+            .and_then(|operands| operands.first().cloned())
+    }
+
+    pub(crate) fn member_matrix_stride(&self, id: u32, member: u32) -> u32 {
+        self.member_decoration(id, member, spirv_headers::Decoration::MatrixStride)
+            .and_then(|operands| operands.first().cloned())
+            .unwrap_or(0)
+    }
+
+    /// Whether a variable, or any member of the struct it holds, carries `decoration`.
+    pub(crate) fn variable_has_decoration(
+        &self,
+        variable_id: u32,
+        decoration: spirv_headers::Decoration,
+    ) -> bool {
+        if self.decoration(variable_id, decoration).is_some() {
+            return true;
+        }
+        let type_id = match self
+            .variables
+            .iter()
+            .find(|variable| variable.id == variable_id)
+        {
+            Some(variable) => self.array_element_type_id(self.pointee_type_id(variable.type_id)),
+            None => return false,
+        };
+        match self.types.get(&type_id) {
+            Some(ty) if ty.is(spirv_headers::Op::TypeStruct) => {
+                (0..ty.operands.len() as u32).any(|member| {
+                    self.member_decoration(type_id, member, decoration)
+                        .is_some()
+                })
+            }
+            _ => false,
+        }
+    }
+
+    /// Raw `BuiltIn` decoration of an id, including values unknown to `spirv_headers`.
+    pub(crate) fn built_in(&self, id: u32) -> Option<u32> {
+        self.decoration(id, spirv_headers::Decoration::BuiltIn)
+            .and_then(|operands| operands.first().cloned())
+    }
+
+    /// Resolves a constant id through the constant table. Nesting deeper than
+    /// `CONSTANT_NESTING_LIMIT`, and constituents past `CONSTANT_VISIT_LIMIT`,
+    /// are reported as `Unknown`.
+    pub(crate) fn constant(&self, id: u32) -> ReflectConstant {
+        let mut visits_left = CONSTANT_VISIT_LIMIT;
+        self.constant_at_depth(id, 0, &mut visits_left)
+    }
+
+    fn constant_at_depth(&self, id: u32, depth: u32, visits_left: &mut u32) -> ReflectConstant {
+        use spirv_headers::Op;
+        let constant = match self.constants.get(&id) {
+            Some(constant) if *visits_left > 0 => constant,
+            _ => {
+                return ReflectConstant {
+                    spirv_id: id,
+                    is_specialization: false,
+                    value: ReflectConstantValue::Unknown,
+                }
+            }
+        };
+        *visits_left -= 1;
+        let op = Op::from_u32(constant.opcode);
+        let value = match op {
+            Some(Op::ConstantTrue) | Some(Op::SpecConstantTrue) => ReflectConstantValue::Bool(true),
+            Some(Op::ConstantFalse) | Some(Op::SpecConstantFalse) => {
+                ReflectConstantValue::Bool(false)
+            }
+            Some(Op::Constant) | Some(Op::SpecConstant) => {
+                ReflectConstantValue::Scalar(constant.operands.clone())
+            }
+            Some(Op::ConstantNull) => ReflectConstantValue::Null,
+            Some(Op::ConstantComposite) | Some(Op::SpecConstantComposite)
+                if depth < CONSTANT_NESTING_LIMIT =>
+            {
+                ReflectConstantValue::Composite(
+                    constant
+                        .operands
+                        .iter()
+                        .map(|constituent| {
+                            self.constant_at_depth(*constituent, depth + 1, visits_left)
+                        })
+                        .collect(),
+                )
+            }
+            _ => ReflectConstantValue::Unknown,
+        };
+        ReflectConstant {
+            spirv_id: id,
+            is_specialization: matches!(
+                op,
+                Some(Op::SpecConstantTrue)
+                    | Some(Op::SpecConstantFalse)
+                    | Some(Op::SpecConstant)
+                    | Some(Op::SpecConstantComposite)
+                    | Some(Op::SpecConstantOp)
+            ),
+            value,
+        }
+    }
+
+    /// Symbolic value of an integer constant, keeping specialization
+    /// constants and `OpSpecConstantOp` trees instead of their defaults.
+    pub(crate) fn size_expr(&self, id: u32) -> ReflectSizeExpr {
+        let mut visits_left = CONSTANT_VISIT_LIMIT;
+        self.size_expr_at_depth(id, 0, &mut visits_left)
+    }
+
+    fn size_expr_at_depth(&self, id: u32, depth: u32, visits_left: &mut u32) -> ReflectSizeExpr {
+        use spirv_headers::Op;
+        let constant = match self.constants.get(&id) {
+            Some(constant) if *visits_left > 0 => constant,
+            _ => return ReflectSizeExpr::Unknown,
+        };
+        *visits_left -= 1;
+        match Op::from_u32(constant.opcode) {
+            Some(Op::Constant) => match self.integer_value(constant) {
+                Some(value) => ReflectSizeExpr::Constant(value),
+                None => ReflectSizeExpr::Unknown,
+            },
+            Some(Op::ConstantNull) if self.is_integer_type(constant.result_type_id) => {
+                ReflectSizeExpr::Constant(0)
+            }
+            Some(Op::SpecConstant) => match self.integer_value(constant) {
+                Some(default) => match self
+                    .decoration(id, spirv_headers::Decoration::SpecId)
+                    .and_then(|operands| operands.first().cloned())
+                {
+                    Some(spec_id) => ReflectSizeExpr::SpecConstant { spec_id, default },
+                    // Without a `SpecId` the value cannot be overridden.
+                    None => ReflectSizeExpr::Constant(default),
+                },
+                None => ReflectSizeExpr::Unknown,
+            },
+            Some(Op::SpecConstantOp) if depth < CONSTANT_NESTING_LIMIT => {
+                match constant.operands.split_first() {
+                    Some((opcode, operands)) => ReflectSizeExpr::Op {
+                        opcode: *opcode,
+                        operands: operands
+                            .iter()
+                            .map(|operand| {
+                                self.size_expr_at_depth(*operand, depth + 1, visits_left)
+                            })
+                            .collect(),
+                    },
+                    None => ReflectSizeExpr::Unknown,
+                }
+            }
+            // Composites, floats, booleans and the like are not sizes.
+            _ => ReflectSizeExpr::Unknown,
+        }
+    }
+
+    fn is_integer_type(&self, type_id: u32) -> bool {
+        self.types
+            .get(&type_id)
+            .is_some_and(|ty| ty.is(spirv_headers::Op::TypeInt))
+    }
+
+    /// Value of an `OpConstant` or `OpSpecConstant` of integer type, if it
+    /// fits in 32 bits.
+    fn integer_value(&self, constant: &Constant) -> Option<u32> {
+        if !self.is_integer_type(constant.result_type_id) {
+            return None;
+        }
+        match constant.operands.split_first() {
+            Some((low, high)) if high.iter().all(|word| *word == 0) => Some(*low),
+            _ => None,
+        }
+    }
+
+    /// `WorkgroupSize` of an entry point: the constant decorated with the
+    /// `WorkgroupSize` built-in if any, since it overrides the execution
+    /// modes, else `LocalSizeId` or `LocalSize`.
+    pub(crate) fn workgroup_size(&self, entry_point: &EntryPoint) -> Option<[ReflectSizeExpr; 3]> {
+        use spirv_headers::ExecutionMode;
+        let built_in = self.constants.iter().find(|(id, _)| {
+            self.built_in(**id) == Some(spirv_headers::BuiltIn::WorkgroupSize as u32)
+        });
+        if let Some((_, constant)) = built_in {
+            if constant.operands.len() == 3 {
+                let size = |index: usize| self.size_expr(constant.operands[index]);
+                return Some([size(0), size(1), size(2)]);
+            }
+        }
+        let execution_modes = self.entry_point_execution_modes(entry_point.id);
+        let execution_mode = execution_modes.iter().find(|execution_mode| {
+            execution_mode.is(ExecutionMode::LocalSize)
+                || execution_mode.is(ExecutionMode::LocalSizeId)
+        })?;
+        if execution_mode.operands.len() != 3 {
+            return None;
+        }
+        let size = |index: usize| {
+            let operand = execution_mode.operands[index];
+            if execution_mode.operands_are_ids {
+                self.size_expr(operand)
+            } else {
+                ReflectSizeExpr::Constant(operand)
+            }
+        };
+        Some([size(0), size(1), size(2)])
+    }
+
+    /// Symbolic counterpart of `array_traits(type_id).dims`.
+    pub(crate) fn array_size_exprs(&self, type_id: u32) -> Vec<ReflectSizeExpr> {
+        let mut dims = Vec::new();
+        let mut current = type_id;
+        for _ in 0..CONSTANT_NESTING_LIMIT {
+            let ty = match self.types.get(&current) {
+                Some(ty) => ty,
+                None => break,
+            };
+            if !ty.is(spirv_headers::Op::TypeArray) || ty.operands.len() < 2 {
+                break;
+            }
+            dims.push(self.size_expr(ty.operands[1]));
+            current = ty.operands[0];
+        }
+        dims
+    }
+
+    pub(crate) fn kernel_arguments(&self, entry_point: &EntryPoint) -> Vec<ReflectKernelArgument> {
+        let parameters = match self.function_parameters.get(&entry_point.id) {
+            Some(parameters) => parameters,
+            None => return Vec::new(),
+        };
+        parameters
+            .iter()
+            .enumerate()
+            .map(|(index, parameter)| {
+                let (id, type_id) = (parameter.id, parameter.type_id);
+                let type_description = self.type_description(type_id, None);
+                let is_pointer = matches!(
+                    self.types.get(&type_id),
+                    Some(ty) if ty.is(spirv_headers::Op::TypePointer)
+                );
+                let address_qualifier = match type_description.storage_class {
+                    _ if !is_pointer => ReflectKernelAddressQualifier::Private,
+                    ReflectStorageClass::CrossWorkGroup => ReflectKernelAddressQualifier::Global,
+                    ReflectStorageClass::UniformConstant => ReflectKernelAddressQualifier::Constant,
+                    ReflectStorageClass::WorkGroup => ReflectKernelAddressQualifier::Local,
+                    ReflectStorageClass::Generic => ReflectKernelAddressQualifier::Generic,
+                    _ => ReflectKernelAddressQualifier::Private,
+                };
+
+                let decorations = self.decorations.get(&id);
+                let function_parameter_attributes: Vec<u32> = decorations
+                    .map(|decorations| {
+                        decorations
+                            .iter()
+                            .filter(|decoration| {
+                                decoration.decoration
+                                    == spirv_headers::Decoration::FuncParamAttr as u32
+                            })
+                            .filter_map(|decoration| decoration.operands.first().cloned())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let mut type_qualifiers = ReflectKernelTypeQualifierFlags::NONE;
+                if function_parameter_attributes
+                    .contains(&(spirv_headers::FunctionParameterAttribute::NoWrite as u32))
+                {
+                    type_qualifiers |= ReflectKernelTypeQualifierFlags::CONST;
+                }
+                if function_parameter_attributes
+                    .contains(&(spirv_headers::FunctionParameterAttribute::NoAlias as u32))
+                    || find_decoration(decorations, spirv_headers::Decoration::Restrict).is_some()
+                {
+                    type_qualifiers |= ReflectKernelTypeQualifierFlags::RESTRICT;
+                }
+                if find_decoration(decorations, spirv_headers::Decoration::Volatile).is_some() {
+                    type_qualifiers |= ReflectKernelTypeQualifierFlags::VOLATILE;
+                }
+
+                ReflectKernelArgument {
+                    index: index as u32,
+                    spirv_id: id,
+                    name: self.name(id),
+                    type_description,
+                    address_qualifier,
+                    access_qualifier: self.access_qualifier(self.pointee_type_id(type_id)),
+                    type_qualifiers,
+                    function_parameter_attributes,
+                    alignment: self.decoration_value(
+                        id,
+                        spirv_headers::Decoration::Alignment,
+                        spirv_headers::Decoration::AlignmentId,
+                    ),
+                    max_byte_offset: self.decoration_value(
+                        id,
+                        spirv_headers::Decoration::MaxByteOffset,
+                        spirv_headers::Decoration::MaxByteOffsetId,
+                    ),
+                }
+            })
+            .collect()
+    }
+
+    /// Access qualifier operand of an `OpTypeImage` or `OpTypePipe`.
+    fn access_qualifier(&self, type_id: u32) -> Option<ReflectAccessQualifier> {
+        let ty = self.types.get(&type_id)?;
+        let access = if ty.is(spirv_headers::Op::TypeImage) {
+            ty.operands.get(7)
+        } else if ty.is(spirv_headers::Op::TypePipe) {
+            ty.operands.first()
+        } else {
+            None
+        };
+        match spirv_headers::AccessQualifier::from_u32(*access?)? {
+            spirv_headers::AccessQualifier::ReadOnly => Some(ReflectAccessQualifier::ReadOnly),
+            spirv_headers::AccessQualifier::WriteOnly => Some(ReflectAccessQualifier::WriteOnly),
+            spirv_headers::AccessQualifier::ReadWrite => Some(ReflectAccessQualifier::ReadWrite),
+        }
+    }
+
+    /// Type pointed to by an `OpTypePointer`, or the type itself otherwise.
+    pub(crate) fn pointee_type_id(&self, type_id: u32) -> u32 {
+        match self.types.get(&type_id) {
+            Some(ty) if ty.is(spirv_headers::Op::TypePointer) && ty.operands.len() >= 2 => {
+                ty.operands[1]
+            }
+            _ => type_id,
+        }
+    }
+
+    /// Traits of `type_id` if it is a physical storage buffer pointer, which
+    /// type descriptions do not look through.
+    pub(crate) fn physical_pointer(&self, type_id: u32) -> Option<ReflectPointerTraits> {
+        match self.types.get(&type_id) {
+            Some(ty)
+                if ty.is(spirv_headers::Op::TypePointer)
+                    && ty.operands.len() >= 2
+                    && ty.operands[0]
+                        == spirv_headers::StorageClass::PhysicalStorageBuffer as u32 =>
+            {
+                Some(ReflectPointerTraits {
+                    storage_class: ReflectStorageClass::PhysicalStorageBuffer,
+                    pointee_type_id: ty.operands[1],
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Global variables of `storage_class` visible to the entry point. Before
+    /// SPIR-V 1.4 only inputs and outputs are listed on `OpEntryPoint`, so all
+    /// module variables of other storage classes are returned.
+    pub(crate) fn entry_point_variables(
+        &self,
+        entry_point: &EntryPoint,
+        storage_class: u32,
+    ) -> Vec<&Variable> {
+        let lists_all_globals = self.version >= 0x0001_0400
+            || storage_class == spirv_headers::StorageClass::Input as u32
+            || storage_class == spirv_headers::StorageClass::Output as u32;
+        self.variables
+            .iter()
+            .filter(|variable| variable.storage_class == storage_class)
+            .filter(|variable| !lists_all_globals || entry_point.interface.contains(&variable.id))
+            .collect()
+    }
+
+    /// Whether interface variables of `storage_class` carry an outer
+    /// per-vertex or per-primitive array that does not consume locations.
+    pub(crate) fn is_arrayed_interface(
+        &self,
+        execution_model: u32,
+        storage_class: u32,
+        variable_id: u32,
+    ) -> bool {
+        use spirv_headers::ExecutionModel;
+        if self
+            .decoration(variable_id, spirv_headers::Decoration::Patch)
+            .is_some()
+        {
+            return false;
+        }
+        let input = storage_class == spirv_headers::StorageClass::Input as u32;
+        match ExecutionModel::from_u32(execution_model) {
+            Some(ExecutionModel::Geometry) | Some(ExecutionModel::TessellationEvaluation) => input,
+            Some(ExecutionModel::TessellationControl) => true,
+            Some(ExecutionModel::MeshNV) => !input,
+            Some(ExecutionModel::Fragment) => {
+                input
+                    && self
+                        .decoration(variable_id, spirv_headers::Decoration::PerVertexNV)
+                        .is_some()
+            }
+            _ => execution_model == EXECUTION_MODEL_MESH_EXT && !input,
+        }
+    }
+
+    /// Inputs or outputs of an entry point flattened to locations, sorted by
+    /// location and component. Built-ins are left out.
+    pub(crate) fn interface_locations(
+        &self,
+        entry_point: &EntryPoint,
+        storage_class: u32,
+    ) -> Vec<ReflectInterfaceLocation> {
+        let mut locations = Vec::new();
+        for variable in self.entry_point_variables(entry_point, storage_class) {
+            if self.built_in(variable.id).is_some() {
+                continue;
+            }
+            let mut type_id = self.pointee_type_id(variable.type_id);
+            if self.is_arrayed_interface(entry_point.execution_model, storage_class, variable.id) {
+                match self.types.get(&type_id) {
+                    Some(ty)
+                        if (ty.is(spirv_headers::Op::TypeArray)
+                            || ty.is(spirv_headers::Op::TypeRuntimeArray))
+                            && !ty.operands.is_empty() =>
+                    {
+                        type_id = ty.operands[0]
+                    }
+                    _ => {}
+                }
+            }
+            let mut cursor = self
+                .decoration(variable.id, spirv_headers::Decoration::Location)
+                .and_then(|operands| operands.first().cloned());
+            let component = self
+                .decoration(variable.id, spirv_headers::Decoration::Component)
+                .and_then(|operands| operands.first().cloned())
+                .unwrap_or(0);
+            let mut name = self.name(variable.id);
+            if name.is_empty() {
+                name = format!("_id{}", variable.id);
+            }
+            let mut flattener = LocationFlattener {
+                module: self,
+                spirv_id: variable.id,
+                member_path: Vec::new(),
+                locations: &mut locations,
+                visits_left: INTERFACE_TYPE_VISIT_LIMIT,
+            };
+            flattener.flatten(type_id, &mut cursor, component, name, 0);
+        }
+        locations.sort_by_key(|location| (location.location, location.component));
+        locations
+    }
+
+    pub(crate) fn decoration_flags(
+        &self,
+        decorations: Option<&Vec<ReflectDecoration>>,
+    ) -> ReflectDecorationFlags {
+        let mut flags = ReflectDecorationFlags::NONE;
+        for decoration in decorations.into_iter().flatten() {
+            flags |= match spirv_headers::Decoration::from_u32(decoration.decoration) {
+                Some(spirv_headers::Decoration::Block) => ReflectDecorationFlags::BLOCK,
+                Some(spirv_headers::Decoration::BufferBlock) => {
+                    ReflectDecorationFlags::BUFFER_BLOCK
+                }
+                Some(spirv_headers::Decoration::RowMajor) => ReflectDecorationFlags::ROW_MAJOR,
+                Some(spirv_headers::Decoration::ColMajor) => ReflectDecorationFlags::COLUMN_MAJOR,
+                Some(spirv_headers::Decoration::BuiltIn) => ReflectDecorationFlags::BUILT_IN,
+                Some(spirv_headers::Decoration::NoPerspective) => {
+                    ReflectDecorationFlags::NO_PERSPECTIVE
+                }
+                Some(spirv_headers::Decoration::Flat) => ReflectDecorationFlags::FLAT,
+                Some(spirv_headers::Decoration::NonWritable) => {
+                    ReflectDecorationFlags::NON_WRITABLE
+                }
+                Some(spirv_headers::Decoration::Sample) => ReflectDecorationFlags::SAMPLE,
+                _ => ReflectDecorationFlags::NONE,
+            };
+        }
+        flags
+    }
+
+    /// Adds the decorations the C library does not reflect to an interface
+    /// variable and its members.
+    pub(crate) fn annotate_interface_variable(&self, variable: &mut ReflectInterfaceVariable) {
+        let flags = self.decoration_flags(self.decorations.get(&variable.spirv_id));
+        variable.decoration_flags |= flags & ReflectDecorationFlags::SAMPLE;
+        variable.index = self
+            .decoration(variable.spirv_id, spirv_headers::Decoration::Index)
+            .and_then(|operands| operands.first().cloned())
+            .unwrap_or(0);
+        variable.component = self
+            .decoration(variable.spirv_id, spirv_headers::Decoration::Component)
+            .and_then(|operands| operands.first().cloned())
+            .unwrap_or(0);
+        variable.stream = self
+            .decoration(variable.spirv_id, spirv_headers::Decoration::Stream)
+            .and_then(|operands| operands.first().cloned())
+            .unwrap_or(0);
+        self.annotate_interface_members(variable, 0);
+    }
+
+    fn annotate_interface_members(&self, variable: &mut ReflectInterfaceVariable, depth: u32) {
+        let struct_id = match variable.type_description {
+            Some(ref type_description) if depth < CONSTANT_NESTING_LIMIT => {
+                self.array_element_type_id(type_description.id)
+            }
+            _ => return,
+        };
+        let mut location = variable.location;
+        for (index, member) in variable.members.iter_mut().enumerate() {
+            let decorations = self.member_decorations.get(&(struct_id, index as u32));
+            if let Some(&member_location) =
+                find_decoration(decorations, spirv_headers::Decoration::Location)
+                    .and_then(|operands| operands.first())
+            {
+                location = member_location;
+            }
+            member.location = location;
+            location = location.saturating_add(member.location_count());
+            member.decoration_flags |=
+                self.decoration_flags(decorations) & ReflectDecorationFlags::SAMPLE;
+            member.component = find_decoration(decorations, spirv_headers::Decoration::Component)
+                .and_then(|operands| operands.first().cloned())
+                .unwrap_or(0);
+            member.stream = find_decoration(decorations, spirv_headers::Decoration::Stream)
+                .and_then(|operands| operands.first().cloned())
+                .unwrap_or(variable.stream);
+            self.annotate_interface_members(member, depth + 1);
+        }
+    }
+
+    /// Whether the struct behind `type_id` carries `Offset` decorations.
+    pub(crate) fn has_explicit_layout(&self, type_id: u32) -> bool {
+        let type_id = self.array_element_type_id(self.pointee_type_id(type_id));
+        self.member_decorations
+            .iter()
+            .any(|(&(id, _), decorations)| {
+                id == type_id
+                    && find_decoration(Some(decorations), spirv_headers::Decoration::Offset)
+                        .is_some()
+            })
+    }
+
+    /// Builds a block description for a struct type the same way the C
+    /// library does for uniform and storage buffers.
+    pub(crate) fn block_variable(
+        &self,
+        spirv_id: u32,
+        name: String,
+        type_id: u32,
+    ) -> ReflectBlockVariable {
+        let type_id = self.pointee_type_id(type_id);
+        let mut block = self.block_member(type_id, name, 0, 0, None);
+        block.spirv_id = spirv_id;
+        block.synthesize_name(type_id);
+        block.decoration_flags |= self.decoration_flags(self.decorations.get(&spirv_id));
+        block.padded_size = round_up(block.size, SPIRV_DATA_ALIGNMENT);
+        block
+    }
+
+    /// Makes the block of a binding declared as an array of blocks, such as
+    /// `ubo[4]`, `ubo[2][3]` or `ubo[]`, describe one element: the array is
+    /// one of descriptors, each bound to a buffer holding one block, not an
+    /// array within the buffer.
+    pub(crate) fn annotate_block_element(&self, block: &mut ReflectBlockVariable, type_id: u32) {
+        let element_type_id = self.array_element_type_id(self.pointee_type_id(type_id));
+        let element = self.block_member(element_type_id, String::new(), 0, 0, None);
+        block.array = ReflectArrayTraits::default();
+        block.size = element.size;
+        block.padded_size = round_up(element.size, SPIRV_DATA_ALIGNMENT);
+        if let Some(ref mut type_description) = block.type_description {
+            type_description.traits.array = ReflectArrayTraits::default();
+        }
+    }
+
+    fn block_member(
+        &self,
+        type_id: u32,
+        name: String,
+        offset: u32,
+        absolute_offset: u32,
+        member_of: Option<(u32, u32)>,
+    ) -> ReflectBlockVariable {
+        let decoration_flags = match member_of {
+            Some(key) => self.decoration_flags(self.member_decorations.get(&key)),
+            None => ReflectDecorationFlags::NONE,
+        };
+        let matrix_stride =
+            member_of.map_or(0, |(id, member)| self.member_matrix_stride(id, member));
+        let array = self.array_traits(type_id);
+        let element_type_id = self.array_element_type_id(type_id);
+        let numeric = self.numeric_traits(element_type_id, matrix_stride);
+
+        let mut members = Vec::new();
+        let mut size = 0;
+        if let Some(ty) = self.types.get(&element_type_id) {
+            if ty.is(spirv_headers::Op::TypeStruct) {
+                for (index, member_type_id) in ty.operands.iter().enumerate() {
+                    let index = index as u32;
+                    let member_offset = self.member_offset(element_type_id, index).unwrap_or(0);
+                    members.push(self.block_member(
+                        *member_type_id,
+                        self.member_name(element_type_id, index),
+                        member_offset,
+                        absolute_offset.saturating_add(member_offset),
+                        Some((element_type_id, index)),
+                    ));
+                }
+                let member_count = members.len();
+                for index in 0..member_count {
+                    let padded_size = if index + 1 < member_count {
+                        members[index + 1]
+                            .offset
+                            .saturating_sub(members[index].offset)
+                    } else {
+                        round_up(
+                            members[index].offset.saturating_add(members[index].size),
+                            SPIRV_DATA_ALIGNMENT,
+                        ) - members[index].offset
+                    };
+                    let member = &mut members[index];
+                    member.padded_size = if member.size == 0 { 0 } else { padded_size };
+                    member.size = member.size.min(member.padded_size);
+                }
+                // A trailing runtime array doesn't count towards the block size.
+                size = match ty.operands.last() {
+                    Some(last_type_id) if self.is_runtime_array(*last_type_id) => {
+                        members.last().map(|member| member.offset).unwrap_or(0)
+                    }
+                    _ => members
+                        .last()
+                        .map(|member| member.offset.saturating_add(member.padded_size))
+                        .unwrap_or(0),
+                };
+            } else if ty.is(spirv_headers::Op::TypeMatrix) {
+                let vector_count = if decoration_flags.contains(ReflectDecorationFlags::ROW_MAJOR) {
+                    numeric.matrix.row_count
+                } else {
+                    numeric.matrix.column_count
+                };
+                size = vector_count.checked_mul(numeric.matrix.stride).unwrap_or(0);
+            } else {
+                size = (numeric.scalar.width / 8)
+                    .checked_mul(numeric.vector.component_count.max(1))
+                    .unwrap_or(0);
+            }
+        }
+        if array.runtime {
+            size = 0;
+        } else if !array.dims.is_empty() {
+            size = array.size();
+        }
+
+        let mut block = ReflectBlockVariable {
+            spirv_id: 0,
+            name,
+            offset,
+            absolute_offset,
+            size,
+            padded_size: size,
+            decoration_flags,
+            numeric,
+            array,
+            members,
+            type_description: Some(self.type_description(type_id, member_of)),
+            name_synthesized: false,
+        };
+        block.synthesize_member_names(element_type_id);
+        block
+    }
+
+    pub(crate) fn is_runtime_array(&self, type_id: u32) -> bool {
+        match self.types.get(&type_id) {
+            Some(ty) => ty.is(spirv_headers::Op::TypeRuntimeArray),
+            None => false,
+        }
+    }
+
+    /// Strips any array types wrapped around `type_id`.
+    pub(crate) fn array_element_type_id(&self, mut type_id: u32) -> u32 {
+        for _ in 0..CONSTANT_NESTING_LIMIT {
+            let ty = match self.types.get(&type_id) {
+                Some(ty) => ty,
+                None => break,
+            };
+            if (ty.is(spirv_headers::Op::TypeArray) || ty.is(spirv_headers::Op::TypeRuntimeArray))
+                && !ty.operands.is_empty()
+            {
+                type_id = ty.operands[0];
+            } else {
+                break;
+            }
+        }
+        type_id
+    }
+
+    /// Array dimensions from outermost to innermost. Like in the C library,
+    /// runtime arrays are not listed in `dims`.
+    pub(crate) fn array_traits(&self, type_id: u32) -> ReflectArrayTraits {
+        let mut traits = ReflectArrayTraits::default();
+        let mut current = type_id;
+        for _ in 0..CONSTANT_NESTING_LIMIT {
+            let ty = match self.types.get(&current) {
+                Some(ty) => ty,
+                None => break,
+            };
+            if ty.operands.is_empty() {
+                break;
+            }
+            let stride = self
+                .decoration(current, spirv_headers::Decoration::ArrayStride)
+                .and_then(|operands| operands.first().cloned())
+                .unwrap_or(0);
+            if ty.is(spirv_headers::Op::TypeArray) && ty.operands.len() >= 2 {
+                let expr = self.size_expr(ty.operands[1]);
+                // Evaluates `OpSpecConstantOp` lengths with the defaults, and
+                // gives 0 for lengths that are not integer constants.
+                traits.dims.push(expr.default_value().unwrap_or(0));
+                traits.strides.push(stride);
+                traits.dim_exprs.push(expr);
+            } else if ty.is(spirv_headers::Op::TypeRuntimeArray) && current == type_id {
+                traits.runtime = true;
+                traits.stride = stride;
+            } else {
+                break;
+            }
+            current = ty.operands[0];
+        }
+        if !traits.runtime {
+            traits.stride = traits.strides.first().cloned().unwrap_or(0);
+        }
+        traits
+    }
+
+    /// Replaces the array traits of the members of `block` and of every
+    /// type description in it with the ones read from the module, which
+    /// carry the stride of each dimension and the specialization constants
+    /// behind their lengths. The array of `block` itself is left alone.
+    /// Member sizes follow `block_variable`: a trailing runtime array takes
+    /// none and a matrix takes `MatrixStride` times its columns, or rows if
+    /// row-major.
+    pub(crate) fn annotate_block_layout(&self, block: &mut ReflectBlockVariable) {
+        self.annotate_block_layout_at_depth(block, 0);
+    }
+
+    fn annotate_block_layout_at_depth(&self, block: &mut ReflectBlockVariable, depth: u32) {
+        if depth >= CONSTANT_NESTING_LIMIT {
+            return;
+        }
+        if let Some(ref mut type_description) = block.type_description {
+            self.annotate_type_layout_at_depth(type_description, depth);
+        }
+        for member in &mut block.members {
+            if let Some(type_id) = member
+                .type_description
+                .as_ref()
+                .filter(|type_description| {
+                    type_description
+                        .type_flags
+                        .contains(ReflectTypeFlags::ARRAY)
+                })
+                .map(|type_description| type_description.id)
+            {
+                member.array = self.array_traits(type_id);
+                if member.array.runtime {
+                    member.size = 0;
+                    member.padded_size = 0;
+                } else if !member.array.dims.is_empty() && !member.array.strides.is_empty() {
+                    member.size = member.array.size();
+                }
+            } else if let (Some((vector_count, _)), Some(stride)) =
+                (member.matrix_vectors(), member.matrix_stride())
+            {
+                if stride != 0 {
+                    member.size = vector_count.checked_mul(stride).unwrap_or(0);
+                }
+            }
+            self.annotate_block_layout_at_depth(member, depth + 1);
+        }
+        self.annotate_pointer_members(block);
+        // Like `block_variable`, the size stops where the runtime array starts.
+        if let Some(offset) = block.runtime_array().map(|member| member.offset) {
+            block.size = offset;
+        }
+    }
+
+    /// Describes the physical storage buffer pointers among the members of
+    /// `block` as 64-bit device addresses rather than as their pointees.
+    fn annotate_pointer_members(&self, block: &mut ReflectBlockVariable) {
+        let struct_id = match block.type_description {
+            Some(ref type_description) => self.array_element_type_id(type_description.id),
+            None => return,
+        };
+        let member_type_ids = match self.types.get(&struct_id) {
+            Some(ty) if ty.is(spirv_headers::Op::TypeStruct) => ty.operands.clone(),
+            _ => return,
+        };
+        for (index, member) in block.members.iter_mut().enumerate() {
+            let member_type_id = member_type_ids.get(index).cloned().unwrap_or(0);
+            if self
+                .physical_pointer(self.array_element_type_id(member_type_id))
+                .is_none()
+            {
+                continue;
+            }
+            let type_description =
+                self.type_description(member_type_id, Some((struct_id, index as u32)));
+            member.numeric = type_description.traits.numeric;
+            member.members.clear();
+            if member.array.dims.is_empty() && !member.array.runtime {
+                member.size = 8;
+            }
+            member.type_description = Some(type_description);
+        }
+    }
+
+    /// Like `annotate_block_layout`, for the arrays of a type description
+    /// and its members, together with the `Offset` and `MatrixStride` of the
+    /// members.
+    pub(crate) fn annotate_type_layout(&self, type_description: &mut ReflectTypeDescription) {
+        self.annotate_type_layout_at_depth(type_description, 0);
+    }
+
+    fn annotate_type_layout_at_depth(
+        &self,
+        type_description: &mut ReflectTypeDescription,
+        depth: u32,
+    ) {
+        if depth >= CONSTANT_NESTING_LIMIT {
+            return;
+        }
+        if type_description
+            .type_flags
+            .contains(ReflectTypeFlags::ARRAY)
+        {
+            type_description.traits.array = self.array_traits(type_description.id);
+        }
+        let struct_id = self.array_element_type_id(type_description.id);
+        let member_type_ids = match self.types.get(&struct_id) {
+            Some(ty) if ty.is(spirv_headers::Op::TypeStruct) => ty.operands.as_slice(),
+            _ => &[],
+        };
+        for (index, member) in type_description.members.iter_mut().enumerate() {
+            let member_type_id = member_type_ids.get(index).cloned().unwrap_or(0);
+            let index = index as u32;
+            if self
+                .physical_pointer(self.array_element_type_id(member_type_id))
+                .is_some()
+            {
+                *member = self.type_description(member_type_id, Some((struct_id, index)));
+                continue;
+            }
+            member.offset = self.member_offset(struct_id, index);
+            let matrix_stride = self.member_matrix_stride(struct_id, index);
+            if matrix_stride != 0 {
+                member.traits.numeric.matrix.stride = matrix_stride;
+            }
+            self.annotate_type_layout_at_depth(member, depth + 1);
+        }
+    }
+
+    pub(crate) fn numeric_traits(&self, type_id: u32, matrix_stride: u32) -> ReflectNumericTraits {
+        let mut traits = ReflectNumericTraits::default();
+        let mut current = type_id;
+        // A matrix, its column vector and their scalar type; going further
+        // means the types of a malformed module refer to themselves.
+        for _ in 0..3 {
+            let ty = match self.types.get(&current) {
+                Some(ty) => ty,
+                None => break,
+            };
+            match spirv_headers::Op::from_u32(ty.opcode) {
+                Some(spirv_headers::Op::TypeMatrix) if ty.operands.len() >= 2 => {
+                    traits.matrix.column_count = ty.operands[1];
+                    traits.matrix.stride = matrix_stride;
+                    current = ty.operands[0];
+                }
+                Some(spirv_headers::Op::TypeVector) if ty.operands.len() >= 2 => {
+                    traits.vector.component_count = ty.operands[1];
+                    if traits.matrix.column_count != 0 {
+                        traits.matrix.row_count = ty.operands[1];
+                    }
+                    current = ty.operands[0];
+                }
+                Some(spirv_headers::Op::TypeInt) if ty.operands.len() >= 2 => {
+                    traits.scalar.width = ty.operands[0];
+                    traits.scalar.signedness = ty.operands[1];
+                    break;
+                }
+                Some(spirv_headers::Op::TypeFloat) if !ty.operands.is_empty() => {
+                    traits.scalar.width = ty.operands[0];
+                    break;
+                }
+                Some(spirv_headers::Op::TypeBool) => {
+                    traits.scalar.width = 32;
+                    break;
+                }
+                Some(spirv_headers::Op::TypePointer) => {
+                    if self.physical_pointer(current).is_some() {
+                        traits.scalar.width = 64;
+                    }
+                    break;
+                }
+                _ => break,
+            }
+        }
+        traits
+    }
+
+    /// Vertex-attribute style format of a scalar or vector type.
+    pub(crate) fn interface_format(&self, type_id: u32) -> ReflectFormat {
+        use ReflectFormat::*;
+        let numeric = self.numeric_traits(type_id, 0);
+        let scalar_type_id = match self.types.get(&type_id) {
+            Some(ty) if ty.is(spirv_headers::Op::TypeVector) && !ty.operands.is_empty() => {
+                ty.operands[0]
+            }
+            _ => type_id,
+        };
+        let is_float = match self.types.get(&scalar_type_id) {
+            Some(ty) => ty.is(spirv_headers::Op::TypeFloat),
+            None => return Undefined,
+        };
+        let formats = match (numeric.scalar.width, is_float, numeric.scalar.signedness) {
+            (32, true, _) => [
+                R32_SFLOAT,
+                R32G32_SFLOAT,
+                R32G32B32_SFLOAT,
+                R32G32B32A32_SFLOAT,
+            ],
+            (32, false, 0) => [R32_UINT, R32G32_UINT, R32G32B32_UINT, R32G32B32A32_UINT],
+            (32, false, _) => [R32_SINT, R32G32_SINT, R32G32B32_SINT, R32G32B32A32_SINT],
+            (64, true, _) => [
+                R64_SFLOAT,
+                R64G64_SFLOAT,
+                R64G64B64_SFLOAT,
+                R64G64B64A64_SFLOAT,
+            ],
+            (64, false, 0) => [R64_UINT, R64G64_UINT, R64G64B64_UINT, R64G64B64A64_UINT],
+            (64, false, _) => [R64_SINT, R64G64_SINT, R64G64B64_SINT, R64G64B64A64_SINT],
+            _ => return Undefined,
+        };
+        let component_count = numeric.vector.component_count.max(1) as usize;
+        formats
+            .get(component_count - 1)
+            .cloned()
+            .unwrap_or(Undefined)
+    }
+
+    pub(crate) fn type_flags(&self, type_id: u32) -> ReflectTypeFlags {
+        self.type_flags_at_depth(type_id, 0)
+    }
+
+    fn type_flags_at_depth(&self, type_id: u32, depth: u32) -> ReflectTypeFlags {
+        let ty = match self.types.get(&type_id) {
+            Some(ty) if depth < CONSTANT_NESTING_LIMIT => ty,
+            _ => return ReflectTypeFlags::UNDEFINED,
+        };
+        let operand_flags = |index: usize| match ty.operands.get(index) {
+            Some(operand) => self.type_flags_at_depth(*operand, depth + 1),
+            None => ReflectTypeFlags::UNDEFINED,
+        };
+        match spirv_headers::Op::from_u32(ty.opcode) {
+            Some(spirv_headers::Op::TypeVoid) => ReflectTypeFlags::VOID,
+            Some(spirv_headers::Op::TypeBool) => ReflectTypeFlags::BOOL,
+            Some(spirv_headers::Op::TypeInt) => ReflectTypeFlags::INT,
+            Some(spirv_headers::Op::TypeFloat) => ReflectTypeFlags::FLOAT,
+            Some(spirv_headers::Op::TypeVector) => ReflectTypeFlags::VECTOR | operand_flags(0),
+            Some(spirv_headers::Op::TypeMatrix) => ReflectTypeFlags::MATRIX | operand_flags(0),
+            Some(spirv_headers::Op::TypeArray) | Some(spirv_headers::Op::TypeRuntimeArray) => {
+                ReflectTypeFlags::ARRAY | operand_flags(0)
+            }
+            Some(spirv_headers::Op::TypeStruct) => {
+                if self
+                    .decoration(type_id, spirv_headers::Decoration::Block)
+                    .is_some()
+                    || self
+                        .decoration(type_id, spirv_headers::Decoration::BufferBlock)
+                        .is_some()
+                {
+                    ReflectTypeFlags::STRUCT | ReflectTypeFlags::EXTERNAL_BLOCK
+                } else {
+                    ReflectTypeFlags::STRUCT
+                }
+            }
+            Some(spirv_headers::Op::TypeImage) => ReflectTypeFlags::EXTERNAL_IMAGE,
+            Some(spirv_headers::Op::TypeSampler) => ReflectTypeFlags::EXTERNAL_SAMPLER,
+            Some(spirv_headers::Op::TypeSampledImage) => {
+                ReflectTypeFlags::EXTERNAL_SAMPLED_IMAGE | operand_flags(0)
+            }
+            Some(spirv_headers::Op::TypeAccelerationStructureNV) => {
+                ReflectTypeFlags::EXTERNAL_ACCELERATION_STRUCTURE_NV
+            }
+            Some(spirv_headers::Op::TypePointer) if self.physical_pointer(type_id).is_some() => {
+                ReflectTypeFlags::REF
+            }
+            Some(spirv_headers::Op::TypePointer) => operand_flags(1),
+            _ => ReflectTypeFlags::UNDEFINED,
+        }
+    }
+
+    /// Type description for `type_id`, looking through pointers.
+    /// `member_of` is the `(struct id, member index)` the type appears in.
+    pub(crate) fn type_description(
+        &self,
+        type_id: u32,
+        member_of: Option<(u32, u32)>,
+    ) -> ReflectTypeDescription {
+        self.type_description_within(type_id, member_of, &mut Vec::new())
+    }
+
+    /// `structs` are the structs being described around this type. A struct
+    /// that contains itself, which only a malformed module can declare as
+    /// physical storage buffer pointers are not looked through, is described
+    /// without members the second time.
+    fn type_description_within(
+        &self,
+        type_id: u32,
+        member_of: Option<(u32, u32)>,
+        structs: &mut Vec<u32>,
+    ) -> ReflectTypeDescription {
+        let storage_class = match self.types.get(&type_id) {
+            Some(ty) if ty.is(spirv_headers::Op::TypePointer) && ty.operands.len() >= 2 => {
+                spirv_headers::StorageClass::from_u32(ty.operands[0])
+                    .and_then(|storage_class| ReflectStorageClass::try_from(storage_class).ok())
+                    .unwrap_or(ReflectStorageClass::Undefined)
+            }
+            _ => ReflectStorageClass::Undefined,
+        };
+        let type_id = match self.physical_pointer(type_id) {
+            Some(_) => type_id,
+            None => self.pointee_type_id(type_id),
+        };
+        let (struct_member_name, mut decoration_flags, offset, matrix_stride) = match member_of {
+            Some((id, member)) => (
+                self.member_name(id, member),
+                self.decoration_flags(self.member_decorations.get(&(id, member))),
+                self.member_offset(id, member),
+                self.member_matrix_stride(id, member),
+            ),
+            None => (String::new(), ReflectDecorationFlags::NONE, None, 0),
+        };
+        decoration_flags |= self.decoration_flags(self.decorations.get(&type_id));
+
+        let element_type_id = self.array_element_type_id(type_id);
+        let element_type = self.types.get(&element_type_id);
+        let members = match element_type {
+            Some(ty)
+                if ty.is(spirv_headers::Op::TypeStruct)
+                    && !structs.contains(&element_type_id)
+                    && structs.len() < CONSTANT_NESTING_LIMIT as usize =>
+            {
+                structs.push(element_type_id);
+                let members = ty
+                    .operands
+                    .iter()
+                    .enumerate()
+                    .map(|(index, member_type_id)| {
+                        self.type_description_within(
+                            *member_type_id,
+                            Some((element_type_id, index as u32)),
+                            structs,
+                        )
+                    })
+                    .collect();
+                structs.pop();
+                members
+            }
+            _ => Vec::new(),
+        };
+        let image = match element_type {
+            Some(ty) if ty.is(spirv_headers::Op::TypeSampledImage) && !ty.operands.is_empty() => {
+                self.image_traits(ty.operands[0])
+            }
+            _ => self.image_traits(element_type_id),
+        };
+
+        ReflectTypeDescription {
+            id: type_id,
+            op: match self
+                .types
+                .get(&type_id)
+                .and_then(|ty| spirv_headers::Op::from_u32(ty.opcode))
+            {
+                Some(op) => ReflectOp::from(op),
+                None => ReflectOp::default(),
+            },
+            type_name: match element_type {
+                Some(ty) if ty.is(spirv_headers::Op::TypeStruct) => self.name(element_type_id),
+                _ => String::new(),
+            },
+            struct_member_name,
+            storage_class,
+            type_flags: self.type_flags(type_id),
+            decoration_flags,
+            offset,
+            traits: ReflectTypeDescriptionTraits {
+                numeric: self.numeric_traits(element_type_id, matrix_stride),
+                image,
+                array: self.array_traits(type_id),
+                pointer: self.physical_pointer(element_type_id),
+            },
+            members,
+        }
+    }
+
+    fn image_traits(&self, type_id: u32) -> ReflectImageTraits {
+        match self.types.get(&type_id) {
+            Some(ty) if ty.is(spirv_headers::Op::TypeImage) && ty.operands.len() >= 7 => {
+                ReflectImageTraits {
+                    dim: spirv_headers::Dim::from_u32(ty.operands[1])
+                        .map(ReflectDimension::from)
+                        .unwrap_or(ReflectDimension::Undefined),
+                    depth: ty.operands[2],
+                    arrayed: ty.operands[3],
+                    ms: ty.operands[4],
+                    sampled: ty.operands[5],
+                    image_format: spirv_headers::ImageFormat::from_u32(ty.operands[6])
+                        .map(ReflectImageFormat::from)
+                        .unwrap_or(ReflectImageFormat::Undefined),
+                }
+            }
+            _ => ReflectImageTraits::default(),
+        }
+    }
+}
+
+const SPIRV_DATA_ALIGNMENT: u32 = 16;
+
+struct LocationFlattener<'a> {
+    module: &'a ParsedModule,
+    spirv_id: u32,
+    member_path: Vec<u32>,
+    locations: &'a mut Vec<ReflectInterfaceLocation>,
+    visits_left: u32,
+}
+
+impl<'a> LocationFlattener<'a> {
+    /// Assigns locations from `cursor` onwards, as members without a
+    /// `Location` of their own follow the previous member.
+    fn flatten(
+        &mut self,
+        type_id: u32,
+        cursor: &mut Option<u32>,
+        component: u32,
+        name: String,
+        depth: u32,
+    ) {
+        use spirv_headers::{Decoration, Op};
+        let module = self.module;
+        let ty = match module.types.get(&type_id) {
+            Some(ty) if depth < CONSTANT_NESTING_LIMIT && self.visits_left > 0 => ty,
+            _ => return,
+        };
+        self.visits_left -= 1;
+        match Op::from_u32(ty.opcode) {
+            Some(Op::TypeStruct) => {
+                for (index, member_type_id) in ty.operands.iter().enumerate() {
+                    let member = index as u32;
+                    if module
+                        .member_decoration(type_id, member, Decoration::BuiltIn)
+                        .is_some()
+                    {
+                        continue;
+                    }
+                    if let Some(location) = module
+                        .member_decoration(type_id, member, Decoration::Location)
+                        .and_then(|operands| operands.first().cloned())
+                    {
+                        *cursor = Some(location);
+                    }
+                    let component = module
+                        .member_decoration(type_id, member, Decoration::Component)
+                        .and_then(|operands| operands.first().cloned())
+                        .unwrap_or(0);
+                    let mut member_name = module.member_name(type_id, member);
+                    if member_name.is_empty() {
+                        member_name = format!("_member{}", member);
+                    }
+                    self.member_path.push(member);
+                    self.flatten(
+                        *member_type_id,
+                        cursor,
+                        component,
+                        format!("{}.{}", name, member_name),
+                        depth + 1,
+                    );
+                    self.member_path.pop();
+                }
+            }
+            Some(Op::TypeArray) if ty.operands.len() >= 2 => {
+                let length = module
+                    .size_expr(ty.operands[1])
+                    .default_value()
+                    .unwrap_or(0)
+                    .min(INTERFACE_ARRAY_LIMIT);
+                for element in 0..length {
+                    let element_name = format!("{}[{}]", name, element);
+                    self.flatten(ty.operands[0], cursor, component, element_name, depth + 1);
+                }
+            }
+            Some(Op::TypeMatrix) if ty.operands.len() >= 2 => {
+                for column in 0..ty.operands[1].min(4) {
+                    let column_name = format!("{}[{}]", name, column);
+                    self.flatten(ty.operands[0], cursor, component, column_name, depth + 1);
+                }
+            }
+            _ => {
+                let location = match *cursor {
+                    Some(location) => location,
+                    None => return,
+                };
+                let numeric = module.numeric_traits(type_id, 0);
+                let location_count =
+                    if numeric.scalar.width == 64 && numeric.vector.component_count > 2 {
+                        2
+                    } else {
+                        1
+                    };
+                self.locations.push(ReflectInterfaceLocation {
+                    spirv_id: self.spirv_id,
+                    name,
+                    member_path: self.member_path.clone(),
+                    location,
+                    component,
+                    location_count,
+                    numeric,
+                    format: module.interface_format(type_id),
+                });
+                *cursor = Some(location.saturating_add(location_count));
+            }
+        }
+    }
+}
+
+fn round_up(value: u32, alignment: u32) -> u32 {
+    value.div_ceil(alignment).saturating_mul(alignment)
+}
+
+/// The last of repeated decorations wins, as in the C library, which
+/// overwrites earlier values as it goes.
+fn find_decoration(
+    decorations: Option<&Vec<ReflectDecoration>>,
+    decoration: spirv_headers::Decoration,
+) -> Option<&[u32]> {
+    decorations?
+        .iter()
+        .rev()
+        .find(|entry| entry.decoration == decoration as u32)
+        .map(|entry| entry.operands.as_slice())
+}
+
+fn is_type_op(op: spirv_headers::Op) -> bool {
+    use spirv_headers::Op;
+    matches!(
+        op,
+        Op::TypeVoid
+            | Op::TypeBool
+            | Op::TypeInt
+            | Op::TypeFloat
+            | Op::TypeVector
+            | Op::TypeMatrix
+            | Op::TypeImage
+            | Op::TypeSampler
+            | Op::TypeSampledImage
+            | Op::TypeArray
+            | Op::TypeRuntimeArray
+            | Op::TypeStruct
+            | Op::TypeOpaque
+            | Op::TypePointer
+            | Op::TypeFunction
+            | Op::TypeEvent
+            | Op::TypeDeviceEvent
+            | Op::TypeReserveId
+            | Op::TypeQueue
+            | Op::TypePipe
+            | Op::TypeAccelerationStructureNV
+            | Op::TypeCooperativeMatrixNV,
+    )
+}
+
+fn is_constant_op(op: spirv_headers::Op) -> bool {
+    use spirv_headers::Op;
+    matches!(
+        op,
+        Op::ConstantTrue
+            | Op::ConstantFalse
+            | Op::Constant
+            | Op::ConstantComposite
+            | Op::ConstantSampler
+            | Op::ConstantNull
+            | Op::SpecConstantTrue
+            | Op::SpecConstantFalse
+            | Op::SpecConstant
+            | Op::SpecConstantComposite
+            | Op::SpecConstantOp
+            | Op::Undef,
+    )
+}
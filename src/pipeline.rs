@@ -0,0 +1,387 @@
+//! Reflection aggregated across the shader stages of a pipeline.
+
+use crate::types::{
+    ReflectBlockVariable, ReflectDescriptorBinding, ReflectDescriptorSet, ReflectShaderStageFlags,
+};
+use crate::ShaderModule;
+
+/// An entry point of a module, as used by one stage of a pipeline.
+#[derive(Clone)]
+pub struct PipelineStage {
+    pub module: ShaderModule,
+    pub entry_point: String,
+    pub shader_stage: ReflectShaderStageFlags,
+}
+
+/// Patched stages and merged layout from
+/// `PipelineReflection::auto_resolve_binding_conflicts`.
+#[derive(Debug, Clone)]
+pub struct ReflectResolvedBindings {
+    /// SPIR-V of every stage, in the order the stages were added.
+    pub code: Vec<Vec<u32>>,
+    pub descriptor_sets: Vec<ReflectDescriptorSet>,
+}
+
+/// A push constant range of a pipeline layout.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectPushConstantRange {
+    pub stage_flags: ReflectShaderStageFlags,
+    pub offset: u32,
+    pub size: u32,
+}
+
+/// Reflection of the stages of a graphics or compute pipeline.
+#[derive(Default, Clone)]
+pub struct PipelineReflection {
+    pub stages: Vec<PipelineStage>,
+}
+
+/// Whether two bindings at the same set and binding number are the same
+/// resource seen from two stages, rather than a collision.
+fn same_resource(a: &ReflectDescriptorBinding, b: &ReflectDescriptorBinding) -> bool {
+    a.name == b.name && a.layout_compatible(b)
+}
+
+impl PipelineReflection {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn add_stage(
+        &mut self,
+        module: &ShaderModule,
+        entry_point: Option<&str>,
+    ) -> Result<(), &'static str> {
+        let entry_points = module.enumerate_entry_points()?;
+        let reflected = match entry_point {
+            Some(name) => entry_points.iter().find(|reflected| reflected.name == name),
+            None => entry_points.first(),
+        }
+        .ok_or("Entry point not found")?;
+        self.stages.push(PipelineStage {
+            module: module.clone(),
+            entry_point: reflected.name.clone(),
+            shader_stage: reflected.shader_stage,
+        });
+        Ok(())
+    }
+
+    /// Descriptor sets of all stages merged by set number. A resource used by
+    /// several stages is listed once, with the `stage_flags` of all of them;
+    /// bindings that alias within one stage are all kept.
+    pub fn descriptor_sets(&self) -> Result<Vec<ReflectDescriptorSet>, &'static str> {
+        let mut sets: Vec<ReflectDescriptorSet> = Vec::new();
+        for stage in &self.stages {
+            let bindings = stage
+                .module
+                .enumerate_descriptor_bindings(Some(&stage.entry_point))?;
+            // Bindings from earlier stages come first in every set.
+            let previous: Vec<(u32, usize)> = sets
+                .iter()
+                .map(|set| (set.set, set.bindings.len()))
+                .collect();
+            for mut binding in bindings {
+                binding.stage_flags |= stage.shader_stage;
+                let earlier = previous
+                    .iter()
+                    .find(|(set, _)| *set == binding.set)
+                    .map_or(0, |(_, count)| *count);
+                match sets.iter_mut().find(|set| set.set == binding.set) {
+                    Some(set) => match set.bindings[..earlier].iter_mut().find(|other| {
+                        other.binding == binding.binding && same_resource(other, &binding)
+                    }) {
+                        Some(other) => other.stage_flags |= binding.stage_flags,
+                        None => set.bindings.push(binding),
+                    },
+                    None => sets.push(ReflectDescriptorSet {
+                        set: binding.set,
+                        bindings: vec![binding],
+                        internal_data: std::ptr::null(),
+                    }),
+                }
+            }
+        }
+        sets.sort_by_key(|set| set.set);
+        for set in &mut sets {
+            set.bindings.sort_by_key(|binding| binding.binding);
+        }
+        Ok(sets)
+    }
+
+    /// Push constant ranges for the pipeline layout, sorted by offset. Each
+    /// stage's range spans the members it declares; overlapping ranges are
+    /// merged into one for all their stages, so every stage is in one range.
+    pub fn push_constant_ranges(&self) -> Result<Vec<ReflectPushConstantRange>, &'static str> {
+        let mut ranges: Vec<ReflectPushConstantRange> = Vec::new();
+        for stage in &self.stages {
+            for block in stage
+                .module
+                .enumerate_push_constant_blocks(Some(&stage.entry_point))?
+            {
+                let start = block.members.iter().map(|member| member.offset).min();
+                let end = block
+                    .members
+                    .iter()
+                    .map(|member| member.offset + member.size)
+                    .max();
+                if let (Some(start), Some(end)) = (start, end) {
+                    ranges.push(ReflectPushConstantRange {
+                        stage_flags: stage.shader_stage,
+                        offset: start,
+                        size: end - start,
+                    });
+                }
+            }
+        }
+
+        ranges.sort_by_key(|range| range.offset);
+        let mut merged: Vec<ReflectPushConstantRange> = Vec::new();
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.offset < last.offset + last.size => {
+                    let end = (last.offset + last.size).max(range.offset + range.size);
+                    last.size = end - last.offset;
+                    last.stage_flags |= range.stage_flags;
+                }
+                _ => merged.push(range),
+            }
+        }
+        Ok(merged)
+    }
+
+    /// Finds bindings at the same set and binding number as a different
+    /// resource in an earlier stage and moves them to the first binding number
+    /// past everything used in their set, rewriting the later stage's
+    /// decorations.
+    pub fn auto_resolve_binding_conflicts(
+        &mut self,
+    ) -> Result<ReflectResolvedBindings, &'static str> {
+        let mut merged: Vec<ReflectDescriptorBinding> = Vec::new();
+        for stage in &mut self.stages {
+            let bindings = stage
+                .module
+                .enumerate_descriptor_bindings(Some(&stage.entry_point))?;
+            let previous = merged.len();
+            let mut moves = Vec::new();
+            for binding in &bindings {
+                let at_slot: Vec<&ReflectDescriptorBinding> = merged[..previous]
+                    .iter()
+                    .filter(|other| other.set == binding.set && other.binding == binding.binding)
+                    .collect();
+                let mut binding = binding.clone();
+                if !at_slot.is_empty()
+                    && !at_slot.iter().any(|other| same_resource(other, &binding))
+                {
+                    let new_binding = merged
+                        .iter()
+                        .chain(bindings.iter())
+                        .filter(|other| other.set == binding.set)
+                        .map(|other| other.binding + 1)
+                        .max()
+                        .unwrap_or(0);
+                    moves.push((binding.spirv_id, new_binding));
+                    binding.binding = new_binding;
+                }
+                merged.push(binding);
+            }
+            for (spirv_id, new_binding) in moves {
+                // Look the binding up again, as every change updates the module.
+                let binding = stage
+                    .module
+                    .enumerate_descriptor_bindings(None)?
+                    .into_iter()
+                    .find(|binding| binding.spirv_id == spirv_id)
+                    .ok_or("Descriptor binding not found")?;
+                stage
+                    .module
+                    .change_descriptor_binding_numbers(&binding, new_binding, None)?;
+            }
+        }
+        Ok(ReflectResolvedBindings {
+            code: self
+                .stages
+                .iter()
+                .map(|stage| stage.module.get_code())
+                .collect(),
+            descriptor_sets: self.descriptor_sets()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectShaderRecord {
+    pub entry_point: String,
+    pub shader_stage: ReflectShaderStageFlags,
+    pub block: Option<ReflectBlockVariable>,
+}
+
+impl ReflectShaderRecord {
+    /// Bytes of shader record data the stage reads after the group handle.
+    pub fn size(&self) -> u32 {
+        match self.block {
+            Some(ref block) => block
+                .members
+                .iter()
+                .map(|member| member.offset + member.size)
+                .max()
+                .unwrap_or(0),
+            None => 0,
+        }
+    }
+}
+
+/// Collects the shader record layouts of ray tracing stages to size the
+/// regions of a shader binding table.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct RayTracingPipelineReflection {
+    pub shader_records: Vec<ReflectShaderRecord>,
+}
+
+impl RayTracingPipelineReflection {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn add_stage(
+        &mut self,
+        module: &ShaderModule,
+        entry_point: Option<&str>,
+    ) -> Result<(), &'static str> {
+        let entry_points = module.enumerate_entry_points()?;
+        let reflected = match entry_point {
+            Some(name) => entry_points.iter().find(|reflected| reflected.name == name),
+            None => entry_points.first(),
+        }
+        .ok_or("Entry point not found")?;
+        let ray_tracing_stages = Self::raygen_stages()
+            | Self::miss_stages()
+            | Self::hit_group_stages()
+            | Self::callable_stages();
+        if !ray_tracing_stages.intersects(reflected.shader_stage) {
+            return Err("Entry point is not a ray tracing stage");
+        }
+        self.shader_records.push(ReflectShaderRecord {
+            entry_point: reflected.name.clone(),
+            shader_stage: reflected.shader_stage,
+            block: module.get_shader_record_block(Some(&reflected.name))?,
+        });
+        Ok(())
+    }
+
+    /// Largest shader record used by any of the added `stages`.
+    pub fn record_size(&self, stages: ReflectShaderStageFlags) -> u32 {
+        self.shader_records
+            .iter()
+            .filter(|record| stages.intersects(record.shader_stage))
+            .map(ReflectShaderRecord::size)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Stride of a shader binding table region holding records for `stages`.
+    /// `handle_size` and `handle_alignment` come from
+    /// `VkPhysicalDeviceRayTracingPropertiesNV`.
+    pub fn sbt_stride(
+        &self,
+        stages: ReflectShaderStageFlags,
+        handle_size: u32,
+        handle_alignment: u32,
+    ) -> u32 {
+        let stride = handle_size + self.record_size(stages);
+        if handle_alignment <= 1 {
+            stride
+        } else {
+            stride.next_multiple_of(handle_alignment)
+        }
+    }
+
+    pub fn raygen_stages() -> ReflectShaderStageFlags {
+        ReflectShaderStageFlags::RAYGEN_BIT_NV
+    }
+
+    pub fn miss_stages() -> ReflectShaderStageFlags {
+        ReflectShaderStageFlags::MISS_BIT_NV
+    }
+
+    pub fn hit_group_stages() -> ReflectShaderStageFlags {
+        ReflectShaderStageFlags::CLOSEST_HIT_BIT_NV
+            | ReflectShaderStageFlags::ANY_HIT_BIT_NV
+            | ReflectShaderStageFlags::INTERSECTION_BIT_NV
+    }
+
+    pub fn callable_stages() -> ReflectShaderStageFlags {
+        ReflectShaderStageFlags::CALLABLE_BIT_NV
+    }
+}
+
+/// Checks that the task payload read by a mesh shader matches the one written
+/// by the task shader feeding it.
+pub fn validate_task_payload(
+    task: &ShaderModule,
+    task_entry_point: Option<&str>,
+    mesh: &ShaderModule,
+    mesh_entry_point: Option<&str>,
+) -> Result<(), &'static str> {
+    let written = task.get_task_payload(task_entry_point)?;
+    let read = mesh.get_task_payload(mesh_entry_point)?;
+    match (written, read) {
+        (Some(written), Some(read)) => {
+            if written.layout_compatible(&read) {
+                Ok(())
+            } else {
+                Err("Task payload layouts of the task and mesh stages differ")
+            }
+        }
+        (None, Some(_)) => Err("Mesh stage reads a task payload the task stage does not write"),
+        _ => Ok(()),
+    }
+}
+
+/// Checks a tessellation control / evaluation pair against the
+/// `patchControlPoints` of the pipeline's tessellation state: the control
+/// stage must declare its output patch size and accept input patches of
+/// `patch_control_points` vertices, and every patch input of the evaluation
+/// stage must be written by the control stage.
+pub fn validate_tessellation(
+    control: &ShaderModule,
+    control_entry_point: Option<&str>,
+    evaluation: &ShaderModule,
+    evaluation_entry_point: Option<&str>,
+    patch_control_points: u32,
+) -> Result<(), &'static str> {
+    let control = match control.get_tessellation_traits(control_entry_point)? {
+        Some(traits) if traits.shader_stage == ReflectShaderStageFlags::TESSELLATION_CONTROL => {
+            traits
+        }
+        _ => return Err("Entry point is not a tessellation control stage"),
+    };
+    let evaluation = match evaluation.get_tessellation_traits(evaluation_entry_point)? {
+        Some(traits) if traits.shader_stage == ReflectShaderStageFlags::TESSELLATION_EVALUATION => {
+            traits
+        }
+        _ => return Err("Entry point is not a tessellation evaluation stage"),
+    };
+
+    if patch_control_points == 0 {
+        return Err("Patch control point count must be greater than zero");
+    }
+    if control.input_patch_vertices != 0 && patch_control_points > control.input_patch_vertices {
+        return Err("Patch control point count exceeds the control stage input patch size");
+    }
+    if control.output_vertices == 0 {
+        return Err("Tessellation control stage does not declare OutputVertices");
+    }
+    if evaluation.output_vertices != 0 && evaluation.output_vertices != control.output_vertices {
+        return Err("OutputVertices of the control and evaluation stages differ");
+    }
+    let all_written = evaluation.patch_inputs.iter().all(|input| {
+        control
+            .patch_outputs
+            .iter()
+            .any(|output| output.location == input.location)
+    });
+    if !all_written {
+        return Err("Evaluation stage reads a patch input the control stage does not write");
+    }
+    Ok(())
+}
@@ -0,0 +1,284 @@
+//! Reflection data put together by hand instead of reflected from SPIR-V,
+//! for unit-testing code that consumes reflection, such as pipeline layout
+//! generation, without compiling shaders.
+
+use crate::cache::ReflectModuleCache;
+use crate::types::{
+    ReflectBindingArrayTraits, ReflectBlockVariable, ReflectBuiltIn, ReflectDecorationFlags,
+    ReflectDescriptorBinding, ReflectDescriptorSet, ReflectDescriptorType, ReflectEntryPoint,
+    ReflectFormat, ReflectInterfaceVariable, ReflectNumericTraits, ReflectResourceType,
+    ReflectShaderStageFlags, ReflectStorageClass,
+};
+use std::collections::BTreeMap;
+
+/// Builds the `ReflectModuleCache` of a module with one entry point that
+/// uses everything added to it. Every binding, block and variable gets a
+/// made-up SPIR-V id of its own.
+#[derive(Debug, Clone)]
+pub struct ReflectionBuilder {
+    entry_point_name: String,
+    shader_stage: ReflectShaderStageFlags,
+    bindings: Vec<ReflectDescriptorBinding>,
+    push_constant_blocks: Vec<ReflectBlockVariable>,
+    input_variables: Vec<ReflectInterfaceVariable>,
+    output_variables: Vec<ReflectInterfaceVariable>,
+    next_id: u32,
+}
+
+impl ReflectionBuilder {
+    pub fn new(entry_point_name: &str, shader_stage: ReflectShaderStageFlags) -> Self {
+        ReflectionBuilder {
+            entry_point_name: entry_point_name.to_owned(),
+            shader_stage,
+            bindings: Vec::new(),
+            push_constant_blocks: Vec::new(),
+            input_variables: Vec::new(),
+            output_variables: Vec::new(),
+            // Id 1 is the entry point.
+            next_id: 2,
+        }
+    }
+
+    /// A binding of `count` descriptors, 0 for a runtime array.
+    pub fn add_binding(
+        self,
+        name: &str,
+        set: u32,
+        binding: u32,
+        descriptor_type: ReflectDescriptorType,
+        count: u32,
+    ) -> Self {
+        self.add_binding_with_block(
+            name,
+            set,
+            binding,
+            descriptor_type,
+            count,
+            Default::default(),
+        )
+    }
+
+    /// A uniform or storage buffer binding with the layout of `block`.
+    pub fn add_buffer_binding(
+        self,
+        name: &str,
+        set: u32,
+        binding: u32,
+        descriptor_type: ReflectDescriptorType,
+        block: ReflectBlockVariable,
+    ) -> Self {
+        self.add_binding_with_block(name, set, binding, descriptor_type, 1, block)
+    }
+
+    pub fn add_push_constant_block(mut self, mut block: ReflectBlockVariable) -> Self {
+        block.spirv_id = self.take_id();
+        self.push_constant_blocks.push(block);
+        self
+    }
+
+    pub fn add_input_variable(mut self, name: &str, location: u32, format: ReflectFormat) -> Self {
+        let variable = self.interface_variable(name, location, format, ReflectStorageClass::Input);
+        self.input_variables.push(variable);
+        self
+    }
+
+    pub fn add_output_variable(mut self, name: &str, location: u32, format: ReflectFormat) -> Self {
+        let variable = self.interface_variable(name, location, format, ReflectStorageClass::Output);
+        self.output_variables.push(variable);
+        self
+    }
+
+    /// The reflection data, ordered the way `ShaderModule` orders it.
+    pub fn build(&self) -> ReflectModuleCache {
+        let mut bindings = self.bindings.clone();
+        bindings.sort_by_key(|binding| (binding.set, binding.binding));
+        let mut grouped: BTreeMap<u32, Vec<ReflectDescriptorBinding>> = BTreeMap::new();
+        for binding in &bindings {
+            grouped
+                .entry(binding.set)
+                .or_default()
+                .push(binding.clone());
+        }
+        let descriptor_sets: Vec<ReflectDescriptorSet> = grouped
+            .into_iter()
+            .map(|(set, bindings)| ReflectDescriptorSet {
+                set,
+                bindings,
+                internal_data: std::ptr::null(),
+            })
+            .collect();
+        let mut input_variables = self.input_variables.clone();
+        input_variables.sort_by_key(|variable| variable.location);
+        let mut output_variables = self.output_variables.clone();
+        output_variables.sort_by_key(|variable| variable.location);
+
+        let entry_point = ReflectEntryPoint {
+            name: self.entry_point_name.clone(),
+            id: 1,
+            spirv_execution_model: execution_model(self.shader_stage),
+            shader_stage: self.shader_stage,
+            input_locations: crate::types::variable::build_location_map(&input_variables),
+            output_locations: crate::types::variable::build_location_map(&output_variables),
+            output_built_ins: crate::types::variable::build_built_in_map(&output_variables),
+            input_variables: input_variables.clone(),
+            output_variables: output_variables.clone(),
+            descriptor_sets: descriptor_sets.clone(),
+            used_uniforms: bindings.iter().map(|binding| binding.spirv_id).collect(),
+            used_push_constants: self
+                .push_constant_blocks
+                .iter()
+                .map(|block| block.spirv_id)
+                .collect(),
+            ext_inst_usage: Vec::new(),
+            barriers: Vec::new(),
+        };
+        ReflectModuleCache {
+            shader_stage: self.shader_stage,
+            entry_point_name: self.entry_point_name.clone(),
+            entry_points: vec![entry_point],
+            descriptor_sets,
+            push_constant_blocks: self.push_constant_blocks.clone(),
+            input_variables,
+            output_variables,
+            ..Default::default()
+        }
+    }
+
+    fn take_id(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn add_binding_with_block(
+        mut self,
+        name: &str,
+        set: u32,
+        binding: u32,
+        descriptor_type: ReflectDescriptorType,
+        count: u32,
+        mut block: ReflectBlockVariable,
+    ) -> Self {
+        let spirv_id = self.take_id();
+        if block.name.is_empty() {
+            block.name = name.to_owned();
+        }
+        self.bindings.push(ReflectDescriptorBinding {
+            spirv_id,
+            name: name.to_owned(),
+            binding,
+            input_attachment_index: 0,
+            set,
+            descriptor_type,
+            resource_type: resource_type(descriptor_type),
+            image: Default::default(),
+            block,
+            array: ReflectBindingArrayTraits {
+                dims: if count == 1 { Vec::new() } else { vec![count] },
+            },
+            count,
+            accessed: true,
+            uav_counter_id: u32::MAX,
+            uav_counter_binding: None,
+            uav_counter_association: None,
+            type_description: None,
+            word_offset: Default::default(),
+            name_synthesized: false,
+            stage_flags: self.shader_stage,
+            internal_data: std::ptr::null(),
+        });
+        self
+    }
+
+    fn interface_variable(
+        &mut self,
+        name: &str,
+        location: u32,
+        format: ReflectFormat,
+        storage_class: ReflectStorageClass,
+    ) -> ReflectInterfaceVariable {
+        ReflectInterfaceVariable {
+            spirv_id: self.take_id(),
+            name: name.to_owned(),
+            location,
+            component: 0,
+            index: 0,
+            stream: 0,
+            storage_class,
+            semantic: String::new(),
+            decoration_flags: ReflectDecorationFlags::NONE,
+            built_in: ReflectBuiltIn::default(),
+            numeric: numeric_traits(format),
+            array: Default::default(),
+            members: Vec::new(),
+            format,
+            type_description: None,
+            word_offset: 0,
+            name_synthesized: false,
+        }
+    }
+}
+
+/// The resource type the C library reflects for a descriptor type.
+fn resource_type(descriptor_type: ReflectDescriptorType) -> ReflectResourceType {
+    match descriptor_type {
+        ReflectDescriptorType::Sampler => ReflectResourceType::Sampler,
+        ReflectDescriptorType::CombinedImageSampler => ReflectResourceType::CombinedImageSampler,
+        ReflectDescriptorType::UniformBuffer | ReflectDescriptorType::UniformBufferDynamic => {
+            ReflectResourceType::ConstantBufferView
+        }
+        ReflectDescriptorType::SampledImage
+        | ReflectDescriptorType::UniformTexelBuffer
+        | ReflectDescriptorType::InputAttachment
+        | ReflectDescriptorType::AccelerationStructureNV => ReflectResourceType::ShaderResourceView,
+        ReflectDescriptorType::StorageImage
+        | ReflectDescriptorType::StorageTexelBuffer
+        | ReflectDescriptorType::StorageBuffer
+        | ReflectDescriptorType::StorageBufferDynamic => ReflectResourceType::UnorderedAccessView,
+        ReflectDescriptorType::Undefined => ReflectResourceType::Undefined,
+    }
+}
+
+fn numeric_traits(format: ReflectFormat) -> ReflectNumericTraits {
+    use ReflectFormat::*;
+    let component_count = match format {
+        R32_UINT | R32_SINT | R32_SFLOAT | R64_UINT | R64_SINT | R64_SFLOAT | Undefined => 0,
+        R32G32_UINT | R32G32_SINT | R32G32_SFLOAT | R64G64_UINT | R64G64_SINT | R64G64_SFLOAT => 2,
+        R32G32B32_UINT | R32G32B32_SINT | R32G32B32_SFLOAT | R64G64B64_UINT | R64G64B64_SINT
+        | R64G64B64_SFLOAT => 3,
+        _ => 4,
+    };
+    let mut numeric = ReflectNumericTraits::default();
+    numeric.scalar.width = match format {
+        Undefined => 0,
+        R64_UINT | R64_SINT | R64_SFLOAT | R64G64_UINT | R64G64_SINT | R64G64_SFLOAT
+        | R64G64B64_UINT | R64G64B64_SINT | R64G64B64_SFLOAT | R64G64B64A64_UINT
+        | R64G64B64A64_SINT | R64G64B64A64_SFLOAT => 64,
+        _ => 32,
+    };
+    numeric.scalar.signedness = match format {
+        R32_SINT | R32G32_SINT | R32G32B32_SINT | R32G32B32A32_SINT | R64_SINT | R64G64_SINT
+        | R64G64B64_SINT | R64G64B64A64_SINT => 1,
+        _ => 0,
+    };
+    numeric.vector.component_count = component_count;
+    numeric
+}
+
+fn execution_model(shader_stage: ReflectShaderStageFlags) -> spirv_headers::ExecutionModel {
+    use spirv_headers::ExecutionModel;
+    match shader_stage {
+        ReflectShaderStageFlags::TESSELLATION_CONTROL => ExecutionModel::TessellationControl,
+        ReflectShaderStageFlags::TESSELLATION_EVALUATION => ExecutionModel::TessellationEvaluation,
+        ReflectShaderStageFlags::GEOMETRY => ExecutionModel::Geometry,
+        ReflectShaderStageFlags::FRAGMENT => ExecutionModel::Fragment,
+        ReflectShaderStageFlags::COMPUTE => ExecutionModel::GLCompute,
+        ReflectShaderStageFlags::RAYGEN_BIT_NV => ExecutionModel::RayGenerationNV,
+        ReflectShaderStageFlags::ANY_HIT_BIT_NV => ExecutionModel::AnyHitNV,
+        ReflectShaderStageFlags::CLOSEST_HIT_BIT_NV => ExecutionModel::ClosestHitNV,
+        ReflectShaderStageFlags::MISS_BIT_NV => ExecutionModel::MissNV,
+        ReflectShaderStageFlags::INTERSECTION_BIT_NV => ExecutionModel::IntersectionNV,
+        ReflectShaderStageFlags::CALLABLE_BIT_NV => ExecutionModel::CallableNV,
+        _ => ExecutionModel::Vertex,
+    }
+}
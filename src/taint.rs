@@ -0,0 +1,539 @@
+//! Coarse static taint of push constant members: which outputs and
+//! descriptor bindings each member can influence, through values, memory,
+//! calls and the branches it decides.
+
+use crate::disassemble;
+use crate::dot;
+use crate::image_usage;
+use crate::parser::{EntryPoint, Instruction, ParsedModule, Variable};
+use crate::types::{ReflectPushConstantTaint, ReflectTaintSink};
+use num_traits::cast::FromPrimitive;
+use spirv_headers::{Decoration, Op, StorageClass};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ops::Range;
+
+/// Members of the push constant block a value or memory depends on.
+type Taint = BTreeSet<u32>;
+
+/// Where tainted data can end up: an output variable and the member of its
+/// block, or a descriptor binding variable.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum Sink {
+    Output(u32, Option<u32>),
+    Descriptor(u32),
+}
+
+/// A basic block: its instructions, where it can branch to, the merge block
+/// it declares, and the id its conditional branch or switch decides on.
+#[derive(Default)]
+struct Block {
+    instructions: Range<usize>,
+    successors: Vec<usize>,
+    merge: Option<usize>,
+    is_loop: bool,
+    condition: Option<u32>,
+}
+
+/// A function body, with the blocks each condition decides whether to run.
+struct Body {
+    id: u32,
+    blocks: Vec<Block>,
+    controls: Vec<(u32, HashSet<usize>)>,
+}
+
+/// Blocks reachable from the successors of `from` without entering `stop`.
+fn region(blocks: &[Block], from: usize, stop: Option<usize>) -> HashSet<usize> {
+    let mut seen = HashSet::new();
+    let mut stack = blocks[from].successors.clone();
+    while let Some(index) = stack.pop() {
+        if Some(index) == stop || !seen.insert(index) {
+            continue;
+        }
+        stack.extend(&blocks[index].successors);
+    }
+    seen
+}
+
+/// Splits `function` into blocks. A condition controls the blocks up to its
+/// merge block, or up to the merge of the innermost loop around it for the
+/// exit condition of a loop, which has no merge of its own.
+fn body(instructions: &[Instruction], function: &dot::Function) -> Body {
+    let mut labels = Vec::new();
+    let mut blocks: Vec<Block> = Vec::new();
+    for index in function.instructions.clone() {
+        let instruction = &instructions[index];
+        if instruction.is(Op::Label) {
+            if let Some(block) = blocks.last_mut() {
+                block.instructions.end = index;
+            }
+            labels.push(instruction.operands.first().cloned().unwrap_or(0));
+            blocks.push(Block {
+                instructions: index..function.instructions.end,
+                ..Default::default()
+            });
+        }
+    }
+    let indices: HashMap<u32, usize> = labels
+        .iter()
+        .enumerate()
+        .map(|(index, label)| (*label, index))
+        .collect();
+    for block in &mut blocks {
+        let mut targets = Vec::new();
+        for instruction in &instructions[block.instructions.clone()] {
+            match (Op::from_u32(instruction.opcode), instruction.operands) {
+                (Some(Op::LoopMerge), [merge, ..]) => {
+                    block.merge = indices.get(merge).cloned();
+                    block.is_loop = true;
+                }
+                (Some(Op::SelectionMerge), [merge, ..]) => {
+                    block.merge = indices.get(merge).cloned()
+                }
+                (Some(Op::Branch), [target, ..]) => targets.push(*target),
+                (Some(Op::BranchConditional), [condition, on_true, on_false, ..]) => {
+                    block.condition = Some(*condition);
+                    targets.extend(&[*on_true, *on_false]);
+                }
+                (Some(Op::Switch), [selector, default, cases @ ..]) => {
+                    block.condition = Some(*selector);
+                    targets.push(*default);
+                    targets.extend(cases.chunks(2).filter_map(|pair| pair.get(1)));
+                }
+                _ => {}
+            }
+        }
+        block.successors = targets
+            .iter()
+            .filter_map(|label| indices.get(label).cloned())
+            .collect();
+    }
+
+    let loops: Vec<(usize, HashSet<usize>)> = blocks
+        .iter()
+        .enumerate()
+        .filter(|(_, block)| block.is_loop)
+        .map(|(header, block)| {
+            let mut construct = region(&blocks, header, block.merge);
+            construct.insert(header);
+            (header, construct)
+        })
+        .collect();
+    let controls = blocks
+        .iter()
+        .enumerate()
+        .filter_map(|(index, block)| {
+            let condition = block.condition?;
+            let stop = block.merge.or_else(|| {
+                loops
+                    .iter()
+                    .filter(|(_, construct)| construct.contains(&index))
+                    .min_by_key(|(_, construct)| construct.len())
+                    .and_then(|(header, _)| blocks[*header].merge)
+            });
+            Some((condition, region(&blocks, index, stop)))
+        })
+        .collect();
+    Body {
+        id: function.id,
+        blocks,
+        controls,
+    }
+}
+
+/// Adds `taint` to the taint of `key`, returning whether it grew.
+fn join<K: std::hash::Hash + Eq>(map: &mut HashMap<K, Taint>, key: K, taint: &Taint) -> bool {
+    let entry = map.entry(key).or_default();
+    let before = entry.len();
+    entry.extend(taint);
+    entry.len() != before
+}
+
+fn constant_index(module: &ParsedModule, id: u32) -> Option<u32> {
+    module
+        .constants
+        .get(&id)
+        .filter(|constant| constant.opcode == Op::Constant as u32)
+        .and_then(|constant| constant.operands.first().cloned())
+}
+
+/// The member of an output block that an access chain into `variable` with
+/// `indexes` selects, past the per-vertex array of arrayed outputs.
+fn output_member(module: &ParsedModule, type_id: u32, indexes: &[u32]) -> Option<u32> {
+    let mut type_id = module.pointee_type_id(type_id);
+    let mut indexes = indexes.iter();
+    if let Some(ty) = module.types.get(&type_id) {
+        if ty.is(Op::TypeArray) || ty.is(Op::TypeRuntimeArray) {
+            type_id = module.array_element_type_id(type_id);
+            indexes.next();
+        }
+    }
+    match module.types.get(&type_id) {
+        Some(ty) if ty.is(Op::TypeStruct) => constant_index(module, *indexes.next()?),
+        _ => None,
+    }
+}
+
+/// Taints gathered so far over the functions of an entry point. Every set
+/// only grows, and `changed` tells whether a pass over the code grew one.
+struct Flow<'a> {
+    module: &'a ParsedModule,
+    push_constant: u32,
+    all_members: Taint,
+    /// Output variables and their pointer types.
+    outputs: HashMap<u32, u32>,
+    descriptors: HashSet<u32>,
+    /// `UniformConstant` variables, whose loads are image and sampler
+    /// handles rather than accesses.
+    handles: HashSet<u32>,
+    /// Members each value, address included, depends on.
+    values: HashMap<u32, Taint>,
+    /// Members stored into each variable.
+    memory: HashMap<u32, Taint>,
+    /// Variables each pointer or image handle may come from.
+    roots: HashMap<u32, Taint>,
+    /// Member of an output block each pointer selects.
+    output_members: HashMap<u32, Option<u32>>,
+    /// Members the result of each function depends on.
+    returns: HashMap<u32, Taint>,
+    /// Members deciding whether each function is called.
+    entered: HashMap<u32, Taint>,
+    sinks: HashMap<Sink, Taint>,
+    changed: bool,
+}
+
+fn get(map: &HashMap<u32, Taint>, id: u32) -> Taint {
+    map.get(&id).cloned().unwrap_or_default()
+}
+
+impl<'a> Flow<'a> {
+    fn add_value(&mut self, id: u32, taint: &Taint) {
+        self.changed |= join(&mut self.values, id, taint);
+    }
+
+    fn add_roots(&mut self, id: u32, roots: &Taint) {
+        self.changed |= join(&mut self.roots, id, roots);
+    }
+
+    fn add_memory(&mut self, variable: u32, taint: &Taint) {
+        self.changed |= join(&mut self.memory, variable, taint);
+    }
+
+    fn add_sink(&mut self, sink: Sink, taint: &Taint) {
+        self.changed |= join(&mut self.sinks, sink, taint);
+    }
+
+    /// Members `ids` depend on.
+    fn values_of(&self, ids: &[u32]) -> Taint {
+        ids.iter()
+            .flat_map(|id| self.values.get(id).into_iter().flatten())
+            .cloned()
+            .collect()
+    }
+
+    fn access_chain(&mut self, op: Op, result: u32, base: u32, indexes: &[u32]) {
+        let mut taint = self.values_of(indexes);
+        if base == self.push_constant {
+            let member = match op {
+                Op::AccessChain | Op::InBoundsAccessChain => indexes.first(),
+                _ => indexes.get(1),
+            };
+            match member.and_then(|id| constant_index(self.module, *id)) {
+                Some(member) => {
+                    taint.insert(member);
+                }
+                None => taint.extend(&self.all_members),
+            }
+        } else {
+            taint.extend(get(&self.values, base));
+        }
+        self.add_value(result, &taint);
+        self.add_roots(result, &get(&self.roots, base));
+        let member = match self.outputs.get(&base) {
+            Some(type_id) => Some(output_member(self.module, *type_id, indexes)),
+            None => self.output_members.get(&base).cloned(),
+        };
+        if let Some(member) = member {
+            self.output_members.insert(result, member);
+        }
+    }
+
+    fn load(&mut self, result: u32, pointer: u32, control: &Taint) {
+        let roots = get(&self.roots, pointer);
+        let address = get(&self.values, pointer);
+        let mut taint = address.clone();
+        for &root in &roots {
+            taint.extend(get(&self.memory, root));
+            if self.descriptors.contains(&root) && !self.handles.contains(&root) {
+                let mut accessed = address.clone();
+                accessed.extend(control);
+                self.add_sink(Sink::Descriptor(root), &accessed);
+            }
+        }
+        self.add_value(result, &taint);
+        self.add_roots(result, &roots);
+    }
+
+    /// A store, or a copy from the memory behind `source`.
+    fn store(&mut self, pointer: u32, value: u32, source: Option<u32>, control: &Taint) {
+        let mut taint = self.values_of(&[pointer, value]);
+        for root in source
+            .map(|source| get(&self.roots, source))
+            .unwrap_or_default()
+        {
+            taint.extend(get(&self.memory, root));
+        }
+        taint.extend(control);
+        for root in get(&self.roots, pointer) {
+            self.add_memory(root, &taint);
+            if self.descriptors.contains(&root) {
+                self.add_sink(Sink::Descriptor(root), &taint);
+            }
+            if self.outputs.contains_key(&root) {
+                let member = self.output_members.get(&pointer).cloned().flatten();
+                self.add_sink(Sink::Output(root, member), &taint);
+            }
+        }
+    }
+
+    fn call(&mut self, result: u32, callee: u32, arguments: &[u32], control: &Taint) {
+        if let Some(parameters) = self.module.function_parameters.get(&callee) {
+            for (parameter, &argument) in parameters.iter().zip(arguments) {
+                self.add_value(parameter.id, &get(&self.values, argument));
+                self.add_roots(parameter.id, &get(&self.roots, argument));
+                if let Some(member) = self.output_members.get(&argument).cloned() {
+                    self.output_members.insert(parameter.id, member);
+                }
+            }
+        }
+        self.changed |= join(&mut self.entered, callee, control);
+        self.add_value(result, &get(&self.returns, callee));
+    }
+
+    /// Any other instruction: its result depends on everything it reads, and
+    /// image and atomic instructions access the variable behind their image
+    /// or pointer.
+    fn other(&mut self, instruction: &Instruction, reads: &[u32], control: &Taint) {
+        let op = Op::from_u32(instruction.opcode);
+        let operands = instruction.operands;
+        let (_, result) = disassemble::result_ids(instruction);
+        let mut taint = self.values_of(reads);
+        let target = match op {
+            Some(op) if image_usage::sampling_op(op).is_some() => Some(2),
+            Some(op) => image_usage::storage_op(op).map(|(_, index)| index),
+            None => None,
+        }
+        .or_else(|| image_usage::atomic_pointer_index(instruction.opcode));
+        if let Some(&target) = target.and_then(|index| operands.get(index)) {
+            let mut accessed = taint.clone();
+            accessed.extend(control);
+            for root in get(&self.roots, target) {
+                if self.descriptors.contains(&root) {
+                    self.add_sink(Sink::Descriptor(root), &accessed);
+                }
+                if result.is_some() {
+                    taint.extend(get(&self.memory, root));
+                }
+                if op != Some(Op::AtomicLoad) {
+                    self.add_memory(root, &accessed);
+                }
+            }
+        }
+
+        let result = match result {
+            Some(result) => result,
+            None => return,
+        };
+        self.add_value(result, &taint);
+        let sources: &[u32] = match (op, operands) {
+            (Some(Op::SampledImage), [_, _, sources @ ..]) => sources,
+            (Some(Op::Image), [_, _, source, ..])
+            | (Some(Op::CopyObject), [_, _, source, ..])
+            | (Some(Op::ImageTexelPointer), [_, _, source, ..]) => std::slice::from_ref(source),
+            (Some(Op::Phi), [_, _, pairs @ ..]) => pairs,
+            (Some(Op::Select), [_, _, _, sources @ ..]) => sources,
+            _ => &[],
+        };
+        for &source in sources {
+            self.add_roots(result, &get(&self.roots, source));
+        }
+    }
+
+    fn step(&mut self, instruction: &Instruction, reads: &[u32], control: &Taint, function: u32) {
+        let op = Op::from_u32(instruction.opcode);
+        match (op, instruction.operands) {
+            (Some(Op::Variable), [_, result, rest @ ..]) => {
+                self.add_roots(*result, &std::iter::once(*result).collect());
+                if let [_, initializer, ..] = rest {
+                    self.add_memory(*result, &get(&self.values, *initializer));
+                }
+            }
+            (Some(op @ Op::AccessChain), [_, result, base, indexes @ ..])
+            | (Some(op @ Op::InBoundsAccessChain), [_, result, base, indexes @ ..])
+            | (Some(op @ Op::PtrAccessChain), [_, result, base, indexes @ ..])
+            | (Some(op @ Op::InBoundsPtrAccessChain), [_, result, base, indexes @ ..]) => {
+                self.access_chain(op, *result, *base, indexes)
+            }
+            (Some(Op::Load), [_, result, pointer, ..]) => self.load(*result, *pointer, control),
+            (Some(Op::Store), [pointer, value, ..]) => self.store(*pointer, *value, None, control),
+            (Some(Op::CopyMemory), [target, source, ..]) => {
+                self.store(*target, *source, Some(*source), control)
+            }
+            (Some(Op::FunctionCall), [_, result, callee, arguments @ ..]) => {
+                self.call(*result, *callee, arguments, control)
+            }
+            (Some(Op::ReturnValue), [value, ..]) => {
+                let mut taint = get(&self.values, *value);
+                taint.extend(control);
+                self.changed |= join(&mut self.returns, function, &taint);
+            }
+            _ => self.other(instruction, reads, control),
+        }
+    }
+}
+
+/// The sinks each member of the entry point's push constant block can reach,
+/// in member order. Empty without push constants.
+pub(crate) fn push_constant_taint(
+    instructions: &[Instruction],
+    functions: &[dot::Function],
+    module: &ParsedModule,
+    entry_point: &EntryPoint,
+) -> Vec<ReflectPushConstantTaint> {
+    let push_constants =
+        module.entry_point_variables(entry_point, StorageClass::PushConstant as u32);
+    let push_constant = match push_constants.first() {
+        Some(variable) => variable,
+        None => return Vec::new(),
+    };
+    let block_type_id = module.pointee_type_id(push_constant.type_id);
+    let member_count = match module.types.get(&block_type_id) {
+        Some(ty) if ty.is(Op::TypeStruct) => ty.operands.len() as u32,
+        _ => 0,
+    };
+    let all_members: Taint = (0..member_count).collect();
+
+    let variables_where = |keep: &dyn Fn(&Variable) -> bool| -> HashSet<u32> {
+        module
+            .variables
+            .iter()
+            .filter(|variable| keep(variable))
+            .map(|variable| variable.id)
+            .collect()
+    };
+    let mut flow = Flow {
+        module,
+        push_constant: push_constant.id,
+        all_members: all_members.clone(),
+        outputs: module
+            .entry_point_variables(entry_point, StorageClass::Output as u32)
+            .into_iter()
+            .map(|variable| (variable.id, variable.type_id))
+            .collect(),
+        descriptors: variables_where(&|variable| {
+            module
+                .decoration(variable.id, Decoration::DescriptorSet)
+                .is_some()
+                || module
+                    .decoration(variable.id, Decoration::Binding)
+                    .is_some()
+        }),
+        handles: variables_where(&|variable| {
+            variable.storage_class == StorageClass::UniformConstant as u32
+        }),
+        values: std::iter::once((push_constant.id, all_members)).collect(),
+        memory: HashMap::new(),
+        roots: module
+            .variables
+            .iter()
+            .map(|variable| (variable.id, std::iter::once(variable.id).collect()))
+            .collect(),
+        output_members: HashMap::new(),
+        returns: HashMap::new(),
+        entered: HashMap::new(),
+        sinks: HashMap::new(),
+        changed: true,
+    };
+
+    let bodies: Vec<Body> = dot::reachable(&[entry_point.id], functions)
+        .into_iter()
+        .map(|function| body(instructions, function))
+        .collect();
+    let reads: HashMap<usize, Vec<u32>> = bodies
+        .iter()
+        .flat_map(|body| &body.blocks)
+        .flat_map(|block| block.instructions.clone())
+        .map(|index| (index, disassemble::operand_ids(&instructions[index])))
+        .collect();
+    while flow.changed {
+        flow.changed = false;
+        for body in &bodies {
+            let outer = get(&flow.entered, body.id);
+            for (block_index, block) in body.blocks.iter().enumerate() {
+                let mut control = outer.clone();
+                for (condition, region) in &body.controls {
+                    if region.contains(&block_index) {
+                        control.extend(get(&flow.values, *condition));
+                    }
+                }
+                for index in block.instructions.clone() {
+                    flow.step(&instructions[index], &reads[&index], &control, body.id);
+                }
+            }
+        }
+    }
+
+    let outputs = &flow.outputs;
+    let mut sinks: Vec<(ReflectTaintSink, Taint)> = flow
+        .sinks
+        .into_iter()
+        .map(|(sink, taint)| {
+            let sink = match sink {
+                Sink::Output(spirv_id, member) => ReflectTaintSink::Output {
+                    spirv_id,
+                    member,
+                    name: match member {
+                        Some(member) => module.member_name(
+                            module
+                                .array_element_type_id(module.pointee_type_id(outputs[&spirv_id])),
+                            member,
+                        ),
+                        None => module.name(spirv_id),
+                    },
+                },
+                Sink::Descriptor(spirv_id) => {
+                    let decoration = |decoration| {
+                        module
+                            .decoration(spirv_id, decoration)
+                            .and_then(|operands| operands.first().cloned())
+                            .unwrap_or(0)
+                    };
+                    ReflectTaintSink::Descriptor {
+                        spirv_id,
+                        set: decoration(Decoration::DescriptorSet),
+                        binding: decoration(Decoration::Binding),
+                        name: module.name(spirv_id),
+                    }
+                }
+            };
+            (sink, taint)
+        })
+        .collect();
+    sinks.sort_by_key(|(sink, _)| match sink {
+        ReflectTaintSink::Output {
+            spirv_id, member, ..
+        } => (0, *spirv_id, member.unwrap_or(0)),
+        ReflectTaintSink::Descriptor { set, binding, .. } => (1, *set, *binding),
+    });
+
+    (0..member_count)
+        .map(|member| ReflectPushConstantTaint {
+            member,
+            name: module.member_name(block_type_id, member),
+            sinks: sinks
+                .iter()
+                .filter(|(_, taint)| taint.contains(&member))
+                .map(|(sink, _)| sink.clone())
+                .collect(),
+        })
+        .collect()
+}
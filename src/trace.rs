@@ -0,0 +1,74 @@
+//! `tracing` instrumentation behind the `trace` feature. Without it these are
+//! no-ops.
+
+#[cfg(feature = "trace")]
+pub(crate) struct Phase {
+    name: &'static str,
+    started: std::time::Instant,
+}
+
+#[cfg(not(feature = "trace"))]
+pub(crate) struct Phase;
+
+impl Phase {
+    #[cfg(feature = "trace")]
+    pub(crate) fn start(name: &'static str) -> Phase {
+        Phase {
+            name,
+            started: std::time::Instant::now(),
+        }
+    }
+
+    #[cfg(not(feature = "trace"))]
+    pub(crate) fn start(_name: &'static str) -> Phase {
+        Phase
+    }
+
+    /// Emits the phase duration along with the number of items it produced.
+    #[cfg(feature = "trace")]
+    pub(crate) fn finish(self, items: usize) {
+        tracing::debug!(
+            phase = self.name,
+            items,
+            elapsed_us = self.started.elapsed().as_micros() as u64,
+            "spirv-reflect phase finished"
+        );
+    }
+
+    #[cfg(not(feature = "trace"))]
+    pub(crate) fn finish(self, _items: usize) {}
+}
+
+#[cfg(feature = "trace")]
+pub(crate) fn unknown_opcodes(opcodes: &[u32]) {
+    if !opcodes.is_empty() {
+        tracing::debug!(count = opcodes.len(), ?opcodes, "skipped unknown opcodes");
+    }
+}
+
+#[cfg(not(feature = "trace"))]
+pub(crate) fn unknown_opcodes(_opcodes: &[u32]) {}
+
+#[cfg(feature = "trace")]
+pub(crate) fn module_created(module: &crate::ffi::SpvReflectShaderModule) {
+    tracing::debug!(
+        entry_points = module.entry_point_count,
+        descriptor_bindings = module.descriptor_binding_count,
+        descriptor_sets = module.descriptor_set_count,
+        input_variables = module.input_variable_count,
+        output_variables = module.output_variable_count,
+        push_constant_blocks = module.push_constant_block_count,
+        "reflected shader module"
+    );
+}
+
+#[cfg(not(feature = "trace"))]
+pub(crate) fn module_created(_module: &crate::ffi::SpvReflectShaderModule) {}
+
+#[cfg(feature = "trace")]
+pub(crate) fn failed(result: &'static str) {
+    tracing::warn!(result, "shader module reflection failed");
+}
+
+#[cfg(not(feature = "trace"))]
+pub(crate) fn failed(_result: &'static str) {}
@@ -1,10 +1,10 @@
 use crate::ffi;
 use crate::types::{
     ReflectBindingArrayTraits, ReflectBlockVariable, ReflectImageTraits, ReflectResourceType,
-    ReflectTypeDescription,
+    ReflectShaderStageFlags, ReflectTypeDescription,
 };
 
-#[derive(Debug, Copy, Clone, Serialize, PartialEq)]
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
 pub enum ReflectDescriptorType {
     Undefined,
     Sampler,
@@ -30,8 +30,21 @@ impl Default for ReflectDescriptorType {
 pub type ReflectOrdinalBinding = u32;
 pub type ReflectOrdinalSet = u32;
 pub type ReflectDescriptorBindingSet = (ReflectOrdinalBinding, ReflectOrdinalSet);
+/// `(input_attachment_index, set, binding, is_depth)` of one input
+/// attachment, for wiring render graph subpasses.
+pub type ReflectInputAttachmentEdge = (u32, ReflectOrdinalSet, ReflectOrdinalBinding, bool);
 
-#[derive(Debug, Clone, Serialize, PartialEq)]
+/// How a storage buffer was associated with its UAV counter.
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
+pub enum ReflectCounterAssociation {
+    /// The `CounterBuffer` decoration of the buffer.
+    Decoration,
+    /// The counter's name, the buffer's followed by the suffix set with
+    /// `ShaderModuleBuilder::counter_name_suffix`.
+    NameSuffix,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct ReflectDescriptorBinding {
     pub spirv_id: u32,
     pub name: String,
@@ -41,21 +54,140 @@ pub struct ReflectDescriptorBinding {
     pub descriptor_type: ReflectDescriptorType,
     pub resource_type: ReflectResourceType,
     pub image: ReflectImageTraits,
+    /// Layout of the buffer behind one descriptor. For an array of blocks
+    /// such as `ubo[4]`, `ubo[2][3]` or `ubo[]` it is one element; `array`
+    /// and `count` tell how many descriptors there are.
     pub block: ReflectBlockVariable,
+    /// Dimensions of an array of descriptors. Arrays inside a buffer are in
+    /// the `array` of the `block` members instead.
     pub array: ReflectBindingArrayTraits,
+    /// Number of descriptors, `array.element_count()`.
     pub count: u32,
+    pub accessed: bool,
     pub uav_counter_id: u32,
     pub uav_counter_binding: Option<Box<ReflectDescriptorBinding>>,
+    /// How `uav_counter_binding` was found, `None` without a counter.
+    pub uav_counter_association: Option<ReflectCounterAssociation>,
     pub type_description: Option<ReflectTypeDescription>,
     pub word_offset: ReflectDescriptorBindingSet,
+    /// Set when the module has no name for the binding and `name` was made up
+    /// from its set and binding numbers (`_set1_binding3`).
+    pub name_synthesized: bool,
+    /// Stages of the entry points that use the binding, the `stageFlags` of
+    /// its layout binding. `PipelineReflection::descriptor_sets` merges them
+    /// across the pipeline's stages.
+    pub stage_flags: ReflectShaderStageFlags,
     #[serde(skip_serializing)]
     pub(crate) internal_data: *const ffi::SpvReflectDescriptorBinding,
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ReflectDescriptorSet {
     pub set: u32,
     pub bindings: Vec<ReflectDescriptorBinding>,
     #[serde(skip_serializing)]
     pub(crate) internal_data: *const ffi::SpvReflectDescriptorSet,
 }
+
+// `internal_data` is an opaque handle, only passed back to the C library by
+// the `change_*` methods of `ShaderModule`.
+unsafe impl Send for ReflectDescriptorBinding {}
+unsafe impl Sync for ReflectDescriptorBinding {}
+unsafe impl Send for ReflectDescriptorSet {}
+unsafe impl Sync for ReflectDescriptorSet {}
+
+eq_without_internal_data!(ReflectDescriptorBinding {
+    spirv_id,
+    name,
+    binding,
+    input_attachment_index,
+    set,
+    descriptor_type,
+    resource_type,
+    image,
+    block,
+    array,
+    count,
+    accessed,
+    uav_counter_id,
+    uav_counter_binding,
+    uav_counter_association,
+    type_description,
+    word_offset,
+    name_synthesized,
+    stage_flags,
+});
+eq_without_internal_data!(ReflectDescriptorSet { set, bindings });
+
+impl ReflectDescriptorBinding {
+    /// Names the binding `_set<set>_binding<binding>` if it has no name, and
+    /// its block the same way.
+    pub(crate) fn synthesize_names(&mut self) {
+        if self.name.is_empty() {
+            self.name = format!("_set{}_binding{}", self.set, self.binding);
+            self.name_synthesized = true;
+        }
+        if !self.block.members.is_empty() && self.block.name.is_empty() {
+            self.block.name = self.name.clone();
+            self.block.name_synthesized = true;
+        }
+    }
+
+    /// Whether the binding is an array taking `count` descriptors, one per
+    /// element, rather than a single descriptor.
+    pub fn is_descriptor_array(&self) -> bool {
+        !self.array.dims.is_empty()
+    }
+
+    /// Size of the block bound to each descriptor of a uniform or storage
+    /// buffer binding, the minimum range of its buffer views before any
+    /// runtime array. `None` for other bindings.
+    pub fn element_block_size(&self) -> Option<u32> {
+        if self.block.members.is_empty() {
+            None
+        } else {
+            Some(self.block.fixed_size())
+        }
+    }
+
+    /// Layout of the block bound to the descriptor at `indices`, one per
+    /// dimension of `array`, or `None` if they are out of bounds or the
+    /// binding is not a buffer. Every element of an array of blocks shares
+    /// the layout of `block`; a runtime array takes a single index.
+    pub fn element_block(&self, indices: &[u32]) -> Option<&ReflectBlockVariable> {
+        if self.block.members.is_empty() {
+            return None;
+        }
+        let in_bounds = match self.type_description {
+            Some(ref type_description) if type_description.traits.array.runtime => {
+                indices.len() == 1
+            }
+            _ => self.array.flatten_index(indices).is_some(),
+        };
+        if in_bounds {
+            Some(&self.block)
+        } else {
+            None
+        }
+    }
+
+    /// Compares the parts of two bindings that matter for a pipeline layout,
+    /// ignoring names and SPIR-V ids.
+    pub fn layout_compatible(&self, other: &ReflectDescriptorBinding) -> bool {
+        self.set == other.set
+            && self.binding == other.binding
+            && self.descriptor_type == other.descriptor_type
+            && self.resource_type == other.resource_type
+            && self.count == other.count
+            && self.array == other.array
+            && self.input_attachment_index == other.input_attachment_index
+            && self.image == other.image
+            && self.block.layout_compatible(&other.block)
+    }
+}
+
+impl ReflectDescriptorSet {
+    pub fn max_binding_index(&self) -> Option<u32> {
+        self.bindings.iter().map(|binding| binding.binding).max()
+    }
+}
@@ -0,0 +1,90 @@
+use num_traits::cast::FromPrimitive;
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectExecutionMode {
+    /// Raw `ExecutionMode` value, kept as-is so modes newer than
+    /// `spirv_headers` are still reported.
+    pub mode: u32,
+    pub operands: Vec<u32>,
+    /// Set for `OpExecutionModeId`, where the operands are ids of constants
+    /// rather than literals.
+    pub operands_are_ids: bool,
+}
+
+impl ReflectExecutionMode {
+    pub fn execution_mode(&self) -> Option<spirv_headers::ExecutionMode> {
+        spirv_headers::ExecutionMode::from_u32(self.mode)
+    }
+
+    pub fn is(&self, mode: spirv_headers::ExecutionMode) -> bool {
+        self.mode == mode as u32
+    }
+}
+
+bitflags! {
+    #[derive(Serialize)]
+    pub struct ReflectFloatWidthFlags: u32 {
+        const NONE = 0;
+        const FLOAT16 = 1;
+        const FLOAT32 = 2;
+        const FLOAT64 = 4;
+    }
+}
+
+impl Default for ReflectFloatWidthFlags {
+    fn default() -> Self {
+        ReflectFloatWidthFlags::NONE
+    }
+}
+
+impl ReflectFloatWidthFlags {
+    fn from_width(width: u32) -> Self {
+        match width {
+            16 => ReflectFloatWidthFlags::FLOAT16,
+            32 => ReflectFloatWidthFlags::FLOAT32,
+            64 => ReflectFloatWidthFlags::FLOAT64,
+            _ => ReflectFloatWidthFlags::NONE,
+        }
+    }
+}
+
+/// Float controls requested by an entry point (`SPV_KHR_float_controls`), per
+/// float width. Compare against `VkPhysicalDeviceFloatControlsProperties`.
+#[derive(Debug, Default, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectFloatControls {
+    pub denorm_preserve: ReflectFloatWidthFlags,
+    pub denorm_flush_to_zero: ReflectFloatWidthFlags,
+    pub signed_zero_inf_nan_preserve: ReflectFloatWidthFlags,
+    pub rounding_mode_rte: ReflectFloatWidthFlags,
+    pub rounding_mode_rtz: ReflectFloatWidthFlags,
+}
+
+impl ReflectFloatControls {
+    pub fn from_execution_modes(execution_modes: &[ReflectExecutionMode]) -> Self {
+        let mut controls = ReflectFloatControls::default();
+        for execution_mode in execution_modes {
+            let width = match execution_mode.operands.first() {
+                Some(width) => ReflectFloatWidthFlags::from_width(*width),
+                None => continue,
+            };
+            let flags = match execution_mode.execution_mode() {
+                Some(spirv_headers::ExecutionMode::DenormPreserve) => &mut controls.denorm_preserve,
+                Some(spirv_headers::ExecutionMode::DenormFlushToZero) => {
+                    &mut controls.denorm_flush_to_zero
+                }
+                Some(spirv_headers::ExecutionMode::SignedZeroInfNanPreserve) => {
+                    &mut controls.signed_zero_inf_nan_preserve
+                }
+                Some(spirv_headers::ExecutionMode::RoundingModeRTE) => {
+                    &mut controls.rounding_mode_rte
+                }
+                Some(spirv_headers::ExecutionMode::RoundingModeRTZ) => {
+                    &mut controls.rounding_mode_rtz
+                }
+                _ => continue,
+            };
+            *flags |= width;
+        }
+        controls
+    }
+}
@@ -0,0 +1,136 @@
+use crate::types::descriptor::{ReflectDescriptorBinding, ReflectDescriptorType};
+use crate::types::variable::{
+    ReflectBlockVariable, ReflectInterfaceVariable, ReflectTypeDescription, ReflectTypeFlags,
+};
+
+bitflags! {
+    #[derive(Serialize)]
+    pub struct ReflectFeatureFlags: u32 {
+        const NONE = 0;
+        const STORAGE_BUFFER_8BIT_ACCESS = 1;
+        const UNIFORM_AND_STORAGE_BUFFER_8BIT_ACCESS = 2;
+        const STORAGE_PUSH_CONSTANT_8 = 4;
+        const STORAGE_BUFFER_16BIT_ACCESS = 8;
+        const UNIFORM_AND_STORAGE_BUFFER_16BIT_ACCESS = 16;
+        const STORAGE_PUSH_CONSTANT_16 = 32;
+        const STORAGE_INPUT_OUTPUT_16 = 64;
+        const SHADER_FLOAT64 = 128;
+        const SHADER_INT64 = 256;
+        const SHADER_STORAGE_IMAGE_READ_WITHOUT_FORMAT = 512;
+        const SHADER_STORAGE_IMAGE_WRITE_WITHOUT_FORMAT = 1024;
+    }
+}
+
+fn wide_scalar_features(type_description: &Option<ReflectTypeDescription>) -> ReflectFeatureFlags {
+    match type_description {
+        Some(ref type_description) if type_description.traits.numeric.scalar.width == 64 => {
+            if type_description
+                .type_flags
+                .contains(ReflectTypeFlags::FLOAT)
+            {
+                ReflectFeatureFlags::SHADER_FLOAT64
+            } else if type_description.type_flags.contains(ReflectTypeFlags::INT) {
+                ReflectFeatureFlags::SHADER_INT64
+            } else {
+                ReflectFeatureFlags::NONE
+            }
+        }
+        _ => ReflectFeatureFlags::NONE,
+    }
+}
+
+impl Default for ReflectFeatureFlags {
+    fn default() -> Self {
+        ReflectFeatureFlags::NONE
+    }
+}
+
+impl ReflectBlockVariable {
+    /// Whether this variable or any of its nested members uses scalars of
+    /// the given bit width.
+    pub fn contains_scalar_width(&self, width: u32) -> bool {
+        (self.members.is_empty() && self.numeric.scalar.width == width)
+            || self
+                .members
+                .iter()
+                .any(|member| member.contains_scalar_width(width))
+    }
+
+    /// `shaderFloat64` and `shaderInt64` requirements of the member types.
+    pub fn wide_scalar_required_features(&self) -> ReflectFeatureFlags {
+        if self.members.is_empty() {
+            wide_scalar_features(&self.type_description)
+        } else {
+            self.members
+                .iter()
+                .fold(ReflectFeatureFlags::NONE, |features, member| {
+                    features | member.wide_scalar_required_features()
+                })
+        }
+    }
+
+    /// Features needed to use this variable as a push constant block.
+    pub fn push_constant_required_features(&self) -> ReflectFeatureFlags {
+        let mut features = self.wide_scalar_required_features();
+        if self.contains_scalar_width(8) {
+            features |= ReflectFeatureFlags::STORAGE_PUSH_CONSTANT_8;
+        }
+        if self.contains_scalar_width(16) {
+            features |= ReflectFeatureFlags::STORAGE_PUSH_CONSTANT_16;
+        }
+        features
+    }
+}
+
+impl ReflectInterfaceVariable {
+    pub fn contains_scalar_width(&self, width: u32) -> bool {
+        (self.members.is_empty() && self.numeric.scalar.width == width)
+            || self
+                .members
+                .iter()
+                .any(|member| member.contains_scalar_width(width))
+    }
+
+    pub fn required_features(&self) -> ReflectFeatureFlags {
+        let mut features = if self.members.is_empty() {
+            wide_scalar_features(&self.type_description)
+        } else {
+            self.members
+                .iter()
+                .fold(ReflectFeatureFlags::NONE, |features, member| {
+                    features | member.required_features()
+                })
+        };
+        if self.contains_scalar_width(16) {
+            features |= ReflectFeatureFlags::STORAGE_INPUT_OUTPUT_16;
+        }
+        features
+    }
+}
+
+impl ReflectDescriptorBinding {
+    /// Device features required by the storage types used in this binding's
+    /// block, such as `storageBuffer16BitAccess` for an SSBO with `float16_t`.
+    pub fn required_features(&self) -> ReflectFeatureFlags {
+        let (storage_8bit, storage_16bit) = match self.descriptor_type {
+            ReflectDescriptorType::UniformBuffer | ReflectDescriptorType::UniformBufferDynamic => (
+                ReflectFeatureFlags::UNIFORM_AND_STORAGE_BUFFER_8BIT_ACCESS,
+                ReflectFeatureFlags::UNIFORM_AND_STORAGE_BUFFER_16BIT_ACCESS,
+            ),
+            ReflectDescriptorType::StorageBuffer | ReflectDescriptorType::StorageBufferDynamic => (
+                ReflectFeatureFlags::STORAGE_BUFFER_8BIT_ACCESS,
+                ReflectFeatureFlags::STORAGE_BUFFER_16BIT_ACCESS,
+            ),
+            _ => return ReflectFeatureFlags::NONE,
+        };
+
+        let mut features = self.block.wide_scalar_required_features();
+        if self.block.contains_scalar_width(8) {
+            features |= storage_8bit;
+        }
+        if self.block.contains_scalar_width(16) {
+            features |= storage_16bit;
+        }
+        features
+    }
+}
@@ -1,5 +1,7 @@
+use crate::types::variable::ReflectConstant;
+
 #[allow(non_camel_case_types)]
-#[derive(Debug, Copy, Clone, Serialize, PartialEq)]
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
 pub enum ReflectImageFormat {
     Undefined,
     RGBA32_FLOAT,
@@ -49,8 +51,135 @@ impl Default for ReflectImageFormat {
     }
 }
 
+impl From<spirv_headers::ImageFormat> for ReflectImageFormat {
+    fn from(image_format: spirv_headers::ImageFormat) -> Self {
+        match image_format {
+            spirv_headers::ImageFormat::Unknown => ReflectImageFormat::Undefined,
+            spirv_headers::ImageFormat::Rgba32f => ReflectImageFormat::RGBA32_FLOAT,
+            spirv_headers::ImageFormat::Rgba16f => ReflectImageFormat::RGBA16_FLOAT,
+            spirv_headers::ImageFormat::R32f => ReflectImageFormat::R32_FLOAT,
+            spirv_headers::ImageFormat::Rgba8 => ReflectImageFormat::RGBA8,
+            spirv_headers::ImageFormat::Rgba8Snorm => ReflectImageFormat::RGBA8_SNORM,
+            spirv_headers::ImageFormat::Rg32f => ReflectImageFormat::RG32_FLOAT,
+            spirv_headers::ImageFormat::Rg16f => ReflectImageFormat::RG16_FLOAT,
+            spirv_headers::ImageFormat::R11fG11fB10f => ReflectImageFormat::R11G11B10_FLOAT,
+            spirv_headers::ImageFormat::R16f => ReflectImageFormat::R16_FLOAT,
+            spirv_headers::ImageFormat::Rgba16 => ReflectImageFormat::RGBA16,
+            spirv_headers::ImageFormat::Rgb10A2 => ReflectImageFormat::RGB10A2,
+            spirv_headers::ImageFormat::Rg16 => ReflectImageFormat::RG16,
+            spirv_headers::ImageFormat::Rg8 => ReflectImageFormat::RG8,
+            spirv_headers::ImageFormat::R16 => ReflectImageFormat::R16,
+            spirv_headers::ImageFormat::R8 => ReflectImageFormat::R8,
+            spirv_headers::ImageFormat::Rgba16Snorm => ReflectImageFormat::RGBA16_SNORM,
+            spirv_headers::ImageFormat::Rg16Snorm => ReflectImageFormat::RG16_SNORM,
+            spirv_headers::ImageFormat::Rg8Snorm => ReflectImageFormat::RG8_SNORM,
+            spirv_headers::ImageFormat::R16Snorm => ReflectImageFormat::R16_SNORM,
+            spirv_headers::ImageFormat::R8Snorm => ReflectImageFormat::R8_SNORM,
+            spirv_headers::ImageFormat::Rgba32i => ReflectImageFormat::RGBA32_INT,
+            spirv_headers::ImageFormat::Rgba16i => ReflectImageFormat::RGBA16_INT,
+            spirv_headers::ImageFormat::Rgba8i => ReflectImageFormat::RGBA8_INT,
+            spirv_headers::ImageFormat::R32i => ReflectImageFormat::R32_INT,
+            spirv_headers::ImageFormat::Rg32i => ReflectImageFormat::RG32_INT,
+            spirv_headers::ImageFormat::Rg16i => ReflectImageFormat::RG16_INT,
+            spirv_headers::ImageFormat::Rg8i => ReflectImageFormat::RG8_INT,
+            spirv_headers::ImageFormat::R16i => ReflectImageFormat::R16_INT,
+            spirv_headers::ImageFormat::R8i => ReflectImageFormat::R8_INT,
+            spirv_headers::ImageFormat::Rgba32ui => ReflectImageFormat::RGBA32_UINT,
+            spirv_headers::ImageFormat::Rgba16ui => ReflectImageFormat::RGBA16_UINT,
+            spirv_headers::ImageFormat::Rgba8ui => ReflectImageFormat::RGBA8_UINT,
+            spirv_headers::ImageFormat::R32ui => ReflectImageFormat::R32_UINT,
+            spirv_headers::ImageFormat::Rgb10a2ui => ReflectImageFormat::RGB10A2_UINT,
+            spirv_headers::ImageFormat::Rg32ui => ReflectImageFormat::RG32_UINT,
+            spirv_headers::ImageFormat::Rg16ui => ReflectImageFormat::RG16_UINT,
+            spirv_headers::ImageFormat::Rg8ui => ReflectImageFormat::RG8_UINT,
+            spirv_headers::ImageFormat::R16ui => ReflectImageFormat::R16_UINT,
+            spirv_headers::ImageFormat::R8ui => ReflectImageFormat::R8_UINT,
+        }
+    }
+}
+
+impl From<ReflectImageFormat> for spirv_headers::ImageFormat {
+    fn from(image_format: ReflectImageFormat) -> Self {
+        match image_format {
+            ReflectImageFormat::Undefined => spirv_headers::ImageFormat::Unknown,
+            ReflectImageFormat::RGBA32_FLOAT => spirv_headers::ImageFormat::Rgba32f,
+            ReflectImageFormat::RGBA16_FLOAT => spirv_headers::ImageFormat::Rgba16f,
+            ReflectImageFormat::R32_FLOAT => spirv_headers::ImageFormat::R32f,
+            ReflectImageFormat::RGBA8 => spirv_headers::ImageFormat::Rgba8,
+            ReflectImageFormat::RGBA8_SNORM => spirv_headers::ImageFormat::Rgba8Snorm,
+            ReflectImageFormat::RG32_FLOAT => spirv_headers::ImageFormat::Rg32f,
+            ReflectImageFormat::RG16_FLOAT => spirv_headers::ImageFormat::Rg16f,
+            ReflectImageFormat::R11G11B10_FLOAT => spirv_headers::ImageFormat::R11fG11fB10f,
+            ReflectImageFormat::R16_FLOAT => spirv_headers::ImageFormat::R16f,
+            ReflectImageFormat::RGBA16 => spirv_headers::ImageFormat::Rgba16,
+            ReflectImageFormat::RGB10A2 => spirv_headers::ImageFormat::Rgb10A2,
+            ReflectImageFormat::RG16 => spirv_headers::ImageFormat::Rg16,
+            ReflectImageFormat::RG8 => spirv_headers::ImageFormat::Rg8,
+            ReflectImageFormat::R16 => spirv_headers::ImageFormat::R16,
+            ReflectImageFormat::R8 => spirv_headers::ImageFormat::R8,
+            ReflectImageFormat::RGBA16_SNORM => spirv_headers::ImageFormat::Rgba16Snorm,
+            ReflectImageFormat::RG16_SNORM => spirv_headers::ImageFormat::Rg16Snorm,
+            ReflectImageFormat::RG8_SNORM => spirv_headers::ImageFormat::Rg8Snorm,
+            ReflectImageFormat::R16_SNORM => spirv_headers::ImageFormat::R16Snorm,
+            ReflectImageFormat::R8_SNORM => spirv_headers::ImageFormat::R8Snorm,
+            ReflectImageFormat::RGBA32_INT => spirv_headers::ImageFormat::Rgba32i,
+            ReflectImageFormat::RGBA16_INT => spirv_headers::ImageFormat::Rgba16i,
+            ReflectImageFormat::RGBA8_INT => spirv_headers::ImageFormat::Rgba8i,
+            ReflectImageFormat::R32_INT => spirv_headers::ImageFormat::R32i,
+            ReflectImageFormat::RG32_INT => spirv_headers::ImageFormat::Rg32i,
+            ReflectImageFormat::RG16_INT => spirv_headers::ImageFormat::Rg16i,
+            ReflectImageFormat::RG8_INT => spirv_headers::ImageFormat::Rg8i,
+            ReflectImageFormat::R16_INT => spirv_headers::ImageFormat::R16i,
+            ReflectImageFormat::R8_INT => spirv_headers::ImageFormat::R8i,
+            ReflectImageFormat::RGBA32_UINT => spirv_headers::ImageFormat::Rgba32ui,
+            ReflectImageFormat::RGBA16_UINT => spirv_headers::ImageFormat::Rgba16ui,
+            ReflectImageFormat::RGBA8_UINT => spirv_headers::ImageFormat::Rgba8ui,
+            ReflectImageFormat::R32_UINT => spirv_headers::ImageFormat::R32ui,
+            ReflectImageFormat::RGB10A2_UINT => spirv_headers::ImageFormat::Rgb10a2ui,
+            ReflectImageFormat::RG32_UINT => spirv_headers::ImageFormat::Rg32ui,
+            ReflectImageFormat::RG16_UINT => spirv_headers::ImageFormat::Rg16ui,
+            ReflectImageFormat::RG8_UINT => spirv_headers::ImageFormat::Rg8ui,
+            ReflectImageFormat::R16_UINT => spirv_headers::ImageFormat::R16ui,
+            ReflectImageFormat::R8_UINT => spirv_headers::ImageFormat::R8ui,
+        }
+    }
+}
+
+impl ReflectImageFormat {
+    pub fn as_raw(self) -> u32 {
+        spirv_headers::ImageFormat::from(self) as u32
+    }
+}
+
+/// The image view type implied by an image's `Dim` and `Arrayed` operands.
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
+pub enum ReflectImageViewDimension {
+    Type1d,
+    Type1dArray,
+    Type2d,
+    Type2dArray,
+    Type3d,
+    Cube,
+    CubeArray,
+}
+
+#[cfg(feature = "ash")]
+impl From<ReflectImageViewDimension> for ash::vk::ImageViewType {
+    fn from(dimension: ReflectImageViewDimension) -> Self {
+        match dimension {
+            ReflectImageViewDimension::Type1d => ash::vk::ImageViewType::TYPE_1D,
+            ReflectImageViewDimension::Type1dArray => ash::vk::ImageViewType::TYPE_1D_ARRAY,
+            ReflectImageViewDimension::Type2d => ash::vk::ImageViewType::TYPE_2D,
+            ReflectImageViewDimension::Type2dArray => ash::vk::ImageViewType::TYPE_2D_ARRAY,
+            ReflectImageViewDimension::Type3d => ash::vk::ImageViewType::TYPE_3D,
+            ReflectImageViewDimension::Cube => ash::vk::ImageViewType::CUBE,
+            ReflectImageViewDimension::CubeArray => ash::vk::ImageViewType::CUBE_ARRAY,
+        }
+    }
+}
+
 #[allow(non_camel_case_types)]
-#[derive(Debug, Copy, Clone, Serialize, PartialEq)]
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
 pub enum ReflectFormat {
     Undefined,
     R32_UINT,
@@ -65,6 +194,18 @@ pub enum ReflectFormat {
     R32G32B32A32_UINT,
     R32G32B32A32_SINT,
     R32G32B32A32_SFLOAT,
+    R64_UINT,
+    R64_SINT,
+    R64_SFLOAT,
+    R64G64_UINT,
+    R64G64_SINT,
+    R64G64_SFLOAT,
+    R64G64B64_UINT,
+    R64G64B64_SINT,
+    R64G64B64_SFLOAT,
+    R64G64B64A64_UINT,
+    R64G64B64A64_SINT,
+    R64G64B64A64_SFLOAT,
 }
 
 impl Default for ReflectFormat {
@@ -72,3 +213,160 @@ impl Default for ReflectFormat {
         ReflectFormat::Undefined
     }
 }
+
+macro_rules! format_conversions {
+    ($($reflect:ident <=> $vk:ident),* $(,)*) => {
+        #[cfg(feature = "ash")]
+        impl From<ReflectFormat> for ash::vk::Format {
+            fn from(format: ReflectFormat) -> Self {
+                match format {
+                    ReflectFormat::Undefined => ash::vk::Format::UNDEFINED,
+                    $(ReflectFormat::$reflect => ash::vk::Format::$vk,)*
+                }
+            }
+        }
+
+        #[cfg(feature = "ash")]
+        impl std::convert::TryFrom<ash::vk::Format> for ReflectFormat {
+            type Error = &'static str;
+
+            fn try_from(format: ash::vk::Format) -> Result<Self, Self::Error> {
+                match format {
+                    ash::vk::Format::UNDEFINED => Ok(ReflectFormat::Undefined),
+                    $(ash::vk::Format::$vk => Ok(ReflectFormat::$reflect),)*
+                    _ => Err("Unsupported Format"),
+                }
+            }
+        }
+    };
+}
+
+format_conversions! {
+    R32_UINT <=> R32_UINT,
+    R32_SINT <=> R32_SINT,
+    R32_SFLOAT <=> R32_SFLOAT,
+    R32G32_UINT <=> R32G32_UINT,
+    R32G32_SINT <=> R32G32_SINT,
+    R32G32_SFLOAT <=> R32G32_SFLOAT,
+    R32G32B32_UINT <=> R32G32B32_UINT,
+    R32G32B32_SINT <=> R32G32B32_SINT,
+    R32G32B32_SFLOAT <=> R32G32B32_SFLOAT,
+    R32G32B32A32_UINT <=> R32G32B32A32_UINT,
+    R32G32B32A32_SINT <=> R32G32B32A32_SINT,
+    R32G32B32A32_SFLOAT <=> R32G32B32A32_SFLOAT,
+    R64_UINT <=> R64_UINT,
+    R64_SINT <=> R64_SINT,
+    R64_SFLOAT <=> R64_SFLOAT,
+    R64G64_UINT <=> R64G64_UINT,
+    R64G64_SINT <=> R64G64_SINT,
+    R64G64_SFLOAT <=> R64G64_SFLOAT,
+    R64G64B64_UINT <=> R64G64B64_UINT,
+    R64G64B64_SINT <=> R64G64B64_SINT,
+    R64G64B64_SFLOAT <=> R64G64B64_SFLOAT,
+    R64G64B64A64_UINT <=> R64G64B64A64_UINT,
+    R64G64B64A64_SINT <=> R64G64B64A64_SINT,
+    R64G64B64A64_SFLOAT <=> R64G64B64A64_SFLOAT,
+}
+
+macro_rules! vertex_format_conversions {
+    ($($reflect:ident <=> $wgpu:ident),* $(,)*) => {
+        #[cfg(feature = "wgpu")]
+        impl std::convert::TryFrom<ReflectFormat> for wgpu_types::VertexFormat {
+            type Error = &'static str;
+
+            fn try_from(format: ReflectFormat) -> Result<Self, Self::Error> {
+                match format {
+                    $(ReflectFormat::$reflect => Ok(wgpu_types::VertexFormat::$wgpu),)*
+                    _ => Err("Unsupported Vertex Format"),
+                }
+            }
+        }
+
+        #[cfg(feature = "wgpu")]
+        impl std::convert::TryFrom<wgpu_types::VertexFormat> for ReflectFormat {
+            type Error = &'static str;
+
+            fn try_from(format: wgpu_types::VertexFormat) -> Result<Self, Self::Error> {
+                match format {
+                    $(wgpu_types::VertexFormat::$wgpu => Ok(ReflectFormat::$reflect),)*
+                    _ => Err("Unsupported Vertex Format"),
+                }
+            }
+        }
+    };
+}
+
+vertex_format_conversions! {
+    R32_UINT <=> Uint,
+    R32_SINT <=> Int,
+    R32_SFLOAT <=> Float,
+    R32G32_UINT <=> Uint2,
+    R32G32_SINT <=> Int2,
+    R32G32_SFLOAT <=> Float2,
+    R32G32B32_UINT <=> Uint3,
+    R32G32B32_SINT <=> Int3,
+    R32G32B32_SFLOAT <=> Float3,
+    R32G32B32A32_UINT <=> Uint4,
+    R32G32B32A32_SINT <=> Int4,
+    R32G32B32A32_SFLOAT <=> Float4,
+}
+
+bitflags! {
+    #[derive(Serialize)]
+    pub struct ReflectImageUsageFlags: u32 {
+        const NONE = 0;
+        const IMPLICIT_LOD = 1;
+        const EXPLICIT_LOD = 2;
+        const BIAS = 4;
+        const GRADIENT = 8;
+        const OFFSET = 16;
+        const GATHER = 32;
+        const MIN_LOD = 64;
+        const DEPTH_COMPARE = 128;
+        const PROJECTION = 256;
+        const FETCH = 512;
+        /// Accessed by `OpAtomic*` through `OpImageTexelPointer`.
+        const ATOMIC = 1024;
+        /// Atomics on a 64-bit integer image, which need
+        /// `shaderImageInt64Atomics`.
+        const ATOMIC_64 = 2048;
+        /// Read by `OpImageRead` or `OpImageSparseRead`.
+        const READ = 4096;
+        /// Written by `OpImageWrite`.
+        const WRITE = 8192;
+        /// Reads of a storage image without a declared format, which need
+        /// `shaderStorageImageReadWithoutFormat`.
+        const READ_WITHOUT_FORMAT = 16384;
+        /// Writes to a storage image without a declared format, which need
+        /// `shaderStorageImageWriteWithoutFormat`.
+        const WRITE_WITHOUT_FORMAT = 32768;
+    }
+}
+
+impl Default for ReflectImageUsageFlags {
+    fn default() -> Self {
+        ReflectImageUsageFlags::NONE
+    }
+}
+
+/// How the code uses an image or sampler binding, over all the sampling,
+/// storage and atomic instructions it reaches.
+#[derive(Debug, Default, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectImageUsage {
+    pub spirv_id: u32,
+    pub name: String,
+    pub set: u32,
+    pub binding: u32,
+    pub flags: ReflectImageUsageFlags,
+    /// Smallest and largest constant offset component outside of gathers,
+    /// to check against `minTexelOffset` and `maxTexelOffset`.
+    pub texel_offset_range: Option<(i32, i32)>,
+    /// Same for gathers, against `minTexelGatherOffset` and
+    /// `maxTexelGatherOffset`.
+    pub gather_offset_range: Option<(i32, i32)>,
+    /// Distinct constant `Bias` operands. Biases computed at run time only
+    /// show up in `flags`.
+    pub lod_biases: Vec<ReflectConstant>,
+    /// Distinct constant `MinLod` operands, like `lod_biases`.
+    pub min_lods: Vec<ReflectConstant>,
+}
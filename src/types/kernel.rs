@@ -0,0 +1,49 @@
+use crate::types::ReflectTypeDescription;
+
+/// OpenCL address space of a kernel argument.
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
+pub enum ReflectKernelAddressQualifier {
+    /// Passed by value, or a `Function` pointer.
+    Private,
+    Global,
+    Constant,
+    Local,
+    Generic,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
+pub enum ReflectAccessQualifier {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+}
+
+bitflags! {
+    /// OpenCL type qualifiers of a kernel argument.
+    #[derive(Serialize)]
+    pub struct ReflectKernelTypeQualifierFlags: u32 {
+        const NONE = 0;
+        /// `FuncParamAttr NoWrite`.
+        const CONST = 1;
+        /// `FuncParamAttr NoAlias` or `Restrict`.
+        const RESTRICT = 2;
+        const VOLATILE = 4;
+    }
+}
+
+/// A parameter of an `ExecutionModel::Kernel` entry point.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectKernelArgument {
+    pub index: u32,
+    pub spirv_id: u32,
+    pub name: String,
+    pub type_description: ReflectTypeDescription,
+    pub address_qualifier: ReflectKernelAddressQualifier,
+    /// Access qualifier of an image or pipe argument.
+    pub access_qualifier: Option<ReflectAccessQualifier>,
+    pub type_qualifiers: ReflectKernelTypeQualifierFlags,
+    /// Raw `FunctionParameterAttribute` values from `FuncParamAttr` decorations.
+    pub function_parameter_attributes: Vec<u32>,
+    pub alignment: Option<u32>,
+    pub max_byte_offset: Option<u32>,
+}
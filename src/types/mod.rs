@@ -1,18 +1,55 @@
+/// Implements `PartialEq`, `Eq` and `Hash` over every field but
+/// `internal_data`, which points into the C module, so that the same
+/// reflection from two modules compares equal.
+macro_rules! eq_without_internal_data {
+    ($name:ident { $($field:ident),* $(,)* }) => {
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                let $name { $($field,)* internal_data: _ } = self;
+                $(*$field == other.$field)&&*
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl std::hash::Hash for $name {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                let $name { $($field,)* internal_data: _ } = self;
+                $($field.hash(state);)*
+            }
+        }
+    };
+}
+
 pub mod descriptor;
+pub mod execution_mode;
+pub mod features;
 pub mod image;
+pub mod kernel;
 pub mod op;
 pub mod resource;
+pub mod stage;
 pub mod traits;
+pub mod validation;
 pub mod variable;
+pub mod warning;
 
 pub use self::descriptor::*;
+pub use self::execution_mode::*;
+pub use self::features::*;
 pub use self::image::*;
+pub use self::kernel::*;
 pub use self::op::*;
 pub use self::resource::*;
+pub use self::stage::*;
 pub use self::traits::*;
+pub use self::validation::*;
 pub use self::variable::*;
+pub use self::warning::*;
 
-#[derive(Debug, Copy, Clone, Serialize, PartialEq)]
+use num_traits::cast::FromPrimitive;
+
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
 pub enum ReflectGenerator {
     Unknown,
     KhronosLlvmSpirvTranslator,
@@ -32,3 +69,119 @@ impl Default for ReflectGenerator {
         ReflectGenerator::Unknown
     }
 }
+
+impl ReflectGenerator {
+    /// The generator of a module, from the tool id in the upper half of the
+    /// generator word of its header.
+    pub fn from_magic(generator_magic: u32) -> Self {
+        match generator_magic >> 16 {
+            6 => ReflectGenerator::KhronosLlvmSpirvTranslator,
+            7 => ReflectGenerator::KhronosSpirvToolsAssembler,
+            8 => ReflectGenerator::KhronosGlslangReferenceFrontEnd,
+            13 => ReflectGenerator::GoogleShadercOverGlslang,
+            14 => ReflectGenerator::GoogleSpiregg,
+            15 => ReflectGenerator::GoogleRspirv,
+            16 => ReflectGenerator::XLegendMesaMesairSpirvTranslator,
+            17 => ReflectGenerator::KhronosSpirvToolsLinker,
+            18 => ReflectGenerator::WineVkd3dShaderCompiler,
+            19 => ReflectGenerator::ClayClayShaderCompiler,
+            _ => ReflectGenerator::Unknown,
+        }
+    }
+}
+
+/// Build settings DXC records in a module compiled from HLSL.
+#[derive(Debug, Default, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectHlslSourceInfo {
+    /// Shader model from the `OpSource` version, e.g. `(6, 2)` for 620.
+    pub shader_model: (u32, u32),
+    /// Target profile passed with `-T`, e.g. `ps_6_2`.
+    pub profile: Option<String>,
+    /// HLSL entry point passed with `-E`, which can differ from the SPIR-V
+    /// entry point name.
+    pub entry_point: Option<String>,
+    /// Arguments from the `dxc-cl-option` `OpModuleProcessed` strings, only
+    /// present with `-Zi` or `-fspv-debug`.
+    pub options: Vec<String>,
+    /// Name of the main source file, if recorded.
+    pub file: Option<String>,
+}
+
+/// An `OpSource`, with the text of the `OpSourceContinued` instructions
+/// after it appended.
+#[derive(Debug, Default, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectSource {
+    pub language: u32,
+    pub language_version: u32,
+    /// The `OpString` naming the file, empty if not recorded.
+    pub file: String,
+    /// Empty if not embedded.
+    pub text: String,
+}
+
+impl ReflectSource {
+    pub fn get_language(&self) -> spirv_headers::SourceLanguage {
+        match spirv_headers::SourceLanguage::from_u32(self.language) {
+            Some(language) => language,
+            None => spirv_headers::SourceLanguage::Unknown,
+        }
+    }
+}
+
+/// An `OpString`: a source file name, a debug printf format or any other
+/// text a tool attached to the module.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectString {
+    pub spirv_id: u32,
+    pub value: String,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
+pub enum ReflectLinkageType {
+    Export,
+    Import,
+    LinkOnceOdr,
+}
+
+/// A function or global variable with `LinkageAttributes`, as resolved by
+/// `spirv-link`.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectLinkageSymbol {
+    pub spirv_id: u32,
+    /// Name the symbol is linked by, which can differ from its `OpName`.
+    pub name: String,
+    pub linkage_type: ReflectLinkageType,
+    pub is_function: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectFunctionParameter {
+    pub spirv_id: u32,
+    pub name: String,
+    pub type_description: ReflectTypeDescription,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectFunction {
+    pub spirv_id: u32,
+    pub name: String,
+    /// `None` for functions returning `void`.
+    pub return_type: Option<ReflectTypeDescription>,
+    pub parameters: Vec<ReflectFunctionParameter>,
+    /// Raw `FunctionControl` mask.
+    pub control: u32,
+    pub linkage: Option<ReflectLinkageSymbol>,
+    /// Unset for imported functions, which are only declared.
+    pub has_body: bool,
+}
+
+/// Code and resources that no entry point reaches. Exported symbols count as
+/// reached, since a linked module may use them.
+#[derive(Debug, Default, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectUnusedObjects {
+    /// Functions with a body that no entry point calls.
+    pub functions: Vec<ReflectFunction>,
+    /// Module-scope variables other than descriptor bindings.
+    pub global_variables: Vec<ReflectGlobalVariable>,
+    pub descriptor_bindings: Vec<ReflectDescriptorBinding>,
+}
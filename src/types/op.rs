@@ -3,7 +3,7 @@ use num_traits::cast::FromPrimitive;
 use spirv_headers;
 use std::ops::Deref;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct ReflectOp(spirv_headers::Op);
 
 impl Default for ReflectOp {
@@ -28,27 +28,98 @@ impl From<ffi::SpvOp> for ReflectOp {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub struct ReflectBuiltIn(spirv_headers::BuiltIn);
+impl From<spirv_headers::Op> for ReflectOp {
+    fn from(op: spirv_headers::Op) -> Self {
+        ReflectOp(op)
+    }
+}
+
+impl From<ReflectOp> for spirv_headers::Op {
+    fn from(op: ReflectOp) -> Self {
+        op.0
+    }
+}
+
+impl ReflectOp {
+    pub fn as_raw(self) -> u32 {
+        self.0 as u32
+    }
+}
+
+/// A `BuiltIn` decoration. Built-ins newer than `spirv_headers`, such as the
+/// mesh shading EXT, shading rate and ray tracing ones, keep their raw value;
+/// they deref and convert to `Position`, so check `built_in` for those.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectBuiltIn {
+    built_in: Option<spirv_headers::BuiltIn>,
+    raw: u32,
+}
 
 impl Default for ReflectBuiltIn {
     fn default() -> Self {
-        ReflectBuiltIn(spirv_headers::BuiltIn::Position)
+        ReflectBuiltIn::from(spirv_headers::BuiltIn::Position)
     }
 }
 
 impl Deref for ReflectBuiltIn {
     type Target = spirv_headers::BuiltIn;
     fn deref(&self) -> &spirv_headers::BuiltIn {
-        &self.0
+        self.built_in
+            .as_ref()
+            .unwrap_or(&spirv_headers::BuiltIn::Position)
     }
 }
 
 impl From<ffi::SpvBuiltIn> for ReflectBuiltIn {
     fn from(raw_built_in: ffi::SpvBuiltIn) -> Self {
-        match spirv_headers::BuiltIn::from_u32(raw_built_in as u32) {
-            Some(built_in) => ReflectBuiltIn(built_in),
-            None => Default::default(),
+        ReflectBuiltIn::from_raw(raw_built_in as u32)
+    }
+}
+
+impl From<spirv_headers::BuiltIn> for ReflectBuiltIn {
+    fn from(built_in: spirv_headers::BuiltIn) -> Self {
+        ReflectBuiltIn {
+            built_in: Some(built_in),
+            raw: built_in as u32,
+        }
+    }
+}
+
+impl From<ReflectBuiltIn> for spirv_headers::BuiltIn {
+    fn from(built_in: ReflectBuiltIn) -> Self {
+        *built_in
+    }
+}
+
+impl std::fmt::Display for ReflectBuiltIn {
+    /// The `spirv_headers` name, or the raw value for unknown built-ins.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.built_in {
+            Some(built_in) => write!(f, "{:?}", built_in),
+            None => write!(f, "{}", self.raw),
+        }
+    }
+}
+
+impl ReflectBuiltIn {
+    pub fn from_raw(raw: u32) -> Self {
+        ReflectBuiltIn {
+            built_in: spirv_headers::BuiltIn::from_u32(raw),
+            raw,
         }
     }
+
+    /// `None` for built-ins unknown to `spirv_headers`.
+    pub fn built_in(self) -> Option<spirv_headers::BuiltIn> {
+        self.built_in
+    }
+
+    /// The decoration's value, known to `spirv_headers` or not.
+    pub fn raw(self) -> u32 {
+        self.raw
+    }
+
+    pub fn as_raw(self) -> u32 {
+        self.raw
+    }
 }
@@ -0,0 +1,247 @@
+use crate::types::{ReflectInterfaceVariable, ReflectShaderStageFlags};
+
+/// Raw `ExecutionModel` and `StorageClass` values of `SPV_EXT_mesh_shader`, which are newer than
+/// `spirv_headers`.
+pub const EXECUTION_MODEL_TASK_EXT: u32 = 5364;
+pub const EXECUTION_MODEL_MESH_EXT: u32 = 5365;
+pub const STORAGE_CLASS_TASK_PAYLOAD_WORKGROUP_EXT: u32 = 5402;
+
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
+pub enum ReflectMeshOutputTopology {
+    Undefined,
+    Points,
+    Lines,
+    Triangles,
+}
+
+/// Output interface of a mesh shader (`SPV_NV_mesh_shader` or `SPV_EXT_mesh_shader`).
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectMeshTraits {
+    pub max_vertices: u32,
+    pub max_primitives: u32,
+    pub output_topology: ReflectMeshOutputTopology,
+    pub per_vertex_outputs: Vec<ReflectInterfaceVariable>,
+    pub per_primitive_outputs: Vec<ReflectInterfaceVariable>,
+    /// The `PrimitiveIndicesNV` or `Primitive*IndicesEXT` output.
+    pub primitive_indices: Option<ReflectInterfaceVariable>,
+}
+
+/// Patch interface of a tessellation control or evaluation entry point.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectTessellationTraits {
+    pub shader_stage: ReflectShaderStageFlags,
+    /// `OutputVertices`, the size of the patch written by a control stage.
+    /// Evaluation stages may declare it too (HLSL domain shaders do not).
+    pub output_vertices: u32,
+    /// Length of the per-vertex input arrays of a control stage, 0 when not
+    /// known. GLSL sizes them with `gl_MaxPatchVertices` unless declared
+    /// explicitly, HLSL with the `InputPatch` size.
+    pub input_patch_vertices: u32,
+    /// `Patch`-decorated inputs and outputs, excluding built-ins.
+    pub patch_inputs: Vec<ReflectInterfaceVariable>,
+    pub patch_outputs: Vec<ReflectInterfaceVariable>,
+    /// Whether `TessLevelOuter` / `TessLevelInner` are part of the interface,
+    /// written by a control stage or read by an evaluation stage.
+    pub uses_tess_level_outer: bool,
+    pub uses_tess_level_inner: bool,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
+pub enum ReflectGeometryInputPrimitive {
+    Undefined,
+    Points,
+    Lines,
+    LinesAdjacency,
+    Triangles,
+    TrianglesAdjacency,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
+pub enum ReflectGeometryOutputTopology {
+    Undefined,
+    Points,
+    LineStrip,
+    TriangleStrip,
+}
+
+/// Execution modes and vertex streams of a geometry entry point.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectGeometryTraits {
+    pub input_primitive: ReflectGeometryInputPrimitive,
+    pub output_topology: ReflectGeometryOutputTopology,
+    pub output_vertices: u32,
+    /// `Invocations`, 1 if not declared.
+    pub invocations: u32,
+    /// Sorted vertex streams the outputs are assigned to, through their own
+    /// or their members' `Stream` decorations; just 0 for single-stream
+    /// shaders.
+    pub streams: Vec<u32>,
+}
+
+bitflags! {
+    /// Fragment outputs that the blend and depth/stencil state have to match.
+    #[derive(Serialize)]
+    pub struct ReflectFragmentOutputFlags: u32 {
+        const NONE = 0;
+        /// Writes `FragStencilRefEXT`, which needs `VK_EXT_shader_stencil_export`.
+        const STENCIL_EXPORT = 1;
+        /// Has an output with `Index` 1, the second source of dual-source blending.
+        const DUAL_SOURCE_BLEND = 2;
+        /// Writes `FragDepth`.
+        const DEPTH_EXPORT = 4;
+        /// Writes `SampleMask`.
+        const SAMPLE_MASK_EXPORT = 8;
+    }
+}
+
+impl Default for ReflectFragmentOutputFlags {
+    fn default() -> Self {
+        ReflectFragmentOutputFlags::NONE
+    }
+}
+
+bitflags! {
+    /// The ways the code a fragment entry point can reach discards fragments.
+    /// Any of them turns off early depth and stencil tests unless the entry
+    /// point declares `EarlyFragmentTests`.
+    #[derive(Serialize)]
+    pub struct ReflectDiscardFlags: u32 {
+        const NONE = 0;
+        /// `OpKill`, which GLSL's `discard` compiles to.
+        const KILL = 1;
+        /// `OpTerminateInvocation` from `SPV_KHR_terminate_invocation`.
+        const TERMINATE_INVOCATION = 2;
+        /// `OpDemoteToHelperInvocationEXT`, which keeps the invocation
+        /// running as a helper for derivatives.
+        const DEMOTE_TO_HELPER_INVOCATION = 4;
+    }
+}
+
+impl Default for ReflectDiscardFlags {
+    fn default() -> Self {
+        ReflectDiscardFlags::NONE
+    }
+}
+
+bitflags! {
+    /// How a fragment entry point observes helper invocations, whose
+    /// behavior after a demote or discard differs between drivers.
+    #[derive(Serialize)]
+    pub struct ReflectHelperInvocationFlags: u32 {
+        const NONE = 0;
+        /// Loads the `HelperInvocation` built-in.
+        const BUILT_IN = 1;
+        /// Calls `OpIsHelperInvocationEXT`.
+        const IS_HELPER_INVOCATION = 2;
+        /// Calls `OpDemoteToHelperInvocationEXT`.
+        const DEMOTE = 4;
+    }
+}
+
+impl Default for ReflectHelperInvocationFlags {
+    fn default() -> Self {
+        ReflectHelperInvocationFlags::NONE
+    }
+}
+
+/// Rough static counts over the code an entry point can reach, for
+/// budgeting shader cost without an offline compiler. Each instruction
+/// counts once, however often loops run or functions are called.
+#[derive(Debug, Default, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectShaderMetrics {
+    /// Instructions in function bodies, not counting labels and function
+    /// declarations.
+    pub instruction_count: u32,
+    pub function_count: u32,
+    /// Basic blocks, one per `OpLabel`.
+    pub block_count: u32,
+    /// Structured loops, one per `OpLoopMerge`.
+    pub loop_count: u32,
+    /// Sampling, fetch and gather instructions.
+    pub image_sample_count: u32,
+    /// Loads and stores through pointers into uniform, storage, push
+    /// constant or physical storage buffers.
+    pub buffer_load_count: u32,
+    pub buffer_store_count: u32,
+}
+
+/// Structured control flow of one function body, or of everything an entry
+/// point can reach.
+#[derive(Debug, Default, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectControlFlow {
+    /// The function, or the entry point's function.
+    pub spirv_id: u32,
+    /// Structured loops, one per `OpLoopMerge`.
+    pub loop_count: u32,
+    /// Deepest loop nesting. For an entry point, a loop in a called function
+    /// nests inside the loops around the call.
+    pub max_loop_depth: u32,
+    /// Structured selections, one per `OpSelectionMerge`.
+    pub selection_count: u32,
+    /// `OpBranchConditional` and `OpSwitch` instructions, whose target may
+    /// differ between invocations.
+    pub divergent_branch_count: u32,
+}
+
+/// Static access sites of one descriptor binding in the code an entry point
+/// can reach, as a hint of how often it is used: for picking the bindings
+/// worth a push descriptor or root descriptor. Each site counts once,
+/// however often loops run or functions are called.
+#[derive(Debug, Default, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectBindingAccess {
+    pub spirv_id: u32,
+    pub name: String,
+    pub set: u32,
+    pub binding: u32,
+    /// Loads through buffer pointers and `OpImageRead`s.
+    pub load_count: u32,
+    pub store_count: u32,
+    /// Sampling, fetch and gather instructions.
+    pub sample_count: u32,
+    pub atomic_count: u32,
+    /// Sites inside a loop, including loops around a call to the function
+    /// holding them.
+    pub loop_access_count: u32,
+    /// Deepest loop nesting of a site.
+    pub max_loop_depth: u32,
+}
+
+impl ReflectBindingAccess {
+    /// Every site, whatever its kind.
+    pub fn access_count(&self) -> u32 {
+        self.load_count + self.store_count + self.sample_count + self.atomic_count
+    }
+}
+
+/// Something a push constant member can influence.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub enum ReflectTaintSink {
+    /// An output variable, or one member of an output block such as
+    /// `gl_PerVertex`.
+    Output {
+        spirv_id: u32,
+        member: Option<u32>,
+        name: String,
+    },
+    /// A descriptor binding accessed at an address or coordinate the member
+    /// computes, written with a value it computes, or accessed under a branch
+    /// it decides.
+    Descriptor {
+        spirv_id: u32,
+        set: u32,
+        binding: u32,
+        name: String,
+    },
+}
+
+/// The outputs and descriptors one member of the push constant block can
+/// influence, by a coarse static taint over the code an entry point can
+/// reach. A member with no sinks has no observable effect.
+#[derive(Debug, Default, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectPushConstantTaint {
+    /// Index of the member in the block.
+    pub member: u32,
+    pub name: String,
+    /// Outputs first, then descriptors by set and binding.
+    pub sinks: Vec<ReflectTaintSink>,
+}
@@ -1,50 +1,167 @@
-use crate::types::image::ReflectImageFormat;
+use crate::types::image::{ReflectImageFormat, ReflectImageViewDimension};
+use crate::types::variable::{ReflectSizeExpr, ReflectStorageClass};
 use crate::types::ReflectDimension;
 
-#[derive(Debug, Default, Clone, Serialize, PartialEq)]
+/// Array dimensions of a descriptor binding, outermost first: `s[4][3]` has
+/// `dims == [4, 3]` and takes 12 consecutive descriptors, flattened row-major
+/// so that `s[i][j]` is descriptor `i * 3 + j` of the binding.
+#[derive(Debug, Default, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct ReflectBindingArrayTraits {
     pub dims: Vec<u32>,
 }
 
-#[derive(Default, Debug, Copy, Clone, Serialize, PartialEq)]
+impl ReflectBindingArrayTraits {
+    /// Descriptors in the binding: the product of `dims`, 1 for non-arrays.
+    /// 0 if the product overflows `u32`, like `ReflectDescriptorBinding::count`.
+    pub fn element_count(&self) -> u32 {
+        self.dims
+            .iter()
+            .try_fold(1u32, |count, dim| count.checked_mul(*dim))
+            .unwrap_or(0)
+    }
+
+    /// Descriptors spanned by a step along each dimension: `[3, 1]` for
+    /// `s[4][3]`.
+    pub fn strides(&self) -> Vec<u32> {
+        let mut strides = vec![1u32; self.dims.len()];
+        for index in (0..self.dims.len().saturating_sub(1)).rev() {
+            strides[index] = strides[index + 1].saturating_mul(self.dims[index + 1]);
+        }
+        strides
+    }
+
+    /// Flat descriptor index of the element at `indices`, one per dimension,
+    /// or `None` if the number of indices is wrong or one is out of bounds.
+    pub fn flatten_index(&self, indices: &[u32]) -> Option<u32> {
+        if indices.len() != self.dims.len() {
+            return None;
+        }
+        let mut flat = 0;
+        for (index, dim) in indices.iter().zip(&self.dims) {
+            if index >= dim {
+                return None;
+            }
+            flat = flat * dim + index;
+        }
+        Some(flat)
+    }
+
+    /// Per-dimension indices of flat descriptor index `flat`, the inverse of
+    /// `flatten_index`.
+    pub fn unflatten_index(&self, mut flat: u32) -> Option<Vec<u32>> {
+        if flat >= self.element_count() {
+            return None;
+        }
+        let mut indices = vec![0; self.dims.len()];
+        for (index, dim) in indices.iter_mut().zip(&self.dims).rev() {
+            *index = flat % dim;
+            flat /= dim;
+        }
+        Some(indices)
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct ReflectNumericTraitsScalar {
     pub width: u32,
     pub signedness: u32,
 }
 
-#[derive(Default, Debug, Copy, Clone, Serialize, PartialEq)]
+#[derive(Default, Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct ReflectNumericTraitsVector {
     pub component_count: u32,
 }
 
-#[derive(Default, Debug, Copy, Clone, Serialize, PartialEq)]
+#[derive(Default, Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct ReflectNumericTraitsMatrix {
     pub column_count: u32,
     pub row_count: u32,
     pub stride: u32,
 }
 
-#[derive(Default, Debug, Copy, Clone, Serialize, PartialEq)]
+#[derive(Default, Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct ReflectNumericTraits {
     pub scalar: ReflectNumericTraitsScalar,
     pub vector: ReflectNumericTraitsVector,
     pub matrix: ReflectNumericTraitsMatrix,
 }
 
-#[derive(Default, Debug, Clone, Serialize, PartialEq)]
+/// Array dimensions of a type, outermost first. A runtime array is not
+/// listed in `dims`; `runtime` marks it instead. A length that is not an
+/// integer constant is 0 in `dims` and `Unknown` in `dim_exprs`.
+#[derive(Default, Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct ReflectArrayTraits {
     pub dims: Vec<u32>,
+    /// `ArrayStride` of the outermost array, the runtime one if `runtime`.
     pub stride: u32,
+    /// `ArrayStride` of each of `dims`: `[48, 16]` for `float a[2][3]` in
+    /// std140. Empty when only the outermost stride is known.
+    pub strides: Vec<u32>,
+    /// Whether the outermost array is an `OpTypeRuntimeArray`.
+    pub runtime: bool,
+    /// Length of each of `dims` as written in the module, telling apart
+    /// lengths that come from specialization constants. `dims` holds their
+    /// default values.
+    pub dim_exprs: Vec<ReflectSizeExpr>,
+}
+
+impl ReflectArrayTraits {
+    /// Byte stride of the dimension `index` of `dims`. Without `strides`,
+    /// inner dimensions are assumed to be packed within `stride`.
+    pub fn dim_stride(&self, index: usize) -> u32 {
+        if let Some(stride) = self.strides.get(index) {
+            return *stride;
+        }
+        let outer = if self.runtime { 0 } else { 1 };
+        let inner_count = self
+            .dims
+            .iter()
+            .take(index + 1)
+            .skip(outer)
+            .try_fold(1u32, |count, dim| count.checked_mul(*dim));
+        match inner_count {
+            Some(inner_count) => self.stride / inner_count.max(1),
+            None => 0,
+        }
+    }
+
+    /// Whether any length in `dim_exprs` depends on a specialization
+    /// constant.
+    pub fn is_specialized(&self) -> bool {
+        self.dim_exprs.iter().any(ReflectSizeExpr::is_specialized)
+    }
+
+    /// Bytes taken by the array: all of `dims`, for one element of a runtime
+    /// array. 0 if the stride is not known or the size overflows `u32`.
+    pub fn size(&self) -> u32 {
+        match self.dims.first() {
+            Some(dim) => dim.checked_mul(self.dim_stride(0)).unwrap_or(0),
+            None => self.stride,
+        }
+    }
 }
 
-#[derive(Default, Debug, Clone, Serialize, PartialEq)]
+#[derive(Default, Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct ReflectTypeDescriptionTraits {
     pub numeric: ReflectNumericTraits,
     pub image: ReflectImageTraits,
     pub array: ReflectArrayTraits,
+    /// Set for physical storage buffer pointers, such as `buffer_reference`
+    /// members, which are described as the pointer instead of its pointee.
+    pub pointer: Option<ReflectPointerTraits>,
+}
+
+/// A pointer holding a device address. Its `numeric` traits are those of a
+/// 64-bit scalar, which is how it is laid out in a block.
+#[derive(Default, Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectPointerTraits {
+    pub storage_class: ReflectStorageClass,
+    /// SPIR-V id of the pointee type, which may be a struct containing the
+    /// pointer; see `ShaderModule::get_type_description`.
+    pub pointee_type_id: u32,
 }
 
-#[derive(Default, Debug, Copy, Clone, Serialize, PartialEq)]
+#[derive(Default, Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct ReflectImageTraits {
     pub dim: ReflectDimension,
     pub depth: u32,
@@ -53,3 +170,41 @@ pub struct ReflectImageTraits {
     pub sampled: u32,
     pub image_format: ReflectImageFormat,
 }
+
+impl ReflectImageTraits {
+    pub fn is_multisampled(&self) -> bool {
+        self.ms != 0
+    }
+
+    pub fn is_arrayed(&self) -> bool {
+        self.arrayed != 0
+    }
+
+    /// Only true for images declared as depth images; a `depth` of 2 means
+    /// "unknown" and is reported as `false`.
+    pub fn is_depth(&self) -> bool {
+        self.depth == 1
+    }
+
+    /// The view type needed to bind this image, or `None` for buffers and
+    /// undefined dimensions.
+    pub fn view_dimension(&self) -> Option<ReflectImageViewDimension> {
+        let arrayed = self.is_arrayed();
+        match self.dim {
+            ReflectDimension::Type1d if arrayed => Some(ReflectImageViewDimension::Type1dArray),
+            ReflectDimension::Type1d => Some(ReflectImageViewDimension::Type1d),
+            ReflectDimension::Type2d | ReflectDimension::Rect | ReflectDimension::SubPassData
+                if arrayed =>
+            {
+                Some(ReflectImageViewDimension::Type2dArray)
+            }
+            ReflectDimension::Type2d | ReflectDimension::Rect | ReflectDimension::SubPassData => {
+                Some(ReflectImageViewDimension::Type2d)
+            }
+            ReflectDimension::Type3d => Some(ReflectImageViewDimension::Type3d),
+            ReflectDimension::Cube if arrayed => Some(ReflectImageViewDimension::CubeArray),
+            ReflectDimension::Cube => Some(ReflectImageViewDimension::Cube),
+            ReflectDimension::Buffer | ReflectDimension::Undefined => None,
+        }
+    }
+}
@@ -0,0 +1,18 @@
+/// The first problem `spirv-val` found in a module.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectValidationError {
+    pub message: String,
+    /// Index of the offending word, when the validator reports one.
+    pub word_index: Option<usize>,
+}
+
+impl std::fmt::Display for ReflectValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.word_index {
+            Some(word_index) => write!(f, "{} (word {})", self.message, word_index),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ReflectValidationError {}
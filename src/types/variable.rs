@@ -1,8 +1,9 @@
-use crate::ffi;
 use crate::types::descriptor::ReflectDescriptorSet;
 use crate::types::image::ReflectFormat;
 use crate::types::op::{ReflectBuiltIn, ReflectOp};
 use crate::types::traits::*;
+use num_traits::cast::FromPrimitive;
+use std::collections::BTreeMap;
 
 bitflags! {
     #[derive(Serialize)]
@@ -16,6 +17,8 @@ bitflags! {
         const NO_PERSPECTIVE = 32;
         const FLAT = 64;
         const NON_WRITABLE = 128;
+        // Not reported by the C library, filled in from the SPIR-V.
+        const SAMPLE = 65_536;
     }
 }
 
@@ -25,6 +28,23 @@ impl Default for ReflectDecorationFlags {
     }
 }
 
+/// A decoration as written in the module, or as applied through a decoration
+/// group.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectDecoration {
+    /// Raw `Decoration` value.
+    pub decoration: u32,
+    pub operands: Vec<u32>,
+    /// Set for `OpDecorateId`, where the operands are ids rather than literals.
+    pub operands_are_ids: bool,
+}
+
+impl ReflectDecoration {
+    pub fn is(&self, decoration: spirv_headers::Decoration) -> bool {
+        self.decoration == decoration as u32
+    }
+}
+
 bitflags! {
     #[derive(Serialize)]
     pub struct ReflectTypeFlags: u32 {
@@ -43,6 +63,8 @@ bitflags! {
         const EXTERNAL_MASK = 2_031_616;
         const STRUCT = 268_435_456;
         const ARRAY = 536_870_912;
+        /// A physical storage buffer pointer.
+        const REF = 1_073_741_824;
     }
 }
 
@@ -77,7 +99,7 @@ impl Default for ReflectShaderStageFlags {
     }
 }
 
-#[derive(Debug, Copy, Clone, Serialize, PartialEq)]
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
 pub enum ReflectDimension {
     Undefined,
     Type1d,
@@ -95,7 +117,48 @@ impl Default for ReflectDimension {
     }
 }
 
-#[derive(Default, Debug, Clone, Serialize, PartialEq)]
+impl From<spirv_headers::Dim> for ReflectDimension {
+    fn from(dim: spirv_headers::Dim) -> Self {
+        match dim {
+            spirv_headers::Dim::Dim1D => ReflectDimension::Type1d,
+            spirv_headers::Dim::Dim2D => ReflectDimension::Type2d,
+            spirv_headers::Dim::Dim3D => ReflectDimension::Type3d,
+            spirv_headers::Dim::DimCube => ReflectDimension::Cube,
+            spirv_headers::Dim::DimRect => ReflectDimension::Rect,
+            spirv_headers::Dim::DimBuffer => ReflectDimension::Buffer,
+            spirv_headers::Dim::DimSubpassData => ReflectDimension::SubPassData,
+        }
+    }
+}
+
+impl std::convert::TryFrom<ReflectDimension> for spirv_headers::Dim {
+    type Error = &'static str;
+
+    fn try_from(dim: ReflectDimension) -> Result<Self, Self::Error> {
+        match dim {
+            ReflectDimension::Undefined => Err("Undefined Dimension"),
+            ReflectDimension::Type1d => Ok(spirv_headers::Dim::Dim1D),
+            ReflectDimension::Type2d => Ok(spirv_headers::Dim::Dim2D),
+            ReflectDimension::Type3d => Ok(spirv_headers::Dim::Dim3D),
+            ReflectDimension::Cube => Ok(spirv_headers::Dim::DimCube),
+            ReflectDimension::Rect => Ok(spirv_headers::Dim::DimRect),
+            ReflectDimension::Buffer => Ok(spirv_headers::Dim::DimBuffer),
+            ReflectDimension::SubPassData => Ok(spirv_headers::Dim::DimSubpassData),
+        }
+    }
+}
+
+impl ReflectDimension {
+    /// The raw `Dim` value, or `None` for `Undefined`.
+    pub fn as_raw(self) -> Option<u32> {
+        use std::convert::TryFrom;
+        spirv_headers::Dim::try_from(self)
+            .ok()
+            .map(|dim| dim as u32)
+    }
+}
+
+#[derive(Default, Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct ReflectTypeDescription {
     pub id: u32,
     #[serde(skip_serializing)]
@@ -105,11 +168,168 @@ pub struct ReflectTypeDescription {
     pub storage_class: ReflectStorageClass,
     pub type_flags: ReflectTypeFlags,
     pub decoration_flags: ReflectDecorationFlags,
+    /// `Offset` of a struct member within its struct, `None` for other types
+    /// and for members of structs without an explicit layout. The
+    /// `ArrayStride` and `MatrixStride` are in `traits`.
+    pub offset: Option<u32>,
     pub traits: ReflectTypeDescriptionTraits,
     pub members: Vec<ReflectTypeDescription>,
 }
 
-#[derive(Default, Debug, Clone, Serialize, PartialEq)]
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
+pub enum ReflectScalarType {
+    Bool,
+    Int { width: u32, signed: bool },
+    Float { width: u32 },
+}
+
+impl ReflectTypeDescription {
+    pub fn is_float(&self) -> bool {
+        self.type_flags.contains(ReflectTypeFlags::FLOAT)
+    }
+
+    /// Whether the type is a physical storage buffer pointer, to be stored
+    /// as a 64-bit device address; `traits.pointer` describes it.
+    pub fn is_physical_pointer(&self) -> bool {
+        self.type_flags.contains(ReflectTypeFlags::REF)
+    }
+
+    pub fn is_int(&self) -> bool {
+        self.type_flags.contains(ReflectTypeFlags::INT)
+    }
+
+    pub fn is_bool(&self) -> bool {
+        self.type_flags.contains(ReflectTypeFlags::BOOL)
+    }
+
+    /// Floats are always signed; integers follow their signedness.
+    pub fn is_signed(&self) -> bool {
+        self.is_float() || (self.is_int() && self.traits.numeric.scalar.signedness != 0)
+    }
+
+    pub fn is_vector(&self) -> bool {
+        self.type_flags.contains(ReflectTypeFlags::VECTOR)
+            && !self.type_flags.contains(ReflectTypeFlags::MATRIX)
+    }
+
+    pub fn is_matrix(&self) -> bool {
+        self.type_flags.contains(ReflectTypeFlags::MATRIX)
+    }
+
+    pub fn is_array(&self) -> bool {
+        self.type_flags.contains(ReflectTypeFlags::ARRAY)
+    }
+
+    pub fn is_struct(&self) -> bool {
+        self.type_flags.contains(ReflectTypeFlags::STRUCT)
+    }
+
+    /// Bit width of the scalar component type, if the type is numeric.
+    pub fn bit_width(&self) -> Option<u32> {
+        match self.traits.numeric.scalar.width {
+            0 => None,
+            width => Some(width),
+        }
+    }
+
+    pub fn vector_size(&self) -> Option<u32> {
+        if self.is_vector() {
+            Some(self.traits.numeric.vector.component_count)
+        } else {
+            None
+        }
+    }
+
+    /// Matrix dimensions as `(columns, rows)`.
+    pub fn matrix_dims(&self) -> Option<(u32, u32)> {
+        if self.is_matrix() {
+            let matrix = &self.traits.numeric.matrix;
+            Some((matrix.column_count, matrix.row_count))
+        } else {
+            None
+        }
+    }
+
+    /// Scalar component type of a scalar, vector, matrix or array of those.
+    pub fn element_type(&self) -> Option<ReflectScalarType> {
+        if self.is_struct() {
+            None
+        } else if self.is_float() {
+            Some(ReflectScalarType::Float {
+                width: self.traits.numeric.scalar.width,
+            })
+        } else if self.is_int() {
+            Some(ReflectScalarType::Int {
+                width: self.traits.numeric.scalar.width,
+                signed: self.traits.numeric.scalar.signedness != 0,
+            })
+        } else if self.is_bool() {
+            Some(ReflectScalarType::Bool)
+        } else {
+            None
+        }
+    }
+
+    /// GLSL spelling of the type, such as `vec4`, `mat3x4` or `uvec2[8]`.
+    /// Falls back to `type_name` for types without a numeric spelling.
+    pub fn type_name_glsl(&self) -> String {
+        let base = match self.element_type() {
+            Some(element_type) => {
+                let (scalar, prefix) = match element_type {
+                    ReflectScalarType::Bool => ("bool".to_owned(), "b".to_owned()),
+                    ReflectScalarType::Float { width: 32 } => ("float".to_owned(), String::new()),
+                    ReflectScalarType::Float { width: 64 } => ("double".to_owned(), "d".to_owned()),
+                    ReflectScalarType::Float { width } => {
+                        (format!("float{}_t", width), format!("f{}", width))
+                    }
+                    ReflectScalarType::Int { width: 32, signed } => {
+                        if signed {
+                            ("int".to_owned(), "i".to_owned())
+                        } else {
+                            ("uint".to_owned(), "u".to_owned())
+                        }
+                    }
+                    ReflectScalarType::Int { width, signed } => {
+                        if signed {
+                            (format!("int{}_t", width), format!("i{}", width))
+                        } else {
+                            (format!("uint{}_t", width), format!("u{}", width))
+                        }
+                    }
+                };
+                if let Some((columns, rows)) = self.matrix_dims() {
+                    if columns == rows {
+                        format!("{}mat{}", prefix, columns)
+                    } else {
+                        format!("{}mat{}x{}", prefix, columns, rows)
+                    }
+                } else if let Some(size) = self.vector_size() {
+                    format!("{}vec{}", prefix, size)
+                } else {
+                    scalar
+                }
+            }
+            None => self.type_name.clone(),
+        };
+
+        if self.is_array() {
+            let array = &self.traits.array;
+            let base = if array.runtime {
+                format!("{}[]", base)
+            } else {
+                base
+            };
+            array.dims.iter().fold(base, |name, &dim| match dim {
+                0 => format!("{}[]", name),
+                dim => format!("{}[{}]", name, dim),
+            })
+        } else {
+            base
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct ReflectBlockVariable {
     pub spirv_id: u32,
     pub name: String,
@@ -122,9 +342,295 @@ pub struct ReflectBlockVariable {
     pub array: ReflectArrayTraits,
     pub members: Vec<ReflectBlockVariable>,
     pub type_description: Option<ReflectTypeDescription>,
+    /// Set when the module has no name for the block or member and `name`
+    /// was made up from its type id (`_type17`, `_type17_member2`).
+    pub name_synthesized: bool,
+}
+
+impl ReflectBlockVariable {
+    /// Names the block `_type<type id>` if it has no name.
+    pub(crate) fn synthesize_name(&mut self, type_id: u32) {
+        if self.name.is_empty() {
+            self.name = format!("_type{}", type_id);
+            self.name_synthesized = true;
+        }
+    }
+
+    /// Names unnamed direct members `_type<struct type id>_member<index>`.
+    pub(crate) fn synthesize_member_names(&mut self, struct_type_id: u32) {
+        for (index, member) in self.members.iter_mut().enumerate() {
+            if member.name.is_empty() {
+                member.name = format!("_type{}_member{}", struct_type_id, index);
+                member.name_synthesized = true;
+            }
+        }
+    }
+
+    /// Compares the memory layout of two blocks, ignoring names and SPIR-V ids.
+    pub fn layout_compatible(&self, other: &ReflectBlockVariable) -> bool {
+        self.offset == other.offset
+            && self.size == other.size
+            && self.padded_size == other.padded_size
+            && self.decoration_flags == other.decoration_flags
+            && self.numeric == other.numeric
+            && self.array == other.array
+            && self.members.len() == other.members.len()
+            && self
+                .members
+                .iter()
+                .zip(&other.members)
+                .all(|(a, b)| a.layout_compatible(b))
+    }
+
+    /// Byte offset from the start of the block of the member at `path`, names
+    /// separated by dots as in `lights.position`. Every array along the path,
+    /// the last member included, takes one index per dimension from
+    /// `array_indices`, outermost first, a runtime array included. Strides
+    /// come from `ReflectArrayTraits::dim_stride`. `None` if a name is not
+    /// found, the number of indices is wrong, one is out of bounds or the
+    /// offset overflows a `u32`.
+    pub fn offset_of(&self, path: &str, array_indices: &[u32]) -> Option<u32> {
+        let mut offset: u32 = 0;
+        let mut indices = array_indices;
+        let mut parent = self;
+        for name in path.split('.').filter(|name| !name.is_empty()) {
+            let member = parent.members.iter().find(|member| member.name == name)?;
+            offset = offset.checked_add(member.offset)?;
+            let array = &member.array;
+            if array.runtime {
+                let (index, rest) = indices.split_first()?;
+                offset = offset.checked_add(index.checked_mul(array.stride)?)?;
+                indices = rest;
+            }
+            if indices.len() < array.dims.len() {
+                return None;
+            }
+            let (member_indices, rest) = indices.split_at(array.dims.len());
+            for (dim_index, (dim, index)) in array.dims.iter().zip(member_indices).enumerate() {
+                // Dimensions of 0 have no bound.
+                if *dim != 0 && index >= dim {
+                    return None;
+                }
+                offset = offset.checked_add(index.checked_mul(array.dim_stride(dim_index))?)?;
+            }
+            indices = rest;
+            parent = member;
+        }
+        if indices.is_empty() {
+            Some(offset)
+        } else {
+            None
+        }
+    }
+
+    /// `MatrixStride` of a matrix member, or of the elements of an array of
+    /// matrices; `None` for other types.
+    pub fn matrix_stride(&self) -> Option<u32> {
+        if self.numeric.matrix.column_count > 0 {
+            Some(self.numeric.matrix.stride)
+        } else {
+            None
+        }
+    }
+
+    /// Majorness of a matrix member, column-major unless decorated
+    /// `RowMajor`; `None` for other types.
+    pub fn matrix_layout(&self) -> Option<ReflectMatrixLayout> {
+        if self.numeric.matrix.column_count == 0 {
+            None
+        } else if self
+            .decoration_flags
+            .contains(ReflectDecorationFlags::ROW_MAJOR)
+        {
+            Some(ReflectMatrixLayout::RowMajor)
+        } else {
+            Some(ReflectMatrixLayout::ColumnMajor)
+        }
+    }
+
+    /// Number and length of the vectors `matrix_stride` apart: the columns
+    /// of a column-major matrix or the rows of a row-major one.
+    pub(crate) fn matrix_vectors(&self) -> Option<(u32, u32)> {
+        let matrix = &self.numeric.matrix;
+        match self.matrix_layout()? {
+            ReflectMatrixLayout::ColumnMajor => Some((matrix.column_count, matrix.row_count)),
+            ReflectMatrixLayout::RowMajor => Some((matrix.row_count, matrix.column_count)),
+        }
+    }
+
+    /// The trailing runtime array of a storage buffer block, if it ends in
+    /// one. Its `size` is 0; the block's `size` covers the members before it.
+    pub fn runtime_array(&self) -> Option<&ReflectBlockVariable> {
+        self.members.last().filter(|member| member.array.runtime)
+    }
+
+    /// Bytes before the trailing runtime array, or `size` without one.
+    pub fn fixed_size(&self) -> u32 {
+        match self.runtime_array() {
+            Some(member) => member.offset,
+            None => self.size,
+        }
+    }
+
+    /// Stride of one element of the trailing runtime array, 0 without one.
+    pub fn runtime_stride(&self) -> u32 {
+        self.runtime_array().map_or(0, |member| member.array.stride)
+    }
+
+    /// Bytes a buffer bound to the block needs to hold `element_count`
+    /// elements of its trailing runtime array.
+    pub fn required_size(&self, element_count: u64) -> u64 {
+        u64::from(self.fixed_size()) + element_count * u64::from(self.runtime_stride())
+    }
 }
 
-#[derive(Debug, Copy, Clone, Serialize, PartialEq)]
+/// A push constant block with the names of its variable and type, which
+/// `ReflectBlockVariable::name` does not tell apart.
+#[derive(Debug, Default, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectPushConstantBlock {
+    /// Name of the `PushConstant` variable, empty if it has none.
+    pub name: String,
+    /// Name of the block's struct type.
+    pub type_name: String,
+    /// Stages of the entry points that use the block.
+    pub stage_flags: ReflectShaderStageFlags,
+    pub block: ReflectBlockVariable,
+}
+
+/// How the elements of a matrix are laid out in memory: a column or a row
+/// after another, `MatrixStride` bytes apart.
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
+pub enum ReflectMatrixLayout {
+    ColumnMajor,
+    RowMajor,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
+pub enum ReflectBlockLayout {
+    Std140,
+    Std430,
+    Scalar,
+}
+
+fn round_up(value: u32, alignment: u32) -> u32 {
+    if alignment <= 1 {
+        value
+    } else {
+        value.next_multiple_of(alignment)
+    }
+}
+
+impl ReflectBlockVariable {
+    /// Size of the variable when laid out following `layout`, computed from
+    /// the member types only. Explicit `Offset`, `ArrayStride` and
+    /// `MatrixStride` decorations are not taken into account.
+    pub fn size_for_layout(&self, layout: ReflectBlockLayout) -> u32 {
+        self.layout_size_and_alignment(layout).0
+    }
+
+    pub fn alignment_for_layout(&self, layout: ReflectBlockLayout) -> u32 {
+        self.layout_size_and_alignment(layout).1
+    }
+
+    /// Offsets of the direct members when laid out following `layout`.
+    pub fn member_offsets_for_layout(&self, layout: ReflectBlockLayout) -> Vec<u32> {
+        let mut offset = 0;
+        self.members
+            .iter()
+            .map(|member| {
+                let (size, alignment) = member.layout_size_and_alignment(layout);
+                let member_offset = round_up(offset, alignment);
+                offset = member_offset + size;
+                member_offset
+            })
+            .collect()
+    }
+
+    /// Replaces offsets and sizes with the ones implied by `layout`, for
+    /// storage classes without explicit layout decorations.
+    pub(crate) fn apply_layout(&mut self, layout: ReflectBlockLayout, absolute_offset: u32) {
+        let offsets = self.member_offsets_for_layout(layout);
+        for (member, offset) in self.members.iter_mut().zip(offsets) {
+            member.offset = offset;
+            member.apply_layout(layout, absolute_offset + offset);
+        }
+        self.absolute_offset = absolute_offset;
+        self.size = self.size_for_layout(layout);
+        self.padded_size = self.size;
+    }
+
+    fn layout_size_and_alignment(&self, layout: ReflectBlockLayout) -> (u32, u32) {
+        let (element_size, element_alignment) = self.element_size_and_alignment(layout);
+        if self.array.dims.is_empty() && !self.array.runtime {
+            return (element_size, element_alignment);
+        }
+
+        let alignment = match layout {
+            ReflectBlockLayout::Std140 => round_up(element_alignment, 16),
+            _ => element_alignment,
+        };
+        let stride = round_up(element_size, alignment);
+        // Runtime arrays, marked as such or with a dimension of 0, don't
+        // contribute to the size.
+        let count = if self.array.runtime {
+            0
+        } else {
+            self.array.dims.iter().product()
+        };
+        (stride * count, alignment)
+    }
+
+    fn element_size_and_alignment(&self, layout: ReflectBlockLayout) -> (u32, u32) {
+        if !self.members.is_empty() {
+            let mut offset = 0;
+            let mut alignment = 1;
+            for member in &self.members {
+                let (member_size, member_alignment) = member.layout_size_and_alignment(layout);
+                offset = round_up(offset, member_alignment) + member_size;
+                alignment = alignment.max(member_alignment);
+            }
+            if layout == ReflectBlockLayout::Std140 {
+                alignment = round_up(alignment, 16);
+            }
+            return (round_up(offset, alignment), alignment);
+        }
+
+        let scalar_size = match self.numeric.scalar.width {
+            0 => 4,
+            width => (width / 8).max(1),
+        };
+        let vector_alignment = |component_count: u32| match layout {
+            ReflectBlockLayout::Scalar => scalar_size,
+            _ => match component_count {
+                2 => 2 * scalar_size,
+                3 | 4 => 4 * scalar_size,
+                _ => scalar_size,
+            },
+        };
+
+        if let Some((vector_count, vector_length)) = self
+            .matrix_vectors()
+            .filter(|&(vector_count, vector_length)| vector_count > 0 && vector_length > 0)
+        {
+            // In std140 the columns, or rows, of a matrix are laid out like
+            // an array of vectors, rounded up to 16 bytes each.
+            let alignment = match layout {
+                ReflectBlockLayout::Std140 => round_up(vector_alignment(vector_length), 16),
+                _ => vector_alignment(vector_length),
+            };
+            let stride = round_up(vector_length * scalar_size, alignment);
+            return (vector_count * stride, alignment);
+        }
+
+        let component_count = self.numeric.vector.component_count.max(1);
+        (
+            component_count * scalar_size,
+            vector_alignment(component_count),
+        )
+    }
+}
+
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
 pub enum ReflectStorageClass {
     Undefined,
     UniformConstant,
@@ -140,6 +646,7 @@ pub enum ReflectStorageClass {
     AtomicCounter,
     Image,
     StorageBuffer,
+    PhysicalStorageBuffer,
 }
 
 impl Default for ReflectStorageClass {
@@ -148,11 +655,87 @@ impl Default for ReflectStorageClass {
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+impl std::convert::TryFrom<spirv_headers::StorageClass> for ReflectStorageClass {
+    type Error = &'static str;
+
+    fn try_from(storage_class: spirv_headers::StorageClass) -> Result<Self, Self::Error> {
+        match storage_class {
+            spirv_headers::StorageClass::UniformConstant => {
+                Ok(ReflectStorageClass::UniformConstant)
+            }
+            spirv_headers::StorageClass::Input => Ok(ReflectStorageClass::Input),
+            spirv_headers::StorageClass::Uniform => Ok(ReflectStorageClass::Uniform),
+            spirv_headers::StorageClass::Output => Ok(ReflectStorageClass::Output),
+            spirv_headers::StorageClass::Workgroup => Ok(ReflectStorageClass::WorkGroup),
+            spirv_headers::StorageClass::CrossWorkgroup => Ok(ReflectStorageClass::CrossWorkGroup),
+            spirv_headers::StorageClass::Private => Ok(ReflectStorageClass::Private),
+            spirv_headers::StorageClass::Function => Ok(ReflectStorageClass::Function),
+            spirv_headers::StorageClass::Generic => Ok(ReflectStorageClass::Generic),
+            spirv_headers::StorageClass::PushConstant => Ok(ReflectStorageClass::PushConstant),
+            spirv_headers::StorageClass::AtomicCounter => Ok(ReflectStorageClass::AtomicCounter),
+            spirv_headers::StorageClass::Image => Ok(ReflectStorageClass::Image),
+            spirv_headers::StorageClass::StorageBuffer => Ok(ReflectStorageClass::StorageBuffer),
+            spirv_headers::StorageClass::PhysicalStorageBuffer => {
+                Ok(ReflectStorageClass::PhysicalStorageBuffer)
+            }
+            _ => Err("Unsupported Storage Class"),
+        }
+    }
+}
+
+impl std::convert::TryFrom<ReflectStorageClass> for spirv_headers::StorageClass {
+    type Error = &'static str;
+
+    fn try_from(storage_class: ReflectStorageClass) -> Result<Self, Self::Error> {
+        match storage_class {
+            ReflectStorageClass::Undefined => Err("Undefined Storage Class"),
+            ReflectStorageClass::UniformConstant => {
+                Ok(spirv_headers::StorageClass::UniformConstant)
+            }
+            ReflectStorageClass::Input => Ok(spirv_headers::StorageClass::Input),
+            ReflectStorageClass::Uniform => Ok(spirv_headers::StorageClass::Uniform),
+            ReflectStorageClass::Output => Ok(spirv_headers::StorageClass::Output),
+            ReflectStorageClass::WorkGroup => Ok(spirv_headers::StorageClass::Workgroup),
+            ReflectStorageClass::CrossWorkGroup => Ok(spirv_headers::StorageClass::CrossWorkgroup),
+            ReflectStorageClass::Private => Ok(spirv_headers::StorageClass::Private),
+            ReflectStorageClass::Function => Ok(spirv_headers::StorageClass::Function),
+            ReflectStorageClass::Generic => Ok(spirv_headers::StorageClass::Generic),
+            ReflectStorageClass::PushConstant => Ok(spirv_headers::StorageClass::PushConstant),
+            ReflectStorageClass::AtomicCounter => Ok(spirv_headers::StorageClass::AtomicCounter),
+            ReflectStorageClass::Image => Ok(spirv_headers::StorageClass::Image),
+            ReflectStorageClass::StorageBuffer => Ok(spirv_headers::StorageClass::StorageBuffer),
+            ReflectStorageClass::PhysicalStorageBuffer => {
+                Ok(spirv_headers::StorageClass::PhysicalStorageBuffer)
+            }
+        }
+    }
+}
+
+impl ReflectStorageClass {
+    /// The raw `StorageClass` value, or `None` for `Undefined`.
+    pub fn as_raw(self) -> Option<u32> {
+        use std::convert::TryFrom;
+        spirv_headers::StorageClass::try_from(self)
+            .ok()
+            .map(|storage_class| storage_class as u32)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct ReflectInterfaceVariable {
     pub spirv_id: u32,
     pub name: String,
+    /// For a block member, its own `Location` decoration or else the
+    /// location following the previous member.
     pub location: u32,
+    /// `Component` decoration: the first component the variable takes in its
+    /// location, 0 if not decorated.
+    pub component: u32,
+    /// `Index` decoration of a fragment output: 1 for the second source of
+    /// dual-source blending, 0 otherwise.
+    pub index: u32,
+    /// `Stream` decoration of a geometry shader output, 0 if not decorated.
+    pub stream: u32,
     pub storage_class: ReflectStorageClass,
     pub semantic: String,
     pub decoration_flags: ReflectDecorationFlags,
@@ -164,11 +747,175 @@ pub struct ReflectInterfaceVariable {
     pub format: ReflectFormat,
     pub type_description: Option<ReflectTypeDescription>,
     pub word_offset: u32,
+    /// Set when the module has no name for the variable or member and `name`
+    /// was made up (`_location2`, `_id31`, `_type17_member2`).
+    pub name_synthesized: bool,
+}
+
+impl ReflectInterfaceVariable {
+    /// Names the variable `_location<location>` if it has no name, or
+    /// `_id<spirv id>` for built-ins.
+    pub(crate) fn synthesize_name(&mut self) {
+        if self.name.is_empty() {
+            self.name = if self
+                .decoration_flags
+                .contains(ReflectDecorationFlags::BUILT_IN)
+            {
+                format!("_id{}", self.spirv_id)
+            } else {
+                format!("_location{}", self.location)
+            };
+            self.name_synthesized = true;
+        }
+    }
+
+    /// Names unnamed direct members `_type<struct type id>_member<index>`.
+    pub(crate) fn synthesize_member_names(&mut self) {
+        let type_id = self
+            .type_description
+            .as_ref()
+            .map_or(0, |type_description| type_description.id);
+        for (index, member) in self.members.iter_mut().enumerate() {
+            if member.name.is_empty() {
+                member.name = format!("_type{}_member{}", type_id, index);
+                member.name_synthesized = true;
+            }
+        }
+    }
+
+    /// Whether the variable is a built-in or a block of built-ins only, such
+    /// as `gl_PerVertex`.
+    pub fn is_built_in(&self) -> bool {
+        self.decoration_flags
+            .contains(ReflectDecorationFlags::BUILT_IN)
+            || (!self.members.is_empty()
+                && self
+                    .members
+                    .iter()
+                    .all(ReflectInterfaceVariable::is_built_in))
+    }
+
+    /// Number of locations consumed by the variable. 64-bit three and four
+    /// component vectors take two locations each; built-ins take none.
+    pub fn location_count(&self) -> u32 {
+        if self
+            .decoration_flags
+            .contains(ReflectDecorationFlags::BUILT_IN)
+        {
+            return 0;
+        }
+
+        let element_count = if !self.members.is_empty() {
+            self.members
+                .iter()
+                .map(|member| member.location_count())
+                .sum()
+        } else {
+            let matrix = &self.numeric.matrix;
+            let (columns, component_count) = if matrix.column_count > 0 {
+                (matrix.column_count, matrix.row_count)
+            } else {
+                (1, self.numeric.vector.component_count)
+            };
+            if self.numeric.scalar.width == 64 && component_count > 2 {
+                columns * 2
+            } else {
+                columns
+            }
+        };
+        self.array.dims.iter().product::<u32>() * element_count
+    }
+}
+
+/// One scalar, vector or matrix column of a stage interface, with interface
+/// blocks and arrays flattened to the locations they occupy.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectInterfaceLocation {
+    /// Id of the interface variable.
+    pub spirv_id: u32,
+    /// Path from the variable, e.g. `vs_out.uv[1]`.
+    pub name: String,
+    /// Struct member indices from the variable down to this location.
+    pub member_path: Vec<u32>,
+    pub location: u32,
+    pub component: u32,
+    /// 2 for 64-bit three and four component vectors, 1 otherwise.
+    pub location_count: u32,
+    pub numeric: ReflectNumericTraits,
+    pub format: ReflectFormat,
+}
+
+/// One input or output of an entry point, reduced to what decides whether
+/// two stages link: where it sits and what format it has.
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectIoSignatureEntry {
+    /// 0 for built-ins.
+    pub location: u32,
+    pub component: u32,
+    pub format: ReflectFormat,
     #[serde(skip_serializing)]
-    pub(crate) internal_data: *const ffi::SpvReflectInterfaceVariable,
+    pub built_in: Option<ReflectBuiltIn>,
+}
+
+/// The inputs and outputs of an entry point in a canonical order, for
+/// pipeline cache keys and quick checks that one stage feeds the next.
+/// Interface blocks are flattened into their members; array sizes are left
+/// out, as per-vertex arrays differ between stages.
+#[derive(Debug, Default, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectIoSignature {
+    /// Sorted by location and component, built-ins last.
+    pub inputs: Vec<ReflectIoSignatureEntry>,
+    pub outputs: Vec<ReflectIoSignatureEntry>,
+}
+
+impl ReflectIoSignature {
+    /// Whether every input of `next` other than built-ins is an output of
+    /// this stage at the same location and component with the same format.
+    pub fn feeds(&self, next: &ReflectIoSignature) -> bool {
+        next.inputs
+            .iter()
+            .filter(|input| input.built_in.is_none())
+            .all(|input| self.outputs.contains(input))
+    }
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// How many times an entry point uses one extended instruction.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectExtInstUsage {
+    /// Name of the `OpExtInstImport`, e.g. `GLSL.std.450`.
+    pub set: String,
+    pub instruction: u32,
+    /// Instruction name for `GLSL.std.450`, e.g. `Fma`; empty for other sets.
+    pub name: String,
+    pub count: u32,
+}
+
+/// An `OpControlBarrier` or `OpMemoryBarrier` an entry point uses, with how
+/// many times it appears in the code. Scopes and semantics that are not plain
+/// constants, e.g. specialization constants, are `None`.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectBarrier {
+    /// Set for `OpControlBarrier`, which also waits for the invocations of
+    /// `execution_scope`.
+    pub control: bool,
+    /// Raw `Scope`; `None` for `OpMemoryBarrier`.
+    pub execution_scope: Option<u32>,
+    /// Raw `Scope` the memory is ordered at.
+    pub memory_scope: Option<u32>,
+    /// Raw `MemorySemantics` mask.
+    pub semantics: Option<u32>,
+    pub count: u32,
+}
+
+impl ReflectBarrier {
+    /// `semantics` as flags, dropping bits unknown to `spirv_headers`.
+    pub fn memory_semantics(&self) -> Option<spirv_headers::MemorySemantics> {
+        self.semantics
+            .map(spirv_headers::MemorySemantics::from_bits_truncate)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct ReflectEntryPoint {
     pub name: String,
     pub id: u32,
@@ -180,4 +927,344 @@ pub struct ReflectEntryPoint {
     pub descriptor_sets: Vec<ReflectDescriptorSet>,
     pub used_uniforms: Vec<u32>,
     pub used_push_constants: Vec<u32>,
+    pub(crate) ext_inst_usage: Vec<ReflectExtInstUsage>,
+    pub(crate) barriers: Vec<ReflectBarrier>,
+    #[serde(skip_serializing)]
+    pub(crate) input_locations: BTreeMap<u32, usize>,
+    #[serde(skip_serializing)]
+    pub(crate) output_locations: BTreeMap<u32, usize>,
+    #[serde(skip_serializing)]
+    pub(crate) output_built_ins: BTreeMap<spirv_headers::BuiltIn, (usize, Option<usize>)>,
+}
+
+fn push_io_signature(
+    variable: &ReflectInterfaceVariable,
+    entries: &mut Vec<ReflectIoSignatureEntry>,
+) {
+    if variable
+        .decoration_flags
+        .contains(ReflectDecorationFlags::BUILT_IN)
+    {
+        entries.push(ReflectIoSignatureEntry {
+            location: 0,
+            component: 0,
+            format: variable.format,
+            built_in: Some(variable.built_in),
+        });
+    } else if variable.members.is_empty() {
+        entries.push(ReflectIoSignatureEntry {
+            location: variable.location,
+            component: variable.component,
+            format: variable.format,
+            built_in: None,
+        });
+    } else {
+        for member in &variable.members {
+            push_io_signature(member, entries);
+        }
+    }
+}
+
+fn io_signature_entries(variables: &[ReflectInterfaceVariable]) -> Vec<ReflectIoSignatureEntry> {
+    let mut entries = Vec::new();
+    for variable in variables {
+        push_io_signature(variable, &mut entries);
+    }
+    entries.sort_by_key(|entry| {
+        (
+            entry.built_in.map(ReflectBuiltIn::raw),
+            entry.location,
+            entry.component,
+        )
+    });
+    entries
+}
+
+impl ReflectEntryPoint {
+    /// The inputs and outputs in canonical form, for pipeline cache keys
+    /// and stage compatibility checks.
+    pub fn io_signature(&self) -> ReflectIoSignature {
+        ReflectIoSignature {
+            inputs: io_signature_entries(&self.input_variables),
+            outputs: io_signature_entries(&self.output_variables),
+        }
+    }
+
+    /// Extended instructions used by the functions the entry point can
+    /// reach, with how often each appears in the code.
+    pub fn ext_inst_usage(&self) -> &[ReflectExtInstUsage] {
+        &self.ext_inst_usage
+    }
+
+    /// Barriers in the functions the entry point can reach, one per distinct
+    /// kind, scopes and semantics.
+    pub fn barriers(&self) -> &[ReflectBarrier] {
+        &self.barriers
+    }
+
+    /// Whether the entry point waits for the other invocations of its
+    /// workgroup with an `OpControlBarrier`.
+    pub fn uses_workgroup_barrier(&self) -> bool {
+        self.barriers.iter().any(|barrier| {
+            barrier.control
+                && barrier.execution_scope == Some(spirv_headers::Scope::Workgroup as u32)
+        })
+    }
+
+    /// Whether any barrier orders memory at `Device` scope or wider, which
+    /// makes writes visible to other workgroups.
+    pub fn uses_device_memory_scope(&self) -> bool {
+        self.barriers.iter().any(|barrier| {
+            barrier.memory_scope == Some(spirv_headers::Scope::Device as u32)
+                || barrier.memory_scope == Some(spirv_headers::Scope::CrossDevice as u32)
+        })
+    }
+
+    /// Bit `n` is set when the entry point accesses a binding of descriptor
+    /// set `n`. Sets numbered 64 and above are left out.
+    pub fn used_set_mask(&self) -> u64 {
+        self.descriptor_sets
+            .iter()
+            .filter(|set| set.set < 64 && !set.bindings.is_empty())
+            .fold(0, |mask, set| mask | (1 << set.set))
+    }
+
+    /// Whether the entry point reads `SampleId`, `SamplePosition` or a
+    /// `Sample` input, which makes the pipeline shade every sample and needs
+    /// the `sampleRateShading` feature.
+    pub fn forces_sample_shading(&self) -> bool {
+        fn per_sample(variable: &ReflectInterfaceVariable) -> bool {
+            variable
+                .decoration_flags
+                .contains(ReflectDecorationFlags::SAMPLE)
+                || (variable
+                    .decoration_flags
+                    .contains(ReflectDecorationFlags::BUILT_IN)
+                    && matches!(
+                        variable.built_in.built_in(),
+                        Some(spirv_headers::BuiltIn::SampleId)
+                            | Some(spirv_headers::BuiltIn::SamplePosition)
+                    ))
+                || variable.members.iter().any(per_sample)
+        }
+        self.input_variables.iter().any(per_sample)
+    }
+
+    pub fn input_by_location(&self, location: u32) -> Option<&ReflectInterfaceVariable> {
+        self.input_locations
+            .get(&location)
+            .and_then(|&index| self.input_variables.get(index))
+    }
+
+    pub fn output_by_location(&self, location: u32) -> Option<&ReflectInterfaceVariable> {
+        self.output_locations
+            .get(&location)
+            .and_then(|&index| self.output_variables.get(index))
+    }
+
+    /// Finds the output variable decorated with `built_in`, looking inside
+    /// built-in blocks such as `gl_PerVertex` as well.
+    pub fn builtin_output(
+        &self,
+        built_in: spirv_headers::BuiltIn,
+    ) -> Option<&ReflectInterfaceVariable> {
+        let &(index, member) = self.output_built_ins.get(&built_in)?;
+        let variable = self.output_variables.get(index)?;
+        match member {
+            Some(member) => variable.members.get(member),
+            None => Some(variable),
+        }
+    }
+}
+
+pub(crate) fn build_location_map(variables: &[ReflectInterfaceVariable]) -> BTreeMap<u32, usize> {
+    let mut locations = BTreeMap::new();
+    for (index, variable) in variables.iter().enumerate() {
+        if !variable
+            .decoration_flags
+            .contains(ReflectDecorationFlags::BUILT_IN)
+        {
+            locations.entry(variable.location).or_insert(index);
+        }
+    }
+    locations
+}
+
+pub(crate) fn build_built_in_map(
+    variables: &[ReflectInterfaceVariable],
+) -> BTreeMap<spirv_headers::BuiltIn, (usize, Option<usize>)> {
+    let mut built_ins = BTreeMap::new();
+    for (index, variable) in variables.iter().enumerate() {
+        if variable
+            .decoration_flags
+            .contains(ReflectDecorationFlags::BUILT_IN)
+        {
+            if let Some(built_in) = variable.built_in.built_in() {
+                built_ins.entry(built_in).or_insert((index, None));
+            }
+        }
+        for (member_index, member) in variable.members.iter().enumerate() {
+            if member
+                .decoration_flags
+                .contains(ReflectDecorationFlags::BUILT_IN)
+            {
+                if let Some(built_in) = member.built_in.built_in() {
+                    built_ins
+                        .entry(built_in)
+                        .or_insert((index, Some(member_index)));
+                }
+            }
+        }
+    }
+    built_ins
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub enum ReflectConstantValue {
+    /// `OpConstantNull`.
+    Null,
+    Bool(bool),
+    /// Literal words of a scalar, low-order word first.
+    Scalar(Vec<u32>),
+    Composite(Vec<ReflectConstant>),
+    /// `OpUndef`, `OpSpecConstantOp`, samplers, or an id that is not a constant.
+    Unknown,
+}
+
+/// A constant resolved through the module's constant table. Specialization
+/// constants hold their default value.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectConstant {
+    pub spirv_id: u32,
+    pub is_specialization: bool,
+    pub value: ReflectConstantValue,
+}
+
+impl ReflectConstant {
+    /// Whether every component is known to be zero (or false). Specialization
+    /// constants can be overridden, so they never count.
+    pub fn is_zero(&self) -> bool {
+        if self.is_specialization {
+            return false;
+        }
+        match self.value {
+            ReflectConstantValue::Null => true,
+            ReflectConstantValue::Bool(value) => !value,
+            ReflectConstantValue::Scalar(ref words) => words.iter().all(|word| *word == 0),
+            ReflectConstantValue::Composite(ref constituents) => {
+                constituents.iter().all(ReflectConstant::is_zero)
+            }
+            ReflectConstantValue::Unknown => false,
+        }
+    }
+
+    /// The value of a 32-bit float scalar, such as a LOD bias.
+    pub fn to_f32(&self) -> Option<f32> {
+        match self.value {
+            ReflectConstantValue::Null => Some(0.0),
+            ReflectConstantValue::Scalar(ref words) if words.len() == 1 => {
+                Some(f32::from_bits(words[0]))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A size that may depend on specialization constants, e.g. an array length
+/// or a `WorkgroupSize` component.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub enum ReflectSizeExpr {
+    Constant(u32),
+    /// A specialization constant with its `SpecId` and default value.
+    SpecConstant {
+        spec_id: u32,
+        default: u32,
+    },
+    /// `OpSpecConstantOp`; `opcode` is the raw opcode of the operation.
+    Op {
+        opcode: u32,
+        operands: Vec<ReflectSizeExpr>,
+    },
+    /// Not an integer scalar constant fitting in 32 bits, such as a
+    /// composite or a float, or too deeply nested.
+    Unknown,
+}
+
+impl ReflectSizeExpr {
+    /// Whether the value can change with specialization.
+    pub fn is_specialized(&self) -> bool {
+        match self {
+            ReflectSizeExpr::Constant(_) | ReflectSizeExpr::Unknown => false,
+            ReflectSizeExpr::SpecConstant { .. } => true,
+            ReflectSizeExpr::Op { operands, .. } => operands.iter().any(Self::is_specialized),
+        }
+    }
+
+    /// Value with every specialization constant at its default.
+    pub fn default_value(&self) -> Option<u32> {
+        self.evaluate(&|_| None)
+    }
+
+    /// Value with the specialization constants `spec_value` returns for a
+    /// `SpecId`; `None` falls back to the default. Operations other than
+    /// integer arithmetic, bitwise ops and shifts evaluate to `None`.
+    pub fn evaluate<F: Fn(u32) -> Option<u32>>(&self, spec_value: &F) -> Option<u32> {
+        use spirv_headers::Op;
+        match self {
+            ReflectSizeExpr::Constant(value) => Some(*value),
+            ReflectSizeExpr::SpecConstant { spec_id, default } => {
+                Some(spec_value(*spec_id).unwrap_or(*default))
+            }
+            ReflectSizeExpr::Unknown => None,
+            ReflectSizeExpr::Op { opcode, operands } => {
+                let values = operands
+                    .iter()
+                    .map(|operand| operand.evaluate(spec_value))
+                    .collect::<Option<Vec<u32>>>()?;
+                let op = Op::from_u32(*opcode)?;
+                match (op, values.as_slice()) {
+                    (Op::UConvert, &[a]) | (Op::SConvert, &[a]) => Some(a),
+                    (Op::SNegate, &[a]) => Some((a as i32).wrapping_neg() as u32),
+                    (Op::Not, &[a]) => Some(!a),
+                    (Op::IAdd, &[a, b]) => Some(a.wrapping_add(b)),
+                    (Op::ISub, &[a, b]) => Some(a.wrapping_sub(b)),
+                    (Op::IMul, &[a, b]) => Some(a.wrapping_mul(b)),
+                    (Op::UDiv, &[a, b]) => a.checked_div(b),
+                    (Op::SDiv, &[a, b]) => (a as i32).checked_div(b as i32).map(|v| v as u32),
+                    (Op::UMod, &[a, b]) => a.checked_rem(b),
+                    (Op::SRem, &[a, b]) => (a as i32).checked_rem(b as i32).map(|v| v as u32),
+                    (Op::SMod, &[a, b]) => {
+                        // Like `SRem`, but takes the sign of the divisor.
+                        let (a, b) = (a as i32, b as i32);
+                        let r = a.checked_rem(b)?;
+                        Some(if r != 0 && (r < 0) != (b < 0) {
+                            r.wrapping_add(b)
+                        } else {
+                            r
+                        } as u32)
+                    }
+                    (Op::ShiftLeftLogical, &[a, b]) => a.checked_shl(b),
+                    (Op::ShiftRightLogical, &[a, b]) => a.checked_shr(b),
+                    (Op::ShiftRightArithmetic, &[a, b]) => {
+                        (a as i32).checked_shr(b).map(|v| v as u32)
+                    }
+                    (Op::BitwiseOr, &[a, b]) => Some(a | b),
+                    (Op::BitwiseXor, &[a, b]) => Some(a ^ b),
+                    (Op::BitwiseAnd, &[a, b]) => Some(a & b),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+/// A module-scope variable that is neither a resource nor part of the
+/// stage interface, e.g. `Private` and `Workgroup` globals.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub struct ReflectGlobalVariable {
+    pub spirv_id: u32,
+    pub name: String,
+    pub storage_class: ReflectStorageClass,
+    pub type_description: ReflectTypeDescription,
+    pub initializer: Option<ReflectConstant>,
 }
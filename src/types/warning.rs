@@ -0,0 +1,50 @@
+/// Non-fatal findings about a module that is valid but probably not what its
+/// author intended.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+pub enum ReflectWarning {
+    /// A resource variable without a `DescriptorSet` decoration.
+    MissingDescriptorSet { spirv_id: u32, name: String },
+    /// A resource variable without a `Binding` decoration.
+    MissingBinding { spirv_id: u32, name: String },
+    /// A user-defined entry point input or output without a `Location`
+    /// decoration. For blocks, `member` is the undecorated member.
+    MissingLocation {
+        spirv_id: u32,
+        name: String,
+        member: Option<u32>,
+    },
+    /// A member of a struct used in a buffer or push constant block without
+    /// an `Offset` decoration.
+    MissingOffset { type_id: u32, member: u32 },
+    /// A `Block` or `BufferBlock` struct without an `OpName`, in a module
+    /// that names other ids.
+    UnnamedBlock { type_id: u32 },
+    /// A descriptor binding that no entry point statically accesses.
+    UnusedBinding {
+        spirv_id: u32,
+        name: String,
+        set: u32,
+        binding: u32,
+    },
+    /// A decoration applied more than once to the same id or member, e.g.
+    /// after linking or through decoration groups. The last one wins.
+    DuplicateDecoration {
+        target_id: u32,
+        member: Option<u32>,
+        decoration: u32,
+    },
+    /// An `OpDecorate` or `OpMemberDecorate` targeting an id that is never
+    /// defined or used.
+    DecorationOnUnknownId { target_id: u32, decoration: u32 },
+    /// A name, string, semantic or entry point name that is not valid
+    /// UTF-8; it is reflected with U+FFFD in place of the invalid bytes.
+    /// `target_id` is the id the string names, or the `OpString` result.
+    InvalidUtf8 {
+        target_id: u32,
+        member: Option<u32>,
+        opcode: u32,
+    },
+    /// An `OpTypeForwardPointer` without an `OpTypePointer` of the same
+    /// storage class. Types using it see a pointer to an unknown type.
+    UnresolvedForwardPointer { pointer_id: u32 },
+}
@@ -0,0 +1,117 @@
+//! `VkDescriptorUpdateTemplateEntry` lists for descriptor sets, so that a set
+//! can be written from one packed blob of descriptor infos.
+
+use crate::types::{ReflectDescriptorSet, ReflectDescriptorType};
+
+/// Size in the blob of one descriptor of each kind. The defaults are the
+/// sizes of the Vulkan structs on 64-bit targets.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectUpdateTemplateStrides {
+    /// `VkDescriptorImageInfo`, for samplers, images and input attachments.
+    pub image_info: u32,
+    /// `VkDescriptorBufferInfo`, for uniform and storage buffers.
+    pub buffer_info: u32,
+    /// `VkBufferView`, for texel buffers.
+    pub buffer_view: u32,
+    /// `VkAccelerationStructureKHR`.
+    pub acceleration_structure: u32,
+}
+
+impl Default for ReflectUpdateTemplateStrides {
+    fn default() -> Self {
+        ReflectUpdateTemplateStrides {
+            image_info: 24,
+            buffer_info: 24,
+            buffer_view: 8,
+            acceleration_structure: 8,
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectUpdateTemplateOptions {
+    pub strides: ReflectUpdateTemplateStrides,
+    /// Offset of the first descriptor in the blob.
+    pub base_offset: u32,
+    /// Descriptors written to a runtime array, the variable descriptor count
+    /// the set is allocated with. A runtime array gets no entry when 0.
+    pub variable_descriptor_count: u32,
+}
+
+/// `VkDescriptorUpdateTemplateEntry`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectUpdateTemplateEntry {
+    pub binding: u32,
+    pub array_element: u32,
+    pub count: u32,
+    pub descriptor_type: ReflectDescriptorType,
+    /// Byte offset of the first descriptor in the blob.
+    pub offset: u32,
+    pub stride: u32,
+    pub name: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct ReflectUpdateTemplate {
+    pub set: u32,
+    pub entries: Vec<ReflectUpdateTemplateEntry>,
+    /// Bytes the blob needs, `base_offset` included.
+    pub size: u32,
+}
+
+impl ReflectUpdateTemplateStrides {
+    fn of(&self, descriptor_type: ReflectDescriptorType) -> Result<u32, &'static str> {
+        match descriptor_type {
+            ReflectDescriptorType::Sampler
+            | ReflectDescriptorType::CombinedImageSampler
+            | ReflectDescriptorType::SampledImage
+            | ReflectDescriptorType::StorageImage
+            | ReflectDescriptorType::InputAttachment => Ok(self.image_info),
+            ReflectDescriptorType::UniformBuffer
+            | ReflectDescriptorType::UniformBufferDynamic
+            | ReflectDescriptorType::StorageBuffer
+            | ReflectDescriptorType::StorageBufferDynamic => Ok(self.buffer_info),
+            ReflectDescriptorType::UniformTexelBuffer
+            | ReflectDescriptorType::StorageTexelBuffer => Ok(self.buffer_view),
+            ReflectDescriptorType::AccelerationStructureNV => Ok(self.acceleration_structure),
+            ReflectDescriptorType::Undefined => Err("Descriptor type has no update template data"),
+        }
+    }
+}
+
+/// The update template of `set`, its descriptors packed in binding order
+/// with the array elements of a binding consecutive.
+pub fn update_template(
+    set: &ReflectDescriptorSet,
+    options: &ReflectUpdateTemplateOptions,
+) -> Result<ReflectUpdateTemplate, &'static str> {
+    let mut bindings: Vec<_> = set.bindings.iter().collect();
+    bindings.sort_by_key(|binding| binding.binding);
+
+    let mut template = ReflectUpdateTemplate {
+        set: set.set,
+        entries: Vec::new(),
+        size: options.base_offset,
+    };
+    for binding in bindings {
+        let stride = options.strides.of(binding.descriptor_type)?;
+        let count = match binding.count {
+            0 => options.variable_descriptor_count,
+            count => count,
+        };
+        if count == 0 {
+            continue;
+        }
+        template.entries.push(ReflectUpdateTemplateEntry {
+            binding: binding.binding,
+            array_element: 0,
+            count,
+            descriptor_type: binding.descriptor_type,
+            offset: template.size,
+            stride,
+            name: binding.name.clone(),
+        });
+        template.size += stride * count;
+    }
+    Ok(template)
+}
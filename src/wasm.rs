@@ -0,0 +1,92 @@
+//! JavaScript bindings for use from `wasm32-unknown-unknown`.
+//!
+//! Reflection results are handed to JS as plain objects, using the same
+//! field names as the serde output of the rust types.
+
+use crate::ShaderModule;
+use wasm_bindgen::prelude::*;
+
+fn to_js<T: serde::Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    // Maps become plain objects rather than `Map`s.
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(JsValue::from)
+}
+
+fn to_js_result<T: serde::Serialize>(value: Result<T, &'static str>) -> Result<JsValue, JsValue> {
+    match value {
+        Ok(value) => to_js(&value),
+        Err(err) => Err(JsValue::from_str(err)),
+    }
+}
+
+#[wasm_bindgen(js_name = ShaderModule)]
+pub struct WasmShaderModule {
+    module: ShaderModule,
+}
+
+#[wasm_bindgen(js_class = ShaderModule)]
+impl WasmShaderModule {
+    #[wasm_bindgen(constructor)]
+    pub fn new(spv_data: &[u8]) -> Result<WasmShaderModule, JsValue> {
+        match ShaderModule::load_u8_data(spv_data) {
+            Ok(module) => Ok(WasmShaderModule { module }),
+            Err(err) => Err(JsValue::from_str(err)),
+        }
+    }
+
+    #[wasm_bindgen(js_name = entryPointName)]
+    pub fn entry_point_name(&self) -> String {
+        self.module.get_entry_point_name()
+    }
+
+    #[wasm_bindgen(js_name = shaderStage)]
+    pub fn shader_stage(&self) -> u32 {
+        self.module.get_shader_stage().bits()
+    }
+
+    #[wasm_bindgen(js_name = entryPoints)]
+    pub fn entry_points(&self) -> Result<JsValue, JsValue> {
+        to_js_result(self.module.enumerate_entry_points())
+    }
+
+    #[wasm_bindgen(js_name = descriptorSets)]
+    pub fn descriptor_sets(&self, entry_point: Option<String>) -> Result<JsValue, JsValue> {
+        to_js_result(
+            self.module
+                .enumerate_descriptor_sets(entry_point.as_deref()),
+        )
+    }
+
+    #[wasm_bindgen(js_name = descriptorBindings)]
+    pub fn descriptor_bindings(&self, entry_point: Option<String>) -> Result<JsValue, JsValue> {
+        to_js_result(
+            self.module
+                .enumerate_descriptor_bindings(entry_point.as_deref()),
+        )
+    }
+
+    #[wasm_bindgen(js_name = pushConstantBlocks)]
+    pub fn push_constant_blocks(&self, entry_point: Option<String>) -> Result<JsValue, JsValue> {
+        to_js_result(
+            self.module
+                .enumerate_push_constant_blocks(entry_point.as_deref()),
+        )
+    }
+
+    #[wasm_bindgen(js_name = inputVariables)]
+    pub fn input_variables(&self, entry_point: Option<String>) -> Result<JsValue, JsValue> {
+        to_js_result(
+            self.module
+                .enumerate_input_variables(entry_point.as_deref()),
+        )
+    }
+
+    #[wasm_bindgen(js_name = outputVariables)]
+    pub fn output_variables(&self, entry_point: Option<String>) -> Result<JsValue, JsValue> {
+        to_js_result(
+            self.module
+                .enumerate_output_variables(entry_point.as_deref()),
+        )
+    }
+}
@@ -46,6 +46,3147 @@ mod tests {
         );
     }
 
+    #[test]
+    fn enumerate_used_bindings() {
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+
+        let bindings = module.enumerate_used_descriptor_bindings(None).unwrap();
+        assert_eq!(bindings.len(), 2);
+        assert!(bindings.iter().all(|binding| binding.accessed));
+
+        let descriptor_sets = module.enumerate_used_descriptor_sets(None).unwrap();
+        assert_eq!(descriptor_sets.len(), 1);
+        assert_eq!(descriptor_sets[0].bindings.len(), 2);
+    }
+
+    #[test]
+    fn interface_variable_lookup() {
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+        let entry_points = module.enumerate_entry_points().unwrap();
+        let entry_point = &entry_points[0];
+
+        let color = entry_point.input_by_location(1).unwrap();
+        assert_eq!(color.name, "in.var.COLOR0");
+        assert!(entry_point.input_by_location(2).is_none());
+
+        let target = entry_point.output_by_location(0).unwrap();
+        assert_eq!(target.name, "out.var.SV_Target0");
+        assert!(entry_point
+            .builtin_output(spirv_headers::BuiltIn::FragDepth)
+            .is_none());
+    }
+
+    #[test]
+    fn type_description_glsl_names() {
+        let mut vec4 = types::ReflectTypeDescription {
+            type_flags: types::ReflectTypeFlags::FLOAT | types::ReflectTypeFlags::VECTOR,
+            ..Default::default()
+        };
+        vec4.traits.numeric.scalar.width = 32;
+        vec4.traits.numeric.vector.component_count = 4;
+        assert!(vec4.is_float() && vec4.is_signed());
+        assert_eq!(vec4.vector_size(), Some(4));
+        assert_eq!(vec4.type_name_glsl(), "vec4");
+
+        let mut mat3x4 = vec4.clone();
+        mat3x4.type_flags |= types::ReflectTypeFlags::MATRIX;
+        mat3x4.traits.numeric.matrix.column_count = 3;
+        mat3x4.traits.numeric.matrix.row_count = 4;
+        assert_eq!(mat3x4.vector_size(), None);
+        assert_eq!(mat3x4.matrix_dims(), Some((3, 4)));
+        assert_eq!(mat3x4.type_name_glsl(), "mat3x4");
+
+        let mut uvec2 = types::ReflectTypeDescription {
+            type_flags: types::ReflectTypeFlags::INT
+                | types::ReflectTypeFlags::VECTOR
+                | types::ReflectTypeFlags::ARRAY,
+            ..Default::default()
+        };
+        uvec2.traits.numeric.scalar.width = 32;
+        uvec2.traits.numeric.vector.component_count = 2;
+        uvec2.traits.array.dims = vec![8];
+        assert!(!uvec2.is_signed());
+        assert_eq!(
+            uvec2.element_type(),
+            Some(types::ReflectScalarType::Int {
+                width: 32,
+                signed: false
+            })
+        );
+        assert_eq!(uvec2.type_name_glsl(), "uvec2[8]");
+    }
+
+    fn float_member(components: u32, columns: u32, dims: Vec<u32>) -> types::ReflectBlockVariable {
+        let mut member = types::ReflectBlockVariable::default();
+        member.numeric.scalar.width = 32;
+        member.numeric.vector.component_count = components;
+        member.numeric.matrix.column_count = columns;
+        member.numeric.matrix.row_count = if columns > 0 { components } else { 0 };
+        member.array.dims = dims;
+        member
+    }
+
+    #[test]
+    fn block_size_for_layout() {
+        // struct { vec3 a; float b; mat4 c; float d[2]; }
+        let block = types::ReflectBlockVariable {
+            members: vec![
+                float_member(3, 0, vec![]),
+                float_member(0, 0, vec![]),
+                float_member(4, 4, vec![]),
+                float_member(0, 0, vec![2]),
+            ],
+            ..Default::default()
+        };
+
+        use types::ReflectBlockLayout::*;
+        assert_eq!(block.member_offsets_for_layout(Std140), vec![0, 12, 16, 80]);
+        assert_eq!(block.size_for_layout(Std140), 112);
+        assert_eq!(block.member_offsets_for_layout(Std430), vec![0, 12, 16, 80]);
+        assert_eq!(block.size_for_layout(Std430), 96);
+        assert_eq!(block.member_offsets_for_layout(Scalar), vec![0, 12, 16, 80]);
+        assert_eq!(block.size_for_layout(Scalar), 88);
+    }
+
+    #[test]
+    fn block_offset_of() {
+        let member =
+            |name: &str, offset, dims: Vec<u32>, stride, members| types::ReflectBlockVariable {
+                name: name.to_owned(),
+                offset,
+                array: types::ReflectArrayTraits {
+                    dims,
+                    stride,
+                    ..Default::default()
+                },
+                members,
+                ..Default::default()
+            };
+        // struct Light { vec4 position; float weights[3]; };
+        // struct { mat4 view; Light lights[4]; float grid[2][3]; }, std140
+        let light = vec![
+            member("position", 0, vec![], 0, vec![]),
+            member("weights", 16, vec![3], 16, vec![]),
+        ];
+        let block = types::ReflectBlockVariable {
+            members: vec![
+                member("view", 0, vec![], 0, vec![]),
+                member("lights", 64, vec![4], 64, light),
+                member("grid", 320, vec![2, 3], 48, vec![]),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(block.offset_of("view", &[]), Some(0));
+        assert_eq!(block.offset_of("lights.position", &[2]), Some(192));
+        assert_eq!(block.offset_of("lights.weights", &[1, 2]), Some(176));
+        assert_eq!(block.offset_of("grid", &[1, 2]), Some(400));
+        assert_eq!(block.offset_of("lights.position", &[4]), None);
+        assert_eq!(block.offset_of("lights.position", &[]), None);
+        assert_eq!(block.offset_of("lights.position", &[0, 0]), None);
+        assert_eq!(block.offset_of("lights.color", &[0]), None);
+
+        // Offsets past `u32::MAX`, through a runtime array or the last element
+        // of an array near the end of the address range.
+        let mut tail = member("tail", 16, vec![], 16, vec![]);
+        tail.array.runtime = true;
+        let block = types::ReflectBlockVariable {
+            members: vec![tail, member("end", u32::MAX - 8, vec![4], 4, vec![])],
+            ..Default::default()
+        };
+        assert_eq!(block.offset_of("tail", &[2]), Some(48));
+        assert_eq!(block.offset_of("tail", &[u32::MAX]), None);
+        assert_eq!(block.offset_of("end", &[2]), Some(u32::MAX));
+        assert_eq!(block.offset_of("end", &[3]), None);
+    }
+
+    #[test]
+    fn cpu_layout_mismatches() {
+        let member = |name: &str, offset, size, members| types::ReflectBlockVariable {
+            name: name.to_owned(),
+            offset,
+            size,
+            members,
+            ..Default::default()
+        };
+        // struct Light { vec3 position; float radius; };
+        // uniform { mat4 view; Light light; vec2 jitter; }, std140
+        let light = vec![
+            member("position", 0, 12, vec![]),
+            member("radius", 12, 4, vec![]),
+        ];
+        let block = types::ReflectBlockVariable {
+            members: vec![
+                member("view", 0, 64, vec![]),
+                member("light", 64, 16, light),
+                member("jitter", 80, 8, vec![]),
+            ],
+            ..Default::default()
+        };
+
+        use layout::{check_cpu_layout, CpuField, ReflectLayoutMismatch};
+        let matching = [
+            CpuField::new("view", 0, 64),
+            CpuField::new("light.position", 64, 12),
+            CpuField::new("light.radius", 76, 4),
+            CpuField::new("jitter", 80, 8),
+        ];
+        assert!(check_cpu_layout(&block, &matching).is_empty());
+
+        // A CPU struct that packs `radius` after a 16-byte `position`.
+        let drifted = [
+            CpuField::new("view", 0, 64),
+            CpuField::new("light.position", 64, 16),
+            CpuField::new("light.radius", 80, 4),
+            CpuField::new("light.color", 84, 12),
+        ];
+        assert_eq!(
+            check_cpu_layout(&block, &drifted),
+            vec![
+                ReflectLayoutMismatch::Size {
+                    name: "light.position".to_owned(),
+                    cpu: 16,
+                    reflected: 12
+                },
+                ReflectLayoutMismatch::Offset {
+                    name: "light.radius".to_owned(),
+                    cpu: 80,
+                    reflected: 76
+                },
+                ReflectLayoutMismatch::UnknownField {
+                    name: "light.color".to_owned()
+                },
+                ReflectLayoutMismatch::MissingField {
+                    name: "jitter".to_owned()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn block_arrays_of_arrays() {
+        let data = include_bytes!("./ArraysOfArrays.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        // buffer Data { float grid[2][3]; float spec[N]; float tail[][2]; }
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        let block = &bindings[0].block;
+        let grid = &block.members[0];
+        assert_eq!(grid.array.dims, vec![2, 3]);
+        assert_eq!(grid.array.strides, vec![12, 4]);
+        assert_eq!(grid.array.stride, 12);
+        assert!(!grid.array.runtime && !grid.array.is_specialized());
+        assert_eq!(grid.size, 24);
+
+        let spec = &block.members[1];
+        assert_eq!(spec.array.dims, vec![4]);
+        assert_eq!(
+            spec.array.dim_exprs,
+            vec![types::ReflectSizeExpr::SpecConstant {
+                spec_id: 0,
+                default: 4
+            }]
+        );
+        assert!(spec.array.is_specialized());
+
+        let tail = &block.members[2];
+        assert!(tail.array.runtime);
+        assert_eq!(tail.array.dims, vec![2]);
+        assert_eq!(tail.array.stride, 8);
+        assert_eq!(tail.array.dim_stride(0), 4);
+
+        assert_eq!(block.offset_of("grid", &[1, 2]), Some(20));
+        assert_eq!(block.offset_of("spec", &[3]), Some(36));
+        assert_eq!(block.offset_of("tail", &[3, 1]), Some(68));
+        assert_eq!(block.offset_of("tail", &[3, 2]), None);
+        assert_eq!(block.offset_of("tail", &[3]), None);
+    }
+
+    #[test]
+    fn runtime_array_required_size() {
+        let data = include_bytes!("./ArraysOfArrays.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        let block = &bindings[0].block;
+        assert_eq!(block.size, 40);
+        assert_eq!(block.fixed_size(), 40);
+        assert_eq!(block.runtime_stride(), 8);
+        assert_eq!(block.runtime_array().unwrap().name, "tail");
+        assert_eq!(block.runtime_array().unwrap().size, 0);
+        assert_eq!(block.required_size(0), 40);
+        assert_eq!(block.required_size(10), 120);
+
+        let grid = &block.members[0];
+        assert!(grid.runtime_array().is_none());
+        assert_eq!(grid.required_size(10), u64::from(grid.size));
+    }
+
+    #[test]
+    fn matrix_member_layout() {
+        let data = include_bytes!("./MatrixLayout.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        // uniform Transforms {
+        //     layout(row_major) mat4x3 row_major;
+        //     layout(column_major) mat4x3 column_major;
+        //     layout(row_major) mat2 row_major_array[2];
+        // }, std140
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        let block = &bindings[0].block;
+        let members = &block.members;
+        use types::ReflectMatrixLayout::*;
+        assert_eq!(members[0].matrix_layout(), Some(RowMajor));
+        assert_eq!(members[0].matrix_stride(), Some(16));
+        assert_eq!(members[0].size, 48);
+        assert_eq!(members[1].matrix_layout(), Some(ColumnMajor));
+        assert_eq!(members[1].matrix_stride(), Some(16));
+        assert_eq!(members[1].size, 64);
+        assert_eq!(members[2].matrix_layout(), Some(RowMajor));
+        assert_eq!(members[2].size, 64);
+        assert_eq!(block.matrix_layout(), None);
+        assert_eq!(block.matrix_stride(), None);
+
+        use types::ReflectBlockLayout::Std140;
+        assert_eq!(block.member_offsets_for_layout(Std140), vec![0, 48, 112]);
+        assert_eq!(block.size_for_layout(Std140), block.size);
+    }
+
+    #[test]
+    fn type_description_member_layout() {
+        let data = include_bytes!("./MatrixLayout.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        let type_description = bindings[0].type_description.as_ref().unwrap();
+        assert_eq!(type_description.offset, None);
+        let members = &type_description.members;
+        let offsets: Vec<_> = members.iter().map(|member| member.offset).collect();
+        assert_eq!(offsets, vec![Some(0), Some(48), Some(112)]);
+        assert!(members
+            .iter()
+            .all(|member| member.traits.numeric.matrix.stride == 16));
+        assert_eq!(members[2].traits.array.stride, 32);
+    }
+
+    #[test]
+    fn physical_pointer_members() {
+        let data = include_bytes!("./BufferReference.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        // layout(buffer_reference) buffer Node { vec4 value; Node next; };
+        // layout(push_constant) uniform Push { Node root; uint count; };
+        let blocks = module.enumerate_push_constant_blocks(None).unwrap();
+        let root = &blocks[0].members[0];
+        assert_eq!(root.size, 8);
+        assert_eq!(root.numeric.scalar.width, 64);
+        assert!(root.members.is_empty());
+        let root_type = root.type_description.as_ref().unwrap();
+        assert!(root_type.is_physical_pointer());
+        assert!(root_type.members.is_empty());
+        let pointer = root_type.traits.pointer.unwrap();
+        assert_eq!(
+            pointer.storage_class,
+            types::ReflectStorageClass::PhysicalStorageBuffer
+        );
+
+        let node = module
+            .get_type_description(pointer.pointee_type_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(node.type_name, "Node");
+        assert_eq!(node.members[1].offset, Some(16));
+        assert!(node.members[1].is_physical_pointer());
+        assert_eq!(
+            node.members[1].traits.pointer.unwrap().pointee_type_id,
+            node.id
+        );
+        assert_eq!(module.get_type_description(u32::MAX).unwrap(), None);
+    }
+
+    #[test]
+    fn forward_pointers() {
+        let data = include_bytes!("./ForwardPointers.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        // Like BufferReference, with a third member whose forward-declared
+        // pointer type is never defined.
+        let blocks = module.enumerate_push_constant_blocks(None).unwrap();
+        let members = &blocks[0].members;
+        let pointee = |index: usize| {
+            let type_description = members[index].type_description.as_ref().unwrap();
+            type_description
+                .traits
+                .pointer
+                .map(|pointer| pointer.pointee_type_id)
+        };
+        assert_eq!(
+            module
+                .get_type_description(pointee(0).unwrap())
+                .unwrap()
+                .unwrap()
+                .type_name,
+            "Node"
+        );
+        assert_eq!(pointee(2), Some(0));
+        assert_eq!(members[2].size, 8);
+        assert_eq!(
+            module.warnings(),
+            vec![types::ReflectWarning::UnresolvedForwardPointer { pointer_id: 25 }]
+        );
+    }
+
+    #[test]
+    fn small_storage_features() {
+        let mut half = float_member(4, 0, vec![]);
+        half.numeric.scalar.width = 16;
+        let block = types::ReflectBlockVariable {
+            members: vec![float_member(4, 0, vec![]), half],
+            ..Default::default()
+        };
+        assert_eq!(block.size_for_layout(types::ReflectBlockLayout::Std430), 32);
+        assert!(block.contains_scalar_width(16));
+        assert!(!block.contains_scalar_width(8));
+        assert_eq!(
+            block.push_constant_required_features(),
+            types::ReflectFeatureFlags::STORAGE_PUSH_CONSTANT_16
+        );
+    }
+
+    #[test]
+    fn double_precision_block() {
+        let mut dvec3 = float_member(3, 0, vec![]);
+        dvec3.numeric.scalar.width = 64;
+        dvec3.type_description = Some(types::ReflectTypeDescription {
+            type_flags: types::ReflectTypeFlags::FLOAT | types::ReflectTypeFlags::VECTOR,
+            ..Default::default()
+        });
+        if let Some(ref mut type_description) = dvec3.type_description {
+            type_description.traits.numeric = dvec3.numeric;
+        }
+        let block = types::ReflectBlockVariable {
+            members: vec![dvec3, float_member(0, 0, vec![])],
+            ..Default::default()
+        };
+        assert_eq!(
+            block.member_offsets_for_layout(types::ReflectBlockLayout::Std430),
+            vec![0, 24]
+        );
+        assert_eq!(block.size_for_layout(types::ReflectBlockLayout::Std430), 32);
+        assert_eq!(
+            block.push_constant_required_features(),
+            types::ReflectFeatureFlags::SHADER_FLOAT64
+        );
+    }
+
+    #[test]
+    fn spirv_enum_conversions() {
+        use std::convert::TryFrom;
+
+        let built_in = types::ReflectBuiltIn::from(spirv_headers::BuiltIn::FragCoord);
+        assert_eq!(
+            spirv_headers::BuiltIn::from(built_in),
+            spirv_headers::BuiltIn::FragCoord
+        );
+        assert_eq!(built_in.as_raw(), 15);
+
+        let format = types::ReflectImageFormat::from(spirv_headers::ImageFormat::Rgba16f);
+        assert_eq!(format, types::ReflectImageFormat::RGBA16_FLOAT);
+        assert_eq!(format.as_raw(), 2);
+
+        assert_eq!(
+            spirv_headers::Dim::try_from(types::ReflectDimension::Cube),
+            Ok(spirv_headers::Dim::DimCube)
+        );
+        assert_eq!(types::ReflectDimension::Undefined.as_raw(), None);
+        assert!(types::ReflectStorageClass::try_from(
+            spirv_headers::StorageClass::ShaderRecordBufferNV
+        )
+        .is_err());
+    }
+
+    #[cfg(feature = "ash")]
+    #[test]
+    fn ash_format_conversions() {
+        use std::convert::TryFrom;
+        use types::ReflectFormat::*;
+
+        let formats = [
+            Undefined,
+            R32_UINT,
+            R32_SINT,
+            R32_SFLOAT,
+            R32G32_UINT,
+            R32G32_SINT,
+            R32G32_SFLOAT,
+            R32G32B32_UINT,
+            R32G32B32_SINT,
+            R32G32B32_SFLOAT,
+            R32G32B32A32_UINT,
+            R32G32B32A32_SINT,
+            R32G32B32A32_SFLOAT,
+            R64_UINT,
+            R64_SINT,
+            R64_SFLOAT,
+            R64G64_UINT,
+            R64G64_SINT,
+            R64G64_SFLOAT,
+            R64G64B64_UINT,
+            R64G64B64_SINT,
+            R64G64B64_SFLOAT,
+            R64G64B64A64_UINT,
+            R64G64B64A64_SINT,
+            R64G64B64A64_SFLOAT,
+        ];
+        for &format in formats.iter() {
+            let vk_format = ash::vk::Format::from(format);
+            // Both sides spell the formats the same.
+            assert_eq!(
+                format!("{:?}", vk_format),
+                format!("{:?}", format).to_uppercase()
+            );
+            assert_eq!(types::ReflectFormat::try_from(vk_format), Ok(format));
+        }
+        assert!(types::ReflectFormat::try_from(ash::vk::Format::R8G8B8A8_UNORM).is_err());
+    }
+
+    #[cfg(feature = "wgpu")]
+    #[test]
+    fn wgpu_vertex_format_conversions() {
+        use std::convert::TryFrom;
+        use types::ReflectFormat;
+        use wgpu_types::VertexFormat;
+
+        let formats = [
+            (ReflectFormat::R32_UINT, VertexFormat::Uint),
+            (ReflectFormat::R32_SINT, VertexFormat::Int),
+            (ReflectFormat::R32_SFLOAT, VertexFormat::Float),
+            (ReflectFormat::R32G32_UINT, VertexFormat::Uint2),
+            (ReflectFormat::R32G32_SINT, VertexFormat::Int2),
+            (ReflectFormat::R32G32_SFLOAT, VertexFormat::Float2),
+            (ReflectFormat::R32G32B32_UINT, VertexFormat::Uint3),
+            (ReflectFormat::R32G32B32_SINT, VertexFormat::Int3),
+            (ReflectFormat::R32G32B32_SFLOAT, VertexFormat::Float3),
+            (ReflectFormat::R32G32B32A32_UINT, VertexFormat::Uint4),
+            (ReflectFormat::R32G32B32A32_SINT, VertexFormat::Int4),
+            (ReflectFormat::R32G32B32A32_SFLOAT, VertexFormat::Float4),
+        ];
+        for &(format, vertex_format) in formats.iter() {
+            assert_eq!(VertexFormat::try_from(format), Ok(vertex_format));
+            assert_eq!(ReflectFormat::try_from(vertex_format), Ok(format));
+        }
+        assert!(VertexFormat::try_from(ReflectFormat::Undefined).is_err());
+        assert!(VertexFormat::try_from(ReflectFormat::R64_SFLOAT).is_err());
+        assert!(ReflectFormat::try_from(VertexFormat::Uchar2).is_err());
+    }
+
+    #[test]
+    fn unknown_built_in() {
+        // `ShadingRateKHR`, newer than `spirv_headers`.
+        let built_in = types::ReflectBuiltIn::from_raw(4444);
+        assert_eq!(built_in.built_in(), None);
+        assert_eq!(built_in.raw(), 4444);
+        assert_eq!(built_in.to_string(), "4444");
+        assert_ne!(built_in, types::ReflectBuiltIn::from_raw(4432));
+        assert_ne!(built_in, types::ReflectBuiltIn::default());
+        assert_eq!(
+            types::ReflectBuiltIn::from_raw(15).built_in(),
+            Some(spirv_headers::BuiltIn::FragCoord)
+        );
+    }
+
+    #[test]
+    fn binding_layout_compatible() {
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+
+        let mut renamed = bindings[0].clone();
+        renamed.name = "renamed".to_owned();
+        renamed.spirv_id += 100;
+        assert_ne!(renamed, bindings[0]);
+        assert!(renamed.layout_compatible(&bindings[0]));
+        assert!(!bindings[0].layout_compatible(&bindings[1]));
+
+        let unique: std::collections::HashSet<_> = bindings.iter().cloned().collect();
+        assert_eq!(unique.len(), bindings.len());
+    }
+
+    #[test]
+    fn equal_across_modules() {
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let first = ShaderModule::load_u8_data(ps_data).unwrap();
+        let second = ShaderModule::load_u8_data(ps_data).unwrap();
+        assert_eq!(
+            first.enumerate_descriptor_bindings(None).unwrap(),
+            second.enumerate_descriptor_bindings(None).unwrap()
+        );
+        assert_eq!(
+            first.enumerate_descriptor_sets(None).unwrap(),
+            second.enumerate_descriptor_sets(None).unwrap()
+        );
+        assert_eq!(
+            first.enumerate_input_variables(None).unwrap(),
+            second.enumerate_input_variables(None).unwrap()
+        );
+        assert_eq!(
+            first.enumerate_output_variables(None).unwrap(),
+            second.enumerate_output_variables(None).unwrap()
+        );
+
+        let hash = |bindings: Vec<types::ReflectDescriptorBinding>| {
+            use std::hash::{Hash, Hasher};
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            bindings.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(
+            hash(first.enumerate_descriptor_bindings(None).unwrap()),
+            hash(second.enumerate_descriptor_bindings(None).unwrap())
+        );
+    }
+
+    #[test]
+    fn cache_round_trip() {
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+        let bytes = module.to_cache_bytes().unwrap();
+
+        let cache = ShaderModule::from_cache_bytes(&bytes).unwrap();
+        assert_eq!(cache.entry_point_name, "ImGuiPs");
+        assert_eq!(cache.descriptor_sets[0].bindings.len(), 2);
+        assert_eq!(
+            cache.descriptor_sets[0].bindings[0].descriptor_type,
+            types::ReflectDescriptorType::SampledImage
+        );
+        assert_eq!(cache.entry_points[0].input_variables.len(), 2);
+        assert_eq!(cache.to_cache_bytes(), bytes);
+
+        assert!(ShaderModule::from_cache_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(ShaderModule::from_cache_bytes(b"SPRC").is_err());
+    }
+
+    #[test]
+    fn truncated_cache() {
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+        let bytes = module.to_cache_bytes().unwrap();
+        for length in 0..bytes.len() {
+            assert!(ShaderModule::from_cache_bytes(&bytes[..length]).is_err());
+        }
+    }
+
+    #[test]
+    fn invalid_instruction_word_counts() {
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let with_first_word_count = |word_count: u32| {
+            let mut data = ps_data.to_vec();
+            let first_word = &mut data[5 * 4..6 * 4];
+            let word =
+                u32::from_ne_bytes([first_word[0], first_word[1], first_word[2], first_word[3]]);
+            first_word.copy_from_slice(&((word & 0xffff) | (word_count << 16)).to_ne_bytes());
+            data
+        };
+        assert_eq!(
+            ShaderModule::load_u8_data(&with_first_word_count(0)).err(),
+            Some("Invalid SPIR-V instruction word count")
+        );
+        assert_eq!(
+            ShaderModule::load_u8_data(&with_first_word_count(0xffff)).err(),
+            Some("Unexpected end of SPIR-V code")
+        );
+        assert_eq!(
+            ShaderModule::load_u8_data(&ps_data[..ps_data.len() - 2]).err(),
+            Some("Invalid Code Size")
+        );
+    }
+
+    #[test]
+    fn overflowing_layout_sizes() {
+        // `ArrayStride` and `MatrixStride` decorations and array lengths whose
+        // products don't fit in a `u32`. Sizes that overflow are reported as 0.
+        let data = include_bytes!("./LayoutOverflow.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        let members = &bindings[0].block.members;
+        let sizes: Vec<_> = members.iter().map(|member| member.size).collect();
+        assert_eq!(sizes, vec![0, 0, 0]);
+        assert_eq!(members[2].array.dims, vec![65536, 65536]);
+        assert_eq!(members[2].array.size(), 0);
+        module.enumerate_descriptor_sets(None).unwrap();
+        module.enumerate_global_variables(None).unwrap();
+    }
+
+    #[test]
+    fn contiguous_descriptor_sets() {
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let mut module = ShaderModule::load_u8_data(ps_data).unwrap();
+        assert_eq!(module.max_set_index(None).unwrap(), Some(0));
+        assert_eq!(module.max_binding_index(0, None).unwrap(), Some(1));
+        assert_eq!(module.max_binding_index(1, None).unwrap(), None);
+
+        let descriptor_sets = module.enumerate_descriptor_sets(None).unwrap();
+        module
+            .change_descriptor_set_number(&descriptor_sets[0], 2)
+            .unwrap();
+        let contiguous = module.enumerate_contiguous_descriptor_sets(None).unwrap();
+        assert_eq!(contiguous.len(), 3);
+        assert!(contiguous[0].bindings.is_empty());
+        assert!(contiguous[1].bindings.is_empty());
+        assert_eq!(contiguous[2].set, 2);
+        assert_eq!(contiguous[2].bindings.len(), 2);
+    }
+
+    #[test]
+    fn image_traits_helpers() {
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        let image = &bindings[0].image;
+        assert!(!image.is_multisampled());
+        assert!(!image.is_arrayed());
+        assert!(!image.is_depth());
+        assert_eq!(
+            image.view_dimension(),
+            Some(types::ReflectImageViewDimension::Type2d)
+        );
+
+        let cube_array = types::ReflectImageTraits {
+            dim: types::ReflectDimension::Cube,
+            arrayed: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            cube_array.view_dimension(),
+            Some(types::ReflectImageViewDimension::CubeArray)
+        );
+    }
+
+    #[test]
+    fn enumerate_execution_modes() {
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+
+        let execution_modes = module.enumerate_execution_modes(Some("ImGuiPs")).unwrap();
+        assert_eq!(execution_modes.len(), 1);
+        assert!(execution_modes[0].is(spirv_headers::ExecutionMode::OriginUpperLeft));
+        assert!(execution_modes[0].operands.is_empty());
+        assert!(module.enumerate_execution_modes(Some("missing")).is_err());
+
+        let controls = types::ReflectFloatControls::from_execution_modes(&[
+            types::ReflectExecutionMode {
+                mode: spirv_headers::ExecutionMode::DenormPreserve as u32,
+                operands: vec![16],
+                operands_are_ids: false,
+            },
+            types::ReflectExecutionMode {
+                mode: spirv_headers::ExecutionMode::RoundingModeRTZ as u32,
+                operands: vec![32],
+                operands_are_ids: false,
+            },
+        ]);
+        assert_eq!(
+            controls.denorm_preserve,
+            types::ReflectFloatWidthFlags::FLOAT16
+        );
+        assert_eq!(
+            controls.rounding_mode_rtz,
+            types::ReflectFloatWidthFlags::FLOAT32
+        );
+        assert!(controls.rounding_mode_rte.is_empty());
+    }
+
+    #[test]
+    fn shader_record_layout() {
+        let rgen_data = include_bytes!("./ShaderRecord.spirv");
+        let module = ShaderModule::load_u8_data(rgen_data).unwrap();
+
+        let block = module.get_shader_record_block(None).unwrap().unwrap();
+        assert_eq!(block.name, "record");
+        assert_eq!(block.members.len(), 2);
+        assert_eq!(block.members[1].name, "index");
+        assert_eq!(block.members[1].offset, 16);
+
+        let mut pipeline = pipeline::RayTracingPipelineReflection::new();
+        pipeline.add_stage(&module, Some("main")).unwrap();
+        let raygen = pipeline::RayTracingPipelineReflection::raygen_stages();
+        assert_eq!(pipeline.record_size(raygen), 20);
+        assert_eq!(pipeline.sbt_stride(raygen, 32, 64), 64);
+        assert_eq!(
+            pipeline.record_size(pipeline::RayTracingPipelineReflection::miss_stages()),
+            0
+        );
+    }
+
+    #[test]
+    fn mesh_traits() {
+        let mesh_data = include_bytes!("./MeshNV.spirv");
+        let module = ShaderModule::load_u8_data(mesh_data).unwrap();
+
+        let traits = module.get_mesh_traits(None).unwrap().unwrap();
+        assert_eq!(traits.max_vertices, 3);
+        assert_eq!(traits.max_primitives, 1);
+        assert_eq!(
+            traits.output_topology,
+            types::ReflectMeshOutputTopology::Triangles
+        );
+        assert_eq!(traits.per_vertex_outputs.len(), 1);
+        assert_eq!(traits.per_vertex_outputs[0].name, "color");
+        assert_eq!(traits.per_primitive_outputs.len(), 1);
+        assert_eq!(traits.per_primitive_outputs[0].name, "prim_id");
+        assert!(traits.primitive_indices.is_some());
+
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+        assert!(module.get_mesh_traits(None).unwrap().is_none());
+    }
+
+    #[test]
+    fn task_payload_layout() {
+        let data = include_bytes!("./TaskPayloadNV.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let payload = module.get_task_payload(Some("task_main")).unwrap().unwrap();
+        assert_eq!(payload.name, "payload_out");
+        assert_eq!(payload.size, 32);
+        let offsets: Vec<u32> = payload.members.iter().map(|member| member.offset).collect();
+        assert_eq!(offsets, vec![0, 16, 28]);
+        assert!(module
+            .get_task_payload(Some("mesh_main"))
+            .unwrap()
+            .is_some());
+
+        pipeline::validate_task_payload(&module, Some("task_main"), &module, Some("mesh_main"))
+            .unwrap();
+
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let ps_module = ShaderModule::load_u8_data(ps_data).unwrap();
+        assert!(ps_module.get_task_payload(None).unwrap().is_none());
+        assert!(
+            pipeline::validate_task_payload(&ps_module, None, &module, Some("mesh_main"),).is_err()
+        );
+    }
+
+    #[test]
+    fn tessellation_traits() {
+        let data = include_bytes!("./Tessellation.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let control = module
+            .get_tessellation_traits(Some("tcs_main"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(control.output_vertices, 3);
+        assert_eq!(control.input_patch_vertices, 32);
+        assert_eq!(control.patch_outputs.len(), 1);
+        assert_eq!(control.patch_outputs[0].name, "patch_data");
+        assert!(control.uses_tess_level_outer);
+        assert!(control.uses_tess_level_inner);
+
+        let evaluation = module
+            .get_tessellation_traits(Some("tes_main"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(evaluation.patch_inputs.len(), 1);
+        assert!(evaluation.uses_tess_level_outer);
+        assert!(!evaluation.uses_tess_level_inner);
+
+        pipeline::validate_tessellation(&module, Some("tcs_main"), &module, Some("tes_main"), 3)
+            .unwrap();
+        assert!(pipeline::validate_tessellation(
+            &module,
+            Some("tcs_main"),
+            &module,
+            Some("tes_main"),
+            33
+        )
+        .is_err());
+        assert!(pipeline::validate_tessellation(
+            &module,
+            Some("tes_main"),
+            &module,
+            Some("tcs_main"),
+            3
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn variable_initializers() {
+        let data = include_bytes!("./Initializers.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let globals = module.enumerate_global_variables(None).unwrap();
+        assert_eq!(globals.len(), 3);
+
+        let weights = globals
+            .iter()
+            .find(|global| global.name == "weights")
+            .unwrap();
+        assert_eq!(weights.storage_class, types::ReflectStorageClass::Private);
+        let initializer = weights.initializer.as_ref().unwrap();
+        assert!(!initializer.is_zero());
+        match initializer.value {
+            types::ReflectConstantValue::Composite(ref constituents) => {
+                assert_eq!(
+                    constituents[1].value,
+                    types::ReflectConstantValue::Scalar(vec![2.0f32.to_bits()])
+                );
+            }
+            _ => panic!("expected a composite initializer"),
+        }
+
+        let shared_sum = globals
+            .iter()
+            .find(|global| global.name == "shared_sum")
+            .unwrap();
+        assert_eq!(
+            shared_sum.storage_class,
+            types::ReflectStorageClass::WorkGroup
+        );
+        assert!(shared_sum.initializer.as_ref().unwrap().is_zero());
+        assert_eq!(
+            module
+                .get_variable_initializer(shared_sum.spirv_id)
+                .unwrap(),
+            shared_sum.initializer
+        );
+
+        let shared_tmp = globals
+            .iter()
+            .find(|global| global.name == "shared_tmp")
+            .unwrap();
+        assert!(shared_tmp.initializer.is_none());
+    }
+
+    #[test]
+    fn kernel_arguments() {
+        let data = include_bytes!("./Kernel.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let arguments = module.enumerate_kernel_arguments(None).unwrap();
+        let names: Vec<&str> = arguments
+            .iter()
+            .map(|argument| argument.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["out", "in", "factor", "img", "scratch"]);
+
+        assert_eq!(
+            arguments[0].address_qualifier,
+            types::ReflectKernelAddressQualifier::Global
+        );
+        assert!(arguments[0]
+            .type_qualifiers
+            .contains(types::ReflectKernelTypeQualifierFlags::RESTRICT));
+        assert_eq!(
+            arguments[1].address_qualifier,
+            types::ReflectKernelAddressQualifier::Constant
+        );
+        assert!(arguments[1]
+            .type_qualifiers
+            .contains(types::ReflectKernelTypeQualifierFlags::CONST));
+        assert_eq!(arguments[1].alignment, Some(16));
+        assert_eq!(
+            arguments[2].address_qualifier,
+            types::ReflectKernelAddressQualifier::Private
+        );
+        assert_eq!(
+            arguments[3].access_qualifier,
+            Some(types::ReflectAccessQualifier::ReadOnly)
+        );
+        assert_eq!(
+            arguments[4].address_qualifier,
+            types::ReflectKernelAddressQualifier::Local
+        );
+
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+        assert!(module.enumerate_kernel_arguments(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn decorate_id_operands() {
+        let data = include_bytes!("./KernelDecorateId.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        // dst has AlignmentId of a constant 64, src has MaxByteOffsetId of a
+        // specialization constant defaulting to 256.
+        let arguments = module.enumerate_kernel_arguments(None).unwrap();
+        assert_eq!(arguments[0].alignment, Some(64));
+        assert_eq!(arguments[1].max_byte_offset, Some(256));
+
+        let decorations = module.get_decorations(arguments[0].spirv_id).unwrap();
+        assert!(decorations[0].operands_are_ids);
+        let constants = module.resolve_decoration_operands(&decorations[0]).unwrap();
+        assert_eq!(
+            constants[0].value,
+            types::ReflectConstantValue::Scalar(vec![64])
+        );
+        let decorations = module.get_decorations(arguments[1].spirv_id).unwrap();
+        assert!(module.resolve_decoration_operands(&decorations[0]).unwrap()[0].is_specialization);
+    }
+
+    #[test]
+    fn member_decorations_out_of_order() {
+        // The `OpMemberDecorate`s of `Params` are shuffled across members and
+        // interleaved with other decorations. The offsets and flags the C
+        // library reports land on the same members as in the parsed module.
+        let data = include_bytes!("./MemberDecorations.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        let struct_id = bindings[0].type_description.as_ref().unwrap().id;
+        let members = &bindings[0].block.members;
+
+        let names: Vec<_> = members.iter().map(|member| member.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c", "d"]);
+        let offsets: Vec<_> = members.iter().map(|member| member.offset).collect();
+        assert_eq!(offsets, vec![0, 16, 32, 96]);
+        for (index, member) in members.iter().enumerate() {
+            let decorations = module
+                .get_member_decorations(struct_id, index as u32)
+                .unwrap();
+            let offset = decorations
+                .iter()
+                .find(|decoration| decoration.is(spirv_headers::Decoration::Offset))
+                .map(|decoration| decoration.operands[0]);
+            assert_eq!(offset, Some(member.offset));
+        }
+        let flags: Vec<_> = members
+            .iter()
+            .map(|member| member.decoration_flags)
+            .collect();
+        assert_eq!(
+            flags,
+            vec![
+                types::ReflectDecorationFlags::NONE,
+                types::ReflectDecorationFlags::NONE,
+                types::ReflectDecorationFlags::ROW_MAJOR,
+                types::ReflectDecorationFlags::NON_WRITABLE,
+            ]
+        );
+        assert_eq!(members[2].numeric.matrix.stride, 16);
+    }
+
+    #[test]
+    fn enumeration_ordering() {
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        let keys: Vec<(u32, u32)> = bindings
+            .iter()
+            .map(|binding| (binding.set, binding.binding))
+            .collect();
+        let mut sorted_keys = keys.clone();
+        sorted_keys.sort();
+        assert_eq!(keys, sorted_keys);
+
+        let inputs = module.enumerate_input_variables(None).unwrap();
+        let locations: Vec<u32> = inputs.iter().map(|input| input.location).collect();
+        assert_eq!(locations, vec![0, 1]);
+
+        let reversed = module
+            .enumerate_descriptor_bindings_sorted_by(None, |a, b| b.binding.cmp(&a.binding))
+            .unwrap();
+        assert_eq!(reversed[0].name, "smp");
+        assert_eq!(reversed[1].name, "tex");
+
+        let sets = module
+            .enumerate_descriptor_sets_sorted_by(None, |a, b| a.name.cmp(&b.name))
+            .unwrap();
+        assert_eq!(sets[0].bindings[0].name, "smp");
+
+        let entry_points = module.enumerate_entry_points().unwrap();
+        assert_eq!(
+            entry_points[0].input_variables,
+            module.enumerate_input_variables(Some("ImGuiPs")).unwrap()
+        );
+    }
+
+    #[test]
+    fn borrowed_enumerations() {
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let mut module = ShaderModule::load_u8_data(ps_data).unwrap();
+
+        assert_eq!(
+            module.descriptor_bindings(),
+            &module.enumerate_descriptor_bindings(None).unwrap()[..]
+        );
+        assert_eq!(module.input_variables().len(), 2);
+        assert_eq!(module.output_variables().len(), 1);
+        assert_eq!(module.descriptor_bindings_in_set(0).count(), 2);
+        assert_eq!(module.descriptor_bindings_in_set(1).count(), 0);
+        let samplers: Vec<&str> = module
+            .descriptor_bindings_of_type(types::ReflectDescriptorType::Sampler)
+            .map(|binding| binding.name.as_str())
+            .collect();
+        assert_eq!(samplers, vec!["smp"]);
+        assert_eq!(
+            module
+                .entry_points_for_stages(types::ReflectShaderStageFlags::FRAGMENT)
+                .count(),
+            1
+        );
+        assert!(module.entry_point("ImGuiPs").is_some());
+
+        let set = module.descriptor_sets()[0].clone();
+        module.change_descriptor_set_number(&set, 2).unwrap();
+        assert_eq!(module.descriptor_sets()[0].set, 2);
+    }
+
+    #[test]
+    fn shared_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<ShaderModule>();
+        assert_send_sync::<types::ReflectEntryPoint>();
+
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = std::sync::Arc::new(ShaderModule::load_u8_data(ps_data).unwrap());
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let module = module.clone();
+                std::thread::spawn(move || module.descriptor_bindings().len())
+            })
+            .collect();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), 2);
+        }
+
+        let mut changed = (*module).clone();
+        let tex_descriptor = module.descriptor_bindings()[0].clone();
+        changed
+            .change_descriptor_binding_numbers(&tex_descriptor, 30, None)
+            .unwrap();
+        assert_eq!(changed.descriptor_bindings()[1].binding, 30);
+        assert_eq!(module.descriptor_bindings()[0].binding, 0);
+    }
+
+    #[test]
+    fn module_warnings() {
+        let data = include_bytes!("./Warnings.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let warnings = module.warnings();
+        assert!(warnings.iter().any(|warning| matches!(
+            warning,
+            types::ReflectWarning::MissingBinding { name, .. } if name == "smp"
+        )));
+        assert!(warnings
+            .iter()
+            .any(|warning| matches!(warning, types::ReflectWarning::UnnamedBlock { .. })));
+        assert!(warnings.iter().any(|warning| matches!(
+            warning,
+            types::ReflectWarning::UnusedBinding { name, .. } if name == "ubo"
+        )));
+        assert!(!warnings
+            .iter()
+            .any(|warning| matches!(warning, types::ReflectWarning::MissingDescriptorSet { .. })));
+
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+        assert!(module.warnings().is_empty());
+    }
+
+    #[test]
+    fn invalid_utf8_strings() {
+        let data = include_bytes!("./InvalidUtf8.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let outputs = module.enumerate_output_locations(None).unwrap();
+        assert_eq!(outputs[0].name, "out\u{FFFD}");
+        let warnings: Vec<(u32, u32)> = module
+            .warnings()
+            .iter()
+            .filter_map(|warning| match warning {
+                types::ReflectWarning::InvalidUtf8 {
+                    target_id, opcode, ..
+                } => Some((*target_id, *opcode)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                (40, spirv_headers::Op::String as u32),
+                (30, spirv_headers::Op::Name as u32)
+            ]
+        );
+    }
+
+    #[test]
+    fn builder_strictness() {
+        use spirv_reflect::builder::{ReflectAnalysisFlags, Strictness};
+
+        let data = include_bytes!("./Warnings.spirv");
+        assert!(ShaderModule::builder()
+            .strictness(Strictness::Strict)
+            .load_u8_data(data)
+            .is_err());
+        let module = ShaderModule::builder()
+            .strictness(Strictness::Lenient)
+            .load_u8_data(data)
+            .unwrap();
+        assert!(!module.warnings().is_empty());
+        let module = ShaderModule::builder()
+            .strictness(Strictness::Minimal)
+            .load_u8_data(data)
+            .unwrap();
+        assert!(module.warnings().is_empty());
+        assert!(!module.descriptor_bindings().is_empty());
+
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        assert!(ShaderModule::builder()
+            .strictness(Strictness::Strict)
+            .load_u8_data(ps_data)
+            .is_ok());
+
+        let data = include_bytes!("./ExtInst.spirv");
+        let module = ShaderModule::builder()
+            .analyses(ReflectAnalysisFlags::BARRIERS)
+            .load_u8_data(data)
+            .unwrap();
+        assert!(module
+            .entry_point("main")
+            .unwrap()
+            .ext_inst_usage()
+            .is_empty());
+
+        // A changed clone keeps the options it was loaded with.
+        let data = include_bytes!("./Warnings.spirv");
+        let module = ShaderModule::builder()
+            .analyses(ReflectAnalysisFlags::NONE)
+            .load_u8_data(data)
+            .unwrap();
+        let mut changed = module.clone();
+        let set = changed.descriptor_sets()[0].clone();
+        changed.change_descriptor_set_number(&set, 5).unwrap();
+        assert_eq!(changed.descriptor_sets()[0].set, 5);
+        assert!(changed.warnings().is_empty());
+    }
+
+    #[test]
+    fn builder_entry_point() {
+        let data = include_bytes!("./Tessellation.spirv");
+        let module = ShaderModule::builder()
+            .for_entry_point("tes_main")
+            .load_u8_data(data)
+            .unwrap();
+        assert_eq!(module.get_entry_point_name(), "tes_main");
+        assert_eq!(
+            module.get_shader_stage(),
+            types::ReflectShaderStageFlags::TESSELLATION_EVALUATION
+        );
+        assert_eq!(
+            module.get_spirv_execution_model(),
+            spirv_headers::ExecutionModel::TessellationEvaluation
+        );
+        assert_eq!(module.entry_points().len(), 1);
+        assert!(!module.input_variables().is_empty());
+        assert!(module
+            .input_variables()
+            .iter()
+            .all(|variable| [8, 9, 10].contains(&variable.spirv_id)));
+        let evaluation = module.get_tessellation_traits(None).unwrap().unwrap();
+        assert_eq!(evaluation.patch_inputs.len(), 1);
+
+        assert!(ShaderModule::builder()
+            .for_entry_point("missing")
+            .load_u8_data(data)
+            .is_err());
+    }
+
+    #[test]
+    fn builder_entry_point_change_location() {
+        // `pos` is an input of `tes_main` only, not of the first entry point.
+        let data = include_bytes!("./Tessellation.spirv");
+        let builder = ShaderModule::builder().for_entry_point("tes_main");
+        let mut module = builder.load_u8_data(data).unwrap();
+        let inputs = module.enumerate_input_variables(None).unwrap();
+        let pos = inputs.iter().find(|input| input.name == "pos").unwrap();
+        module.change_input_variable_location(pos, 4).unwrap();
+
+        let input_location = |module: &ShaderModule| {
+            module
+                .input_variables()
+                .iter()
+                .find(|input| input.name == "pos")
+                .map(|input| input.location)
+        };
+        assert_eq!(input_location(&module), Some(4));
+        let reloaded = builder.load_u32_data(&module.get_code()).unwrap();
+        assert_eq!(input_location(&reloaded), Some(4));
+    }
+
+    #[test]
+    fn synthesized_names() {
+        let data = include_bytes!("./Stripped.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        let texture = &bindings[0];
+        assert_eq!(texture.name, "_set0_binding0");
+        assert!(texture.name_synthesized);
+
+        let ubo = &bindings[1];
+        assert_eq!(ubo.name, "_set1_binding3");
+        assert!(ubo.name_synthesized);
+        assert_eq!(ubo.block.name, "_set1_binding3");
+        assert!(ubo.block.name_synthesized);
+        let ubo_type_id = ubo.block.type_description.as_ref().unwrap().id;
+        assert_eq!(ubo.block.members.len(), 2);
+        for (index, member) in ubo.block.members.iter().enumerate() {
+            assert_eq!(member.name, format!("_type{}_member{}", ubo_type_id, index));
+            assert!(member.name_synthesized);
+        }
+
+        let push_constants = module.enumerate_push_constant_blocks(None).unwrap();
+        let push_type_id = push_constants[0].type_description.as_ref().unwrap().id;
+        assert_eq!(push_constants[0].name, format!("_type{}", push_type_id));
+        assert!(push_constants[0].name_synthesized);
+        assert_eq!(
+            push_constants[0].members[0].name,
+            format!("_type{}_member0", push_type_id)
+        );
+
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        assert!(bindings.iter().all(|binding| !binding.name_synthesized));
+    }
+
+    #[test]
+    fn stripped_module_reflection() {
+        let data = include_bytes!("./StrippedCounter.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        let counter = bindings[0].uav_counter_binding.as_ref().unwrap();
+        assert_eq!(counter.binding, 1);
+        assert_eq!(bindings[0].uav_counter_id, bindings[1].spirv_id);
+        let sets = module.enumerate_descriptor_sets(None).unwrap();
+        assert_eq!(
+            sets[0].bindings[0]
+                .uav_counter_binding
+                .as_ref()
+                .unwrap()
+                .binding,
+            1
+        );
+
+        let inputs = module.enumerate_input_variables(None).unwrap();
+        assert_eq!(inputs[0].name, "_location2");
+        assert!(inputs[0].name_synthesized);
+        let outputs = module.enumerate_output_variables(None).unwrap();
+        assert_eq!(outputs[0].name, "_location0");
+
+        assert!(!module
+            .warnings()
+            .iter()
+            .any(|warning| matches!(warning, types::ReflectWarning::UnnamedBlock { .. })));
+    }
+
+    #[test]
+    fn image_bindings_accessed() {
+        let data = include_bytes!("./ImageAccess.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        let accessed: Vec<(&str, bool)> = bindings
+            .iter()
+            .map(|binding| (binding.name.as_str(), binding.accessed))
+            .collect();
+        assert_eq!(
+            accessed,
+            vec![
+                ("tex", true),
+                ("smp", true),
+                ("subpass", true),
+                ("counts", true),
+                ("unused", false),
+            ]
+        );
+
+        let used = module.enumerate_used_descriptor_sets(None).unwrap();
+        assert_eq!(used[0].bindings.len(), 4);
+    }
+
+    #[test]
+    fn generator_quirks() {
+        use spirv_reflect::builder::ReflectQuirkFlags;
+
+        let with_generator = |tool_id: u32| {
+            let mut data = include_bytes!("./GeneratorQuirks.spirv").to_vec();
+            data[8..12].copy_from_slice(&(tool_id << 16 | 11).to_le_bytes());
+            data
+        };
+        let unnamed_block = |module: &ShaderModule| {
+            module
+                .warnings()
+                .iter()
+                .any(|warning| matches!(warning, types::ReflectWarning::UnnamedBlock { .. }))
+        };
+
+        let module = ShaderModule::load_u8_data(&with_generator(8)).unwrap();
+        assert_eq!(
+            module.get_generator(),
+            types::ReflectGenerator::KhronosGlslangReferenceFrontEnd
+        );
+        assert_eq!(
+            module.get_quirks(),
+            ReflectQuirkFlags::GLSLANG_READONLY_MEMBERS | ReflectQuirkFlags::LEGACY_COUNTER_NAMES
+        );
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        assert!(bindings[0]
+            .block
+            .decoration_flags
+            .contains(types::ReflectDecorationFlags::NON_WRITABLE));
+        assert!(unnamed_block(&module));
+
+        let module = ShaderModule::load_u8_data(&with_generator(15)).unwrap();
+        assert_eq!(
+            module.get_generator(),
+            types::ReflectGenerator::GoogleRspirv
+        );
+        assert_eq!(
+            module.get_quirks(),
+            ReflectQuirkFlags::RSPIRV_UNNAMED_BLOCKS
+        );
+        assert!(!unnamed_block(&module));
+
+        let module = ShaderModule::builder()
+            .quirks(ReflectQuirkFlags::NONE)
+            .load_u8_data(&with_generator(15))
+            .unwrap();
+        assert_eq!(module.get_quirks(), ReflectQuirkFlags::NONE);
+        assert!(unnamed_block(&module));
+
+        let data = include_bytes!("./LegacyCounters.spirv");
+        let module = ShaderModule::builder()
+            .quirks(ReflectQuirkFlags::all() - ReflectQuirkFlags::LEGACY_COUNTER_NAMES)
+            .load_u8_data(data)
+            .unwrap();
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        assert!(bindings
+            .iter()
+            .all(|binding| binding.uav_counter_binding.is_none()));
+    }
+
+    #[test]
+    fn legacy_counter_names() {
+        let data = include_bytes!("./LegacyCounters.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        let counter = bindings[0].uav_counter_binding.as_ref().unwrap();
+        assert_eq!(counter.name, "buf@count");
+        assert_eq!(bindings[0].uav_counter_id, bindings[1].spirv_id);
+        assert_eq!(
+            bindings[0].uav_counter_association,
+            Some(types::ReflectCounterAssociation::NameSuffix)
+        );
+        assert!(bindings[2].uav_counter_binding.is_none());
+
+        let module = ShaderModule::builder()
+            .counter_name_suffix(Some(".ctr"))
+            .load_u8_data(data)
+            .unwrap();
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        assert!(bindings[0].uav_counter_binding.is_none());
+        assert_eq!(bindings[0].uav_counter_id, u32::MAX);
+        let counter = bindings[2].uav_counter_binding.as_ref().unwrap();
+        assert_eq!(counter.name, "other.ctr");
+
+        let module = ShaderModule::builder()
+            .counter_name_suffix(None)
+            .load_u8_data(data)
+            .unwrap();
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        assert!(bindings
+            .iter()
+            .all(|binding| binding.uav_counter_association.is_none()));
+
+        let data = include_bytes!("./StrippedCounter.spirv");
+        let module = ShaderModule::builder()
+            .counter_name_suffix(None)
+            .load_u8_data(data)
+            .unwrap();
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        assert_eq!(
+            bindings[0].uav_counter_association,
+            Some(types::ReflectCounterAssociation::Decoration)
+        );
+    }
+
+    #[test]
+    fn disassembly() {
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+
+        let text = module.disassemble().unwrap();
+        assert!(text.starts_with("; SPIR-V\n; Version: 1.0\n"));
+        assert!(text.contains("OpEntryPoint Fragment %1 \"ImGuiPs\" %2 %3 %4 %5\n"));
+        assert!(text.contains("%11 = OpTypeFloat 32\n"));
+        assert!(text.contains(" %6 = OpTypeImage %11 2D 2 0 0 1 Unknown\n"));
+        assert!(text.contains("%28 = OpImageSampleExplicitLod %16 %27 %23 Lod %21\n"));
+        assert!(!text.contains("; set"));
+
+        let annotated = module.disassemble_annotated().unwrap();
+        assert!(annotated.contains(" %7 = OpVariable %12 UniformConstant ; set 0, binding 0, "));
+        assert!(annotated.contains(" %3 = OpVariable %19 Input ; location 0\n"));
+    }
+
+    #[test]
+    fn call_graph_dot() {
+        let data = include_bytes!("./CallGraph.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let dot = module.call_graph_dot(Some("main")).unwrap();
+        assert!(dot.starts_with("digraph \"main\" {\n"));
+        assert!(dot.contains("    f1 [label=\"main\", style=bold];\n"));
+        assert!(dot.contains("    f1 -> f3;\n"));
+        assert!(dot.contains("    f3 -> f4;\n"));
+        assert!(dot.contains("    r6 [shape=ellipse, label=\"albedo\\nset 0, binding 1\"];\n"));
+        assert!(dot.contains("    f3 -> r6 [style=dashed];\n"));
+        assert!(dot.contains("    f4 -> r5 [style=dashed];\n"));
+        assert!(!dot.contains("f1 -> r"));
+
+        assert!(module.call_graph_dot(Some("missing")).is_err());
+    }
+
+    #[test]
+    fn describe_report() {
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+
+        let report = module.describe();
+        assert!(report.starts_with("entry point \"ImGuiPs\" (FRAGMENT)\n  set 0:\n"));
+        assert!(report.contains("    binding 0: \"tex\" SampledImage\n"));
+        assert!(report.contains("    binding 0: \"smp\" Sampler\n"));
+        assert!(report.contains("  input built-in FragCoord: "));
+        assert!(report.contains("  input location 0: \"in.var.TEXCOORD0\" R32G32_SFLOAT\n"));
+        assert!(report.contains("  input location 1: \"in.var.COLOR0\" R32G32B32A32_SFLOAT\n"));
+        assert!(
+            report.contains("  output location 0: \"out.var.SV_Target0\" R32G32B32A32_SFLOAT\n")
+        );
+        assert_eq!(module.to_string(), report);
+    }
+
+    #[test]
+    fn reflection_diff() {
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let before = ShaderModule::load_u8_data(ps_data).unwrap();
+        let mut after = ShaderModule::load_u8_data(ps_data).unwrap();
+        assert!(
+            diff::reflect_diff(&before.to_cache().unwrap(), &after.to_cache().unwrap()).is_empty()
+        );
+
+        let smp = after
+            .enumerate_descriptor_bindings(None)
+            .unwrap()
+            .into_iter()
+            .find(|binding| binding.name == "smp")
+            .unwrap();
+        after
+            .change_descriptor_binding_numbers(&smp, 3, None)
+            .unwrap();
+        let color = after
+            .enumerate_input_variables(None)
+            .unwrap()
+            .into_iter()
+            .find(|variable| variable.location == 1)
+            .unwrap();
+        after.change_input_variable_location(&color, 5).unwrap();
+
+        let diff = diff::reflect_diff(&before.to_cache().unwrap(), &after.to_cache().unwrap());
+        assert_eq!(diff.added_bindings.len(), 1);
+        assert_eq!(diff.added_bindings[0].binding, 3);
+        assert_eq!(diff.removed_bindings.len(), 1);
+        assert_eq!(diff.removed_bindings[0].name, "smp");
+        assert!(diff.changed_bindings.is_empty());
+        assert!(diff.push_constants.is_none());
+        assert_eq!(diff.added_inputs[0].location, 5);
+        assert_eq!(diff.removed_inputs[0].location, 1);
+        assert!(diff.changed_inputs.is_empty());
+        assert!(diff.added_outputs.is_empty() && diff.removed_outputs.is_empty());
+    }
+
+    #[test]
+    fn binding_array_of_blocks() {
+        let data = include_bytes!("./BlockArray.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        // uniform Light { vec4 color; float intensity; } lights[4], settings;
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        let lights = &bindings[0];
+        assert!(lights.is_descriptor_array());
+        assert_eq!(lights.count, 4);
+        assert!(lights.block.array.dims.is_empty());
+        assert_eq!(lights.element_block_size(), Some(32));
+
+        let settings = &bindings[1];
+        assert!(!settings.is_descriptor_array());
+        assert_eq!(settings.count, 1);
+        assert_eq!(settings.element_block_size(), Some(32));
+        assert!(lights.block.layout_compatible(&settings.block));
+    }
+
+    #[test]
+    fn binding_arrays_of_buffer_blocks() {
+        let data = include_bytes!("./BufferBlockArrays.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        // buffer Buffer { uint count; float data[]; } grid[2][3], tail[];
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        let grid = &bindings[0];
+        assert_eq!(grid.array.dims, vec![2, 3]);
+        assert_eq!(grid.count, 6);
+        assert!(grid.block.array.dims.is_empty());
+        assert_eq!(grid.element_block_size(), Some(16));
+        assert_eq!(grid.block.members[1].offset, 16);
+        assert!(grid.element_block(&[1, 2]).is_some());
+        assert!(grid.element_block(&[2, 0]).is_none());
+        assert!(grid.element_block(&[5]).is_none());
+
+        let tail = &bindings[1];
+        assert!(tail.block.array.dims.is_empty() && !tail.block.array.runtime);
+        assert_eq!(tail.element_block_size(), Some(16));
+        assert!(tail.element_block(&[100]).is_some());
+        assert!(grid.block.layout_compatible(&tail.block));
+    }
+
+    #[test]
+    fn binding_array_of_arrays() {
+        let data = include_bytes!("./ArrayOfArrays.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let bindings = module.enumerate_descriptor_bindings(None).unwrap();
+        let array = &bindings[0].array;
+        assert_eq!(array.dims, vec![4, 3]);
+        assert_eq!(bindings[0].count, 12);
+        assert_eq!(array.element_count(), 12);
+        assert_eq!(array.strides(), vec![3, 1]);
+        assert_eq!(array.flatten_index(&[2, 1]), Some(7));
+        assert_eq!(array.unflatten_index(7), Some(vec![2, 1]));
+        assert_eq!(array.flatten_index(&[4, 0]), None);
+        assert_eq!(array.flatten_index(&[1]), None);
+        assert_eq!(array.unflatten_index(12), None);
+    }
+
+    #[test]
+    fn specialization_dependent_sizes() {
+        let data = include_bytes!("./SpecSizes.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let size = module.get_workgroup_size(None).unwrap().unwrap();
+        assert_eq!(
+            size[0],
+            types::ReflectSizeExpr::SpecConstant {
+                spec_id: 0,
+                default: 64
+            }
+        );
+        assert_eq!(size[1], types::ReflectSizeExpr::Constant(1));
+        assert_eq!(size[0].evaluate(&|spec_id| Some(spec_id + 32)), Some(32));
+
+        let globals = module.enumerate_global_variables(None).unwrap();
+        let dims = module
+            .get_array_size_exprs(&globals[0].type_description)
+            .unwrap();
+        assert_eq!(dims.len(), 1);
+        assert!(dims[0].is_specialized());
+        assert_eq!(dims[0].default_value(), Some(8));
+        assert_eq!(
+            dims[0].evaluate(&|spec_id| if spec_id == 3 { Some(16) } else { None }),
+            Some(32)
+        );
+    }
+
+    #[test]
+    fn array_length_constants() {
+        let data = include_bytes!("./ArrayLengths.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        // Lengths from OpConstantNull, a 64-bit OpConstant, OpSpecConstantOp,
+        // OpConstantComposite and a float OpConstant.
+        let globals = module.enumerate_global_variables(None).unwrap();
+        let dims: Vec<_> = globals
+            .iter()
+            .map(|global| {
+                module
+                    .get_array_size_exprs(&global.type_description)
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(dims[0], vec![types::ReflectSizeExpr::Constant(0)]);
+        assert_eq!(dims[1], vec![types::ReflectSizeExpr::Constant(5)]);
+        assert_eq!(dims[2][0].default_value(), Some(3));
+        assert_eq!(dims[3], vec![types::ReflectSizeExpr::Unknown]);
+        assert_eq!(dims[4], vec![types::ReflectSizeExpr::Unknown]);
+
+        let array_dims: Vec<_> = globals
+            .iter()
+            .map(|global| global.type_description.traits.array.dims.clone())
+            .collect();
+        assert_eq!(
+            array_dims,
+            vec![vec![0], vec![5], vec![3], vec![0], vec![0]]
+        );
+    }
+
+    #[test]
+    fn compact_descriptor_sets() {
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let mut module = ShaderModule::load_u8_data(ps_data).unwrap();
+
+        let descriptor_sets = module.enumerate_descriptor_sets(None).unwrap();
+        module
+            .change_descriptor_binding_numbers(&descriptor_sets[0].bindings[1], 1, Some(7))
+            .unwrap();
+        let descriptor_sets = module.enumerate_descriptor_sets(None).unwrap();
+        module
+            .change_descriptor_set_number(&descriptor_sets[0], 3)
+            .unwrap();
+
+        let remap = module.compact_descriptor_sets().unwrap();
+        assert_eq!(remap.into_iter().collect::<Vec<_>>(), vec![(3, 0), (7, 1)]);
+        let sets: Vec<u32> = module
+            .enumerate_descriptor_sets(None)
+            .unwrap()
+            .iter()
+            .map(|set| set.set)
+            .collect();
+        assert_eq!(sets, vec![0, 1]);
+    }
+
+    #[test]
+    fn incremental_reflection_updates() {
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let mut module = ShaderModule::load_u8_data(ps_data).unwrap();
+        assert!(module.revalidate().is_ok());
+
+        let smp = module.descriptor_bindings()[1].clone();
+        module
+            .change_descriptor_binding_numbers(&smp, 4, Some(2))
+            .unwrap();
+        let set = module.descriptor_sets()[0].clone();
+        module.change_descriptor_set_number(&set, 1).unwrap();
+        let color = module.input_variables()[0].clone();
+        module.change_input_variable_location(&color, 6).unwrap();
+
+        assert_eq!(
+            module.descriptor_bindings(),
+            &module.enumerate_descriptor_bindings(None).unwrap()[..]
+        );
+        assert_eq!(
+            module.descriptor_sets(),
+            &module.enumerate_descriptor_sets(None).unwrap()[..]
+        );
+        assert_eq!(
+            module.input_variables(),
+            &module.enumerate_input_variables(None).unwrap()[..]
+        );
+        assert_eq!(
+            module.entry_points(),
+            &module.enumerate_entry_points().unwrap()[..]
+        );
+        assert_eq!(module.binding_for_id(smp.spirv_id).unwrap().set, 2);
+        assert!(module.revalidate().is_ok());
+    }
+
+    #[test]
+    fn synthetic_reflection() {
+        let push = types::ReflectBlockVariable {
+            name: "push".to_owned(),
+            size: 16,
+            padded_size: 16,
+            ..Default::default()
+        };
+        let reflection =
+            synthetic::ReflectionBuilder::new("main", types::ReflectShaderStageFlags::FRAGMENT)
+                .add_binding("smp", 1, 0, types::ReflectDescriptorType::Sampler, 1)
+                .add_binding(
+                    "textures",
+                    0,
+                    2,
+                    types::ReflectDescriptorType::SampledImage,
+                    0,
+                )
+                .add_buffer_binding(
+                    "globals",
+                    0,
+                    0,
+                    types::ReflectDescriptorType::UniformBuffer,
+                    types::ReflectBlockVariable {
+                        size: 64,
+                        padded_size: 64,
+                        ..Default::default()
+                    },
+                )
+                .add_push_constant_block(push)
+                .add_input_variable("uv", 1, types::ReflectFormat::R32G32_SFLOAT)
+                .add_input_variable("color", 0, types::ReflectFormat::R32G32B32A32_SFLOAT)
+                .add_output_variable("target", 0, types::ReflectFormat::R32G32B32A32_SFLOAT)
+                .build();
+
+        let sets: Vec<(u32, Vec<(&str, u32)>)> = reflection
+            .descriptor_sets
+            .iter()
+            .map(|set| {
+                let bindings = set
+                    .bindings
+                    .iter()
+                    .map(|binding| (binding.name.as_str(), binding.binding))
+                    .collect();
+                (set.set, bindings)
+            })
+            .collect();
+        assert_eq!(
+            sets,
+            vec![
+                (0, vec![("globals", 0), ("textures", 2)]),
+                (1, vec![("smp", 0)]),
+            ]
+        );
+        assert_eq!(reflection.descriptor_sets[0].bindings[1].count, 0);
+        assert_eq!(reflection.input_variables[0].name, "color");
+        assert_eq!(
+            reflection.entry_points[0]
+                .input_by_location(1)
+                .unwrap()
+                .name,
+            "uv"
+        );
+        assert_eq!(reflection.entry_points[0].used_uniforms.len(), 3);
+        assert_eq!(
+            cache::ReflectModuleCache::from_cache_bytes(&reflection.to_cache_bytes()).unwrap(),
+            reflection
+        );
+
+        let template =
+            update_template::update_template(&reflection.descriptor_sets[0], &Default::default())
+                .unwrap();
+        assert_eq!(template.entries.len(), 1);
+    }
+
+    #[test]
+    fn pipeline_binding_conflicts() {
+        let imgui = ShaderModule::load_u8_data(include_bytes!("./ImGuiPs.spirv")).unwrap();
+        let arrays = ShaderModule::load_u8_data(include_bytes!("./ArrayOfArrays.spirv")).unwrap();
+        let mut pipeline = pipeline::PipelineReflection::new();
+        pipeline.add_stage(&imgui, None).unwrap();
+        pipeline.add_stage(&arrays, None).unwrap();
+        pipeline.add_stage(&imgui, None).unwrap();
+
+        let resolved = pipeline.auto_resolve_binding_conflicts().unwrap();
+        assert_eq!(resolved.code.len(), 3);
+        let moved = ShaderModule::load_u32_data(&resolved.code[1]).unwrap();
+        let bindings = moved.enumerate_descriptor_bindings(None).unwrap();
+        assert_eq!((bindings[0].set, bindings[0].binding), (0, 1));
+        assert_eq!(resolved.code[2], imgui.get_code());
+
+        let set = &resolved.descriptor_sets[0];
+        let names: Vec<(&str, u32)> = set
+            .bindings
+            .iter()
+            .map(|binding| (binding.name.as_str(), binding.binding))
+            .collect();
+        assert_eq!(names, vec![("tex", 0), ("smp", 0), ("s", 1)]);
+    }
+
+    #[cfg(feature = "spirv-tools")]
+    #[test]
+    fn validate_valid_module() {
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+        assert_eq!(module.validate(), Ok(()));
+    }
+
+    #[cfg(feature = "spirv-tools")]
+    #[test]
+    fn validate_invalid_module() {
+        // Loads a float through a pointer as an unsigned integer, which the
+        // C library doesn't check.
+        let data = include_bytes!("./InvalidLoad.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        let error = module.validate().unwrap_err();
+        assert!(!error.message.is_empty());
+        assert!(error.to_string().starts_with(&error.message));
+    }
+
+    #[cfg(feature = "naga-interface")]
+    #[test]
+    fn naga_entry_points() {
+        use naga_interface::*;
+        let module = ShaderModule::load_u8_data(include_bytes!("./SharedBindings.spirv")).unwrap();
+        let entry_points = naga_interface::entry_points(&module).unwrap();
+        assert_eq!(entry_points.len(), 2);
+
+        let globals = NagaGlobalVariable {
+            name: Some("globals".to_owned()),
+            space: NagaAddressSpace::Uniform,
+            binding: Some(NagaResourceBinding {
+                group: 0,
+                binding: 0,
+            }),
+        };
+        let vs = &entry_points[0];
+        assert_eq!(vs.stage, NagaShaderStage::Vertex);
+        assert_eq!(vs.workgroup_size, [0; 3]);
+        assert!(vs.inputs.is_empty());
+        assert_eq!(
+            vs.outputs[0].binding,
+            NagaBinding::BuiltIn(types::ReflectBuiltIn::from(
+                spirv_headers::BuiltIn::Position
+            ))
+        );
+        assert_eq!(vs.globals, vec![globals.clone()]);
+
+        let fs = &entry_points[1];
+        assert_eq!(fs.stage, NagaShaderStage::Fragment);
+        assert_eq!(
+            fs.outputs,
+            vec![NagaVarying {
+                name: Some("color".to_owned()),
+                binding: NagaBinding::Location {
+                    location: 0,
+                    second_blend_source: false,
+                    interpolation: None,
+                },
+            }]
+        );
+        assert_eq!(fs.globals.len(), 2);
+        assert_eq!(fs.globals[0], globals);
+        assert_eq!(
+            fs.globals[1].binding,
+            Some(NagaResourceBinding {
+                group: 0,
+                binding: 1
+            })
+        );
+    }
+
+    #[test]
+    fn gl_binding_points() {
+        let module = ShaderModule::load_u8_data(include_bytes!("./MixedBindings.spirv")).unwrap();
+        let sets = module.enumerate_descriptor_sets(None).unwrap();
+        let bases = gl::ReflectGlBindingBases {
+            texture: 4,
+            ..Default::default()
+        };
+        let points: Vec<_> = gl::gl_bindings(&sets, &bases)
+            .unwrap()
+            .into_iter()
+            .map(|point| (point.name, point.kind, point.gl_binding, point.count))
+            .collect();
+
+        use gl::ReflectGlBindingKind::*;
+        assert_eq!(
+            points,
+            vec![
+                ("camera".to_owned(), UniformBuffer, 0, 1),
+                ("textures".to_owned(), Texture, 4, 2),
+                ("particles".to_owned(), ShaderStorageBuffer, 0, 1),
+                ("target".to_owned(), Image, 0, 1),
+                ("material".to_owned(), UniformBuffer, 1, 1),
+            ]
+        );
+    }
+
+    #[cfg(feature = "msl")]
+    #[test]
+    fn msl_slots() {
+        let module = ShaderModule::load_u8_data(include_bytes!("./MixedBindings.spirv")).unwrap();
+        let sets = module.enumerate_descriptor_sets(None).unwrap();
+        let slots = |layout: &msl::ReflectMslLayout| {
+            layout
+                .bindings
+                .iter()
+                .map(|binding| {
+                    let slots = binding.slots;
+                    (
+                        binding.name.clone(),
+                        slots.buffer,
+                        slots.texture,
+                        slots.sampler,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let discrete = msl::msl_layout(&sets, true, &Default::default()).unwrap();
+        assert_eq!(
+            slots(&discrete),
+            vec![
+                ("camera".to_owned(), Some(0), None, None),
+                ("textures".to_owned(), None, Some(0), Some(0)),
+                ("particles".to_owned(), Some(1), None, None),
+                ("target".to_owned(), None, Some(2), None),
+                ("material".to_owned(), Some(2), None, None),
+                ("linear".to_owned(), None, None, Some(2)),
+            ]
+        );
+        assert!(discrete.argument_buffers.is_empty());
+        assert_eq!(discrete.push_constant_buffer, Some(3));
+
+        let options = msl::ReflectMslOptions {
+            model: msl::ReflectMslBindingModel::ArgumentBuffers,
+            ..Default::default()
+        };
+        let argument_buffers = msl::msl_layout(&sets, false, &options).unwrap();
+        assert_eq!(
+            slots(&argument_buffers),
+            vec![
+                ("camera".to_owned(), Some(0), None, None),
+                ("textures".to_owned(), None, Some(1), Some(3)),
+                ("particles".to_owned(), Some(0), None, None),
+                ("target".to_owned(), None, Some(1), None),
+                ("material".to_owned(), Some(2), None, None),
+                ("linear".to_owned(), None, None, Some(3)),
+            ]
+        );
+        assert_eq!(
+            argument_buffers.argument_buffers,
+            vec![
+                msl::ReflectMslArgumentBuffer { set: 0, buffer: 0 },
+                msl::ReflectMslArgumentBuffer { set: 1, buffer: 1 },
+            ]
+        );
+        assert_eq!(argument_buffers.push_constant_buffer, None);
+    }
+
+    #[cfg(feature = "d3d12")]
+    #[test]
+    fn d3d12_root_signature() {
+        use d3d12::ReflectD3d12RootParameter as Parameter;
+
+        let module = ShaderModule::load_u8_data(include_bytes!("./MixedBindings.spirv")).unwrap();
+        let sets = module.enumerate_descriptor_sets(None).unwrap();
+        let push_constants = [pipeline::ReflectPushConstantRange {
+            stage_flags: types::ReflectShaderStageFlags::FRAGMENT,
+            offset: 0,
+            size: 18,
+        }];
+        let summary = |signature: &d3d12::ReflectD3d12RootSignature| {
+            signature
+                .parameters
+                .iter()
+                .map(|parameter| match parameter {
+                    Parameter::Constants {
+                        num_32bit_values,
+                        register_space,
+                        ..
+                    } => format!("constants {} space{}", num_32bit_values, register_space),
+                    Parameter::Descriptor {
+                        range_type,
+                        shader_register,
+                        register_space,
+                        ..
+                    } => format!(
+                        "root {:?} {} space{}",
+                        range_type, shader_register, register_space
+                    ),
+                    Parameter::DescriptorTable { set, ranges, .. } => format!(
+                        "table {}: {}",
+                        set,
+                        ranges
+                            .iter()
+                            .map(|range| format!(
+                                "{:?} {}+{} @{}",
+                                range.range_type,
+                                range.base_shader_register,
+                                range.num_descriptors,
+                                range.offset_in_descriptors_from_table_start
+                            ))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let signature = d3d12::root_signature(&sets, &push_constants, &Default::default()).unwrap();
+        assert_eq!(
+            summary(&signature),
+            vec![
+                "constants 5 space2",
+                "table 0: Cbv 0+1 @0, Srv 1+2 @1",
+                "table 0: Sampler 1+2 @0",
+                "table 1: Uav 0+1 @0, Uav 1+1 @1, Cbv 2+1 @2",
+                "table 1: Sampler 3+1 @0",
+            ]
+        );
+        assert_eq!(signature.cost(), 9);
+        match &signature.parameters[0] {
+            Parameter::Constants { visibility, .. } => {
+                assert_eq!(*visibility, d3d12::ReflectD3d12Visibility::Pixel)
+            }
+            _ => panic!("expected root constants first"),
+        }
+
+        let options = d3d12::ReflectD3d12Options {
+            root_descriptors: vec![(0, 0), (1, 0), (1, 1)],
+        };
+        let signature = d3d12::root_signature(&sets, &[], &options).unwrap();
+        assert_eq!(
+            summary(&signature),
+            vec![
+                "root Cbv 0 space0",
+                "root Uav 0 space1",
+                "table 0: Srv 1+2 @0",
+                "table 0: Sampler 1+2 @0",
+                "table 1: Uav 1+1 @0, Cbv 2+1 @1",
+                "table 1: Sampler 3+1 @0",
+            ]
+        );
+        assert_eq!(signature.cost(), 8);
+    }
+
+    #[test]
+    fn descriptor_update_template() {
+        use types::ReflectDescriptorType as Type;
+
+        let module = ShaderModule::load_u8_data(include_bytes!("./MixedBindings.spirv")).unwrap();
+        let sets = module.enumerate_descriptor_sets(None).unwrap();
+        let entries = |template: &update_template::ReflectUpdateTemplate| {
+            template
+                .entries
+                .iter()
+                .map(|entry| {
+                    (
+                        entry.binding,
+                        entry.count,
+                        entry.descriptor_type,
+                        entry.offset,
+                        entry.stride,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let template = update_template::update_template(&sets[0], &Default::default()).unwrap();
+        assert_eq!(template.set, 0);
+        assert_eq!(
+            entries(&template),
+            vec![
+                (0, 1, Type::UniformBuffer, 0, 24),
+                (1, 2, Type::CombinedImageSampler, 24, 24),
+            ]
+        );
+        assert_eq!(template.size, 72);
+
+        let options = update_template::ReflectUpdateTemplateOptions {
+            base_offset: 16,
+            ..Default::default()
+        };
+        let template = update_template::update_template(&sets[1], &options).unwrap();
+        assert_eq!(
+            entries(&template),
+            vec![
+                (0, 1, Type::StorageBuffer, 16, 24),
+                (1, 1, Type::StorageImage, 40, 24),
+                (2, 1, Type::UniformBuffer, 64, 24),
+                (3, 1, Type::Sampler, 88, 24),
+            ]
+        );
+        assert_eq!(template.size, 112);
+    }
+
+    #[test]
+    fn pipeline_binding_stages() {
+        let module = ShaderModule::load_u8_data(include_bytes!("./SharedBindings.spirv")).unwrap();
+        let stages = |bindings: &[types::ReflectDescriptorBinding]| {
+            bindings
+                .iter()
+                .map(|binding| (binding.name.clone(), binding.stage_flags))
+                .collect::<Vec<_>>()
+        };
+        let vertex = types::ReflectShaderStageFlags::VERTEX;
+        let fragment = types::ReflectShaderStageFlags::FRAGMENT;
+        assert_eq!(
+            stages(&module.enumerate_descriptor_bindings(None).unwrap()),
+            vec![
+                ("globals".to_owned(), vertex | fragment),
+                ("tint".to_owned(), fragment),
+            ]
+        );
+        assert_eq!(
+            stages(&module.enumerate_descriptor_bindings(Some("vs")).unwrap()),
+            vec![("globals".to_owned(), vertex)]
+        );
+
+        let mut pipeline = pipeline::PipelineReflection::new();
+        pipeline.add_stage(&module, Some("vs")).unwrap();
+        pipeline.add_stage(&module, Some("fs")).unwrap();
+        let sets = pipeline.descriptor_sets().unwrap();
+        assert_eq!(sets.len(), 1);
+        assert_eq!(
+            stages(&sets[0].bindings),
+            vec![
+                ("globals".to_owned(), vertex | fragment),
+                ("tint".to_owned(), fragment),
+            ]
+        );
+    }
+
+    #[test]
+    fn pipeline_push_constant_ranges() {
+        let module =
+            ShaderModule::load_u8_data(include_bytes!("./PushConstantStages.spirv")).unwrap();
+        let push_constants = module.enumerate_push_constants(Some("fs")).unwrap();
+        assert_eq!(push_constants.len(), 1);
+        assert_eq!(push_constants[0].name, "fs_pc");
+        assert_eq!(push_constants[0].type_name, "FsConstants");
+        assert_eq!(
+            push_constants[0].stage_flags,
+            types::ReflectShaderStageFlags::FRAGMENT
+        );
+
+        let range = |stage_flags, offset, size| pipeline::ReflectPushConstantRange {
+            stage_flags,
+            offset,
+            size,
+        };
+        let vertex = types::ReflectShaderStageFlags::VERTEX;
+        let geometry = types::ReflectShaderStageFlags::GEOMETRY;
+        let fragment = types::ReflectShaderStageFlags::FRAGMENT;
+
+        // Adjacent ranges stay apart.
+        let mut pipeline = pipeline::PipelineReflection::new();
+        pipeline.add_stage(&module, Some("vs")).unwrap();
+        pipeline.add_stage(&module, Some("fs")).unwrap();
+        assert_eq!(
+            pipeline.push_constant_ranges().unwrap(),
+            vec![range(vertex, 0, 16), range(fragment, 16, 16)]
+        );
+
+        // The geometry range overlaps both.
+        pipeline.add_stage(&module, Some("gs")).unwrap();
+        assert_eq!(
+            pipeline.push_constant_ranges().unwrap(),
+            vec![range(vertex | geometry | fragment, 0, 32)]
+        );
+    }
+
+    #[test]
+    fn entry_point_descriptor_sets() {
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let mut module = ShaderModule::load_u8_data(ps_data).unwrap();
+
+        let entry_points = module.enumerate_entry_points().unwrap();
+        assert_eq!(entry_points[0].descriptor_sets.len(), 1);
+        assert_eq!(entry_points[0].descriptor_sets[0].bindings.len(), 2);
+        assert_eq!(entry_points[0].used_set_mask(), 0b1);
+
+        let descriptor_sets = module.enumerate_descriptor_sets(None).unwrap();
+        module
+            .change_descriptor_set_number(&descriptor_sets[0], 2)
+            .unwrap();
+        assert_eq!(module.entry_points()[0].used_set_mask(), 0b100);
+    }
+
+    #[test]
+    fn interface_block_locations() {
+        let data = include_bytes!("./InterfaceBlocks.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let inputs = module.enumerate_input_locations(None).unwrap();
+        let flattened: Vec<(&str, u32, u32)> = inputs
+            .iter()
+            .map(|input| (input.name.as_str(), input.location, input.location_count))
+            .collect();
+        assert_eq!(
+            flattened,
+            vec![
+                ("vs_out.uv", 1, 1),
+                ("vs_out.m[0]", 5, 1),
+                ("vs_out.m[1]", 6, 1),
+                ("vs_out.d", 7, 2),
+                ("vs_out.c[0]", 9, 1),
+                ("vs_out.c[1]", 10, 1),
+            ]
+        );
+        assert_eq!(inputs[3].member_path, vec![2]);
+        assert_eq!(inputs[3].format, types::ReflectFormat::R64G64B64_SFLOAT);
+
+        let outputs = module.enumerate_output_locations(None).unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].name, "color");
+    }
+
+    #[test]
+    fn io_signature() {
+        let data = include_bytes!("./InterfaceBlocks.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        let signature = module.entry_points()[0].io_signature();
+
+        let locations: Vec<(u32, bool)> = signature
+            .inputs
+            .iter()
+            .map(|input| (input.location, input.built_in.is_some()))
+            .collect();
+        assert_eq!(
+            locations,
+            vec![(1, false), (5, false), (7, false), (9, false)]
+        );
+        assert_eq!(
+            signature.inputs[2].format,
+            types::ReflectFormat::R64G64B64_SFLOAT
+        );
+        assert!(signature.outputs[0].built_in.is_none());
+        assert!(signature.outputs.last().unwrap().built_in.is_some());
+
+        let reloaded = ShaderModule::load_u8_data(data).unwrap();
+        assert_eq!(reloaded.entry_points()[0].io_signature(), signature);
+
+        let mut previous = types::ReflectIoSignature {
+            inputs: Vec::new(),
+            outputs: signature.inputs.clone(),
+        };
+        assert!(previous.feeds(&signature));
+        previous.outputs.remove(0);
+        assert!(!previous.feeds(&signature));
+    }
+
+    #[test]
+    fn user_defined_interface() {
+        let data = include_bytes!("./InterfaceBlocks.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let outputs = module.enumerate_output_variables(None).unwrap();
+        assert!(outputs.iter().any(|output| output.is_built_in()));
+        let outputs = module.enumerate_user_defined_outputs(None).unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].name, "color");
+        let inputs = module.enumerate_user_defined_inputs(None).unwrap();
+        assert_eq!(inputs.len(), 1);
+    }
+
+    #[test]
+    fn sample_shading() {
+        let data = include_bytes!("./PerSample.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let inputs = module.enumerate_input_variables(None).unwrap();
+        assert!(inputs[0]
+            .decoration_flags
+            .contains(types::ReflectDecorationFlags::SAMPLE));
+        assert!(module.entry_points()[0].forces_sample_shading());
+
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+        assert!(!module.entry_points()[0].forces_sample_shading());
+    }
+
+    #[test]
+    fn fragment_output_flags() {
+        let data = include_bytes!("./DualSource.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        assert_eq!(
+            module.get_fragment_output_flags(None).unwrap(),
+            types::ReflectFragmentOutputFlags::STENCIL_EXPORT
+                | types::ReflectFragmentOutputFlags::DUAL_SOURCE_BLEND
+        );
+
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+        assert!(module.get_fragment_output_flags(None).unwrap().is_empty());
+
+        // Declares depth and stencil outputs without storing to them, and
+        // writes the sample mask from a called function.
+        let data = include_bytes!("./FragmentOutputs.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        assert_eq!(
+            module.get_fragment_output_flags(Some("main")).unwrap(),
+            types::ReflectFragmentOutputFlags::SAMPLE_MASK_EXPORT
+        );
+        assert_eq!(
+            module
+                .get_fragment_output_flags(Some("depth_main"))
+                .unwrap(),
+            types::ReflectFragmentOutputFlags::DEPTH_EXPORT
+        );
+    }
+
+    #[test]
+    fn binding_for_id() {
+        let data = include_bytes!("./Unused.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        for binding in module.descriptor_bindings() {
+            assert_eq!(module.binding_for_id(binding.spirv_id), Some(binding));
+        }
+        assert_eq!(module.binding_for_id(4).unwrap().name, "unused_buf");
+        // The private `counter` variable.
+        assert_eq!(module.binding_for_id(5), None);
+    }
+
+    #[test]
+    fn discard_flags() {
+        let data = include_bytes!("./Discard.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        assert_eq!(
+            module.get_discard_flags(Some("kill_main")).unwrap(),
+            types::ReflectDiscardFlags::KILL
+        );
+        assert_eq!(
+            module.get_discard_flags(Some("terminate_main")).unwrap(),
+            types::ReflectDiscardFlags::TERMINATE_INVOCATION
+        );
+        // Through a call.
+        assert_eq!(
+            module.get_discard_flags(Some("demote_main")).unwrap(),
+            types::ReflectDiscardFlags::DEMOTE_TO_HELPER_INVOCATION
+        );
+        assert!(module
+            .get_discard_flags(Some("plain_main"))
+            .unwrap()
+            .is_empty());
+
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+        assert!(module.get_discard_flags(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn helper_invocation_flags() {
+        let data = include_bytes!("./Discard.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        assert_eq!(
+            module
+                .get_helper_invocation_flags(Some("terminate_main"))
+                .unwrap(),
+            types::ReflectHelperInvocationFlags::BUILT_IN
+        );
+        assert_eq!(
+            module
+                .get_helper_invocation_flags(Some("demote_main"))
+                .unwrap(),
+            types::ReflectHelperInvocationFlags::IS_HELPER_INVOCATION
+                | types::ReflectHelperInvocationFlags::DEMOTE
+        );
+        assert!(module
+            .get_helper_invocation_flags(Some("kill_main"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn input_attachment_edges() {
+        let data = include_bytes!("./InputAttachments.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        assert_eq!(
+            module.get_input_attachment_edges(None).unwrap(),
+            vec![
+                (0, 0, 0, false),
+                (1, 0, 1, true),
+                (2, 1, 0, false),
+                (3, 1, 0, false),
+            ]
+        );
+
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+        assert!(module.get_input_attachment_edges(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn output_index_decoration() {
+        let data = include_bytes!("./DualSource.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let outputs = module.enumerate_user_defined_outputs(None).unwrap();
+        let indices: Vec<(&str, u32, u32)> = outputs
+            .iter()
+            .map(|output| (output.name.as_str(), output.location, output.index))
+            .collect();
+        assert!(indices.contains(&("color", 0, 0)));
+        assert!(indices.contains(&("blend", 0, 1)));
+    }
+
+    #[test]
+    fn geometry_streams() {
+        let data = include_bytes!("./GeometryStreams.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let traits = module.get_geometry_traits(None).unwrap().unwrap();
+        assert_eq!(
+            traits.input_primitive,
+            types::ReflectGeometryInputPrimitive::Points
+        );
+        assert_eq!(
+            traits.output_topology,
+            types::ReflectGeometryOutputTopology::Points
+        );
+        assert_eq!(traits.output_vertices, 4);
+        assert_eq!(traits.invocations, 2);
+        assert_eq!(traits.streams, vec![0, 1]);
+
+        let outputs = module.enumerate_output_variables(None).unwrap();
+        assert_eq!(outputs[1].name, "b");
+        assert_eq!(outputs[1].stream, 1);
+    }
+
+    #[test]
+    fn hlsl_source_info() {
+        let data = include_bytes!("./HlslDebug.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        let info = module.get_hlsl_source_info().unwrap().unwrap();
+        assert_eq!(info.shader_model, (6, 2));
+        assert_eq!(info.profile.as_deref(), Some("ps_6_2"));
+        assert_eq!(info.entry_point.as_deref(), Some("PSMain"));
+        assert_eq!(info.file.as_deref(), Some("shaders/blit.hlsl"));
+
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+        let info = module.get_hlsl_source_info().unwrap().unwrap();
+        assert_eq!(info.shader_model, (6, 0));
+        assert_eq!(info.profile, None);
+
+        let data = include_bytes!("./PerSample.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        assert_eq!(module.get_hlsl_source_info().unwrap(), None);
+    }
+
+    #[test]
+    fn name_lookup() {
+        let data = include_bytes!("./InterfaceBlocks.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let ids = module.ids_named("color").unwrap();
+        assert_eq!(ids.len(), 1);
+        assert_eq!(module.name_of(ids[0]).unwrap().as_deref(), Some("color"));
+        assert!(module.ids_named("missing").unwrap().is_empty());
+
+        let members = module.members_named("VS_OUT.m").unwrap();
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].1, 1);
+        assert_eq!(
+            module
+                .member_name_of(members[0].0, members[0].1)
+                .unwrap()
+                .as_deref(),
+            Some("VS_OUT.m")
+        );
+        assert_eq!(module.members_named("gl_Position").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn entry_point_names() {
+        // An empty name, and word-aligned names whose terminating nul takes a
+        // word of its own.
+        let data = include_bytes!("./EntryPointNames.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        for (name, output) in &[
+            ("", "empty_out"),
+            ("main", "main_out"),
+            ("fragment_main123", "long_out"),
+        ] {
+            let outputs = module.enumerate_output_locations(Some(name)).unwrap();
+            assert_eq!(outputs.len(), 1);
+            assert_eq!(outputs[0].name, *output);
+        }
+        assert!(module.enumerate_output_locations(Some("fragment")).is_err());
+
+        // The names the C library decodes agree.
+        let names: Vec<String> = module
+            .enumerate_entry_points()
+            .unwrap()
+            .into_iter()
+            .map(|entry_point| entry_point.name)
+            .collect();
+        assert_eq!(names, vec!["", "main", "fragment_main123"]);
+        assert_eq!(module.get_entry_point_name(), "");
+
+        // DXC output, and glslang output whose name is word-aligned.
+        for (data, name) in &[
+            (&include_bytes!("./ImGuiPs.spirv")[..], "ImGuiPs"),
+            (&include_bytes!("../examples/sample.spv")[..], "main"),
+        ] {
+            let module = ShaderModule::load_u8_data(data).unwrap();
+            assert_eq!(module.get_entry_point_name(), *name);
+            let entry_points = module.enumerate_entry_points().unwrap();
+            assert_eq!(entry_points.len(), 1);
+            assert_eq!(entry_points[0].name, *name);
+            assert_eq!(
+                module.enumerate_output_locations(Some(name)).unwrap(),
+                module.enumerate_output_locations(None).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn interface_completeness() {
+        let data = include_bytes!("./Incomplete.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        assert_eq!(
+            module.check_interface_completeness().unwrap(),
+            vec![
+                types::ReflectWarning::MissingBinding {
+                    spirv_id: 9,
+                    name: "tex".to_owned(),
+                },
+                types::ReflectWarning::MissingLocation {
+                    spirv_id: 2,
+                    name: "uv".to_owned(),
+                    member: None,
+                },
+                types::ReflectWarning::MissingLocation {
+                    spirv_id: 3,
+                    name: "blk".to_owned(),
+                    member: Some(1),
+                },
+                types::ReflectWarning::MissingOffset {
+                    type_id: 6,
+                    member: 1,
+                },
+                types::ReflectWarning::MissingOffset {
+                    type_id: 7,
+                    member: 1,
+                },
+            ]
+        );
+
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+        assert!(module.check_interface_completeness().unwrap().is_empty());
+    }
+
+    #[test]
+    fn duplicate_decorations() {
+        let data = include_bytes!("./Duplicates.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let decorations = module.get_decorations(4).unwrap();
+        let raw: Vec<(u32, Vec<u32>)> = decorations
+            .iter()
+            .map(|decoration| (decoration.decoration, decoration.operands.clone()))
+            .collect();
+        assert_eq!(
+            raw,
+            vec![
+                (spirv_headers::Decoration::Binding as u32, vec![0]),
+                (spirv_headers::Decoration::Binding as u32, vec![3]),
+                (spirv_headers::Decoration::DescriptorSet as u32, vec![1]),
+            ]
+        );
+        assert!(module.get_decorations(5).unwrap()[1].is(spirv_headers::Decoration::DescriptorSet));
+        assert_eq!(module.get_member_decorations(3, 0).unwrap().len(), 1);
+
+        let duplicates: Vec<_> = module
+            .warnings()
+            .iter()
+            .filter(|warning| matches!(warning, types::ReflectWarning::DuplicateDecoration { .. }))
+            .collect();
+        assert_eq!(
+            duplicates,
+            vec![&types::ReflectWarning::DuplicateDecoration {
+                target_id: 4,
+                member: None,
+                decoration: spirv_headers::Decoration::Binding as u32,
+            }]
+        );
+    }
+
+    #[test]
+    fn linkage_symbols() {
+        let data = include_bytes!("./Linkage.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let symbols = module.enumerate_linkage_symbols().unwrap();
+        let summary: Vec<(&str, types::ReflectLinkageType, bool)> = symbols
+            .iter()
+            .map(|symbol| {
+                (
+                    symbol.name.as_str(),
+                    symbol.linkage_type,
+                    symbol.is_function,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("lib_helper", types::ReflectLinkageType::Import, true),
+                ("lib_scale", types::ReflectLinkageType::Import, false),
+                ("shared_fn", types::ReflectLinkageType::Export, true),
+            ]
+        );
+
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+        assert!(module.enumerate_linkage_symbols().unwrap().is_empty());
+    }
+
+    #[test]
+    fn function_signatures() {
+        let data = include_bytes!("./Linkage.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let functions = module.enumerate_functions().unwrap();
+        let names: Vec<&str> = functions
+            .iter()
+            .map(|function| function.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["helper", "shared_fn", "main"]);
+
+        let helper = &functions[0];
+        assert!(!helper.has_body);
+        assert_eq!(
+            helper.linkage.as_ref().unwrap().linkage_type,
+            types::ReflectLinkageType::Import
+        );
+        assert_eq!(
+            *helper.return_type.as_ref().unwrap().op,
+            spirv_headers::Op::TypeFloat
+        );
+        assert_eq!(helper.parameters.len(), 1);
+        assert_eq!(
+            helper.parameters[0]
+                .type_description
+                .traits
+                .numeric
+                .scalar
+                .width,
+            32
+        );
+
+        assert!(functions[1].has_body);
+        assert!(functions[1].return_type.is_none());
+        assert!(functions[1].parameters.is_empty());
+        assert!(functions[2].linkage.is_none());
+    }
+
+    #[test]
+    fn image_usage() {
+        let data = include_bytes!("./ImageUsage.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        use types::ReflectImageUsageFlags as Flags;
+        let usage = module.enumerate_image_usage().unwrap();
+        let summary: Vec<(&str, u32, u32, Flags)> = usage
+            .iter()
+            .map(|usage| (usage.name.as_str(), usage.set, usage.binding, usage.flags))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "tex",
+                    0,
+                    0,
+                    Flags::IMPLICIT_LOD | Flags::EXPLICIT_LOD | Flags::BIAS | Flags::OFFSET
+                ),
+                ("img", 0, 1, Flags::GATHER | Flags::OFFSET),
+                ("smp", 0, 2, Flags::GATHER | Flags::OFFSET),
+                (
+                    "shadow",
+                    1,
+                    0,
+                    Flags::IMPLICIT_LOD | Flags::DEPTH_COMPARE | Flags::MIN_LOD
+                ),
+                ("fetch_only", 1, 1, Flags::FETCH),
+            ]
+        );
+        assert_eq!(usage[0].texel_offset_range, Some((-2, 3)));
+        assert_eq!(usage[0].gather_offset_range, None);
+        assert_eq!(usage[1].gather_offset_range, Some((-8, 7)));
+
+        let biases: Vec<_> = usage[0]
+            .lod_biases
+            .iter()
+            .map(|bias| bias.to_f32())
+            .collect();
+        assert_eq!(biases, vec![Some(0.5)]);
+        assert!(usage[0].min_lods.is_empty());
+        assert_eq!(usage[3].min_lods[0].to_f32(), Some(2.0));
+        assert!(usage[3].lod_biases.is_empty());
+    }
+
+    #[test]
+    fn image_atomics() {
+        let data = include_bytes!("./ImageAtomics.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        use types::ReflectImageUsageFlags as Flags;
+        let usage = module.enumerate_image_usage().unwrap();
+        let atomics: Vec<(&str, bool)> = usage
+            .iter()
+            .filter(|usage| usage.flags.contains(Flags::ATOMIC))
+            .map(|usage| (usage.name.as_str(), usage.flags.contains(Flags::ATOMIC_64)))
+            .collect();
+        assert_eq!(atomics, vec![("counts", false), ("depth", true)]);
+    }
+
+    #[test]
+    fn storage_image_without_format() {
+        let data = include_bytes!("./StorageImageFormat.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        use types::ReflectImageUsageFlags as Flags;
+        let usage = module.enumerate_image_usage().unwrap();
+        let summary: Vec<(&str, u32, Flags)> = usage
+            .iter()
+            .map(|usage| (usage.name.as_str(), usage.binding, usage.flags))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "unknown_rw",
+                    0,
+                    Flags::READ
+                        | Flags::WRITE
+                        | Flags::READ_WITHOUT_FORMAT
+                        | Flags::WRITE_WITHOUT_FORMAT
+                ),
+                ("unknown_w", 1, Flags::WRITE | Flags::WRITE_WITHOUT_FORMAT),
+                ("rgba32f_rw", 2, Flags::READ | Flags::WRITE),
+            ]
+        );
+        assert_eq!(
+            module.get_required_features(None).unwrap(),
+            types::ReflectFeatureFlags::SHADER_STORAGE_IMAGE_READ_WITHOUT_FORMAT
+                | types::ReflectFeatureFlags::SHADER_STORAGE_IMAGE_WRITE_WITHOUT_FORMAT
+        );
+    }
+
+    #[test]
+    fn module_strings() {
+        let data = include_bytes!("./HlslDebug.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        assert_eq!(
+            module.enumerate_strings().unwrap(),
+            vec![types::ReflectString {
+                spirv_id: 3,
+                value: "shaders/blit.hlsl".to_owned(),
+            }]
+        );
+
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+        assert!(module.enumerate_strings().unwrap().is_empty());
+    }
+
+    #[test]
+    fn multiple_sources() {
+        let data = include_bytes!("./MultipleSources.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        let sources = module.enumerate_sources().unwrap();
+        assert_eq!(
+            sources,
+            vec![
+                types::ReflectSource {
+                    language: spirv_headers::SourceLanguage::HLSL as u32,
+                    language_version: 620,
+                    file: "shaders/main.hlsl".to_owned(),
+                    text: "#include <common.hlsli>\nvoid main() {}\n".to_owned(),
+                },
+                types::ReflectSource {
+                    language: spirv_headers::SourceLanguage::HLSL as u32,
+                    language_version: 620,
+                    file: "shaders/common.hlsli".to_owned(),
+                    text: "#define ONE 1.0\n".to_owned(),
+                },
+            ]
+        );
+        assert_eq!(module.get_source_file(), "shaders/main.hlsl");
+        assert_eq!(module.get_source_text(), sources[0].text);
+
+        let cache = module.to_cache().unwrap();
+        assert_eq!(cache.sources, sources);
+    }
+
+    #[test]
+    fn ext_inst_usage() {
+        let data = include_bytes!("./ExtInst.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let usage = |name: &str| -> Vec<(String, u32)> {
+            module
+                .entry_point(name)
+                .unwrap()
+                .ext_inst_usage()
+                .iter()
+                .map(|usage| {
+                    assert_eq!(usage.set, "GLSL.std.450");
+                    (usage.name.clone(), usage.count)
+                })
+                .collect()
+        };
+        assert_eq!(
+            usage("main"),
+            vec![("Fma".to_owned(), 2), ("Normalize".to_owned(), 2)]
+        );
+        assert_eq!(usage("other"), vec![("Sqrt".to_owned(), 1)]);
+    }
+
+    #[test]
+    fn shader_metrics() {
+        let data = include_bytes!("./Loops.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        assert_eq!(
+            module.get_metrics(None).unwrap(),
+            types::ReflectShaderMetrics {
+                instruction_count: 19,
+                function_count: 1,
+                block_count: 6,
+                loop_count: 1,
+                image_sample_count: 0,
+                buffer_load_count: 2,
+                buffer_store_count: 1,
+            }
+        );
+
+        let data = include_bytes!("./ImageUsage.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        let metrics = module.get_metrics(Some("main")).unwrap();
+        assert_eq!(metrics.function_count, 2);
+        assert_eq!(metrics.block_count, 2);
+        assert_eq!(metrics.loop_count, 0);
+        assert_eq!(metrics.image_sample_count, 5);
+        assert_eq!(metrics.buffer_load_count, 0);
+    }
+
+    #[test]
+    fn control_flow() {
+        let data = include_bytes!("./ControlFlow.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let functions = module.enumerate_function_control_flow().unwrap();
+        let summary: Vec<(u32, u32, u32, u32, u32)> = functions
+            .iter()
+            .map(|flow| {
+                (
+                    flow.spirv_id,
+                    flow.loop_count,
+                    flow.max_loop_depth,
+                    flow.selection_count,
+                    flow.divergent_branch_count,
+                )
+            })
+            .collect();
+        assert_eq!(summary, vec![(1, 1, 1, 1, 2), (2, 2, 2, 1, 3)]);
+
+        // `helper` is called from inside the loop of `main`.
+        assert_eq!(
+            module.get_control_flow(Some("main")).unwrap(),
+            types::ReflectControlFlow {
+                spirv_id: 1,
+                loop_count: 3,
+                max_loop_depth: 3,
+                selection_count: 2,
+                divergent_branch_count: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn binding_access_hints() {
+        let data = include_bytes!("./BindingAccess.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let accesses = module.enumerate_binding_access(None).unwrap();
+        let summary: Vec<(&str, u32, u32, u32, u32, u32, u32)> = accesses
+            .iter()
+            .map(|access| {
+                (
+                    access.name.as_str(),
+                    access.load_count,
+                    access.store_count,
+                    access.sample_count,
+                    access.atomic_count,
+                    access.loop_access_count,
+                    access.max_loop_depth,
+                )
+            })
+            .collect();
+        // `tex` is sampled in a function called from inside the loop.
+        assert_eq!(
+            summary,
+            vec![
+                ("params", 1, 0, 0, 0, 0, 0),
+                ("data", 1, 1, 0, 1, 2, 1),
+                ("tex", 0, 0, 1, 0, 1, 1),
+                ("unused", 0, 0, 0, 0, 0, 0),
+            ]
+        );
+        assert_eq!(accesses[1].access_count(), 3);
+    }
+
+    #[test]
+    fn push_constant_taint() {
+        use types::ReflectTaintSink as Sink;
+
+        let data = include_bytes!("./PushConstantTaint.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let color = Sink::Output {
+            spirv_id: 5,
+            member: None,
+            name: "color".to_owned(),
+        };
+        let taint = module.enumerate_push_constant_taint(None).unwrap();
+        let summary: Vec<(&str, Vec<Sink>)> = taint
+            .iter()
+            .map(|member| (member.name.as_str(), member.sinks.clone()))
+            .collect();
+        // `index` picks the texture that is sampled, `mode` decides the
+        // branch writing `counters` and `mask`.
+        assert_eq!(
+            summary,
+            vec![
+                ("scale", vec![color.clone()]),
+                (
+                    "mode",
+                    vec![
+                        Sink::Output {
+                            spirv_id: 6,
+                            member: None,
+                            name: "mask".to_owned(),
+                        },
+                        Sink::Descriptor {
+                            spirv_id: 7,
+                            set: 0,
+                            binding: 1,
+                            name: "counters".to_owned(),
+                        },
+                    ]
+                ),
+                (
+                    "index",
+                    vec![
+                        color,
+                        Sink::Descriptor {
+                            spirv_id: 4,
+                            set: 0,
+                            binding: 0,
+                            name: "textures".to_owned(),
+                        },
+                    ]
+                ),
+                ("unused", vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn barriers() {
+        let data = include_bytes!("./Barriers.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let main = module.entry_point("main").unwrap();
+        assert_eq!(
+            main.barriers(),
+            &[
+                types::ReflectBarrier {
+                    control: false,
+                    execution_scope: None,
+                    memory_scope: Some(spirv_headers::Scope::Device as u32),
+                    semantics: Some(0x48),
+                    count: 1,
+                },
+                types::ReflectBarrier {
+                    control: true,
+                    execution_scope: Some(spirv_headers::Scope::Workgroup as u32),
+                    memory_scope: Some(spirv_headers::Scope::Workgroup as u32),
+                    semantics: Some(0x108),
+                    count: 2,
+                },
+            ]
+        );
+        assert!(main.barriers()[1]
+            .memory_semantics()
+            .unwrap()
+            .contains(spirv_headers::MemorySemantics::WORKGROUP_MEMORY));
+        assert!(main.uses_workgroup_barrier());
+        assert!(main.uses_device_memory_scope());
+
+        let other = module.entry_point("other").unwrap();
+        assert_eq!(other.barriers().len(), 1);
+        assert!(!other.uses_workgroup_barrier());
+        assert!(!other.uses_device_memory_scope());
+    }
+
+    #[test]
+    fn extract_entry_point() {
+        let data = include_bytes!("./ExtInst.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        let other = module.extract_entry_point("other").unwrap();
+        assert!(other.get_code().len() < module.get_code().len());
+        assert_eq!(other.entry_points().len(), 1);
+        assert_eq!(other.get_entry_point_name(), "other");
+        // `helper` is only called from `main`.
+        let functions: Vec<u32> = other
+            .enumerate_functions()
+            .unwrap()
+            .iter()
+            .map(|function| function.spirv_id)
+            .collect();
+        assert_eq!(functions, vec![5]);
+        assert_eq!(
+            other.entry_points()[0].ext_inst_usage(),
+            module.entry_point("other").unwrap().ext_inst_usage()
+        );
+        assert!(module.extract_entry_point("missing").is_err());
+
+        let data = include_bytes!("./Tessellation.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+        let evaluation = module.extract_entry_point("tes_main").unwrap();
+        assert_eq!(
+            evaluation.get_shader_stage(),
+            types::ReflectShaderStageFlags::TESSELLATION_EVALUATION
+        );
+        assert_eq!(
+            evaluation.input_variables().len(),
+            module
+                .entry_point("tes_main")
+                .unwrap()
+                .input_variables
+                .len()
+        );
+        assert!(evaluation.output_variables().is_empty());
+        let traits = evaluation.get_tessellation_traits(None).unwrap().unwrap();
+        assert_eq!(traits.patch_inputs.len(), 1);
+    }
+
+    #[test]
+    fn unused_objects() {
+        let data = include_bytes!("./Unused.spirv");
+        let module = ShaderModule::load_u8_data(data).unwrap();
+
+        let unused = module.unused_objects().unwrap();
+        let names = |names: Vec<&str>| names.into_iter().map(str::to_owned).collect::<Vec<_>>();
+        assert_eq!(
+            unused
+                .functions
+                .iter()
+                .map(|function| function.name.clone())
+                .collect::<Vec<_>>(),
+            names(vec!["dead"])
+        );
+        assert_eq!(
+            unused
+                .global_variables
+                .iter()
+                .map(|variable| variable.name.clone())
+                .collect::<Vec<_>>(),
+            names(vec!["scratch"])
+        );
+        assert_eq!(
+            unused
+                .descriptor_bindings
+                .iter()
+                .map(|binding| (binding.name.clone(), binding.binding))
+                .collect::<Vec<_>>(),
+            vec![("unused_buf".to_owned(), 1)]
+        );
+
+        let ps_data = include_bytes!("./ImGuiPs.spirv");
+        let module = ShaderModule::load_u8_data(ps_data).unwrap();
+        assert_eq!(module.unused_objects().unwrap(), Default::default());
+    }
+
     #[test]
     fn change_binding_numbers() {
         let ps_data = include_bytes!("./ImGuiPs.spirv");